@@ -1,13 +1,15 @@
-use proc_macro2::{Ident, Span, TokenStream, TokenTree};
-use quote::{quote, ToTokens};
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
 use syn::{
     parenthesized,
     parse::{self, discouraged::Speculative, Parse, ParseStream},
     parse_macro_input,
-    punctuated::{self as punc, Punctuated},
-    token, Attribute, Token,
+    punctuated::Punctuated,
+    token, Token,
 };
 
+use calcurs_wolfram as wolfram;
+
 mod rubi;
 
 #[derive(PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
@@ -17,6 +19,14 @@ enum OpKind {
     Mul,
     Div,
     Pow,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
 }
 
 impl std::fmt::Debug for OpKind {
@@ -27,6 +37,14 @@ impl std::fmt::Debug for OpKind {
             OpKind::Mul => "Mul",
             OpKind::Div => "Div",
             OpKind::Pow => "Pow",
+            OpKind::Eq => "Eq",
+            OpKind::Ne => "Ne",
+            OpKind::Lt => "Lt",
+            OpKind::Le => "Le",
+            OpKind::Gt => "Gt",
+            OpKind::Ge => "Ge",
+            OpKind::And => "And",
+            OpKind::Or => "Or",
         };
         write!(f, "{}", str)
     }
@@ -41,9 +59,12 @@ struct Op {
 impl OpKind {
     fn precedence(&self) -> i32 {
         match self {
-            OpKind::Add | OpKind::Sub => 1,
-            OpKind::Mul | OpKind::Div => 2,
-            OpKind::Pow => 3,
+            OpKind::Or => 1,
+            OpKind::And => 2,
+            OpKind::Eq | OpKind::Ne | OpKind::Lt | OpKind::Le | OpKind::Gt | OpKind::Ge => 3,
+            OpKind::Add | OpKind::Sub => 4,
+            OpKind::Mul | OpKind::Div => 5,
+            OpKind::Pow => 6,
         }
     }
 }
@@ -56,7 +77,24 @@ impl Op {
 
 impl Parse for Op {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        let (kind, span) = if let Ok(op) = input.parse::<Token![+]>() {
+        // multi character operators first, e.g <= would be parsed as <
+        let (kind, span) = if let Ok(op) = input.parse::<Token![==]>() {
+            (OpKind::Eq, op.spans[0])
+        } else if let Ok(op) = input.parse::<Token![!=]>() {
+            (OpKind::Ne, op.spans[0])
+        } else if let Ok(op) = input.parse::<Token![<=]>() {
+            (OpKind::Le, op.spans[0])
+        } else if let Ok(op) = input.parse::<Token![>=]>() {
+            (OpKind::Ge, op.spans[0])
+        } else if let Ok(op) = input.parse::<Token![&&]>() {
+            (OpKind::And, op.spans[0])
+        } else if let Ok(op) = input.parse::<Token![||]>() {
+            (OpKind::Or, op.spans[0])
+        } else if let Ok(op) = input.parse::<Token![<]>() {
+            (OpKind::Lt, op.span)
+        } else if let Ok(op) = input.parse::<Token![>]>() {
+            (OpKind::Gt, op.span)
+        } else if let Ok(op) = input.parse::<Token![+]>() {
             (OpKind::Add, op.span)
         } else if let Ok(op) = input.parse::<Token![-]>() {
             (OpKind::Sub, op.span)
@@ -69,7 +107,7 @@ impl Parse for Op {
         } else {
             return Err(parse::Error::new(
                 input.span(),
-                "expected operator { +, -, *, / }",
+                "expected operator { +, -, *, /, ^, ==, !=, <, <=, >, >=, &&, || }",
             ));
         };
         Ok(Self { kind, span })
@@ -83,6 +121,8 @@ enum Expr {
     Symbol(String),
     Binary(OpKind, Box<Expr>, Box<Expr>),
     Func(syn::Ident, Vec<Expr>),
+    Not(Box<Expr>),
+    Bool(bool),
     Infinity { sign: i8 },
     Undef,
     PlaceHolder(String),
@@ -94,6 +134,8 @@ impl Expr {
             let sid = id.to_string();
             if sid == "oo" {
                 Ok(Expr::Infinity { sign: 1 })
+            } else if sid == "zoo" {
+                Ok(Expr::Infinity { sign: 0 })
             } else if sid == "undef" {
                 Ok(Expr::Undef)
             } else if s.peek(token::Paren) {
@@ -105,12 +147,16 @@ impl Expr {
             } else {
                 Ok(Expr::Symbol(sid.to_string()))
             }
+        } else if s.peek(syn::LitBool) {
+            let b: syn::LitBool = s.parse()?;
+            Ok(Expr::Bool(b.value))
+        } else if s.peek(syn::LitFloat) {
+            // a failed LitInt::parse would consume the float literal
+            let f: syn::LitFloat = s.parse()?;
+            Ok(Expr::Float(f.base10_parse()?))
         } else if let Ok(i) = syn::LitInt::parse(s) {
             let val: i64 = i.base10_parse().unwrap();
             Ok(Expr::Num(val))
-        } else if let Ok(f) = syn::LitFloat::parse(s) {
-            let val: f64 = f.base10_parse().unwrap();
-            Ok(Expr::Float(val))
         } else if s.peek(token::Paren) {
             let content;
             parenthesized!(content in s);
@@ -126,14 +172,16 @@ impl Expr {
                 OpKind::Sub => {
                     let operand = Self::parse_operand(s)?;
                     Ok(if let Expr::Num(n) = operand {
-                        Expr::Num(-1 * n)
+                        Expr::Num(-n)
                     } else {
                         Expr::Binary(OpKind::Mul, Expr::Num(-1).into(), operand.into())
                     })
                 }
                 _ => Err(parse::Error::new(op.span, "expected unary operator")),
             }
-        } else if let Ok(_) = s.parse::<Token![?]>() {
+        } else if s.parse::<Token![!]>().is_ok() {
+            Ok(Expr::Not(Self::parse_unary_expr(s)?.into()))
+        } else if s.parse::<Token![?]>().is_ok() {
             let mut id = "?".to_string();
             id.push_str(&syn::Ident::parse(s)?.to_string());
             Ok(Expr::PlaceHolder(id.to_string()))
@@ -172,7 +220,7 @@ impl Expr {
 
 impl Parse for Expr {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        Expr::parse_bin_expr(input, 0 + 1)
+        Expr::parse_bin_expr(input, 1)
     }
 }
 
@@ -190,18 +238,38 @@ fn gen_expr_stream(e: &Expr) -> parse::Result<TokenStream> {
     let cname = get_crate_name();
     Ok(match e {
         E::Num(n) => quote!(#cname::Expr::rational(#n)),
+        E::Float(f) => quote!(#cname::Expr::float(#f)),
         E::Symbol(s) if s == "pi" => quote!(#cname::Expr::pi()),
+        E::Symbol(s) if s == "I" => quote!(#cname::Expr::i()),
         E::Symbol(s) => quote!(#cname::Expr::from(#s)),
         E::Undef => quote!(#cname::Expr::undef()),
+        E::Bool(b) => quote!(#cname::Expr::from(#b)),
+        E::Not(x) => {
+            let x = gen_expr_stream(x)?;
+            quote!(#cname::Expr::not(#x))
+        }
+        E::Infinity { sign: 1 } => quote!(#cname::Expr::inf()),
+        E::Infinity { sign: -1 } => quote!(#cname::Expr::neg_inf()),
+        E::Infinity { .. } => quote!(#cname::Expr::complex_inf()),
         E::Binary(op, lhs, rhs) => {
             let lhs = gen_expr_stream(lhs)?;
             let rhs = gen_expr_stream(rhs)?;
+            let rel =
+                |op: TokenStream| quote!(#cname::Expr::rel(#cname::atom::RelOp::#op, #lhs, #rhs));
             let op = match op {
                 OK::Add => quote!(add),
                 OK::Sub => quote!(sub),
                 OK::Mul => quote!(mul),
                 OK::Div => quote!(div),
                 OK::Pow => quote!(pow),
+                OK::And => quote!(and),
+                OK::Or => quote!(or),
+                OK::Eq => return Ok(rel(quote!(Eq))),
+                OK::Ne => return Ok(rel(quote!(Ne))),
+                OK::Lt => return Ok(rel(quote!(Lt))),
+                OK::Le => return Ok(rel(quote!(Le))),
+                OK::Gt => return Ok(rel(quote!(Gt))),
+                OK::Ge => return Ok(rel(quote!(Ge))),
             };
             quote! { #cname::Expr::#op(#lhs, #rhs)}
         }
@@ -211,6 +279,21 @@ fn gen_expr_stream(e: &Expr) -> parse::Result<TokenStream> {
                 "placeholder not allowed in expressions, only in patterns",
             ))
         }
+        // the base of log is a Real, so only numeric constants are allowed
+        E::Func(func, args) if func == "log" && args.len() == 2 => {
+            let base = match &args[0] {
+                b @ (E::Num(_) | E::Float(_)) => gen_expr_stream(b)?,
+                b @ E::Symbol(s) if s == "pi" => gen_expr_stream(b)?,
+                _ => {
+                    return Err(parse::Error::new(
+                        func.span(),
+                        "logarithm base must be a real constant",
+                    ))
+                }
+            };
+            let x = gen_expr_stream(&args[1])?;
+            quote!(#cname::Expr::log(#base.try_as_real().unwrap(), #x))
+        }
         E::Func(func, args) => {
             let mut args_tok = TokenStream::default();
             for a in args {
@@ -219,7 +302,6 @@ fn gen_expr_stream(e: &Expr) -> parse::Result<TokenStream> {
             }
             quote!(#cname::Expr::#func(#args_tok))
         }
-        _ => todo!(),
     })
 }
 
@@ -250,22 +332,24 @@ pub fn expr(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
 }
 
 #[proc_macro]
-pub fn integration_rules(_: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    rubi::load_rubi();
-    TokenStream::new().into()
+pub fn integration_rules(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let parser = Punctuated::<syn::LitStr, Token![,]>::parse_terminated;
+    let sections = parse_macro_input!(input with parser);
+    let sections: Vec<_> = sections.iter().map(|s| s.value()).collect();
+    rubi::integration_rules(&sections).into()
 }
 
 struct ArithOpsArgs {
-    ref_tok: syn::Token![ref],
-    comma: syn::Token![,],
+    _ref_tok: syn::Token![ref],
+    _comma: syn::Token![,],
     field: syn::ExprField,
 }
 
 impl Parse for ArithOpsArgs {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         Ok(Self {
-            ref_tok: input.parse()?,
-            comma: input.parse()?,
+            _ref_tok: input.parse()?,
+            _comma: input.parse()?,
             field: input.parse()?,
         })
     }
@@ -1,1222 +1,467 @@
-use std::{
-    collections::{HashMap, HashSet},
-    fmt::{self, Write},
-    ops,
-};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use proc_macro2::TokenStream;
 use quote::quote;
 
-use logos::{Logos, Source};
-use ordered_float::OrderedFloat;
-use serde::{Deserialize, Serialize};
-
-use derive_more::Debug;
-
-type F64 = OrderedFloat<f64>;
-
-const INTEGRATION_RULES: &'static str = include_str!("rubi.data");
-
-macro_rules! error {
-    ($($tt:tt)*) => {{
-        use std::fmt::Write;
-        let mut buf = String::new();
-        write!(buf, "ERROR: file: {}, line: {}\n", file!(), line!()).unwrap();
-        write!(buf, $($tt)*).unwrap();
-        panic!("{}", buf);
-    }}
-}
-
-macro_rules! function_name {
-    ($lvl: literal) => {{
-        fn __f__() {}
-        fn type_name_of<T>(_: T) -> &'static str {
-            std::any::type_name::<T>()
-        }
-        type_name_of(__f__)
-            .split("::")
-            .skip($lvl)
-            .filter(|&name| name != "__f__")
-            .collect::<Vec<_>>()
-            .join("::")
-
-        //.find(|&part| part!= "f" && part != "{{closure}}")
-        //.expect("function name")
-    }};
-}
-
-macro_rules! trace {
-    () => {{
-        use std::fmt::Write;
-        let mut buff = String::new();
-        write!(buff, "TRACE: {}", function_name!(2)).unwrap();
-        println!("{}", buff);
-    }};
-    ($($tt:tt)+) => {{
-        use std::fmt::Write;
-        let mut buff = String::new();
-        write!(buff, "TRACE: {}: ", function_name!(2)).unwrap();
-        write!(buff, $($tt)*).unwrap();
-        println!("{}", buff);
-    }}
-}
-
-fn parse_string(lex: &mut logos::Lexer<Wolfram>) -> String {
-    let chars = lex.remainder().chars();
-
-    let mut str = String::new();
-    let mut escape = false;
-    let mut len = 0;
-
-    for c in chars {
-        len += 1;
-
-        if escape {
-            match c {
-                'n' => str.push('\n'),
-                't' => str.push('\t'),
-                _ => str.push_str(&format!("\\{}", c)),
-            }
-            escape = false;
-        } else {
-            match c {
-                '\\' => escape = true,
-                '"' => break,
-                _ => str.push(c),
-            }
-        }
-    }
-
-    lex.bump(len);
-    str
-}
+use crate::wolfram::{
+    parse, var_name, FuncCall, FuncDef, List, Num, OpKind, Pat, Pattern, Scope, ScopeKind, SymExpr,
+};
 
-fn parse_comment(lex: &mut logos::Lexer<Wolfram>) -> String {
-    let mut res = String::new();
-    loop {
-        let tok = lex.next().expect("unclosed comment");
-        res += lex.slice();
-        if tok == Ok(Wolfram::CommentEnd) {
-            break;
-        }
+const INTEGRATION_RULES: &str = include_str!("rubi.data");
+
+/// the functions implemented by `WlfrmBuiltins`, rules calling anything else are not compiled
+const BUILTINS: &[&str] = &[
+    "FalseQ",
+    "TrueQ",
+    "PossibleZeroQ",
+    "NonsumQ",
+    "CoprimeQ",
+    "PerfectSquareQ",
+    "MemberQ",
+    "PowerQ",
+    "IntegerPowerQ",
+    "FractionalPowerQ",
+    "PolynomialQ",
+    "PolyQ",
+    "NumericQ",
+    "ListQ",
+    "LinearPairQ",
+    "IntQuadraticQ",
+    "NiceSqrtQ",
+    "SimplerQ",
+    "SumSimplerQ",
+    "SimplerSqrtQ",
+    "IntegerQ",
+    "IntegersQ",
+    "FractionQ",
+    "RationalQ",
+    "NumberQ",
+    "EvenQ",
+    "OddQ",
+    "ProductQ",
+    "SumQ",
+    "PowQ",
+    "AtomQ",
+    "ComplexNumberQ",
+    "FreeQ",
+    "LinearQ",
+    "EqQ",
+    "NeQ",
+    "GtQ",
+    "LtQ",
+    "GeQ",
+    "LeQ",
+    "IGtQ",
+    "ILtQ",
+    "IGeQ",
+    "ILeQ",
+    "PosQ",
+    "NegQ",
+    "Not",
+    "Sin",
+    "ArcSin",
+    "Cos",
+    "ArcCos",
+    "Tan",
+    "ArcTan",
+    "ArcSinh",
+    "ArcCosh",
+    "ArcTanh",
+    "Sqrt",
+    "Rt",
+    "Log",
+    "Exp",
+    "Numerator",
+    "Denominator",
+    "FracPart",
+    "IntPart",
+    "Coefficient",
+    "Derivative",
+    "Expand",
+    "ExpandIntegrand",
+    "RemoveContent",
+    "Simp",
+    "Simplify",
+    "Subst",
+    "Binomial",
+    "Sign",
+    "Abs",
+    "Numer",
+    "Denom",
+    "Together",
+    "FractionalPart",
+    "IntegerPart",
+    "Floor",
+    "Mod",
+    "GCD",
+    "Max",
+    "Min",
+    "LeafCount",
+    "Coeff",
+    "Expon",
+    "Exponent",
+    "ExpandToSum",
+    "PolynomialQuotient",
+    "PolynomialRemainder",
+    "PolynomialDivide",
+    "PolynomialGCD",
+    "D",
+    "SimplifyIntegrand",
+    "Unintegrable",
+    "CannotIntegrate",
+    "Int",
+];
+
+fn num_tokens(num: Num) -> TokenStream {
+    let (n, d) = (num.0 as i128, num.1 as i128);
+    if num.is_int() {
+        quote!(Expr::rational(#n))
+    } else {
+        quote!(Expr::rational((#n, #d)))
     }
-    res
-    //let end = lex.spanned().position(|(t, _)| t == Ok(Wolfram::CommentEnd)).expect("comment was not closed");
-    //let mut res = String::new();
-    //for _ in 0..end {
-    //    res += lex.slice();
-    //    println!("{}", res);
-    //    lex.next().unwrap();
-    //}
-    //panic!("{}", end);
-    //res
-}
-
-#[derive(Logos, Clone, Debug, PartialEq, PartialOrd)]
-#[logos(skip r"[ \t\r\f]+")]
-enum Wolfram {
-    #[token("(*", parse_comment)]
-    Comment(String),
-    #[token("*)")]
-    CommentEnd,
-    #[token("\n")]
-    NL,
-
-    //#[regex("[_a-zA-Z][_0-9a-zA-Z]*", |lex| lex.slice().to_owned())]
-    #[regex("[A-Za-z$_][A-Za-z0-9$_]*", |lex| lex.slice().to_owned())]
-    Ident(String),
-
-    #[regex(r"[0-9]+\.", |lex| lex.slice().parse::<f64>().unwrap() as u64, priority=100)]
-    #[regex(r"[0-9]+", |lex| lex.slice().parse::<u64>().unwrap(), priority=100)]
-    Integer(u64),
-
-    #[regex(r"(?:0|[1-9]\d*)(?:\.\d+)?(?:[eE][+-]?\d+)?", |lex| lex.slice().parse::<f64>().unwrap(), priority=5)]
-    Float(f64),
-
-    #[token("\"", parse_string)]
-    Str(String),
-
-    #[token("+")]
-    Add,
-    #[token("-")]
-    Sub,
-    #[token("*")]
-    #[token("\\[Star]")]
-    Mul,
-    #[token("/")]
-    Div,
-    #[token("^")]
-    Pow,
-
-    #[token("&&")]
-    And,
-    #[token("||")]
-    Or,
-    #[token("!")]
-    Not,
-
-    #[token("==")]
-    Eq,
-    #[token("!=")]
-    NEq,
-    #[token("<")]
-    Lt,
-    #[token(">")]
-    Ge,
-    #[token("<=")]
-    LtEq,
-    #[token(">=")]
-    GeEq,
-    #[token("===")]
-    Same,
-    #[token("=!=")]
-    UnSame,
-
-    #[token("=")]
-    Assign,
-    #[token(":=")]
-    Def,
-    #[token("/;")]
-    Cond,
-    #[token("/.")]
-    Replace,
-    #[token("->")]
-    Rule,
-    #[token("\\")]
-    BackSlash,
-    #[token("#")]
-    Slot,
-    #[token("'")]
-    Deriv,
-
-    #[token("(")]
-    LParen,
-    #[token(")")]
-    RParen,
-    #[token("[")]
-    LBracket,
-    #[token("]")]
-    RBracket,
-    #[token("{")]
-    LBrace,
-    #[token("}")]
-    RBrace,
-
-    #[token(".")]
-    Dot,
-    #[token(",")]
-    Comma,
-    #[token(";")]
-    Semicolon,
-    #[token(":")]
-    Colon,
-    #[token("::")]
-    DoublColon,
 }
 
-impl Wolfram {
-    const fn op_prec(&self) -> u32 {
-        use Wolfram as W;
-        match self {
-            W::Replace => 1,
-            W::Rule => 2,
-            W::Assign => 3,
-            W::Or => 4,
-            W::And => 5,
-            W::NEq | W::Eq | W::Ge | W::GeEq | W::Lt | W::LtEq | W::Same | W::UnSame => 6,
-            W::Add | W::Sub => 7,
-            W::Mul | W::Div => 8,
-            W::Pow => 9,
-            _ => 0,
+/// the `Pattern` built by the rule, symbols and functions are only matched by rules loaded at
+/// runtime
+fn pat_tokens(p: &Pat) -> Result<TokenStream, String> {
+    Ok(match p {
+        Pat::Sym(x) => quote!(Pattern::Sym(#x.into())),
+        Pat::Blank(a) => quote!(Pattern::Blank(#a.into())),
+        Pat::Opt(a) => quote!(Pattern::Opt(#a.into())),
+        Pat::Num(n) => {
+            let n = num_tokens(*n);
+            quote!(Pattern::Lit(#n))
         }
-    }
-
-    const fn is_op(&self) -> bool {
-        self.op_prec() != 0
-    }
-
-    const fn skippable(&self) -> bool {
-        use Wolfram as W;
-        match self {
-            W::Comment(_) | W::CommentEnd | W::NL => true,
-            _ => false,
+        Pat::Sum(args) => {
+            let args = args.iter().map(pat_tokens).collect::<Result<Vec<_>, _>>()?;
+            quote!(Pattern::Sum(vec![#(#args),*]))
         }
-    }
-}
-
-#[derive(Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
-struct Pattern {
-    pat: Box<SymExpr>,
-    cond: Option<Box<SymExpr>>,
-}
-
-impl fmt::Debug for Pattern {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if let Some(_) = &self.cond {
-            write!(f, "(")?;
+        Pat::Prod(args) => {
+            let args = args.iter().map(pat_tokens).collect::<Result<Vec<_>, _>>()?;
+            quote!(Pattern::Prod(vec![#(#args),*]))
         }
-        write!(f, "{:?}", self.pat)?;
-        if let Some(cond) = &self.cond {
-            write!(f, "/;  {cond:?})")?;
+        Pat::Pow(b, e) => {
+            let (b, e) = (pat_tokens(b)?, pat_tokens(e)?);
+            quote!(Pattern::Pow(Box::new([#b, #e])))
         }
-        Ok(())
-    }
+        p @ (Pat::Ident(_) | Pat::Func(..)) => return Err(format!("{p:?} in pattern")),
+    })
 }
 
-#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-struct FuncDef {
-    call: FuncCall,
-    pat: Pattern,
-    //rhs: Box<SymExpr>,
-    //cond: Option<Box<SymExpr>>,
+fn var_ident(name: &str) -> proc_macro2::Ident {
+    proc_macro2::Ident::new(&format!("v_{name}"), proc_macro2::Span::call_site())
 }
 
-impl FuncDef {
-    fn for_each<F>(&self, f: F)
-    where
-        F: Fn(&SymExpr) + Copy,
-    {
-        self.call.for_each(f);
-        self.pat.for_each(f);
-    }
+/// translates the right hand side and condition of a rule to rust code evaluating to a
+/// `WlfrmAtom`
+struct Codegen<'a> {
+    /// the names bound to rust variables
+    vars: Vec<String>,
+    /// the functions defined by the rules themselves, e.g `IntLinearQ`
+    helpers: &'a HashSet<String>,
 }
 
-impl FuncCall {
-    fn for_each<F>(&self, f: F)
-    where
-        F: Fn(&SymExpr) + Copy,
-    {
-        self.args.iter().for_each(|e| e.for_each(f));
+impl Codegen<'_> {
+    fn exprs(&mut self, es: &[SymExpr]) -> Result<Vec<TokenStream>, String> {
+        es.iter().map(|e| self.expr(e)).collect()
     }
-}
 
-impl Pattern {
-    fn for_each<F>(&self, f: F)
-    where
-        F: Fn(&SymExpr) + Copy,
-    {
-        self.pat.for_each(f);
-        if let Some(cond) = &self.cond {
-            cond.for_each(f)
+    fn call_args(args: Vec<TokenStream>) -> TokenStream {
+        match args.as_slice() {
+            [a] => quote!(WlfrmFuncArgs::One(#a)),
+            [a, b] => quote!(WlfrmFuncArgs::Two([#a, #b])),
+            [a, b, c] => quote!(WlfrmFuncArgs::Three([#a, #b, #c])),
+            args => quote!(WlfrmFuncArgs::Var(vec![#(#args),*])),
         }
     }
-}
-
-impl fmt::Debug for FuncDef {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{:?} := {:?}", self.call, self.pat)?;
-        Ok(())
-    }
-}
-
-#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-struct FuncCall {
-    name: String,
-    args: Vec<SymExpr>,
-}
-
-impl fmt::Debug for FuncCall {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}{:?}", self.name, self.args)
-    }
-}
-
-#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
-enum OpKind {
-    Assign,
-    Add,
-    Sub,
-    Mul,
-    Div,
-    Pow,
-    And,
-    Or,
-    NEq,
-    Eq,
-    Ge,
-    GeEq,
-    Lt,
-    LtEq,
-    Same,
-    UnSame,
-    Rule,
-    Replace,
-    Fac,
-    Deriv,
-}
-
-impl OpKind {
-    fn binary(value: &Wolfram) -> Option<Self> {
-        use OpKind as O;
-        use Wolfram as W;
-        Some(match value {
-            W::Assign => O::Assign,
-            W::Add => O::Add,
-            W::Sub => O::Sub,
-            W::Mul => O::Mul,
-            W::Div => O::Div,
-            W::Pow => O::Pow,
-            W::And => O::And,
-            W::Or => O::Or,
-            W::Eq => O::Eq,
-            W::NEq => O::NEq,
-            W::Ge => O::Ge,
-            W::GeEq => O::GeEq,
-            W::Lt => O::Lt,
-            W::LtEq => O::LtEq,
-            W::Same => O::Same,
-            W::UnSame => O::UnSame,
-            W::Rule => O::Rule,
-            W::Replace => O::Replace,
-            _ => return None,
-        })
-    }
-
-    fn unary(value: &Wolfram) -> Option<Self> {
-        use OpKind as O;
-        use Wolfram as W;
-        Some(match value {
-            W::Sub => O::Sub,
-            _ => return None,
-        })
-    }
-}
-
-#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub struct List {
-    args: Vec<SymExpr>,
-}
-
-impl List {
-    fn for_each<F>(&self, f: F)
-    where
-        F: Fn(&SymExpr) + Copy,
-    {
-        self.args.iter().for_each(|e| e.for_each(f))
-    }
-}
 
-impl fmt::Debug for List {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{:?}", self.args)
+    fn builtin(name: &str, args: Vec<TokenStream>) -> TokenStream {
+        let f = proc_macro2::Ident::new(name, proc_macro2::Span::call_site());
+        let args = Self::call_args(args);
+        quote!(WlfrmBuiltins::#f(#args))
     }
-}
-
-#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
-enum ScopeKind {
-    With,
-    Block,
-    Module,
-}
-
-impl TryFrom<&str> for ScopeKind {
-    type Error = ();
 
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        if value == "With" {
-            Ok(ScopeKind::With)
-        } else if value == "Block" {
-            Ok(ScopeKind::Block)
-        } else if value == "Module" {
-            Ok(ScopeKind::Module)
+    fn call(&mut self, name: &str, args: &[SymExpr]) -> Result<TokenStream, String> {
+        let args = self.exprs(args)?;
+        if self.helpers.contains(name) {
+            let f = proc_macro2::Ident::new(name, proc_macro2::Span::call_site());
+            let args = Self::call_args(args);
+            Ok(quote!(#f(#args)))
+        } else if BUILTINS.contains(&name) {
+            Ok(Self::builtin(name, args))
         } else {
-            Err(())
+            Err(format!("unsupported function: {name}"))
         }
     }
-}
-
-#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-struct Scope {
-    kind: ScopeKind,
-    vars: List,
-    pat: Pattern,
-    //expr: Box<SymExpr>,
-    //cond: Option<Box<SymExpr>>,
-}
-
-impl fmt::Debug for Scope {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{:?}[{:?}, {:?}]", self.kind, self.vars, self.pat)
-    }
-}
-
-#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
-enum SymExpr {
-    #[debug("{_0}")]
-    Ident(String),
-    #[debug("({_0} /; {_1:?})")]
-    IdentRestr(String, Vec<SymExpr>),
-    #[debug("{}", _0.as_slice().join("::"))]
-    IdentField(Vec<String>),
-    #[debug("{_0}")]
-    Pat(String),
-    #[debug("({_0}.)")]
-    PatOpt(String),
-    #[debug("({_0}:{_1:?})")]
-    PatRestr(String, Vec<SymExpr>),
-    #[debug("{_0}")]
-    Integer(u64),
-    #[debug("{_0}")]
-    Float(F64),
-    #[debug("\"{_0}\"")]
-    Str(String),
-    #[debug("#{}", _0.map(|i| i.to_string()).unwrap_or_default())]
-    Slot(Option<u64>),
-
-    #[debug("{_0:?}[{_1:?}, {_2:?}]")]
-    BinOp(OpKind, Box<Self>, Box<Self>),
-    #[debug("{_0:?}[{_1:?}]")]
-    UnryOp(OpKind, Box<Self>),
-
-    #[debug("{{{_0:?}}}")]
-    List(List),
-    #[debug("{_0:?}[[{_1:?}]]")]
-    Part(Box<SymExpr>, Vec<SymExpr>),
-    #[debug("{_0:?}")]
-    FuncDef(FuncDef),
-    #[debug("{_0:?}")]
-    FuncCall(FuncCall),
-    #[debug("{_0:?}[_1:?]")]
-    Call(Box<SymExpr>, Vec<SymExpr>),
 
-    #[debug("{_0:?}")]
-    Scope(Scope),
-    #[debug("MatchQ[{_0:?}, {_1:?}]")]
-    MatchQ(Box<SymExpr>, Pattern),
-    // a /. b -> c
-    //ReplaceAll(Box<SymExpr>, Box<SymExpr>, Box<SymExpr>),
-    #[debug("Compnd[{_0:?}]")]
-    Compnd(Vec<SymExpr>),
-}
-
-impl SymExpr {
-    fn for_each<F>(&self, f: F)
-    where
-        F: Fn(&SymExpr) + Copy,
-    {
+    fn expr(&mut self, e: &SymExpr) -> Result<TokenStream, String> {
+        use OpKind as O;
         use SymExpr as S;
-        f(self);
-        match self {
-            S::Compnd(e) | S::IdentRestr(_, e) | S::PatRestr(_, e) | S::List(List { args: e }) => {
-                e.iter().for_each(|e| e.for_each(f))
+        Ok(match e {
+            S::Ident(id) if self.vars.contains(id) => {
+                let v = var_ident(id);
+                quote!(#v.clone())
             }
-            S::BinOp(_, lhs, rhs) => {
-                lhs.for_each(f);
-                rhs.for_each(f);
+            S::Ident(id) => match id.as_str() {
+                "Pi" => quote!(WlfrmAtom::from(Expr::pi())),
+                "E" => quote!(WlfrmAtom::from(Expr::e())),
+                "I" => quote!(WlfrmAtom::from(Expr::i())),
+                "True" => quote!(WlfrmAtom::TRUE),
+                "False" => quote!(WlfrmAtom::FALSE),
+                _ => return Err(format!("unknown symbol: {id}")),
+            },
+            S::Integer(n) => {
+                let n = num_tokens(Num::new(*n as i64, 1));
+                quote!(WlfrmAtom::from(#n))
             }
-            S::UnryOp(_, e) => e.for_each(f),
-            S::Part(e, indx) => {
-                e.for_each(f);
-                indx.iter().for_each(|e| e.for_each(f));
+            S::UnryOp(O::Sub, e) => {
+                let e = self.expr(e)?;
+                Self::builtin("Minus", vec![e])
             }
-            S::FuncDef(FuncDef { call, pat }) => {
-                call.for_each(f);
-                pat.for_each(f);
+            S::BinOp(op @ (O::And | O::Or), l, r) => {
+                let (l, r) = (self.expr(l)?, self.expr(r)?);
+                match op {
+                    O::And => {
+                        quote!(WlfrmAtom::from(#l == WlfrmAtom::TRUE && #r == WlfrmAtom::TRUE))
+                    }
+                    _ => quote!(WlfrmAtom::from(#l == WlfrmAtom::TRUE || #r == WlfrmAtom::TRUE)),
+                }
             }
-            S::FuncCall(call) => call.for_each(f),
-            S::Call(e, args) => {
-                e.for_each(f);
-                args.iter().for_each(|e| e.for_each(f));
+            S::BinOp(op, l, r) => {
+                let name = match op {
+                    O::Add => "Plus",
+                    O::Sub => "Subtract",
+                    O::Mul => "Times",
+                    O::Div => "Divide",
+                    O::Pow => "Power",
+                    O::Eq => "EqQ",
+                    O::NEq => "NeQ",
+                    O::Lt => "LtQ",
+                    O::Ge => "GtQ",
+                    O::LtEq => "LeQ",
+                    O::GeEq => "GeQ",
+                    op => return Err(format!("unsupported operator: {op:?}")),
+                };
+                let args = vec![self.expr(l)?, self.expr(r)?];
+                Self::builtin(name, args)
             }
-            S::Scope(Scope { kind, vars, pat }) => {
-                vars.args.iter().for_each(|e| e.for_each(f));
-                pat.for_each(f);
+            S::List(List { args }) => {
+                let args = self.exprs(args)?;
+                quote!(WlfrmAtom::List(vec![#(#args),*]))
             }
-            S::MatchQ(e, p) => {
-                e.for_each(f);
-                p.for_each(f);
+            S::FuncCall(FuncCall { name, args }) => self.call(name, args)?,
+            S::Scope(Scope {
+                kind: ScopeKind::With,
+                vars,
+                pat,
+            }) => {
+                let n_vars = self.vars.len();
+                let mut lets = vec![];
+                for v in &vars.args {
+                    let S::BinOp(O::Assign, name, val) = v else {
+                        return Err(format!("unsupported With variable: {v:?}"));
+                    };
+                    let S::Ident(name) = &**name else {
+                        return Err(format!("unsupported With variable: {v:?}"));
+                    };
+                    let (id, val) = (var_ident(&var_name(name)?), self.expr(val)?);
+                    lets.push(quote!(let #id = #val;));
+                    self.vars.push(name.clone());
+                }
+                let body = self.pattern(pat);
+                self.vars.truncate(n_vars);
+                let body = body?;
+                quote!({ #(#lets)* #body })
             }
-            _ => (),
-        }
-    }
-}
-
-/*
-impl fmt::Debug for SymExpr {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        use SymExpr as S;
-        match self {
-            S::Ident(id) => write!(f, "Id({})", id),
-            S::IdentRestr(id, restr) => write!(f, "Id({id}, {restr:?})"),
-            S::IdentField(ids) => write!(f, "Id({})", ids.as_slice().join("::")),
-            S::Pat(pat) => write!(f, "Pat({})", pat),
-            S::PatOpt(pat) => write!(f, "PatOpt({})", pat),
-            S::PatRestr(pat, restr) => write!(f, "Pat({pat}, {restr:?})"),
-            S::Integer(i) => write!(f, "{}", i),
-            S::Float(v) => write!(f, "{}", v),
-            S::Str(str) => write!(f, "{}", str),
-            S::BinOp(op, lhs, rhs) => write!(f, "{op:?}[{rhs:?}, {lhs:?}]"),
-            S::UnryOp(op, val) => write!(f, "{op:?}[{val:?}]"),
-            S::List(l) => write!(f, "{{{l:?}}}"),
-            S::Part(list, indx) => write!(f, "{:?}[[{:?}]]", list, indx),
-            S::FuncDef(fd) => write!(f, "FuncDef[{:?}]", fd),
-            S::FuncCall(call) => write!(f, "Call[{:?}]", call),
-            S::Call(expr, args) => write!(f, "Call[{expr:?}[{args:?}]]"),
-            S::Scope(scope) => write!(f, "{scope:?}"),
-            S::MatchQ(expr, from) => write!(f, "MatchQ[{expr:?}, {from:?}]"),
-            //S::ReplaceAll(a, b, c) => write!(f, "ReplaceAll[{a:?} /. {b:?} -> {c:?}]"),
-            S::Compnd(exprs) => write!(f, "Compnd[{exprs:?}]"),
-            S::Slot(None) => write!(f, "#"),
-            S::Slot(Some(i)) => write!(f, "#{i}"),
-        }
-    }
-}
-*/
-
-impl SymExpr {
-    fn binary(op: &Wolfram, lhs: Self, rhs: Self) -> Self {
-        match OpKind::binary(op) {
-            Some(op) => Self::BinOp(op, lhs.into(), rhs.into()),
-            _ => error!("unexpected binary op: {:?}", op),
-        }
-    }
-
-    fn unary(op: &Wolfram, val: Self) -> Self {
-        match OpKind::unary(op) {
-            Some(op) => Self::UnryOp(op, val.into()),
-            _ => error!("unexpected binary op: {:?}", op),
-        }
-    }
-}
-
-#[derive(Debug, Default, Clone, Serialize, Deserialize)]
-struct WolframContext {
-    exprs: Vec<SymExpr>,
-    func_defs: HashMap<String, Vec<FuncDef>>,
-    func_calls: HashMap<String, Vec<FuncCall>>,
-}
-
-impl WolframContext {
-    fn push_expr(&mut self, e: SymExpr) {
-        self.exprs.push(e);
-    }
-
-    fn func_defs(&self) -> Vec<(String, Vec<FuncDef>)> {
-        //self.func_defs.keys().cloned().collect()
-        self.func_defs
-            .iter()
-            .map(|(a, b)| (a.clone(), b.iter().cloned().collect()))
-            .collect()
-    }
-
-    fn func_call(&mut self, fc: &FuncCall) {
-        self.func_calls
-            .entry(fc.name.clone())
-            .and_modify(|calls| {
-                calls.push(fc.clone());
-            })
-            .or_insert([fc.clone()].into_iter().collect());
-    }
-    fn func_def(&mut self, fd: &FuncDef) {
-        self.func_defs
-            .entry(fd.call.name.clone())
-            .and_modify(|calls| {
-                calls.push(fd.clone());
-            })
-            .or_insert([fd.clone()].into_iter().collect());
-    }
-
-    fn func_calls(&self) -> Vec<String> {
-        self.func_calls.keys().cloned().collect()
-    }
-
-    fn builtin_func_calls(&self) -> Vec<String> {
-        self.func_calls
-            .keys()
-            .filter(|fc| !self.func_defs.contains_key(*fc))
-            .cloned()
-            .collect()
-    }
-
-    fn register_expr(&mut self, e: &SymExpr) {
-        match e {
-            SymExpr::FuncDef(fd) => self.func_def(fd),
-            SymExpr::FuncCall(fc) => self.func_call(fc),
-            _ => (),
-        }
-    }
-}
-
-#[derive(Debug, Default, Clone, Serialize, Deserialize)]
-struct File {
-    name: String,
-    cntxt: WolframContext,
-}
-
-impl From<(String, WolframContext)> for File {
-    fn from(value: (String, WolframContext)) -> Self {
-        File {
-            name: value.0,
-            cntxt: value.1,
-        }
-    }
-}
-
-#[derive(Debug, Default, Clone, Serialize, Deserialize)]
-struct WolframFiles {
-    files: Vec<File>,
-}
-
-impl WolframFiles {
-    fn next_file(&mut self, name: impl Into<String>) {
-        self.files.push((name.into(), Default::default()).into())
-    }
-
-    fn current_file(&mut self) -> &mut File {
-        if self.files.is_empty() {
-            self.next_file("NO FILENAME");
-            self.files.last_mut().unwrap()
-        } else {
-            self.files.last_mut().unwrap()
-        }
-    }
-
-    fn current_cntxt(&mut self) -> &mut WolframContext {
-        &mut self.current_file().cntxt
-    }
-
-    fn push_expr(&mut self, e: SymExpr) {
-        self.register_expr(&e);
-        self.current_cntxt().push_expr(e)
-    }
-
-    fn register_expr(&mut self, e: &SymExpr) {
-        self.current_cntxt().register_expr(e)
-    }
-
-    fn func_defs_glob(&self) -> HashMap<String, Vec<FuncDef>> {
-        self.func_defs_from(0..self.files.len())
+            e => return Err(format!("unsupported expression: {e:?}")),
+        })
     }
 
-    fn func_defs_from(&self, span: ops::Range<usize>) -> HashMap<String, Vec<FuncDef>> {
-        let mut func_defs = HashMap::new();
-        for i in span {
-            for def in self.files[i].cntxt.func_defs() {
-                func_defs.insert(def.0, def.1);
+    /// evaluates to `WlfrmAtom::None` if the condition is not [True]
+    fn pattern(&mut self, pat: &Pattern) -> Result<TokenStream, String> {
+        let body = self.expr(&pat.pat)?;
+        Ok(match &pat.cond {
+            None => body,
+            Some(cond) => {
+                let cond = self.expr(cond)?;
+                quote!(if #cond == WlfrmAtom::TRUE { #body } else { WlfrmAtom::None })
             }
-        }
-        func_defs
-    }
-
-    fn builtin_fns_glob(&self) -> HashSet<String> {
-        self.builtin_fns_from(0..self.files.len())
+        })
     }
 
-    fn builtin_fns_from(&self, span: ops::Range<usize>) -> HashSet<String> {
-        let glob_defs = self.func_defs_glob();
-        let mut builtins = HashSet::new();
-        for i in span {
-            let f = &self.files[i];
-            for call in f.cntxt.func_calls() {
-                if !glob_defs.contains_key(&call) {
-                    builtins.insert(call);
+    /// a helper like `IntLinearQ[a_, b_, x_] := ...` whose arguments are plain patterns, the
+    /// definitions are chosen by the number of arguments
+    fn helper(defs: &[&FuncDef], helpers: &HashSet<String>) -> Result<TokenStream, String> {
+        let mut arms = vec![];
+        let mut arities = HashSet::new();
+        for fd in defs {
+            let mut names = vec![];
+            for a in &fd.call.args {
+                match a {
+                    SymExpr::Pat(p) => names.push(var_name(p.trim_end_matches('_'))?),
+                    SymExpr::Ident(id) if id.ends_with("_Symbol") => {
+                        names.push(var_name(id.trim_end_matches("_Symbol"))?)
+                    }
+                    a => return Err(format!("unsupported helper argument: {a:?}")),
+                }
+            }
+            if !arities.insert(names.len()) {
+                return Err(format!("overloaded helper: {}", fd.call.name));
+            }
+            let body = Codegen {
+                vars: names.clone(),
+                helpers,
+            }
+            .pattern(&fd.pat)?;
+            let (n, vars) = (names.len(), names.iter().map(|n| var_ident(n)));
+            arms.push(quote!(#n => {
+                let [#(#vars),*] = <[WlfrmAtom; #n]>::try_from(args).unwrap();
+                #body
+            }));
+        }
+        let f = proc_macro2::Ident::new(
+            &var_name(&defs[0].call.name)?,
+            proc_macro2::Span::call_site(),
+        );
+        Ok(quote! {
+            fn #f(args: WlfrmFuncArgs) -> WlfrmAtom {
+                let args = args.into_vec();
+                match args.len() {
+                    #(#arms)*
+                    _ => WlfrmAtom::None,
                 }
             }
-        }
-        builtins
-    }
-}
-
-struct Parser {
-    current: (Wolfram, ops::Range<usize>),
-    glob_cntxt: WolframFiles,
-    tok_count: usize,
-    token_iter: std::iter::Peekable<Box<dyn Iterator<Item = (Wolfram, ops::Range<usize>)>>>,
-}
-
-impl fmt::Debug for Parser {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("Parser")
-            .field("current", &self.current)
-            .field("context", &self.glob_cntxt)
-            .finish()
-    }
-}
-
-macro_rules! assert_tok {
-    ($t: ident, $e:expr) => {{
-        if !$t.check($e) {
-            let line = $t.slice_line();
-            let line_part = $t.slice(15);
-            error!(
-                "expected: {:?}, found: {:?}\nhere: {line_part}\nline: {line}",
-                $e,
-                $t.current()
-            );
-        } else {
-            $t.advance();
-        }
-    }};
-}
-
-impl Parser {
-    fn new(mut token_iter: impl Iterator<Item = (Wolfram, ops::Range<usize>)> + 'static) -> Self {
-        let current = token_iter.next().expect("empty iterator");
-        let token_iter: Box<dyn Iterator<Item = (Wolfram, ops::Range<usize>)>> =
-            Box::new(token_iter);
-        Self {
-            current,
-            glob_cntxt: Default::default(),
-            tok_count: 0,
-            token_iter: token_iter.peekable(),
-        }
-    }
-
-    fn next(&mut self) -> Option<&Wolfram> {
-        self.token_iter.next().map(|t| {
-            self.current = t;
-            self.tok_count += 1;
-            &self.current.0
         })
     }
 
-    fn skip_comment(&mut self) -> Option<&Wolfram> {
-        let mut n = self.current.0.clone();
-        while let Wolfram::Comment(c) = n {
-            if c.contains("FILE:") {
-                self.glob_cntxt.next_file(c)
+    /// an `Int[pat, x_Symbol] := rhs /; cond` rule, returns the rule function and the
+    /// `IntRule` constructor
+    fn rule(
+        fd: &FuncDef,
+        f: &proc_macro2::Ident,
+        name: &str,
+        helpers: &HashSet<String>,
+    ) -> Result<(TokenStream, TokenStream), String> {
+        let [integrand, SymExpr::Ident(x)] = fd.call.args.as_slice() else {
+            return Err(format!("unsupported rule: {:?}", fd.call));
+        };
+        let Some(x) = x.strip_suffix("_Symbol") else {
+            return Err(format!("unsupported integration variable: {x}"));
+        };
+        let x = var_name(x)?;
+        let pat = Pat::new(integrand)?;
+        let mut names = vec![x.clone()];
+        pat.names(&mut names);
+
+        let body = Codegen {
+            vars: names.clone(),
+            helpers,
+        }
+        .pattern(&fd.pat)?;
+        let vars = names.iter().map(|n| var_ident(n));
+        let pat = pat_tokens(&pat)?;
+        // every rule is built by its own function, one function building all of them would
+        // need a stack frame for the temporaries of every pattern in debug builds
+        let def = proc_macro2::Ident::new(&format!("{f}_def"), f.span());
+        let func = quote! {
+            fn #f(b: &Bindings) -> WlfrmAtom {
+                #(let #vars = WlfrmAtom::from(b[#names].clone());)*
+                #body
             }
-            n = self.next()?.clone();
-        }
-        Some(&self.current.0)
-    }
 
-    fn skip_comment_nl(&mut self) -> Option<&Wolfram> {
-        let mut n = self.current.0.clone();
-        while n.skippable() {
-            if let Wolfram::Comment(c) = n {
-                if c.contains("FILE:") {
-                    self.glob_cntxt.next_file(c)
+            fn #def() -> IntRule {
+                IntRule {
+                    name: #name,
+                    var: #x,
+                    pat: #pat,
+                    apply: #f,
                 }
             }
-            n = self.next()?.clone();
-        }
-        Some(&self.current.0)
-    }
-
-    fn advance(&mut self) {
-        self.next();
-        let _ = self.skip_comment();
-    }
-
-    fn peek(&mut self) -> Option<&Wolfram> {
-        self.token_iter.peek().map(|(t, _)| t)
-    }
-
-    fn check_peek(&mut self, t: &Wolfram) -> bool {
-        self.peek().is_some_and(|tok| tok == t)
-    }
-
-    fn reached_end(&mut self) -> bool {
-        self.token_iter.peek().is_none()
-    }
-
-    fn check(&mut self, expected: &Wolfram) -> bool {
-        if self.current() != expected {
-            false
-        } else {
-            true
-        }
-    }
-
-    fn slice_line(&self) -> &'static str {
-        let src = INTEGRATION_RULES;
-        let tok_span = self.current_span();
-        let line_start = src[..tok_span.start].rfind('\n').map_or(0, |pos| pos + 1);
-        let line_end = src[tok_span.end..]
-            .find('\n')
-            .map_or(src.len(), |pos| tok_span.end + pos);
-        &src[line_start..line_end]
-    }
-
-    fn slice(&self, len: usize) -> &'static str {
-        let src = INTEGRATION_RULES;
-        let mut span = self.current.1.clone();
-        span.start = std::cmp::max(0isize, span.start as isize - len as isize) as usize;
-        span.end = std::cmp::min(src.len(), span.end + len);
-        &src[span]
-    }
-
-    fn assert(&mut self, expected: &Wolfram) {
-        if self.current() != expected {
-            error!(
-                "found: {:?}, expected: {:?}\nline: {}",
-                self.current.0,
-                expected,
-                self.slice_line()
-            );
-        }
-        self.advance();
-    }
-
-    fn current(&self) -> &Wolfram {
-        &self.current.0
-    }
-
-    fn current_span(&self) -> &ops::Range<usize> {
-        &self.current.1
-    }
-}
-
-fn parse_func_def(call: FuncCall, t: &mut Parser) -> FuncDef {
-    use Wolfram as W;
-    assert_tok!(t, &W::Def);
-    let pat = parse_pattern(t);
-    let res = FuncDef { call, pat };
-    //t.cntxt.func_def(&res);
-    res
-}
-
-fn parse_scope(kind: ScopeKind, t: &mut Parser) -> Scope {
-    use Wolfram as W;
-    assert_tok!(t, &W::LBracket);
-    let vars = parse_list(t);
-    assert_tok!(t, &W::Comma);
-    let pat = parse_pattern(t);
-    assert_tok!(t, &W::RBracket);
-    Scope { kind, vars, pat }
-}
-
-fn parse_matchq(t: &mut Parser) -> SymExpr {
-    use Wolfram as W;
-    assert_tok!(t, &W::LBracket);
-    let expr = parse_expr(t);
-    assert_tok!(t, &W::Comma);
-    let pat = parse_pattern(t);
-    assert_tok!(t, &W::RBracket);
-    SymExpr::MatchQ(expr.into(), pat)
-}
-
-fn parse_pattern(t: &mut Parser) -> Pattern {
-    use Wolfram as W;
-    let pat = parse_expr(t).into();
-    let mut cond = None;
+        };
+        Ok((func, quote!(#def)))
+    }
+}
+
+/// generates a module `rubi_rules` with the function `integration_rules() -> Vec<IntRule>`
+///
+/// Only the rule files in one of the `sections` are compiled. Rules using a function that is
+/// neither a builtin nor defined by a helper rule are skipped. The generated code expects
+/// `Expr`, `Pattern`, `Bindings`, `IntRule` and the `Wlfrm*` items in the parent module
+pub fn integration_rules(sections: &[String]) -> TokenStream {
+    let rubi = parse(INTEGRATION_RULES).unwrap_or_else(|e| {
+        panic!(
+            "could not parse rubi: {e}\nline: {}",
+            e.line(INTEGRATION_RULES)
+        )
+    });
+    let files: Vec<_> = rubi
+        .files
+        .iter()
+        .filter(|f| sections.iter().any(|s| f.in_section(s)))
+        .collect();
+
+    let defs = || {
+        files.iter().flat_map(|f| {
+            f.cntxt.exprs.iter().filter_map(move |e| match e {
+                SymExpr::FuncDef(fd) => Some((*f, fd)),
+                _ => None,
+            })
+        })
+    };
 
-    if t.check(&W::Cond) {
-        assert_tok!(t, &W::Cond);
-        cond = Box::new(parse_expr(t)).into();
+    let mut helper_defs: BTreeMap<&str, Vec<&FuncDef>> = BTreeMap::new();
+    for (_, fd) in defs().filter(|(_, fd)| fd.call.name != "Int") {
+        helper_defs.entry(&fd.call.name).or_default().push(fd);
     }
-    Pattern { pat, cond }
-}
-
-fn parse_args(t: &mut Parser) -> Vec<SymExpr> {
-    use Wolfram as W;
-    let mut args = vec![];
-    assert_tok!(t, &W::LBracket);
+    // helpers can call each other, add them until no more can be compiled
+    let mut helpers = HashSet::new();
+    let mut helper_fns = vec![];
     loop {
-        if t.check(&W::RBracket) {
-            break;
-        }
-        let arg = parse_expr(t);
-        args.push(arg);
-        if t.check(&W::RBracket) {
-            break;
-        }
-        assert_tok!(t, &W::Comma)
-    }
-    assert_tok!(t, &W::RBracket);
-    args
-}
-
-fn parse_call(name: String, t: &mut Parser) -> SymExpr {
-    use Wolfram as W;
-
-    if let Ok(sk) = ScopeKind::try_from(name.as_str()) {
-        return SymExpr::Scope(parse_scope(sk, t));
-    } else if name == "MatchQ" {
-        return parse_matchq(t);
-    }
-
-    let args = parse_args(t);
-    let call = FuncCall { name, args };
-    if t.check(&W::Def) {
-        let fd = SymExpr::FuncDef(parse_func_def(call, t));
-        //t.glob_cntxt.register_expr(&fd);
-        fd
-    } else {
-        //t.cntxt.func_call(&call);
-        let fc = SymExpr::FuncCall(call);
-        t.glob_cntxt.register_expr(&fc);
-        fc
-    }
-}
-
-fn parse_ident(str: String, t: &mut Parser) -> SymExpr {
-    use Wolfram as W;
-    if str.ends_with('_') {
-        // is a pattern
-        if t.check(&W::Dot) {
-            t.advance();
-            SymExpr::PatOpt(str)
-        } else if t.check(&W::Colon) {
-            t.advance();
-            let r = parse_expr(t);
-            SymExpr::PatRestr(str, vec![r])
-        } else {
-            SymExpr::Pat(str)
-        }
-    } else if t.check(&W::Colon) {
-        t.advance();
-        let r = parse_expr(t);
-        SymExpr::IdentRestr(str, vec![r])
-    } else if t.check(&W::DoublColon) {
-        let mut ids = vec![str];
-        loop {
-            assert_tok!(t, &W::DoublColon);
-            if let W::Ident(id) = t.current() {
-                ids.push(id.clone());
-            } else {
-                error!("expected identifier, found: {:?}", t.current());
+        let n_helpers = helpers.len();
+        for (name, defs) in &helper_defs {
+            if helpers.contains(*name) {
+                continue;
             }
-            if !t.check(&W::DoublColon) {
-                break;
+            if let Ok(f) = Codegen::helper(defs, &helpers) {
+                helpers.insert(name.to_string());
+                helper_fns.push(f);
             }
         }
-        SymExpr::IdentField(ids)
-    } else {
-        SymExpr::Ident(str)
-    }
-}
-
-fn parse_list(t: &mut Parser) -> List {
-    use Wolfram as W;
-    let mut args = vec![];
-    assert_tok!(t, &W::LBrace);
-    loop {
-        if t.check(&W::RBrace) {
-            break;
-        }
-        let arg = parse_expr(t);
-        args.push(arg);
-        t.skip_comment_nl();
-        if t.check(&W::RBrace) {
+        if helpers.len() == n_helpers {
             break;
         }
-        assert_tok!(t, &W::Comma)
     }
-    assert_tok!(t, &W::RBrace);
-    List { args }
-}
-
-//fn parse_opt_mul(lhs: SymExpr, t: &mut Parser) -> SymExpr {
-//    use Wolfram as W;
-//    match t.current() {
-//        W::LParen | W::Integer(_) | W::Ident(_) => {
-//            let rhs = parse_operand(t);
-//            SymExpr::binary(&W::Mul, lhs, rhs)
-//        }
-//        _ => lhs,
-//    }
-//}
 
-fn parse_operand(t: &mut Parser) -> SymExpr {
-    use SymExpr as SE;
-    use Wolfram as W;
-
-    match t.current().clone() {
-        W::Ident(v) => {
-            t.advance();
-            if t.check(&W::LBracket) && !t.check_peek(&W::LBracket) {
-                parse_call(v, t)
-            } else {
-                parse_ident(v, t)
-            }
+    let mut rule_fns = vec![];
+    let mut rules = vec![];
+    let mut file_rules = HashMap::new();
+    for (file, fd) in defs().filter(|(_, fd)| fd.call.name == "Int") {
+        let n = file_rules.entry(file.title()).or_insert(0);
+        *n += 1;
+        let name = format!("{}: {n}", file.title());
+        let f = proc_macro2::Ident::new(
+            &format!("rule_{}", rule_fns.len()),
+            proc_macro2::Span::call_site(),
+        );
+        if let Ok((func, rule)) = Codegen::rule(fd, &f, &name, &helpers) {
+            rule_fns.push(func);
+            rules.push(rule);
         }
-        W::Str(v) => {
-            t.advance();
-            SE::Str(v)
-        }
-        W::Integer(v) => {
-            t.advance();
-            SE::Integer(v)
-        }
-        W::Float(v) => {
-            t.advance();
-            SE::Float(v.into())
-        }
-        //W::Float(v) => {
-        //    t.advance();
-        //    SE::Float(v)
-        //}
-        W::LParen => {
-            t.advance();
-            let expr = parse_expr(t);
-            assert_tok!(t, &W::RParen);
-            expr
-            //let expr = parse_expr(t);
-            //if !t.check(&W::RParen) {
-            //    let expr = parse_opt_mul(expr, t);
-            //    assert_tok!(t, &W::RParen);
-            //    expr
-            //} else {
-            //    assert_tok!(t, &W::RParen);
-            //    let expr = parse_opt_mul(expr, t);
-            //    expr
-            //}
-        }
-        W::LBrace => SE::List(parse_list(t)),
-        W::Add => {
-            t.advance();
-            parse_expr(t)
-        }
-        W::Sub => {
-            t.advance();
-            let expr = parse_bin_expr(t, W::Mul.op_prec() + 1);
-            SE::unary(&W::Sub, expr)
-        }
-        W::Slot => {
-            t.advance();
-            if let W::Integer(i) = t.current().clone() {
-                t.advance();
-                SE::Slot(i.into())
-            } else {
-                SE::Slot(None)
-            }
-        }
-        tok => {
-            if tok.skippable() {
-                t.skip_comment_nl();
-                parse_operand(t)
-            } else {
-                error!(
-                    "unexpected token: {:?}\nhere: {}\nline: {}",
-                    tok,
-                    t.slice(15),
-                    t.slice_line()
-                )
-            }
-        }
-    }
-}
-
-fn parse_unary(expr: SymExpr, t: &mut Parser) -> SymExpr {
-    use Wolfram as W;
-    //let expr = parse_operand(t);
-    let unary = if t.check(&W::LBracket) && t.check_peek(&W::LBracket) {
-        assert_tok!(t, &W::LBracket);
-        //assert_tok!(t, &W::LBracket);
-        let indx = parse_args(t);
-        //assert_tok!(t, &W::RBracket);
-        assert_tok!(t, &W::RBracket);
-        SymExpr::Part(expr.into(), indx.into())
-    } else if t.check(&W::LBracket) {
-        let args = parse_args(t);
-        SymExpr::Call(expr.into(), args)
-    } else if t.check(&W::Not) {
-        assert_tok!(t, &W::Not);
-        SymExpr::UnryOp(OpKind::Fac, expr.into())
-    } else if t.check(&W::Deriv) {
-        assert_tok!(t, &W::Deriv);
-        SymExpr::UnryOp(OpKind::Deriv, expr.into())
-    } else {
-        return expr;
-    };
-    parse_unary(unary, t)
-}
-
-fn parse_compl_expr(t: &mut Parser) -> SymExpr {
-    let expr = parse_operand(t);
-    parse_unary(expr, t)
-}
-
-fn parse_compound(t: &mut Parser) -> SymExpr {
-    use Wolfram as W;
-    let expr = parse_compl_expr(t);
-    if !t.check(&W::Semicolon) {
-        return expr;
     }
 
-    let mut exprs = vec![expr];
-    while t.check(&W::Semicolon) {
-        assert_tok!(t, &W::Semicolon);
-        let e = parse_compl_expr(t);
-        exprs.push(e)
-    }
-    SymExpr::Compnd(exprs)
-}
+    quote! {
+        #[allow(non_snake_case, unused_variables, clippy::all)]
+        mod rubi_rules {
+            use super::*;
 
-//fn get_rhs_for_mul(e: SymExpr) -> (Option<SymExpr>, SymExpr) {
-//    use OpKind as O;
-//    use SymExpr as S;
-//    match e {
-//        S::BinOp(O::Mul | O::Add | O::Sub, lhs, rhs) => (Some(lhs), rhs),
-//        lhs => (None, lhs),
-//    }
-//}
+            #(#helper_fns)*
 
-fn parse_bin_expr(t: &mut Parser, prec_in: u32) -> SymExpr {
-    use Wolfram as W;
-    let mut lhs = parse_compound(t);
-    loop {
-        let mut op = t.current().clone();
+            #(#rule_fns)*
 
-        match &op {
-            W::Ident(_) => {
-                //if p.is_some_and(|p| p.is_op() || p == &W::LBracket) {
-                op = W::Mul;
-                if op.op_prec() < prec_in {
-                    break;
-                }
-                //} else {
-                //    break;
-                //}
-            }
-            W::LParen => {
-                op = W::Mul;
-                if op.op_prec() < prec_in {
-                    break;
-                }
-            }
-            _ => {
-                if op.op_prec() < prec_in {
-                    break;
-                }
-                t.advance();
+            pub(super) fn integration_rules() -> Vec<IntRule> {
+                let rules: &[fn() -> IntRule] = &[#(#rules),*];
+                rules.iter().map(|rule| rule()).collect()
             }
         }
-        let rhs = parse_bin_expr(t, op.op_prec() + 1);
-        lhs = SymExpr::binary(&op, lhs, rhs);
-    }
-
-    lhs
-}
-
-fn parse_expr(t: &mut Parser) -> SymExpr {
-    //t.skip_comment_nl();
-    parse_bin_expr(t, 0 + 1)
-}
-
-pub fn load_rubi() {
-    let lexer = Wolfram::lexer(INTEGRATION_RULES);
-    let tokens = lexer.spanned().map(|(tok, span)| {
-        if let Ok(tok) = tok {
-            (tok, span)
-        } else {
-            panic!("could not lex: {:?}", INTEGRATION_RULES.slice(span));
-        }
-    });
-
-    let mut t = Parser::new(tokens);
-    //let mut count = 0;
-    loop {
-        let expr = parse_expr(&mut t);
-        t.glob_cntxt.push_expr(expr);
-        t.skip_comment_nl();
-        if t.reached_end() {
-            break;
-        }
-        //assert_tok!(t, &Wolfram::NL);
-    }
-
-    let mut buf = String::new();
-    let defs = t.glob_cntxt.func_defs_from(0..1);
-
-    for def in defs {
-        buf += &format!("DEFINE {}:\n", def.0);
-        for func in def.1 {
-            buf += &format!("{:?}\n", func);
-        }
     }
-    println!("{buf}");
-    //assert!(false, "{buf}");
 }
@@ -1,7 +1,8 @@
 use std::{borrow::Borrow, cmp, ops, slice};
 
 use crate::{
-    atom::{Atom, Expr, Func, Pow, Prod, Real, Sum, SymbolicExpr},
+    atom::{Atom, Expr, Func, Infinity, Pow, Prod, Real, Rel, RelOp, Sum, SymbolicExpr},
+    float::Float,
     rational::{binomial_coeff, Int, Rational},
     utils::HashSet,
 };
@@ -30,10 +31,28 @@ impl Sum {
             A::Sum(sum) => {
                 sum.args.iter().for_each(|a| self.add_rhs(a));
             }
-            A::Rational(r1) => {
+            A::Infinity(inf) => {
+                if let Some(indx) = self.args.iter().position(|a| a.is_infinity()) {
+                    // oo - oo = undef
+                    match self.args[indx].unwrap_infinity_ref().checked_add(*inf) {
+                        Some(res) => self.args[indx] = res.into(),
+                        None => {
+                            self.args.clear();
+                            self.args.push(Expr::undef());
+                        }
+                    }
+                } else {
+                    // oo + r = oo
+                    self.args.retain(|a| !a.is_number());
+                    self.args.push(rhs.clone());
+                }
+            }
+            A::Rational(_) | A::Irrational(_) | A::Float(_)
+                if self.args.iter().any(|a| a.is_infinity()) => {}
+            A::Rational(_) | A::Float(_) => {
                 for a in &mut self.args {
-                    if let A::Rational(r2) = a.atom() {
-                        *a = A::Rational(r1.clone() + r2).into();
+                    if let Some(res) = Expr::add_num(a, rhs) {
+                        *a = res;
                         return;
                     }
                 }
@@ -84,11 +103,28 @@ impl Sum {
     }
 
     fn cmp_args(lhs: &Expr, rhs: &Expr) -> cmp::Ordering {
-        match (lhs.atom(), rhs.atom()) {
-            (Atom::Prod(p1), Atom::Prod(p2)) => p1.cmp(p2),
-            (Atom::Var(a), Atom::Var(b)) => a.cmp(b),
-            _ => rhs.cmp(lhs),
+        // like terms have to be next to each other for [Sum::merge_args] to combine them,
+        // so terms are ordered by their factors without the coefficient first
+        fn term(e: &Expr) -> &[Expr] {
+            match e.atom() {
+                Atom::Prod(Prod { args }) if args.len() > 1 && args[0].is_rational() => &args[1..],
+                Atom::Prod(Prod { args }) => args,
+                _ => std::slice::from_ref(e),
+            }
+        }
+        fn cmp_factor(lhs: &Expr, rhs: &Expr) -> cmp::Ordering {
+            match (lhs.atom(), rhs.atom()) {
+                (Atom::Var(a), Atom::Var(b)) => a.cmp(b),
+                _ => rhs.cmp(lhs),
+            }
         }
+        let (l, r) = (term(lhs), term(rhs));
+        l.iter()
+            .zip(r)
+            .map(|(l, r)| cmp_factor(l, r))
+            .find(|o| o.is_ne())
+            .unwrap_or(l.len().cmp(&r.len()))
+            .then_with(|| cmp_factor(lhs, rhs))
     }
 
     fn add_sorted(lhs: &Expr, rhs: &Expr) -> Sum {
@@ -99,8 +135,8 @@ impl Sum {
             std::mem::swap(&mut lhs, &mut rhs)
         }
         let mut sum = Sum::zero();
-        sum.add_rhs(&lhs);
-        sum.add_rhs(&rhs);
+        sum.add_rhs(lhs);
+        sum.add_rhs(rhs);
         sum
     }
 
@@ -179,6 +215,31 @@ impl Sum {
         }
     }
 
+    /// collects all imaginary terms into a single term
+    ///
+    /// a + b*i + c*i => a + (b + c)*i
+    pub(crate) fn collect_imag(&mut self) {
+        let n_imag = self
+            .args
+            .iter()
+            .filter(|a| a.try_as_imag_term().is_some())
+            .count();
+        if n_imag < 2 {
+            return;
+        }
+
+        let mut coeff = Expr::zero();
+        self.args.retain(|a| match a.try_as_imag_term() {
+            Some(b) => {
+                coeff += b;
+                false
+            }
+            None => true,
+        });
+        let imag = Prod::reduce_rec(&[coeff, Expr::i()]);
+        self.add_rhs(&imag.reduce());
+    }
+
     pub(crate) fn reduce_rec(args: &[Expr]) -> Sum {
         //println!("reduce_rec: {args:?}");
         let res = if args.len() < 2 {
@@ -259,6 +320,14 @@ impl Prod {
             return;
         }
 
+        let inf_indx = self.args.iter().position(|a| a.is_infinity());
+        if (inf_indx.is_some() && rhs.is_zero()) || (self.is_zero() && rhs.is_infinity()) {
+            // 0 * oo = undef
+            self.args.clear();
+            self.args.push(Expr::undef());
+            return;
+        }
+
         if self.is_undef() || self.is_zero() || rhs.is_one() {
             return;
         } else if rhs.is_undef() || rhs.is_zero() {
@@ -271,14 +340,39 @@ impl Prod {
             A::Prod(prod) => {
                 prod.args.iter().for_each(|a| self.mul_rhs(a));
             }
-            A::Rational(r1) => {
+            A::Infinity(inf) => {
+                // infinities absorb all real numbers, e.g -2 * oo = -oo
+                let mut inf = *inf;
+                self.args.retain(|a| match a.atom() {
+                    A::Infinity(rhs) => {
+                        inf = inf * *rhs;
+                        false
+                    }
+                    A::Rational(_) | A::Float(_) if a.is_neg() => {
+                        inf = -inf;
+                        false
+                    }
+                    A::Rational(_) | A::Irrational(_) | A::Float(_) => false,
+                    _ => true,
+                });
+                self.args.insert(0, inf.into());
+            }
+            A::Rational(_) | A::Irrational(_) | A::Float(_) if inf_indx.is_some() => {
+                if rhs.is_neg() {
+                    let indx = inf_indx.unwrap();
+                    let inf = -*self.args[indx].unwrap_infinity_ref();
+                    self.args[indx] = inf.into();
+                }
+            }
+            A::Rational(_) | A::Float(_) => {
                 for a in &mut self.args {
-                    if let A::Rational(r2) = a.atom() {
-                        *a = A::Rational(r1.clone() * r2).into();
+                    if let Some(res) = Expr::mul_num(a, rhs) {
+                        *a = res;
                         return;
                     }
                 }
-                self.args.push(rhs.clone())
+                // numbers are kept at the front, e.g x * 0.5 => 0.5x
+                self.args.insert(0, rhs.clone())
             }
             A::Pow(pow) => {
                 if pow.base().is_prod() {
@@ -299,29 +393,74 @@ impl Prod {
                         let rem = Expr::pow(remainder.remove(0), expon);
                         self.args.push(rem)
                     } else if remainder.len() > 1 {
-                        let rem = Expr::pow(&Prod { args: remainder }.into(), expon);
+                        let rem = Expr::pow(Expr::from(Prod { args: remainder }), expon);
                         self.args.push(rem)
                     }
-                } else if let Some(arg) = self.args.iter_mut().find(|a| &a.base() == pow.base()) {
-                    *arg = Expr::pow(arg.base(), arg.exponent() + pow.exponent())
-                } else {
+                } else if !self.merge_pow(pow.base(), pow.exponent()) {
                     self.args.push(rhs.clone())
                 }
             }
             _ => {
-                if let Some(arg) = self.args.iter_mut().find(|a| a.base() == rhs.base()) {
-                    *arg = Expr::pow(arg.base(), arg.exponent() + rhs.exponent())
-                } else {
+                if !self.merge_pow(&rhs.base(), &rhs.exponent()) {
                     self.args.push(rhs.clone())
                 }
             }
         }
     }
 
+    /// multiplies base^exponent into the argument with the same base
+    ///
+    /// returns false if no such argument exists
+    fn merge_pow(&mut self, base: &Expr, exponent: &Expr) -> bool {
+        let Some(indx) = self.args.iter().position(|a| &a.base() == base) else {
+            return false;
+        };
+        if Expr::is_radical_coeff(&self.args[indx], &Expr::pow(base, exponent)) {
+            return false;
+        }
+
+        let arg = self.args.remove(indx);
+        let merged = Expr::pow(arg.base(), arg.exponent() + exponent);
+        // the merged power can become rational, e.g i * i = -1
+        if merged.is_rational() {
+            self.mul_rhs(&merged)
+        } else {
+            self.args.insert(indx, merged)
+        }
+        true
+    }
+
     fn cmp_args(lhs: &Expr, rhs: &Expr) -> cmp::Ordering {
         lhs.cmp(rhs)
     }
 
+    /// multiplies all complex numerals into a single one
+    ///
+    /// (1 + i) * x * (1 - i) => 2x
+    pub(crate) fn collect_complex(&mut self) {
+        let n_complex = self
+            .args
+            .iter()
+            .filter(|a| a.try_as_complex_numeral().is_some())
+            .count();
+        if n_complex < 2 {
+            return;
+        }
+
+        let mut z = (Rational::ONE, Rational::ZERO);
+        self.args.retain(|a| match a.try_as_complex_numeral() {
+            Some(w) => {
+                z = Expr::mul_complex(&z, &w);
+                false
+            }
+            None => true,
+        });
+        let rest = Expr::from(Prod {
+            args: std::mem::take(&mut self.args),
+        });
+        *self = Prod::reduce_rec(&[Expr::complex_numeral(z), rest]);
+    }
+
     fn mul_sorted(lhs: &Expr, rhs: &Expr) -> Prod {
         let mut lhs = lhs;
         let mut rhs = rhs;
@@ -329,8 +468,8 @@ impl Prod {
             std::mem::swap(&mut lhs, &mut rhs)
         }
         let mut mul = Prod::one();
-        mul.mul_rhs(&lhs);
-        mul.mul_rhs(&rhs);
+        mul.mul_rhs(lhs);
+        mul.mul_rhs(rhs);
         mul
     }
 
@@ -568,16 +707,27 @@ impl Pow {
             }
             (A::Sum(sum), A::Rational(r)) if r > &Rational::ONE && sum.args.len() > 1 => {
                 let (div, rem) = r.div_rem();
-                return expand_mul(
-                    &expand_pow(self.base(), &Expr::from(div)),
-                    &expand_pow(self.base(), &Expr::from(rem)),
-                );
+                let int_pow = expand_pow(self.base(), &Expr::from(div));
+                let frac_pow = expand_pow(self.base(), &Expr::from(rem));
+                // distribute termwise, (a + b) * (a + b)^(1/2) would merge back into (a + b)^(3/2)
+                return match int_pow.atom() {
+                    A::Sum(Sum { args }) => args
+                        .iter()
+                        .map(|t| expand_mul(t, &frac_pow))
+                        .fold(Expr::zero(), |sum, t| sum + t),
+                    _ => expand_mul(&int_pow, &frac_pow),
+                };
             }
             (A::Prod(_), _) => {
-                return self
+                let prod = self
                     .base()
                     .clone()
                     .map_args(|a| *a = expand_pow(a, self.exponent()));
+                // the factors can expand into sums, e.g (c*(a + b))^2 = c^2 * (a^2 + 2ab + b^2)
+                return match recurse {
+                    true => prod.expand_main_op(),
+                    false => prod,
+                };
                 //return args
                 //    .iter()
                 //    .map(|a| expand_pow(a, self.exponent()))
@@ -596,8 +746,8 @@ impl Pow {
         //let b = A::Sum(Sum { args }).into();
 
         let mut res = Sum::zero();
-        for k in 0..=e {
-            let rhs = if k == 0 {
+        for k in Int::range_inclusive(Int::ZERO, e.clone()) {
+            let rhs = if k.is_zero() {
                 // 1 * a^exp
                 expand_pow(&a, &exp)
             } else if k == e {
@@ -605,7 +755,7 @@ impl Pow {
                 expand_pow(&b, &Expr::from(k.clone()))
             } else {
                 // a^k + b^(exp-k)
-                let c = binomial_coeff(e, k);
+                let c = binomial_coeff(&e, &k);
                 let k_e = Expr::from(k.clone());
 
                 expand_mul(
@@ -635,15 +785,17 @@ impl Expr {
 
         if lhs.is_undef() || rhs.is_undef() {
             return A::Undef.into();
+        } else if let Some(res) = Expr::add_rel(lhs, rhs) {
+            return res;
         } else if lhs.is_zero() {
             return rhs.clone();
         } else if rhs.is_zero() {
             return lhs.clone();
         }
 
-        match (lhs.atom(), rhs.atom()) {
-            (A::Rational(r1), A::Rational(r2)) => A::Rational(*r1 + r2).into(),
-            (_, _) => {
+        match Expr::add_num(lhs, rhs) {
+            Some(res) => res,
+            None => {
                 let mut sum = Sum::zero();
                 sum.add_rhs(lhs);
                 sum.add_rhs(rhs);
@@ -661,24 +813,32 @@ impl Expr {
         use Atom as A;
         let (lhs, rhs): (&Expr, &Expr) = (lhs.borrow(), rhs.borrow());
 
-        if lhs.is_undef() || rhs.is_undef() {
-            return Expr::undef();
+        if lhs.is_undef()
+            || rhs.is_undef()
+            || (lhs.is_zero() && rhs.is_infinity())
+            || (lhs.is_infinity() && rhs.is_zero())
+        {
+            Expr::undef()
+        } else if let Some(res) = Expr::mul_rel(lhs, rhs) {
+            res
         } else if lhs.is_zero() || rhs.is_zero() {
-            return Expr::zero();
+            Expr::zero()
         } else if lhs.is_one() {
             rhs.clone()
         } else if rhs.is_one() {
             lhs.clone()
-        } else if let (Ok(r1), Ok(r2)) =
-            (lhs.try_unwrap_rational_ref(), rhs.try_unwrap_rational_ref())
-        {
-            (r1.clone() * r2).into()
-        } else if lhs.base() == rhs.base() {
+        } else if let Some(res) = Expr::mul_num(lhs, rhs) {
+            res
+        } else if lhs.base() == rhs.base() && !Expr::is_radical_coeff(lhs, rhs) {
             Expr::pow(lhs.base(), lhs.exponent() + rhs.exponent())
         } else {
             let mut prod = Prod::one();
             prod.mul_rhs(lhs);
             prod.mul_rhs(rhs);
+            if prod.args.len() == 1 {
+                // e.g 2 * oo = oo
+                return prod.args.remove(0);
+            }
             A::Prod(prod).into()
         }
 
@@ -705,7 +865,7 @@ impl Expr {
 
         if lhs.is_undef() || rhs.is_undef() {
             Expr::undef()
-        } else if lhs == rhs {
+        } else if lhs == rhs && !lhs.is_zero() && !lhs.is_infinity() {
             Expr::one()
         } else {
             let min_one = Expr::from(-1);
@@ -737,6 +897,11 @@ impl Expr {
         Atom::Sum(prod).into()
     }
 
+    /// rational coefficients are kept apart from radicals of the same base, e.g 2 * 2^(1/2)
+    fn is_radical_coeff(lhs: &Expr, rhs: &Expr) -> bool {
+        (lhs.is_rational() || rhs.is_rational()) && !(lhs.exponent() + rhs.exponent()).is_int()
+    }
+
     pub fn mul_raw(lhs: impl Borrow<Expr>, rhs: impl Borrow<Expr>) -> Expr {
         let (lhs, rhs) = (lhs.borrow(), rhs.borrow());
 
@@ -762,12 +927,15 @@ impl Expr {
 
         let (base, exponent) = (base.borrow(), exponent.borrow());
 
-        if base.is_undef()
-            || exponent.is_undef()
-            || (base.is_zero() && exponent.is_zero())
-            || (base.is_zero() && exponent.is_neg())
-        {
+        if base.is_undef() || exponent.is_undef() || (base.is_zero() && exponent.is_zero()) {
             return Expr::undef();
+        } else if base.is_zero() && exponent.is_neg() {
+            // division by zero
+            return Expr::complex_inf();
+        } else if let Some(res) = Expr::pow_inf(base, exponent) {
+            return res;
+        } else if let Some(res) = Expr::pow_float(base, exponent) {
+            return res;
         }
 
         if base.is_one() {
@@ -779,13 +947,20 @@ impl Expr {
         }
         match (base.atom(), exponent.atom()) {
             (A::Rational(b), A::Rational(e)) if b.is_int() && e.is_int() => {
-                let (pow, rem) = b.clone().pow(e.clone());
-                assert!(rem.is_zero());
-                Expr::from(pow)
+                match b.clone().pow_basic(e.clone()) {
+                    Some(pow) => Expr::from(pow),
+                    // too large, see [Rational::pow]
+                    None => Expr::pow_raw(base, exponent),
+                }
             }
             (A::Pow(pow), A::Rational(e)) if e.is_int() => {
                 Expr::pow(pow.base(), exponent * pow.exponent())
             }
+            (A::ImagUnit, A::Rational(e)) if e.is_int() => Expr::i_pow(&e.numer()),
+            (A::Rational(b), A::Rational(e)) if b.is_neg() && e.denom() == Int::TWO => {
+                // (-b)^(p/2) = b^(p/2) * i^p
+                Expr::pow(Expr::from(b.clone().abs()), exponent) * Expr::i_pow(&e.numer())
+            }
 
             //(A::Pow(pow), A::Rational(e2)) if e2.is_int() => {
             //    println!("pow: {base:?}, {exponent:?}");
@@ -810,7 +985,9 @@ impl Expr {
 
         match self.atom() {
             A::Undef => self.clone(),
-            A::Irrational(_) | A::Rational(_) => Expr::zero(),
+            A::Irrational(_) | A::Rational(_) | A::Float(_) | A::ImagUnit | A::Infinity(_) => {
+                Expr::zero()
+            }
             A::Sum(Sum { args }) => {
                 let mut res = Sum::zero();
                 args.iter()
@@ -849,6 +1026,12 @@ impl Expr {
                 }
             }
             A::Func(f) => f.derivative(x),
+            // d/dx (a = b) => da/dx = db/dx
+            A::Rel(rel) if rel.op == RelOp::Eq => {
+                Expr::rel(RelOp::Eq, rel.lhs().derivative(x), rel.rhs().derivative(x))
+            }
+            A::List(list) => Expr::from(list.iter().map(|a| a.derivative(x)).collect::<Vec<_>>()),
+            A::Bool(_) | A::Rel(_) | A::Logic(_) => Expr::undef(),
         }
     }
 }
@@ -868,6 +1051,7 @@ impl CostFn for ExprCost {
 
 impl Expr {
     pub fn simplify(&self) -> Vec<Self> {
+        #[allow(clippy::type_complexity)]
         let operations: Vec<(fn(&Expr) -> Expr, &'static str)> = vec![
             (Self::expand, "expand"),
             //(Self::expand_main_op, "expand_main_op"),
@@ -890,7 +1074,7 @@ impl Expr {
 
         let mut steps = 0;
         while let Some(expr) = todo.pop() {
-            for (op, name) in &operations {
+            for (op, _name) in &operations {
                 let e = op(&expr).reduce();
                 //println!("{name}: {expr} -> {e}");
 
@@ -929,8 +1113,15 @@ impl Expr {
             A::Prod(prod) => prod.distribute(),
             A::Pow(pow) => pow.expand_pow_rec(false),
             A::Sum(sum) if sum.n_args() == 1 => sum.args()[0].expand_main_op(),
-            A::Irrational(_) | A::Undef | A::Rational(_) | A::Var(_) | A::Sum(_) => self.clone(),
-            A::Func(_) => self.clone(),
+            A::Irrational(_)
+            | A::Float(_)
+            | A::ImagUnit
+            | A::Infinity(_)
+            | A::Undef
+            | A::Rational(_)
+            | A::Var(_)
+            | A::Sum(_) => self.clone(),
+            A::Func(_) | A::Bool(_) | A::Rel(_) | A::Logic(_) | A::List(_) => self.clone(),
         }
     }
 
@@ -989,8 +1180,8 @@ impl Expr {
         let e = self.clone().map_args(|a| *a = a.expand_trig());
         if let Ok(f) = e.try_unwrap_func_ref() {
             match f {
-                Func::Sin(phi) => expand_trig_arg(&phi).0,
-                Func::Cos(phi) => expand_trig_arg(&phi).1,
+                Func::Sin(phi) => expand_trig_arg(phi).0,
+                Func::Cos(phi) => expand_trig_arg(phi).1,
                 _ => e,
             }
         } else {
@@ -1019,7 +1210,7 @@ impl Expr {
         let e = self.clone().map_args(|a| *a = a.expand_ln());
         if let Ok(log) = e.try_unwrap_func_ref() {
             match log {
-                Func::Log(Real::E, x) => expand_ln_arg(&x),
+                Func::Log(Real::E, x) => expand_ln_arg(x),
                 _ => e,
             }
         } else {
@@ -1082,7 +1273,7 @@ impl Expr {
             //}
             (A::Rational(r1), A::Rational(r2)) => match r1.int_gcd(r2) {
                 Some(gcd) => {
-                    let rgcd = Rational::from(gcd);
+                    let rgcd = gcd;
                     let l = r1.clone() / &rgcd;
                     let r = r2.clone() / &rgcd;
                     (rgcd.into(), (l.into(), r.into()))
@@ -1092,7 +1283,7 @@ impl Expr {
             //(A::Rational(r1), A::Rational(r2)) if r1.is_int() && r2.is_int() => {
             //    let (i1, i2) = (r1.to_int().unwrap(), r2.to_int().unwrap());
             //    let gcd = i1.gcd(&i2);
-            //    let rgcd = Rational::from(gcd);
+            //    let rgcd = gcd;
             //    let l = r1.clone() / &rgcd;
             //    let r = r2.clone() / &rgcd;
             //    (rgcd.into(), (l.into(), r.into()))
@@ -1246,6 +1437,9 @@ impl Expr {
     pub fn cancel(&self) -> Expr {
         let n = self.numerator();
         let d = self.denominator();
+        if let Some(res) = Self::cancel_poly(&n, &d) {
+            return res;
+        }
         let numer = n.factor_out();
         let denom = d.factor_out();
         (numer / denom).reduce()
@@ -1290,7 +1484,14 @@ impl Expr {
     pub fn sort_args(&self) -> Self {
         use Atom as A;
         match self.atom() {
-            A::Undef | A::Rational(_) | A::Irrational(_) | A::Var(_) => self.clone(),
+            A::Undef
+            | A::Rational(_)
+            | A::Irrational(_)
+            | A::Float(_)
+            | A::ImagUnit
+            | A::Infinity(_)
+            | A::Var(_)
+            | A::Bool(_) => self.clone(),
             A::Sum(sum) => {
                 let mut s = sum.clone().map_args(|a| *a = a.sort_args());
                 s.args_mut().sort_by(Sum::cmp_args);
@@ -1311,6 +1512,15 @@ impl Expr {
                 let f = func.clone().map_args(|a| *a = a.sort_args());
                 f.into()
             }
+            A::Rel(rel) => rel.clone().map_args(|a| *a = a.sort_args()).into(),
+            A::List(list) => Expr::from(list.iter().map(|a| a.sort_args()).collect::<Vec<_>>()),
+            A::Logic(logic) => {
+                let mut l = logic.clone().map_args(|a| *a = a.sort_args());
+                if !l.is_not() {
+                    l.args_mut().sort();
+                }
+                l.into()
+            }
         }
     }
 
@@ -1339,15 +1549,451 @@ impl Expr {
     }
 }
 
+// floating point numbers
+impl Expr {
+    /// adds two numbers, floats are contagious: 0.5 + 1 = 1.5
+    pub(crate) fn add_num(lhs: &Expr, rhs: &Expr) -> Option<Expr> {
+        use Atom as A;
+        Some(match (lhs.atom(), rhs.atom()) {
+            (A::Rational(r1), A::Rational(r2)) => A::Rational(r1.clone() + r2).into(),
+            (A::Float(f1), A::Float(f2)) => Expr::float(*f1 + *f2),
+            (A::Float(f), A::Rational(r)) | (A::Rational(r), A::Float(f)) => {
+                Expr::float(*f + Float::from(r))
+            }
+            _ => return None,
+        })
+    }
+
+    /// relations are threaded through sums: (a < b) + c = a + c < b + c
+    ///
+    /// two relations are added side by side if they have the same operator
+    pub(crate) fn add_rel(lhs: &Expr, rhs: &Expr) -> Option<Expr> {
+        use Atom as A;
+        Some(match (lhs.atom(), rhs.atom()) {
+            (A::Rel(r1), A::Rel(r2)) if r1.op == r2.op && r1.op != RelOp::Ne => {
+                Rel::new(r1.op, r1.lhs() + r2.lhs(), r1.rhs() + r2.rhs()).into()
+            }
+            (A::Rel(_), A::Rel(_)) => Expr::undef(),
+            (A::Rel(r), _) => r.add(rhs).into(),
+            (_, A::Rel(r)) => r.add(lhs).into(),
+            _ => return None,
+        })
+    }
+
+    /// (a < b) * c = a * c < b * c, the inequality is flipped for a negative c
+    pub(crate) fn mul_rel(lhs: &Expr, rhs: &Expr) -> Option<Expr> {
+        use Atom as A;
+        let res = match (lhs.atom(), rhs.atom()) {
+            (A::Rel(_), A::Rel(_)) => None,
+            (A::Rel(r), _) => r.mul(rhs),
+            (_, A::Rel(r)) => r.mul(lhs),
+            _ => return None,
+        };
+        Some(res.map_or_else(Expr::undef, Expr::from))
+    }
+
+    /// multiplies two numbers, floats are contagious: 0.5 * 3 = 1.5
+    pub(crate) fn mul_num(lhs: &Expr, rhs: &Expr) -> Option<Expr> {
+        use Atom as A;
+        Some(match (lhs.atom(), rhs.atom()) {
+            (A::Rational(r1), A::Rational(r2)) => A::Rational(r1.clone() * r2).into(),
+            (A::Float(f1), A::Float(f2)) => Expr::float(*f1 * *f2),
+            (A::Float(f), A::Rational(r)) | (A::Rational(r), A::Float(f)) => {
+                Expr::float(*f * Float::from(r))
+            }
+            _ => return None,
+        })
+    }
+
+    /// powers of real numbers where the base or the exponent is a float: 2^0.5 = 1.414..
+    pub(crate) fn pow_float(base: &Expr, exponent: &Expr) -> Option<Expr> {
+        if !base.is_float() && !exponent.is_float() {
+            return None;
+        }
+        let (b, e) = (base.try_as_real()?, exponent.try_as_real()?);
+        let res = Float::new(b.f64_approx()).pow(Float::new(e.f64_approx()))?;
+        Some(Expr::float(res))
+    }
+
+    /// pull perfect powers out of rational radicals: 12^(1/2) = 2 * 3^(1/2)
+    ///
+    /// with b = o^q * i, b^(w + r/q) = b^w * o^r * i^(r/q)
+    pub(crate) fn pow_radical(base: &Rational, exponent: &Rational) -> Option<Expr> {
+        use num::Integer;
+        if !base.is_pos() || exponent.is_int() {
+            return None;
+        }
+        let q = exponent.denom();
+        let (w, r) = exponent.numer().div_mod_floor(&q);
+        let q_u32 = u32::try_from(&q.0).ok()?;
+
+        let (n_out, n_in) = base.numer().split_power(q_u32);
+        let (d_out, d_in) = base.denom().split_power(q_u32);
+        if w.is_zero() && n_out.is_one() && d_out.is_one() {
+            return None;
+        }
+
+        let coeff = base.clone().pow_basic(Rational::from(w))?
+            * Rational::from((n_out.pow(&r)?, d_out.pow(&r)?));
+        let root = Pow {
+            args: [
+                Expr::from(Rational::from((n_in, d_in))),
+                Expr::from(Rational::from((r, q))),
+            ],
+        };
+        Some((Expr::from(coeff) * root.reduce()).reduce())
+    }
+}
+
+// infinities
+impl Expr {
+    /// powers where the base or the exponent is an infinity
+    ///
+    /// e.g oo^2 = oo, oo^-1 = 0, 2^oo = oo, (1/2)^oo = 0, 1^oo = undef
+    pub(crate) fn pow_inf(base: &Expr, exponent: &Expr) -> Option<Expr> {
+        use Atom as A;
+        use Infinity as Inf;
+
+        match (base.atom(), exponent.atom()) {
+            (A::Infinity(_), A::Rational(e)) if e.is_zero() => Some(Expr::one()),
+            (A::Infinity(_), A::Rational(e)) if e.is_neg() => Some(Expr::zero()),
+            (A::Infinity(Inf::Neg), A::Rational(e)) if e.is_int() => Some(match e.is_even() {
+                true => Expr::inf(),
+                false => Expr::neg_inf(),
+            }),
+            (A::Infinity(Inf::Neg), A::Rational(_)) => Some(Expr::complex_inf()),
+            (A::Infinity(inf), A::Rational(_)) => Some((*inf).into()),
+            (A::Infinity(_), A::Infinity(Inf::Neg)) => Some(Expr::zero()),
+            (A::Infinity(Inf::Neg), A::Infinity(Inf::Pos)) => Some(Expr::complex_inf()),
+            (A::Infinity(inf), A::Infinity(Inf::Pos)) => Some((*inf).into()),
+            (A::Infinity(_), A::Infinity(Inf::Complex)) => Some(Expr::undef()),
+            (_, A::Infinity(inf)) => {
+                let b = base.try_as_real()?;
+                if inf == &Inf::Complex {
+                    return Some(Expr::undef());
+                }
+
+                let (zero, one) = (
+                    Real::Rational(Rational::ZERO),
+                    Real::Rational(Rational::ONE),
+                );
+                let min_one = Real::Rational(Rational::MINUS_ONE);
+                let grows = inf == &Inf::Pos;
+
+                Some(if b == one || b == min_one {
+                    // 1^oo is indeterminate, (-1)^oo oscillates
+                    Expr::undef()
+                } else if b > one {
+                    if grows {
+                        Expr::inf()
+                    } else {
+                        Expr::zero()
+                    }
+                } else if b < min_one {
+                    if grows {
+                        Expr::complex_inf()
+                    } else {
+                        Expr::zero()
+                    }
+                } else if grows {
+                    Expr::zero()
+                } else if b > zero {
+                    Expr::inf()
+                } else {
+                    Expr::complex_inf()
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+// complex numbers
+impl Expr {
+    /// i^n = i^(n mod 4)
+    pub(crate) fn i_pow(n: &Int) -> Expr {
+        use num::{Integer, ToPrimitive};
+        match n.mod_floor(&Int::from(4)).to_u8().unwrap() {
+            0 => Expr::one(),
+            1 => Expr::i(),
+            2 => Expr::min_one(),
+            _ => Expr::min_one() * Expr::i(),
+        }
+    }
+
+    /// returns b if the expression has the form b*i
+    pub fn try_as_imag_term(&self) -> Option<Expr> {
+        match self.flatten().atom() {
+            Atom::ImagUnit => Some(Expr::one()),
+            Atom::Prod(prod) => {
+                let indx = prod.args.iter().position(|a| a.is_imag_unit())?;
+                let mut args = prod.args.clone();
+                args.remove(indx);
+                Some(match args.len() {
+                    0 => Expr::one(),
+                    1 => args.remove(0),
+                    _ => Prod { args }.into(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// returns (a, b) if the expression is a complex numeral a + b*i with rational a and b != 0
+    pub(crate) fn try_as_complex_numeral(&self) -> Option<(Rational, Rational)> {
+        let terms = match self.flatten().atom() {
+            Atom::Sum(sum) => sum.args.as_slice(),
+            _ => slice::from_ref(self),
+        };
+
+        let (mut re, mut im) = (Rational::ZERO, Rational::ZERO);
+        for t in terms {
+            match (t.try_unwrap_rational_ref(), t.try_as_imag_term()) {
+                (Ok(r), _) => re += r,
+                (_, Some(b)) => im += b.try_unwrap_rational_ref().ok()?,
+                _ => return None,
+            }
+        }
+        match im.is_zero() {
+            true => None,
+            false => Some((re, im)),
+        }
+    }
+
+    /// a + b*i
+    pub(crate) fn complex_numeral((re, im): (Rational, Rational)) -> Expr {
+        (Expr::from(re) + Expr::from(im) * Expr::i()).reduce()
+    }
+
+    /// (a + b*i) * (c + d*i) = (ac - bd) + (ad + bc)*i
+    pub(crate) fn mul_complex(
+        (a, b): &(Rational, Rational),
+        (c, d): &(Rational, Rational),
+    ) -> (Rational, Rational) {
+        (a.clone() * c - b.clone() * d, a.clone() * d + b.clone() * c)
+    }
+
+    /// integer powers of complex numerals, the reciprocal is taken via the conjugate:
+    ///
+    /// 1 / (a + b*i) = (a - b*i) / (a^2 + b^2)
+    pub(crate) fn pow_complex(base: &Expr, exponent: &Expr) -> Option<Expr> {
+        let n = exponent.try_unwrap_int()?;
+        let (a, b) = base.try_as_complex_numeral()?;
+        let mut n_abs = u32::try_from(&n.abs().0)
+            .ok()
+            .filter(|n| *n <= Self::MAX_SPLIT_POW)?;
+
+        let mut sq = match n.is_neg() {
+            true => {
+                let abs2 = a.clone() * &a + b.clone() * &b;
+                (a / &abs2, Rational::ZERO - b / abs2)
+            }
+            false => (a, b),
+        };
+        // binary powering
+        let mut res = (Rational::ONE, Rational::ZERO);
+        while n_abs > 0 {
+            if n_abs & 1 == 1 {
+                res = Expr::mul_complex(&res, &sq);
+            }
+            n_abs >>= 1;
+            if n_abs > 0 {
+                sq = Expr::mul_complex(&sq, &sq);
+            }
+        }
+        Some(Expr::complex_numeral(res))
+    }
+
+    /// integer powers above this are not expanded by [Expr::split_complex] or
+    /// [Expr::pow_complex]
+    const MAX_SPLIT_POW: u32 = 64;
+
+    /// splits the expression into its real and imaginary part: a + b*i => (a, b)
+    ///
+    /// returns [None] if the expression contains terms of unknown domain, e.g variables
+    pub fn split_complex(&self) -> Option<(Expr, Expr)> {
+        use Atom as A;
+        use Func as F;
+
+        let real = |e: &Expr| Some((e.clone(), Expr::zero()));
+        let is_real = |e: &Expr| e.split_complex().is_some_and(|(_, im)| im.is_zero());
+
+        match self.flatten().atom() {
+            A::Undef | A::Var(_) | A::Bool(_) | A::Rel(_) | A::Logic(_) | A::List(_) => None,
+            A::Rational(_) | A::Irrational(_) | A::Float(_) => real(self),
+            A::ImagUnit => Some((Expr::zero(), Expr::one())),
+            A::Infinity(inf) if inf.is_real() => real(self),
+            A::Infinity(_) => None,
+            A::Sum(sum) => sum
+                .iter_args()
+                .try_fold((Expr::zero(), Expr::zero()), |(re, im), a| {
+                    let (a_re, a_im) = a.split_complex()?;
+                    Some((re + a_re, im + a_im))
+                }),
+            A::Prod(prod) => {
+                // (a + b*i)(c + d*i) = (ac - bd) + (ad + bc)*i
+                prod.iter_args()
+                    .try_fold((Expr::one(), Expr::zero()), |(a, b), rhs| {
+                        let (c, d) = rhs.split_complex()?;
+                        let re = &a * &c - &b * &d;
+                        let im = a * d + b * c;
+                        Some((re, im))
+                    })
+            }
+            A::Pow(pow) => {
+                let (base, expon) = (pow.base(), pow.exponent());
+                if expon.is_int() && base.split_complex().is_some() {
+                    // expand the integer power, e.g (1 + i)^2 = 2i
+                    let n = expon.unwrap_int();
+                    // larger powers are left symbolic
+                    let mut n_abs = u32::try_from(&n.abs().0)
+                        .ok()
+                        .filter(|n| *n <= Self::MAX_SPLIT_POW)?;
+                    let (a, b) = base.split_complex()?;
+                    let mul = |(a, b): &(Expr, Expr), (c, d): &(Expr, Expr)| {
+                        (a * c - b * d, a * d + b * c)
+                    };
+                    // binary powering
+                    let mut res = (Expr::one(), Expr::zero());
+                    let mut sq = (a, b);
+                    while n_abs > 0 {
+                        if n_abs & 1 == 1 {
+                            res = mul(&res, &sq);
+                        }
+                        n_abs >>= 1;
+                        if n_abs > 0 {
+                            sq = mul(&sq, &sq);
+                        }
+                    }
+                    if n.is_neg() {
+                        // 1/(a + b*i) = (a - b*i)/(a^2 + b^2)
+                        let (a, b) = res;
+                        let abs2 = Expr::pow(&a, Expr::two()) + Expr::pow(&b, Expr::two());
+                        res = (&a / &abs2, Expr::min_one() * b / abs2);
+                    }
+                    Some(res)
+                } else if (base.is_pos() || base.is_irrational()) && is_real(expon) {
+                    real(self)
+                } else {
+                    None
+                }
+            }
+            A::Func(func) => match func {
+                F::Re(_) | F::Im(_) | F::Arg(_) => real(self),
+                // integer valued
+                F::IsPrime(_)
+                | F::NextPrime(_)
+                | F::Totient(_)
+                | F::Mod(_)
+                | F::PowMod(_)
+                | F::ModInv(_) => real(self),
+                F::RootOf(_) | F::Order(_) => None,
+                F::Conj(x) => {
+                    let (re, im) = x.split_complex()?;
+                    Some((re, Expr::min_one() * im))
+                }
+                F::Sin(x)
+                | F::Cos(x)
+                | F::Tan(x)
+                | F::Sec(x)
+                | F::Cot(x)
+                | F::Csc(x)
+                | F::ArcTan(x)
+                | F::ArcCot(x)
+                    if is_real(x) =>
+                {
+                    real(self)
+                }
+                _ => None,
+            },
+        }
+    }
+
+    /// re(a + b*i) = a
+    pub(crate) fn reduce_re(x: &Expr) -> Expr {
+        if let Some((re, _)) = x.split_complex() {
+            re.reduce()
+        } else if x.is_sum() {
+            x.iter_args()
+                .fold(Expr::zero(), |s, a| s + Expr::reduce_re(a))
+        } else {
+            Expr::re(x)
+        }
+    }
+
+    /// im(a + b*i) = b
+    pub(crate) fn reduce_im(x: &Expr) -> Expr {
+        if let Some((_, im)) = x.split_complex() {
+            im.reduce()
+        } else if x.is_sum() {
+            x.iter_args()
+                .fold(Expr::zero(), |s, a| s + Expr::reduce_im(a))
+        } else {
+            Expr::im(x)
+        }
+    }
+
+    /// conj(a + b*i) = a - b*i
+    pub(crate) fn reduce_conj(x: &Expr) -> Expr {
+        if let Some((re, im)) = x.split_complex() {
+            (re - im * Expr::i()).reduce()
+        } else if x.is_sum() {
+            x.iter_args()
+                .fold(Expr::zero(), |s, a| s + Expr::reduce_conj(a))
+        } else if let Some(b) = x.try_as_imag_term() {
+            Expr::min_one() * Expr::i() * Expr::reduce_conj(&b)
+        } else {
+            Expr::conj(x)
+        }
+    }
+
+    /// arg(a + b*i) for rational or irrational a and b
+    pub(crate) fn reduce_arg(x: &Expr) -> Expr {
+        let Some((re, im)) = x.split_complex().map(|(re, im)| (re.reduce(), im.reduce())) else {
+            return Expr::arg(x);
+        };
+
+        let sign = |e: &Expr| match e.atom() {
+            Atom::Rational(r) if r.is_pos() => Some(cmp::Ordering::Greater),
+            Atom::Rational(r) if r.is_neg() => Some(cmp::Ordering::Less),
+            Atom::Rational(_) => Some(cmp::Ordering::Equal),
+            // e and pi are positive
+            Atom::Irrational(_) => Some(cmp::Ordering::Greater),
+            _ => None,
+        };
+        let (Some(re_sign), Some(im_sign)) = (sign(&re), sign(&im)) else {
+            return Expr::arg(x);
+        };
+
+        use cmp::Ordering as O;
+        let half_pi = Expr::from(Rational::from((1, 2))) * Expr::pi();
+        match (re_sign, im_sign) {
+            (O::Equal, O::Equal) => Expr::undef(),
+            (O::Greater, O::Equal) => Expr::zero(),
+            (O::Less, O::Equal) => Expr::pi(),
+            (O::Equal, O::Greater) => half_pi,
+            (O::Equal, O::Less) => Expr::min_one() * half_pi,
+            (O::Greater, _) => Expr::arc_tan(im / re),
+            (O::Less, O::Greater) => Expr::arc_tan(im / re) + Expr::pi(),
+            (O::Less, O::Less) => Expr::arc_tan(im / re) - Expr::pi(),
+        }
+    }
+}
+
 /// helper function for [Expr::expand_exponential]
 ///
 /// will expand the argument to a exponential function
 fn expand_exponential_arg(a: &Expr) -> Expr {
     let exp_args = expand_exponential_arg;
 
+    // euler's formula: exp(x*i) = cos(x) + i*sin(x)
+    if let Some(x) = a.try_as_imag_term() {
+        return Expr::cos(&x) + Expr::i() * Expr::sin(&x);
+    }
+
     match a.atom() {
         Atom::Sum(s) => {
-            let args: Vec<_> = s.iter_args().map(|a| exp_args(a)).collect();
+            let args: Vec<_> = s.iter_args().map(&exp_args).collect();
             return Prod { args }.into();
         }
         Atom::Prod(p) => {
@@ -1371,21 +2017,21 @@ enum TrigTyp {
 /// expands the expression sin(n*phi) to
 ///
 /// sin(n*phi) = sum(j=1 && odd(j); n) { (-1)^((j-1)/2) * binom(n,j) * cos(phi)^(n-j) * sin(phi)^j  }
-fn expand_sin_n_times_phi(n: i128, phi: &Expr) -> Expr {
+fn expand_sin_n_times_phi(n: &Int, phi: &Expr) -> Expr {
     _expand_sin_cos_n_times_phi(n, phi, TrigTyp::Sin)
 }
 
 /// expands the expression cos(n*phi) to
 ///
 /// cos(n*phi) = sum(j=0 && even(j); n) { (-1)^(j/2) * binom(n,j) * cos(phi)^(n-j) * sin(phi)^j  }
-fn expand_cos_n_times_phi(n: i128, phi: &Expr) -> Expr {
+fn expand_cos_n_times_phi(n: &Int, phi: &Expr) -> Expr {
     _expand_sin_cos_n_times_phi(n, phi, TrigTyp::Cos)
 }
 
 /// expands the expression sin(n*phi) or cos(n*phi)
 ///
 /// helper functions for [expand_sin_n_times_phi] and [expand_cos_n_times_phi]
-fn _expand_sin_cos_n_times_phi(n: i128, phi: &Expr, typ: TrigTyp) -> Expr {
+fn _expand_sin_cos_n_times_phi(n: &Int, phi: &Expr, typ: TrigTyp) -> Expr {
     let mut min_one = false;
 
     // TODO expand here?
@@ -1394,8 +2040,8 @@ fn _expand_sin_cos_n_times_phi(n: i128, phi: &Expr, typ: TrigTyp) -> Expr {
     let mut sum = Sum::zero();
     let ne = Expr::from(n.clone());
 
-    for i in 0..=n {
-        if typ == TrigTyp::Cos && (i % 2 != 0) || typ == TrigTyp::Sin && (i % 2 == 0) {
+    for i in Int::range_inclusive(Int::ZERO, n.clone()) {
+        if typ == TrigTyp::Cos && i.is_odd() || typ == TrigTyp::Sin && i.is_even() {
             continue;
         }
 
@@ -1406,16 +2052,16 @@ fn _expand_sin_cos_n_times_phi(n: i128, phi: &Expr, typ: TrigTyp) -> Expr {
             true => Expr::one(),
         };
 
-        let rhs = if i == 0 {
+        let rhs = if i.is_zero() {
             // 1 * cos(phi)^n
             Expr::pow(&cos, &ne)
-        } else if i == n {
+        } else if &i == n {
             // 1 * sin(phi)^n
             Expr::pow(&sin, &ne)
         } else {
             // binom(n, i) * sin(phi)^i * cos(phi)^(n-i)
-            let b = Expr::from(binomial_coeff(n, i));
-            b * Expr::pow(&cos, Expr::from(n - &i)) * Expr::pow(&sin, Expr::from(i))
+            let b = Expr::from(binomial_coeff(n, &i));
+            b * Expr::pow(&cos, Expr::from(n.clone() - &i)) * Expr::pow(&sin, Expr::from(i))
         };
 
         sum.add_rhs(&(sign * rhs));
@@ -1441,10 +2087,10 @@ fn expand_trig_arg(a: &Expr) -> (Expr, Expr) {
             let prod = prod.as_binary_mul();
             if let Some(n) = prod.0.try_unwrap_int() {
                 let n_abs = n.abs();
-                let mut s = expand_sin_n_times_phi(n_abs, &prod.1);
-                let c = expand_cos_n_times_phi(n_abs, &prod.1);
+                let mut s = expand_sin_n_times_phi(&n_abs, &prod.1);
+                let c = expand_cos_n_times_phi(&n_abs, &prod.1);
 
-                if n < 0 {
+                if n.is_neg() {
                     s = Expr::min_one() * s;
                 }
                 return (s, c);
@@ -1489,8 +2135,6 @@ fn contract_trig_arg(e: &Expr) -> Expr {
 }
 
 fn contract_trig_pow(p: &Expr) -> Expr {
-    use num::Integer;
-
     if !p.is_pow() {
         return p.clone();
     }
@@ -1498,55 +2142,55 @@ fn contract_trig_pow(p: &Expr) -> Expr {
     let f = p.base();
     let n = p.exponent();
 
-    if !(n.is_int() && n.is_pos()) && !(f.is_sin() || f.is_cos()) {
+    if !(f.is_sin() || f.is_cos() || n.is_int() && n.is_pos()) {
         return p.clone();
     }
 
     let func = || {
         let en = n;
-        let n = en.unwrap_rational_ref().to_int().unwrap();
-        let n_min_one = n - 1;
-        let f = f.unwrap_func_ref();
+        let n = en.try_unwrap_int()?;
+        let n_min_one = n.clone() - Int::ONE;
+        let f = f.try_unwrap_func_ref().ok()?;
 
-        let half_n = n / 2;
+        let half_n = n.clone() / Int::TWO;
 
-        let binom = |l: i128, r: i128| Expr::from(binomial_coeff(l, r));
-        let min_one_pow = |n: &i128| match n % 2 == 0 {
+        let binom = |l: &Int, r: &Int| Expr::from(binomial_coeff(l, r));
+        let min_one_pow = |n: &Int| match n.is_even() {
             true => Expr::one(),
             false => Expr::min_one(),
         };
+        let two_pow = |n: &Int| Rational::TWO.pow_basic(n.clone().into());
 
         match f {
             Func::Sin(x) => {
-                if n % 2 == 0 {
-                    let a = Rational::from(binomial_coeff(n, half_n))
-                        / Rational::from(2i128.checked_pow(n.try_into().ok()?)?);
+                if n.is_even() {
+                    let a = Rational::from(binomial_coeff(&n, &half_n)) / two_pow(&n)?;
                     let mut b = match half_n.is_even() {
                         true => Rational::ONE,
                         false => Rational::MINUS_ONE,
                     };
-                    b /= Rational::from(2i128.checked_pow(n_min_one.try_into().ok()?)?);
+                    b /= two_pow(&n_min_one)?;
 
                     let mut sum = Expr::zero();
-                    for j in 0..=half_n - 1 {
+                    for j in Int::range_inclusive(Int::ZERO, half_n - Int::ONE) {
                         let mut term = min_one_pow(&j);
-                        term *= binom(n, j);
-                        term *= Expr::cos((&en - Expr::from(2 * j)) * x);
+                        term *= binom(&n, &j);
+                        term *= Expr::cos((en.clone() - Expr::from(Int::TWO * j)) * x);
                         sum += term;
                     }
 
                     Some(Expr::from(a) + Expr::from(b) * sum)
                 } else {
-                    let mut b = match (n_min_one.clone() / 2).is_even() {
+                    let mut b = match (n_min_one.clone() / Int::TWO).is_even() {
                         true => Rational::ONE,
                         false => Rational::MINUS_ONE,
                     };
-                    b /= Rational::from(2i128.checked_pow(n_min_one.try_into().ok()?)?);
+                    b /= two_pow(&n_min_one)?;
                     let mut sum = Expr::zero();
-                    for j in 0..=half_n {
+                    for j in Int::range_inclusive(Int::ZERO, half_n) {
                         let mut term = min_one_pow(&j);
-                        term *= binom(n, j);
-                        term *= Expr::sin((&en - Expr::from(2 * j)) * x);
+                        term *= binom(&n, &j);
+                        term *= Expr::sin((en.clone() - Expr::from(Int::TWO * j)) * x);
                         sum += term;
                     }
                     Some(Expr::from(b) * sum)
@@ -1554,32 +2198,30 @@ fn contract_trig_pow(p: &Expr) -> Expr {
             }
             Func::Cos(x) => {
                 if n.is_even() {
-                    let mut a = Rational::from(binomial_coeff(n, half_n));
-                    a /= Rational::from(2i128.checked_pow(n.try_into().ok()?)?);
+                    let mut a = Rational::from(binomial_coeff(&n, &half_n));
+                    a /= two_pow(&n)?;
 
-                    let b = Rational::ONE
-                        / Rational::from(2i128.checked_pow(n_min_one.try_into().ok()?)?);
+                    let b = Rational::ONE / two_pow(&n_min_one)?;
 
                     let mut sum = Expr::zero();
-                    for j in 0..=half_n - 1 {
-                        let mut term = binom(n, j);
-                        term *= Expr::cos((&en - Expr::from(2 * j)) * x);
+                    for j in Int::range_inclusive(Int::ZERO, half_n - Int::ONE) {
+                        let mut term = binom(&n, &j);
+                        term *= Expr::cos((en.clone() - Expr::from(Int::TWO * j)) * x);
                         sum += term
                     }
                     Some(Expr::from(a) + Expr::from(b) * sum)
                 } else {
-                    let b = Rational::ONE
-                        / Rational::from(2i128.checked_pow(n_min_one.try_into().ok()?)?);
+                    let b = Rational::ONE / two_pow(&n_min_one)?;
                     let mut sum = Expr::zero();
-                    for j in 0..=half_n {
-                        let mut term = binom(n, j);
-                        term *= Expr::cos((&en - Expr::from(2 * j)) * x);
+                    for j in Int::range_inclusive(Int::ZERO, half_n) {
+                        let mut term = binom(&n, &j);
+                        term *= Expr::cos((en.clone() - Expr::from(Int::TWO * j)) * x);
                         sum += term
                     }
                     Some(Expr::from(b) * sum)
                 }
             }
-            _ => unreachable!(),
+            _ => None,
         }
     };
     func().unwrap_or(p.clone())
@@ -1596,10 +2238,10 @@ fn contract_trig_prod(p: &[Expr]) -> Expr {
 
         if let Atom::Pow(_) = a.atom() {
             let a = contract_trig_pow(a);
-            return contract_trig_arg(&(a * b));
+            contract_trig_arg(&(a * b))
         } else if let Atom::Pow(_) = b.atom() {
             let b = contract_trig_pow(b);
-            return contract_trig_arg(&(a * b));
+            contract_trig_arg(&(a * b))
         } else {
             assert!(a.is_sin() || a.is_cos());
             assert!(b.is_sin() || b.is_cos());
@@ -1612,13 +2254,13 @@ fn contract_trig_prod(p: &[Expr]) -> Expr {
             let sin = |e: Expr| Expr::sin(e);
 
             if a.is_sin() && b.is_sin() {
-                return cos(theta - phi) / &two - cos(theta + phi) / &two;
+                cos(theta - phi) / &two - cos(theta + phi) / &two
             } else if a.is_cos() && b.is_cos() {
-                return cos(theta + phi) / &two + cos(theta - phi) / &two;
+                cos(theta + phi) / &two + cos(theta - phi) / &two
             } else if a.is_sin() && b.is_cos() {
-                return sin(theta + phi) / &two + sin(theta - phi) / &two;
+                sin(theta + phi) / &two + sin(theta - phi) / &two
             } else if a.is_cos() && b.is_sin() {
-                return sin(theta + phi) / &two + sin(phi - theta) / &two;
+                sin(theta + phi) / &two + sin(phi - theta) / &two
             } else {
                 unreachable!()
             }
@@ -1626,7 +2268,7 @@ fn contract_trig_prod(p: &[Expr]) -> Expr {
     } else {
         let a = &p[0];
         let b = contract_trig_prod(&p[1..]);
-        return contract_trig_arg(&(a * b));
+        contract_trig_arg(&(a * b))
     }
 }
 
@@ -1779,6 +2421,41 @@ mod test {
             e!(((x + 2) ^ 2 + 3) ^ 2).expand().reduce(),
             e!(x ^ 4 + 8 * x ^ 3 + 30 * x ^ 2 + 56 * x + 49).reduce()
         );
+        // the factors of a product expand into sums that still have to be distributed
+        eq!(
+            e!((c * (a + b)) ^ 2 - c ^ 2 * (a + b) ^ 2)
+                .expand()
+                .reduce(),
+            e!(0)
+        );
+        let root = e!(((c ^ 2 - 4 * k) ^ (1 / 2) - c) / 2);
+        eq!(
+            (&root * &root + e!(c) * &root + e!(k)).expand().reduce(),
+            e!(0)
+        );
+    }
+
+    #[test]
+    fn expand_fractional_pow() {
+        // (x + y)^(3/2) = (x + y) * (x + y)^(1/2) is distributed termwise, multiplying the
+        // whole sum with the root would merge back into (x + y)^(3/2)
+        eq!(
+            e!(c * (x + y) ^ (3 / 2)).expand().reduce().sort_args(),
+            e!(c * x * (x + y) ^ (1 / 2) + c * y * (x + y) ^ (1 / 2)).sort_args()
+        );
+        // like terms with radicals cancel, e.g in the determinants of Matrix
+        eq!(
+            e!((x + y) ^ (3 / 2) - x * (x + y) ^ (1 / 2))
+                .expand()
+                .reduce(),
+            e!(y * (x + y) ^ (1 / 2))
+        );
+        eq!(
+            e!((x + 1) ^ (5 / 2) - (x + 1) ^ 2 * (x + 1) ^ (1 / 2))
+                .expand()
+                .reduce(),
+            e!(0)
+        );
     }
 
     #[test]
@@ -1882,12 +2559,156 @@ mod test {
             (e!(sin(0)), e!(0)),
             (e!(sin(-x)), e!(-1 * sin(x))),
             (e!(cos(-x)), e!(cos(x))),
+            (e!(sin(pi / 2)), e!(1)),
+            (e!(cos(pi)), e!(-1)),
+            (e!(sin(-3 * pi / 2)), e!(1)),
+            (e!(cos(5 * pi / 2)), e!(0)),
             (e!(x * y / (y * x)), e!(1)),
             (Expr::ln(Expr::e()), e!(1)),
+            (e!(12 ^ (1 / 2)), e!(2 * 3 ^ (1 / 2))),
+            (e!(4 ^ (1 / 2)), e!(2)),
+            (e!(16 ^ (3 / 4)), e!(8)),
+            (e!(24 ^ (4 / 3)), e!(48 * 3 ^ (1 / 3))),
+            (e!((9 / 8) ^ (1 / 2)), e!(3 / 2 * (1 / 2) ^ (1 / 2))),
+            (e!(12 ^ (-1 / 2)), e!(1 / 6 * 3 ^ (1 / 2))),
+            (e!(6 ^ (1 / 2)), e!(6 ^ (1 / 2))),
         ];
         for (calc, res) in checks {
             eq!(calc.reduce(), res.sort_args());
         }
+
+        // like terms with different coefficients have to merge in one pass
+        let e = e!((a * g - c * d) * (a * f - b * d) - (a * g - c * d) * (a * f - b * d));
+        eq!(e.expand().reduce(), e!(0));
+        let e = e!((a + b) ^ 2 - (a ^ 2 + 2 * a * b + b ^ 2));
+        eq!(e.expand().reduce(), e!(0));
+    }
+
+    #[test]
+    fn complex() {
+        let checks = vec![
+            (e!(I ^ 2), e!(-1)),
+            (e!(I ^ 3), e!(-1 * I)),
+            (e!(I ^ 4), e!(1)),
+            (e!(I ^ -1), e!(-1 * I)),
+            (e!(I * I), e!(-1)),
+            (e!(2 * I * x * I), e!(-2 * x)),
+            (e!(sqrt(-1)), e!(I)),
+            (e!(sqrt(-2)), e!(sqrt(2) * I)),
+            (e!((2 + 3 * I) + (1 - I)), e!(3 + 2 * I)),
+            (e!(a + x * I + y * I), e!(a + (x + y) * I)),
+            (e!(re(2 + 3 * I)), e!(2)),
+            (e!(im(2 + 3 * I)), e!(3)),
+            (e!(im(x + 3 * I)), e!(im(x) + 3)),
+            (e!(re((1 + I) ^ 2)), e!(0)),
+            (e!(im((1 + I) ^ 2)), e!(2)),
+            (e!(conj(2 + 3 * I)), e!(2 - 3 * I)),
+            (e!(conj(x * I)), e!(-1 * conj(x) * I)),
+            (e!(arg(I)), e!(pi / 2)),
+            (e!(arg(-1)), e!(pi)),
+            (e!(arg(1 + I)), e!(arc_tan(1))),
+            (e!(arg(-1 - I)), e!(arc_tan(1) - pi)),
+            (e!(re((1 + I) ^ 10)), e!(0)),
+            (e!(im((1 + I) ^ 10)), e!(32)),
+            (e!(re((1 + I) ^ -2)), e!(0)),
+            (e!(im((1 + I) ^ -2)), e!(-1 / 2)),
+            (e!(re((1 + I) ^ 1000)), e!(re((1 + I) ^ 1000))),
+            (e!((1 + I) * (1 - I)), e!(2)),
+            (e!((1 + I) ^ 2), e!(2 * I)),
+            (e!(1 / (1 + I)), e!(1 / 2 - I / 2)),
+            (e!((1 + I) * x * (1 + I) * 3), e!(6 * I * x)),
+            (e!(I * (1 + I)), e!(I - 1)),
+            (e!((1 / 2 + I / 3) ^ 3), e!(-1 / 24 + 23 / 108 * I)),
+            (e!((2 + 3 * I) * x), e!((2 + 3 * I) * x)),
+        ];
+        for (calc, res) in checks {
+            eq!(calc.reduce(), res.reduce(), "{calc}");
+        }
+
+        eq!(
+            Expr::exp(e!(x * I)).expand_exponential(),
+            e!(cos(x) + I * sin(x))
+        );
+        eq!(e!(2 + 3 * I).to_string(), "2 + 3i");
+    }
+
+    #[test]
+    fn infinity() {
+        let checks = vec![
+            (e!(oo + 1), e!(oo)),
+            (e!(oo + pi), e!(oo)),
+            (e!(oo + oo), e!(oo)),
+            (e!(oo - oo), e!(undef)),
+            (e!(-oo - 3), e!(-oo)),
+            (e!(zoo + 1), e!(zoo)),
+            (e!(zoo + zoo), e!(undef)),
+            (e!(zoo + oo), e!(undef)),
+            (e!(2 * oo), e!(oo)),
+            (e!(-2 * oo), e!(-oo)),
+            (e!(-oo * -oo), e!(oo)),
+            (e!(oo * -oo), e!(-oo)),
+            (e!(-1 * zoo), e!(zoo)),
+            (e!(0 * oo), e!(undef)),
+            (e!(oo * 0), e!(undef)),
+            (e!(1 / oo), e!(0)),
+            (e!(1 / -oo), e!(0)),
+            (e!(oo / oo), e!(undef)),
+            (e!(1 / 0), e!(zoo)),
+            (e!(-3 / 0), e!(zoo)),
+            (e!(0 / 0), e!(undef)),
+            (e!(x / 0), e!(zoo * x)),
+            (e!(oo ^ 2), e!(oo)),
+            (e!(oo ^ 0), e!(1)),
+            (e!((-oo) ^ 3), e!(-oo)),
+            (e!((-oo) ^ 2), e!(oo)),
+            (e!(oo ^ (1 / 2)), e!(oo)),
+            (e!(2 ^ oo), e!(oo)),
+            (e!(2 ^ -oo), e!(0)),
+            (e!((1 / 2) ^ oo), e!(0)),
+            (e!((1 / 2) ^ -oo), e!(oo)),
+            (e!(1 ^ oo), e!(undef)),
+            (e!(0 ^ oo), e!(0)),
+            (e!(0 ^ -oo), e!(zoo)),
+            (Expr::exp(e!(-oo)), e!(0)),
+            (e!(x + oo + 1), e!(x + oo)),
+        ];
+        for (calc, res) in checks {
+            eq!(calc.reduce(), res.reduce(), "{calc}");
+        }
+
+        eq!(e!(-oo).to_string(), "-∞");
+        eq!(e!(x + oo).to_latex(), r"x + \infty");
+    }
+
+    #[test]
+    fn float() {
+        let f = |v: f64| Expr::float(v);
+        let checks = vec![
+            (e!(0.5 + 1), f(1.5)),
+            (e!(1 / 4 + 0.5), f(0.75)),
+            (e!(0.5 * 3), f(1.5)),
+            (e!(2 * x * 0.25), e!(0.5 * x)),
+            (e!(4.0 ^ (1 / 2)), f(2.0)),
+            (e!(4 ^ 0.5), f(2.0)),
+            (e!(x + 1.5 + 1 / 2), e!(x + 2.0)),
+            (e!(oo + 1.5), e!(oo)),
+            (e!(-0.5 * oo), e!(-oo)),
+            (Expr::float(f64::NAN), e!(undef)),
+            // zero floats behave like 0
+            (e!(0.0 * oo), e!(undef)),
+            (e!(1 / 0.0), e!(zoo)),
+            (e!(1 / 0.0) * f(-1.0), e!(zoo)),
+            (e!(1) / f(-0.0), e!(zoo)),
+            (e!(0.0 ^ 0), e!(undef)),
+        ];
+        for (calc, res) in checks {
+            eq!(calc.reduce(), res, "{calc}");
+        }
+
+        eq!(e!(0.5 * x).to_string(), "0.5x");
+        eq!(e!(y - 0.5 * x).to_string(), "y − 0.5x");
+        eq!(format!("{:.2}", e!(x / 3.0)), "0.33x");
+        eq!(e!(2 ^ 0.5).to_latex(), format!("{:?}", 2f64.sqrt()));
     }
 
     #[test]
@@ -1,6 +1,7 @@
 use crate::{
+    float::Float,
     polynomial::{MonomialView, PolynomialView, VarSet},
-    rational::Rational,
+    rational::{Int, Rational},
     sym_fmt,
     utils::{log_macros::*, HashSet},
 };
@@ -35,12 +36,21 @@ pub struct Var(pub(crate) PTR<str>);
 pub enum Atom {
     #[debug("undef")]
     Undef,
-    #[from(i32, i64, u32, u64, i128, Rational)]
+    #[from(i32, i64, u32, u64, i128, Int, Rational)]
     #[debug("{_0:?}")]
     Rational(Rational),
     #[from]
     #[debug("{_0:?}")]
     Irrational(Irrational),
+    #[from]
+    #[debug("{_0:?}")]
+    Float(Float),
+    /// the imaginary unit, i^2 = -1
+    #[debug("i")]
+    ImagUnit,
+    #[from]
+    #[debug("{_0:?}")]
+    Infinity(Infinity),
     #[from(forward)]
     #[debug("{_0:?}")]
     Var(Var),
@@ -56,11 +66,26 @@ pub enum Atom {
     #[from]
     #[debug("{_0:?}")]
     Func(Func),
+    #[from]
+    #[debug("{_0:?}")]
+    Bool(bool),
+    /// a relation between two expressions, e.g x < 3
+    #[from]
+    #[debug("{_0:?}")]
+    Rel(Rel),
+    #[from]
+    #[debug("{_0:?}")]
+    Logic(Logic),
+    /// a finite list of expressions, e.g divisors(12) = {1, 2, 3, 4, 6, 12}
+    #[from]
+    #[debug("List{_0:?}")]
+    List(Vec<Expr>),
 }
 
 impl fmt::Display for Atom {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.fmt_ast())
+        // forward the formatter, so that e.g the float precision is kept
+        fmt::Display::fmt(&self.fmt_ast(), f)
     }
 }
 
@@ -75,9 +100,17 @@ impl Atom {
 
     pub const PI: Atom = Atom::Irrational(Irrational::PI);
     pub const E: Atom = Atom::Irrational(Irrational::E);
+    pub const I: Atom = Atom::ImagUnit;
+    pub const INF: Atom = Atom::Infinity(Infinity::Pos);
+    pub const NEG_INF: Atom = Atom::Infinity(Infinity::Neg);
+    pub const COMPLEX_INF: Atom = Atom::Infinity(Infinity::Complex);
 
+    /// true for 0 and the float zeros 0.0 and -0.0
     pub fn is_zero(&self) -> bool {
-        self == &Atom::ZERO
+        match self {
+            Atom::Float(f) => f.is_zero(),
+            _ => self == &Atom::ZERO,
+        }
     }
     pub fn is_one(&self) -> bool {
         self == &Atom::ONE
@@ -92,10 +125,16 @@ impl Atom {
         self == &Atom::E
     }
     pub fn is_neg(&self) -> bool {
-        self.is_rational_and(Rational::is_neg)
+        match self {
+            Atom::Float(f) => f.is_neg(),
+            _ => self.is_rational_and(Rational::is_neg),
+        }
     }
     pub fn is_pos(&self) -> bool {
-        self.is_rational_and(Rational::is_pos)
+        match self {
+            Atom::Float(f) => f.is_pos(),
+            _ => self.is_rational_and(Rational::is_pos),
+        }
     }
     pub fn is_int(&self) -> bool {
         self.is_rational_and(Rational::is_int)
@@ -110,13 +149,26 @@ impl Atom {
         self.is_real()
     }
     pub fn is_real(&self) -> bool {
-        self.is_rational() || self.is_irrational()
+        self.is_rational() || self.is_irrational() || self.is_float()
     }
     pub fn is_irreducible(&self) -> bool {
         match self {
-            Atom::Undef | Atom::Rational(_) | Atom::Irrational(_) | Atom::Var(_) => true,
-
-            Atom::Func(_) | Atom::Sum(_) | Atom::Prod(_) | Atom::Pow(_) => false,
+            Atom::Undef
+            | Atom::Rational(_)
+            | Atom::Irrational(_)
+            | Atom::Float(_)
+            | Atom::ImagUnit
+            | Atom::Infinity(_)
+            | Atom::Var(_)
+            | Atom::Bool(_) => true,
+
+            Atom::Func(_)
+            | Atom::Sum(_)
+            | Atom::Prod(_)
+            | Atom::Pow(_)
+            | Atom::Rel(_)
+            | Atom::Logic(_)
+            | Atom::List(_) => false,
         }
     }
     pub fn is_rational_and(&self, cond: impl Fn(&Rational) -> bool) -> bool {
@@ -126,7 +178,7 @@ impl Atom {
         }
     }
     pub fn is_const(&self) -> bool {
-        self.is_number()
+        self.is_number() || self.is_imag_unit() || self.is_infinity()
     }
     pub fn is_sin(&self) -> bool {
         match self {
@@ -147,20 +199,21 @@ impl Atom {
     //    !self.is_coeff()
     //}
 
-    pub fn try_unwrap_int(&self) -> Option<i128> {
+    pub fn try_unwrap_int(&self) -> Option<Int> {
         match self {
-            Atom::Rational(r) if r.is_int() => Some(r.numer().into()),
+            Atom::Rational(r) => r.to_int(),
             _ => None,
         }
     }
-    pub fn unwrap_int(&self) -> i128 {
+    pub fn unwrap_int(&self) -> Int {
         self.try_unwrap_int().unwrap()
     }
 
     pub fn try_as_real(&self) -> Option<Real> {
         match self {
-            Atom::Rational(r) => Some(Real::Rational(*r)),
+            Atom::Rational(r) => Some(Real::Rational(r.clone())),
             Atom::Irrational(i) => Some(Real::Irrational(*i)),
+            Atom::Float(f) => Some(Real::Float(*f)),
             _ => None,
         }
     }
@@ -172,6 +225,10 @@ impl Atom {
     pub fn fmt_ast(&self) -> sym_fmt::FmtAtom {
         sym_fmt::FmtAtom::from(self)
     }
+
+    pub fn to_latex(&self) -> String {
+        self.fmt_ast().fmt_with::<sym_fmt::LatexFmt>().to_string()
+    }
 }
 
 impl PartialOrd for Atom {
@@ -185,7 +242,7 @@ fn cmp_slice_rev(lhs: &[Expr], rhs: &[Expr]) -> cmp::Ordering {
 
     for (l, r) in args {
         if !l.cmp(r).is_eq() {
-            return l.cmp(r)
+            return l.cmp(r);
         }
     }
 
@@ -196,7 +253,7 @@ fn cmp_slice(lhs: &[Expr], rhs: &[Expr]) -> cmp::Ordering {
 
     for (l, r) in args {
         if !l.cmp(r).is_eq() {
-            return l.cmp(r)
+            return l.cmp(r);
         }
     }
 
@@ -212,12 +269,30 @@ impl Ord for Atom {
         if let (Some(l), Some(r)) = (&r1, &r2) {
             return l.cmp(r);
         } else if r1.is_some() {
-            return cmp::Ordering::Less
+            return cmp::Ordering::Less;
         } else if r2.is_some() {
-            return cmp::Ordering::Greater
+            return cmp::Ordering::Greater;
         }
 
         match (lhs, rhs) {
+            (A::ImagUnit, A::ImagUnit) => cmp::Ordering::Equal,
+            (A::ImagUnit, _) => cmp::Ordering::Less,
+            (_, A::ImagUnit) => cmp::Ordering::Greater,
+            (A::Infinity(l), A::Infinity(r)) => l.cmp(r),
+            (A::Infinity(_), _) => cmp::Ordering::Less,
+            (_, A::Infinity(_)) => cmp::Ordering::Greater,
+            (A::Bool(l), A::Bool(r)) => l.cmp(r),
+            (A::Bool(_), _) => cmp::Ordering::Less,
+            (_, A::Bool(_)) => cmp::Ordering::Greater,
+            // relations, connectives and lists come after all other expressions
+            (A::List(l), A::List(r)) => cmp_slice(l, r),
+            (A::List(_), _) => cmp::Ordering::Greater,
+            (_, A::List(_)) => cmp::Ordering::Less,
+            (A::Rel(l), A::Rel(r)) => l.cmp(r),
+            (A::Logic(l), A::Logic(r)) => l.cmp(r),
+            (A::Rel(_), A::Logic(_)) => cmp::Ordering::Less,
+            (A::Rel(_) | A::Logic(_), _) => cmp::Ordering::Greater,
+            (_, A::Rel(_) | A::Logic(_)) => cmp::Ordering::Less,
             (A::Var(l), A::Var(r)) => l.cmp(r),
             (A::Prod(_), A::Prod(_)) | (A::Sum(_), A::Sum(_)) => {
                 cmp_slice_rev(lhs.args(), rhs.args())
@@ -261,9 +336,7 @@ impl Ord for Atom {
                     n.as_str().cmp(&v.0)
                 }
             }
-            (_, _) => {
-                rhs.cmp(lhs).reverse()
-            }
+            (_, _) => rhs.cmp(lhs).reverse(),
         }
     }
 }
@@ -275,6 +348,8 @@ pub enum Real {
     Rational(Rational),
     #[debug("{_0}")]
     Irrational(Irrational),
+    #[debug("{_0}")]
+    Float(Float),
 }
 
 impl PartialOrd for Real {
@@ -285,7 +360,10 @@ impl PartialOrd for Real {
 impl Ord for Real {
     fn cmp(&self, other: &Self) -> cmp::Ordering {
         use ordered_float::OrderedFloat;
-        OrderedFloat(self.f64_approx()).cmp(&OrderedFloat(other.f64_approx()))
+        // exact numbers come before equal floats
+        OrderedFloat(self.f64_approx())
+            .cmp(&OrderedFloat(other.f64_approx()))
+            .then_with(|| self.is_float().cmp(&other.is_float()))
     }
 }
 
@@ -297,8 +375,13 @@ impl Real {
         match self {
             Real::Rational(r) => r.f64_approx(),
             Real::Irrational(i) => i.f64_approx(),
+            Real::Float(f) => f.to_f64(),
         }
     }
+
+    pub fn is_float(&self) -> bool {
+        matches!(self, Real::Float(_))
+    }
 }
 
 impl PartialEq<Atom> for Real {
@@ -306,6 +389,7 @@ impl PartialEq<Atom> for Real {
         match self {
             Real::Rational(r) => other.try_unwrap_rational_ref() == Ok(r),
             Real::Irrational(i) => other.try_unwrap_irrational_ref() == Ok(i),
+            Real::Float(f) => other.try_unwrap_float_ref() == Ok(f),
         }
     }
 }
@@ -320,6 +404,7 @@ impl From<Real> for Atom {
         match value {
             Real::Rational(r) => r.into(),
             Real::Irrational(i) => i.into(),
+            Real::Float(f) => f.into(),
         }
     }
 }
@@ -345,6 +430,67 @@ impl Irrational {
     }
 }
 
+/// points at infinity of the extended real line and the riemann sphere
+#[derive(
+    Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display, Serialize, Deserialize,
+)]
+pub enum Infinity {
+    #[debug("oo")]
+    #[display("{}", unicode::inf())]
+    Pos,
+    #[debug("-oo")]
+    #[display("{}{}", unicode::unry_sub(), unicode::inf())]
+    Neg,
+    /// infinity with unknown direction, e.g 1/0
+    #[debug("zoo")]
+    #[display("{}", unicode::complex_inf())]
+    Complex,
+}
+
+impl Infinity {
+    pub fn is_real(&self) -> bool {
+        !matches!(self, Infinity::Complex)
+    }
+
+    /// oo + oo = oo, returns [None] for oo - oo
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        match (self, rhs) {
+            (Infinity::Pos, Infinity::Pos) => Some(Infinity::Pos),
+            (Infinity::Neg, Infinity::Neg) => Some(Infinity::Neg),
+            _ => None,
+        }
+    }
+}
+
+impl ops::Neg for Infinity {
+    type Output = Self;
+
+    /// -oo => oo, oo => -oo
+    fn neg(self) -> Self::Output {
+        match self {
+            Infinity::Pos => Infinity::Neg,
+            Infinity::Neg => Infinity::Pos,
+            Infinity::Complex => Infinity::Complex,
+        }
+    }
+}
+
+impl ops::Mul for Infinity {
+    type Output = Self;
+
+    /// oo * -oo = -oo
+    fn mul(self, rhs: Self) -> Self::Output {
+        match (self, rhs) {
+            (Infinity::Complex, _) | (_, Infinity::Complex) => Infinity::Complex,
+            (lhs, rhs) if lhs == rhs => Infinity::Pos,
+            _ => Infinity::Neg,
+        }
+    }
+}
+
+/// see [Func::int_fn]
+pub type IntFn = fn(&[Int]) -> Option<Int>;
+
 #[derive(
     Clone,
     PartialEq,
@@ -362,7 +508,7 @@ impl Irrational {
 )]
 #[unwrap(ref)]
 #[try_unwrap(ref)]
-#[display("{}({_0})", self.name())]
+#[display("{}({_variant})", self.name())]
 pub enum Func {
     Sin(Expr),
     ArcSin(Expr),
@@ -383,8 +529,31 @@ pub enum Func {
     Csc(Expr),
     ArcCsc(Expr),
 
+    Re(Expr),
+    Im(Expr),
+    Conj(Expr),
+    Arg(Expr),
+
+    IsPrime(Expr),
+    NextPrime(Expr),
+    Totient(Expr),
+    Divisors(Expr),
+    #[display("{}, {}", _0[0], _0[1])]
+    Mod([Expr; 2]),
+    #[display("{}, {}, {}", _0[0], _0[1], _0[2])]
+    PowMod([Expr; 3]),
+    #[display("{}, {}", _0[0], _0[1])]
+    ModInv([Expr; 2]),
+
+    /// k-th root of a polynomial with rational coefficients, see [Expr::solve_poly]
+    #[display("{}, {}", _0[0], _0[1])]
+    RootOf([Expr; 2]),
+
+    /// the order term O(x) of a truncated series, see [Expr::series]
+    Order(Expr),
+
     //Exp(Expr),
-    #[display("{}{_1}", self.name())]
+    #[display("{_1}")]
     Log(Real, Expr),
 }
 
@@ -436,13 +605,43 @@ impl Func {
             Func::ArcCot(_) => "arccot",
             Func::Csc(_) => "csc",
             Func::ArcCsc(_) => "arccsc",
+            Func::Re(_) => "re",
+            Func::Im(_) => "im",
+            Func::Conj(_) => "conj",
+            Func::Arg(_) => "arg",
+            Func::IsPrime(_) => "isprime",
+            Func::NextPrime(_) => "nextprime",
+            Func::Totient(_) => "totient",
+            Func::Divisors(_) => "divisors",
+            Func::Mod(_) => "mod",
+            Func::PowMod(_) => "powmod",
+            Func::ModInv(_) => "modinv",
+            Func::RootOf(_) => "rootof",
+            Func::Order(_) => "O",
             //Func::Exp(_) => "exp",
-            Func::Log(Real::Irrational(Irrational::E), _) => "ln".into(),
-            Func::Log(Real::Rational(r), _) if r == &Rational::from(10) => "log".into(),
+            Func::Log(Real::Irrational(Irrational::E), _) => "ln",
+            Func::Log(Real::Rational(r), _) if r == &Rational::from(10) => "log",
             Func::Log(base, _) => return format!("log{base}"),
         }
         .into()
     }
+
+    /// the number theoretic functions evaluated on integer arguments
+    ///
+    /// the returned function gives None if the arguments are outside of its domain
+    pub fn int_fn(&self) -> Option<IntFn> {
+        use Func as F;
+        Some(match self {
+            F::IsPrime(_) => |a| Some(Int::from(a[0].is_prime() as u32)),
+            F::NextPrime(_) => |a| Some(a[0].next_prime()),
+            F::Totient(_) => |a| a[0].totient(),
+            F::Mod(_) => |a| a[0].modulo(&a[1]),
+            F::PowMod(_) => |a| a[0].pow_mod(&a[1], &a[2]),
+            F::ModInv(_) => |a| a[0].mod_inv(&a[1]),
+            _ => return None,
+        })
+    }
+
     //pub fn iter_args_mut(&mut self) -> impl Iterator<Item = &mut Expr> {
     //    self.args_mut().iter_mut()
     //}
@@ -476,11 +675,31 @@ impl Func {
                 e(-1) * d(f) / (E::sqrt(e(1) - e(1) / E::pow(f, e(2))) * E::pow(f, e(2)))
             }
             F::Log(base, f) => d(f) * E::pow(f * E::ln(E::from(base.clone())), e(-1)),
+            F::Re(f) => E::re(d(f)),
+            F::Im(f) => E::im(d(f)),
+            F::Conj(f) => E::conj(d(f)),
+            // arg(u)' = (re(u)*im(u)' - im(u)*re(u)') / (re(u)^2 + im(u)^2)
+            F::Arg(f) => {
+                let (re, im, df) = (E::re(f), E::im(f), d(f));
+                (&re * E::im(&df) - &im * E::re(df)) / (E::pow(re, e(2)) + E::pow(im, e(2)))
+            }
+            // only defined on the integers
+            F::IsPrime(_)
+            | F::NextPrime(_)
+            | F::Totient(_)
+            | F::Divisors(_)
+            | F::Mod(_)
+            | F::PowMod(_)
+            | F::ModInv(_) => E::undef(),
+            // constant
+            F::RootOf(_) => E::zero(),
+            F::Order(_) => E::undef(),
             //F::Exp(f) => E::exp(f) * d(f),
         }
     }
 }
 
+#[allow(clippy::derived_hash_with_manual_eq)]
 #[derive(Clone, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct Sum {
     pub args: Vec<Expr>,
@@ -560,6 +779,7 @@ impl Sum {
     }
 }
 
+#[allow(clippy::derived_hash_with_manual_eq)]
 #[derive(Clone, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct Prod {
     pub args: Vec<Expr>,
@@ -681,6 +901,189 @@ impl fmt::Debug for Pow {
     }
 }
 
+/// the comparison of a [Rel]
+#[derive(
+    Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display, Serialize, Deserialize,
+)]
+pub enum RelOp {
+    #[debug("==")]
+    #[display("=")]
+    Eq,
+    #[debug("!=")]
+    #[display("{}", unicode::ne())]
+    Ne,
+    #[debug("<")]
+    #[display("<")]
+    Lt,
+    #[debug("<=")]
+    #[display("{}", unicode::le())]
+    Le,
+    #[debug(">")]
+    #[display(">")]
+    Gt,
+    #[debug(">=")]
+    #[display("{}", unicode::ge())]
+    Ge,
+}
+
+impl RelOp {
+    /// a op b <=> not (a op' b)
+    pub fn negate(self) -> Self {
+        use RelOp as R;
+        match self {
+            R::Eq => R::Ne,
+            R::Ne => R::Eq,
+            R::Lt => R::Ge,
+            R::Le => R::Gt,
+            R::Gt => R::Le,
+            R::Ge => R::Lt,
+        }
+    }
+
+    /// a op b <=> b op' a
+    pub fn reverse(self) -> Self {
+        use RelOp as R;
+        match self {
+            R::Lt => R::Gt,
+            R::Le => R::Ge,
+            R::Gt => R::Lt,
+            R::Ge => R::Le,
+            op => op,
+        }
+    }
+
+    pub fn is_ineq(self) -> bool {
+        !matches!(self, RelOp::Eq | RelOp::Ne)
+    }
+
+    /// whether a op b holds, given the ordering of a and b
+    pub fn holds(self, ord: cmp::Ordering) -> bool {
+        use RelOp as R;
+        match self {
+            R::Eq => ord.is_eq(),
+            R::Ne => ord.is_ne(),
+            R::Lt => ord.is_lt(),
+            R::Le => ord.is_le(),
+            R::Gt => ord.is_gt(),
+            R::Ge => ord.is_ge(),
+        }
+    }
+}
+
+/// the relation lhs op rhs, e.g x + 1 < 3
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Rel {
+    pub op: RelOp,
+    /// [lhs, rhs]
+    pub(crate) args: [Expr; 2],
+}
+
+impl Rel {
+    pub fn new(op: RelOp, lhs: impl Borrow<Expr>, rhs: impl Borrow<Expr>) -> Self {
+        Rel {
+            op,
+            args: [lhs.borrow().clone(), rhs.borrow().clone()],
+        }
+    }
+
+    pub fn lhs(&self) -> &Expr {
+        &self.args[0]
+    }
+
+    pub fn rhs(&self) -> &Expr {
+        &self.args[1]
+    }
+
+    /// a op b => a + e op b + e
+    pub fn add(&self, e: &Expr) -> Rel {
+        Rel::new(self.op, self.lhs() + e, self.rhs() + e)
+    }
+
+    /// a op b => a * e op b * e
+    ///
+    /// inequalities are flipped if `e` is negative. Returns [None] for an inequality if the
+    /// sign of `e` is unknown or zero
+    pub fn mul(&self, e: &Expr) -> Option<Rel> {
+        let op = match self.op {
+            op if !op.is_ineq() => op,
+            op => match e.cmp_real(&Expr::zero())? {
+                cmp::Ordering::Greater => op,
+                cmp::Ordering::Less => op.reverse(),
+                cmp::Ordering::Equal => return None,
+            },
+        };
+        Some(Rel::new(op, self.lhs() * e, self.rhs() * e))
+    }
+}
+
+impl fmt::Debug for Rel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} {:?} {:?}", self.args[0], self.op, self.args[1])
+    }
+}
+
+/// boolean connectives, usually of relations
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, IsVariant)]
+pub enum Logic {
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+    Not(Expr),
+}
+
+impl fmt::Debug for Logic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Logic::And(_) => "And",
+            Logic::Or(_) => "Or",
+            Logic::Not(_) => "Not",
+        };
+        write!(f, "{name}[")?;
+        let mut args = self.iter_args();
+        if let Some(a) = args.next() {
+            write!(f, "{a:?}")?;
+        }
+        for a in args {
+            write!(f, ", {a:?}")?;
+        }
+        write!(f, "]")
+    }
+}
+
+impl Logic {
+    /// flattens, sorts and deduplicates the arguments of and / or
+    ///
+    /// `unit` is the neutral element, true for and, false for or
+    fn reduce_assoc(args: &[Expr], unit: bool) -> Expr {
+        let mut flat = vec![];
+        for a in args {
+            match a.atom() {
+                Atom::Logic(Logic::And(inner)) if unit => flat.extend(inner.iter().cloned()),
+                Atom::Logic(Logic::Or(inner)) if !unit => flat.extend(inner.iter().cloned()),
+                Atom::Bool(b) if *b == unit => (),
+                Atom::Bool(_) => return Expr::from(!unit),
+                _ => flat.push(a.clone()),
+            }
+        }
+        flat.sort();
+        flat.dedup();
+
+        // p and not p = false, p or not p = true
+        let complement = flat
+            .iter()
+            .any(|a| matches!(a.atom(), Atom::Logic(Logic::Not(p)) if flat.contains(p)));
+        if complement {
+            return Expr::from(!unit);
+        }
+
+        match flat.len() {
+            0 => Expr::from(unit),
+            1 => flat.remove(0),
+            _ if unit => Expr::from(Logic::And(flat)),
+            _ => Expr::from(Logic::Or(flat)),
+        }
+    }
+}
+
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub(crate) struct Explanation {
     pub(crate) explanation: PTR<str>,
@@ -689,9 +1092,8 @@ pub(crate) struct Explanation {
 
 const RECORD_STEPS: bool = false;
 
-#[derive(Clone, Debug, Display, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[debug("{:?}", self.atom())]
-#[display("{}", self.fmt_ast())]
 pub struct Expr {
     pub(crate) atom: PTR<Atom>,
     //pub(crate) expl: Option<Explanation>,
@@ -703,7 +1105,7 @@ impl hash::Hash for Expr {
     }
 }
 
-fn expr_as_cmp_slice<'a>(e: &'a Expr) -> &[Expr] {
+fn expr_as_cmp_slice(e: &Expr) -> &[Expr] {
     match e.atom() {
         Atom::Sum(_) | Atom::Prod(_) => e.args(),
         _ => slice::from_ref(e),
@@ -741,6 +1143,12 @@ impl<T: Into<Atom>> From<T> for Expr {
     }
 }
 
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.atom(), f)
+    }
+}
+
 impl ops::Deref for Expr {
     type Target = Atom;
 
@@ -772,6 +1180,10 @@ impl Expr {
 
         static _PI: Expr = Expr::from(Atom::PI);
         static _E: Expr = Expr::from(Atom::E);
+        static _I: Expr = Expr::from(Atom::I);
+        static _INF: Expr = Expr::from(Atom::INF);
+        static _NEG_INF: Expr = Expr::from(Atom::NEG_INF);
+        static _COMPLEX_INF: Expr = Expr::from(Atom::COMPLEX_INF);
     }
 
     fn from_atom(a: Atom) -> Expr {
@@ -805,6 +1217,18 @@ impl Expr {
     pub fn e() -> Expr {
         Self::_E.with(|e| e.clone())
     }
+    pub fn i() -> Expr {
+        Self::_I.with(|e| e.clone())
+    }
+    pub fn inf() -> Expr {
+        Self::_INF.with(|e| e.clone())
+    }
+    pub fn neg_inf() -> Expr {
+        Self::_NEG_INF.with(|e| e.clone())
+    }
+    pub fn complex_inf() -> Expr {
+        Self::_COMPLEX_INF.with(|e| e.clone())
+    }
 
     /*
     pub fn min_two() -> Expr { Expr::from_atom(Atom::Rational(Rational::from(-2))) }
@@ -829,6 +1253,17 @@ impl Expr {
         Atom::Rational(r.into()).into()
     }
 
+    /// NaN is mapped to [Expr::undef] and ±inf to the infinity atoms
+    pub fn float<T: Into<Float>>(f: T) -> Expr {
+        let f = f.into();
+        match f.to_f64() {
+            v if v.is_nan() => Expr::undef(),
+            f64::INFINITY => Expr::inf(),
+            f64::NEG_INFINITY => Expr::neg_inf(),
+            _ => Atom::Float(f).into(),
+        }
+    }
+
     func_atom!(cos);
     func_atom!(arc_cos);
     func_atom!(sin);
@@ -841,6 +1276,65 @@ impl Expr {
     func_atom!(arc_cot);
     func_atom!(csc);
     func_atom!(arc_csc);
+    func_atom!(re);
+    func_atom!(im);
+    func_atom!(conj);
+    func_atom!(arg);
+    func_atom!(totient);
+    func_atom!(divisors);
+
+    pub fn isprime(e: impl Borrow<Expr>) -> Expr {
+        Expr::from(Atom::Func(Func::IsPrime(e.borrow().clone())))
+    }
+    pub fn nextprime(e: impl Borrow<Expr>) -> Expr {
+        Expr::from(Atom::Func(Func::NextPrime(e.borrow().clone())))
+    }
+    pub fn modulo(e: impl Borrow<Expr>, m: impl Borrow<Expr>) -> Expr {
+        let args = [e.borrow().clone(), m.borrow().clone()];
+        Expr::from(Atom::Func(Func::Mod(args)))
+    }
+    pub fn powmod(b: impl Borrow<Expr>, e: impl Borrow<Expr>, m: impl Borrow<Expr>) -> Expr {
+        let args = [b.borrow().clone(), e.borrow().clone(), m.borrow().clone()];
+        Expr::from(Atom::Func(Func::PowMod(args)))
+    }
+    pub fn modinv(e: impl Borrow<Expr>, m: impl Borrow<Expr>) -> Expr {
+        let args = [e.borrow().clone(), m.borrow().clone()];
+        Expr::from(Atom::Func(Func::ModInv(args)))
+    }
+    /// the bound variable of the polynomial in [Func::RootOf]
+    pub const ROOT_OF_VAR: &'static str = "_x";
+
+    /// the k-th root of a univariate polynomial, the real roots come first in ascending
+    /// order, followed by the complex roots ordered by their real and imaginary part
+    ///
+    /// the variable of the polynomial is replaced by [Expr::ROOT_OF_VAR], so that e.g
+    /// rootof(x^5 - x + 1, 0) is free of x
+    pub fn root_of(poly: impl Borrow<Expr>, k: impl Borrow<Expr>) -> Expr {
+        let mut poly = poly.borrow().clone();
+        let vars: Vec<_> = poly.variables().into_iter().collect();
+        if let [x] = vars.as_slice() {
+            if x.is_var() {
+                poly = poly.substitude(x, &Expr::var(Self::ROOT_OF_VAR));
+            }
+        }
+        let args = [poly, k.borrow().clone()];
+        Expr::from(Atom::Func(Func::RootOf(args)))
+    }
+    pub fn order(e: impl Borrow<Expr>) -> Expr {
+        Expr::from(Atom::Func(Func::Order(e.borrow().clone())))
+    }
+    pub fn rel(op: RelOp, lhs: impl Borrow<Expr>, rhs: impl Borrow<Expr>) -> Expr {
+        Expr::from(Rel::new(op, lhs, rhs))
+    }
+    pub fn and(lhs: impl Borrow<Expr>, rhs: impl Borrow<Expr>) -> Expr {
+        Expr::from(Logic::And(vec![lhs.borrow().clone(), rhs.borrow().clone()]))
+    }
+    pub fn or(lhs: impl Borrow<Expr>, rhs: impl Borrow<Expr>) -> Expr {
+        Expr::from(Logic::Or(vec![lhs.borrow().clone(), rhs.borrow().clone()]))
+    }
+    pub fn not(e: impl Borrow<Expr>) -> Expr {
+        Expr::from(Logic::Not(e.borrow().clone()))
+    }
 
     pub fn exp(e: impl Borrow<Expr>) -> Expr {
         Expr::pow(Expr::e(), e)
@@ -869,13 +1363,14 @@ impl Expr {
         PolynomialView::new(self, vars)
     }
 
+    /// the factors with a non-negative exponent, e.g numerator(x / y^(1/2)) = x
     pub fn numerator(&self) -> Expr {
         use Atom as A;
         match self.atom() {
             A::Undef => self.clone(),
             A::Rational(r) => r.numer().into(),
             A::Pow(pow) => {
-                if pow.exponent().is_min_one() {
+                if pow.exponent().is_neg() {
                     Expr::one()
                 } else {
                     self.clone()
@@ -888,6 +1383,7 @@ impl Expr {
             _ => self.clone(),
         }
     }
+    /// the factors with a negative exponent, inverted, e.g denominator(x / y^(1/2)) = y^(1/2)
     pub fn denominator(&self) -> Expr {
         use Atom as A;
         match self.atom() {
@@ -896,6 +1392,8 @@ impl Expr {
             A::Pow(pow) => {
                 if pow.exponent().is_min_one() {
                     pow.base().clone()
+                } else if pow.exponent().is_neg() {
+                    Expr::pow(pow.base(), Expr::min_one() * pow.exponent())
                 } else {
                     Expr::one()
                 }
@@ -964,9 +1462,18 @@ impl Expr {
         }
 
         match self.atom() {
-            Atom::Irrational(_) | Atom::Var(_) | Atom::Sum(_) | Atom::Pow(_) | Atom::Func(_) => {
-                Some(Rational::ONE)
-            }
+            Atom::Irrational(_)
+            | Atom::Float(_)
+            | Atom::ImagUnit
+            | Atom::Infinity(_)
+            | Atom::Var(_)
+            | Atom::Sum(_)
+            | Atom::Pow(_)
+            | Atom::Func(_)
+            | Atom::Bool(_)
+            | Atom::Rel(_)
+            | Atom::Logic(_)
+            | Atom::List(_) => Some(Rational::ONE),
             Atom::Prod(prod) => prod
                 .iter_args()
                 .filter_map(|a| a.try_unwrap_rational_ref().ok())
@@ -976,15 +1483,33 @@ impl Expr {
             Atom::Rational(_) | Atom::Undef => None,
         }
     }
+    /// k mod 4 if [self] is k * pi/2 for an integer k
+    pub(crate) fn half_pi_multiple(&self) -> Option<usize> {
+        if !self.non_rational_term()?.is_pi() {
+            return None;
+        }
+        let k = self.rational_coeff()? * Rational::TWO;
+        let k = k.to_int()?.modulo(&Int::from(4))?;
+        num::ToPrimitive::to_usize(&k)
+    }
     pub fn non_rational_term(&self) -> Option<Expr> {
         if self.is_undef() {
             return None;
         }
 
         match self.atom() {
-            Atom::Irrational(_) | Atom::Var(_) | Atom::Sum(_) | Atom::Pow(_) | Atom::Func(_) => {
-                Some(self.clone())
-            }
+            Atom::Irrational(_)
+            | Atom::Float(_)
+            | Atom::ImagUnit
+            | Atom::Infinity(_)
+            | Atom::Var(_)
+            | Atom::Sum(_)
+            | Atom::Pow(_)
+            | Atom::Func(_)
+            | Atom::Bool(_)
+            | Atom::Rel(_)
+            | Atom::Logic(_)
+            | Atom::List(_) => Some(self.clone()),
             Atom::Prod(prod) => {
                 let mut terms: Vec<_> = prod
                     .iter_args()
@@ -1037,7 +1562,13 @@ impl Expr {
     fn variables_impl(&self, vars: &mut HashSet<Expr>) {
         use Atom as A;
         match self.atom() {
-            A::Irrational(_) | A::Rational(_) | A::Undef => (),
+            A::Irrational(_)
+            | A::Rational(_)
+            | A::Float(_)
+            | A::ImagUnit
+            | A::Infinity(_)
+            | A::Bool(_)
+            | A::Undef => (),
             A::Var(_) => {
                 vars.insert(self.clone());
             }
@@ -1060,7 +1591,10 @@ impl Expr {
                 }
                 vars.insert(self.clone());
             }
-            A::Func(_) => todo!(),
+            A::Func(_) => {
+                vars.insert(self.clone());
+            }
+            A::Rel(_) | A::Logic(_) | A::List(_) => self.for_each_arg(|a| a.variables_impl(vars)),
         }
     }
 
@@ -1207,22 +1741,42 @@ impl SymbolicExpr for Atom {
     fn args(&self) -> &[Expr] {
         use Atom as A;
         match self {
-            A::Undef | A::Rational(_) | A::Irrational(_) | A::Var(_) => &[],
+            A::Undef
+            | A::Rational(_)
+            | A::Irrational(_)
+            | A::Float(_)
+            | A::ImagUnit
+            | A::Infinity(_)
+            | A::Var(_)
+            | A::Bool(_) => &[],
             A::Sum(sum) => sum.args(),
             A::Prod(prod) => prod.args(),
             A::Pow(pow) => pow.args(),
             A::Func(func) => func.args(),
+            A::Rel(rel) => rel.args(),
+            A::Logic(logic) => logic.args(),
+            A::List(list) => list,
         }
     }
 
     fn args_mut(&mut self) -> &mut [Expr] {
         use Atom as A;
         match self {
-            A::Undef | A::Rational(_) | A::Irrational(_) | A::Var(_) => &mut [],
+            A::Undef
+            | A::Rational(_)
+            | A::Irrational(_)
+            | A::Float(_)
+            | A::ImagUnit
+            | A::Infinity(_)
+            | A::Var(_)
+            | A::Bool(_) => &mut [],
             A::Sum(sum) => sum.args_mut(),
             A::Prod(prod) => prod.args_mut(),
             A::Pow(pow) => pow.args_mut(),
             A::Func(func) => func.args_mut(),
+            A::Rel(rel) => rel.args_mut(),
+            A::Logic(logic) => logic.args_mut(),
+            A::List(list) => list,
         }
     }
 }
@@ -1236,11 +1790,21 @@ impl SymbolicExpr for Expr {
         use Atom as A;
         let res = self.clone().map_args(|a| *a = a.reduce());
         match res.atom() {
-            A::Irrational(_) | A::Undef | A::Rational(_) | A::Var(_) => res,
+            A::Irrational(_)
+            | A::Float(_)
+            | A::ImagUnit
+            | A::Infinity(_)
+            | A::Undef
+            | A::Rational(_)
+            | A::Var(_)
+            | A::Bool(_)
+            | A::List(_) => res,
             A::Sum(sum) => sum.reduce(),
             A::Prod(prod) => prod.reduce(),
             A::Pow(pow) => pow.reduce(),
             A::Func(func) => func.reduce(),
+            A::Rel(rel) => rel.reduce(),
+            A::Logic(logic) => logic.reduce(),
         }
     }
 
@@ -1254,7 +1818,12 @@ impl SymbolicExpr for Expr {
 
 impl SymbolicExpr for Sum {
     fn reduce(&self) -> Expr {
+        // relations are threaded through the sum, e.g (x < 1) + 2 = x + 2 < 3
+        if self.args.iter().any(|a| a.is_rel()) {
+            return self.args.iter().fold(Expr::zero(), Expr::add).reduce();
+        }
         let mut sum = Sum::reduce_rec(&self.args);
+        sum.collect_imag();
         if sum.is_zero() {
             Expr::zero()
         } else if sum.is_undef() {
@@ -1276,7 +1845,11 @@ impl SymbolicExpr for Sum {
 
 impl SymbolicExpr for Prod {
     fn reduce(&self) -> Expr {
+        if self.args.iter().any(|a| a.is_rel()) {
+            return self.args.iter().fold(Expr::one(), Expr::mul).reduce();
+        }
         let mut prod = Prod::reduce_rec(&self.args);
+        prod.collect_complex();
         if prod.is_one() {
             Expr::one()
         } else if prod.is_undef() {
@@ -1302,9 +1875,17 @@ impl SymbolicExpr for Pow {
         if self.base().is_undef()
             || self.exponent().is_undef()
             || (self.base().is_zero() && self.exponent().is_zero())
-            || (self.base().is_zero() && self.exponent().is_neg())
         {
             return Expr::undef();
+        } else if self.base().is_zero() && self.exponent().is_neg() {
+            // division by zero
+            return Expr::complex_inf();
+        } else if let Some(res) = Expr::pow_inf(self.base(), self.exponent()) {
+            return res;
+        } else if let Some(res) = Expr::pow_float(self.base(), self.exponent()) {
+            return res;
+        } else if let Some(res) = Expr::pow_complex(self.base(), self.exponent()) {
+            return res;
         }
 
         if self.base().is_one() {
@@ -1316,10 +1897,20 @@ impl SymbolicExpr for Pow {
         }
 
         match (self.base().atom(), self.exponent().atom()) {
+            (A::ImagUnit, A::Rational(e)) if e.is_int() => Expr::i_pow(&e.numer()),
+            (A::Rational(b), A::Rational(e)) if b.is_neg() && e.denom() == Int::TWO => {
+                // (-b)^(p/2) = b^(p/2) * i^p
+                let abs = Pow {
+                    args: [Expr::from(b.clone().abs()), self.exponent().clone()],
+                };
+                (abs.reduce() * Expr::i_pow(&e.numer())).reduce()
+            }
             (A::Rational(b), A::Rational(e)) => {
                 let (res, rem) = b.clone().pow(e.clone());
                 if rem.is_zero() {
                     Expr::from(res)
+                } else if let Some(res) = Expr::pow_radical(b, e) {
+                    res
                 } else {
                     //Expr::from(res) * Expr::from(b.clone()).pow(Expr::from(rem))
                     A::Pow(self.clone()).into()
@@ -1345,6 +1936,7 @@ impl SymbolicExpr for Pow {
 
 impl SymbolicExpr for Func {
     fn reduce(&self) -> Expr {
+        use Atom as A;
         use Func as F;
 
         //let mut e = self.clone();
@@ -1353,6 +1945,8 @@ impl SymbolicExpr for Func {
             F::Sin(x) => {
                 if x.is_zero() {
                     return Expr::zero();
+                } else if let Some(k) = Expr::half_pi_multiple(&x) {
+                    return Expr::from([0, 1, 0, -1][k]);
                 }
 
                 if let Some(c) = x.rational_coeff() {
@@ -1369,6 +1963,8 @@ impl SymbolicExpr for Func {
             F::Cos(x) => {
                 if x.is_zero() {
                     return Expr::one();
+                } else if let Some(k) = Expr::half_pi_multiple(&x) {
+                    return Expr::from([1, 0, -1, 0][k]);
                 }
 
                 if let Some(c) = x.rational_coeff() {
@@ -1388,6 +1984,24 @@ impl SymbolicExpr for Func {
             //    todo!()
             //}
             F::Log(base, x) if x.atom() == &base => Expr::one(),
+            F::Log(_, x) if x.is_one() => Expr::zero(),
+            F::Re(x) => Expr::reduce_re(&x),
+            F::Im(x) => Expr::reduce_im(&x),
+            F::Conj(x) => Expr::reduce_conj(&x),
+            F::Arg(x) => Expr::reduce_arg(&x),
+            F::IsPrime(x) if x.is_rational() && !x.is_int() => Expr::zero(),
+            // every integer divides 0
+            F::Divisors(n) if n.is_zero() => Expr::undef(),
+            F::Divisors(n) if n.is_int() => {
+                let divs = n.unwrap_int().divisors();
+                Expr::from(divs.into_iter().map(Expr::from).collect::<Vec<_>>())
+            }
+            // mod(mod(x, m), m) = mod(x, m)
+            F::Mod([x, m]) if matches!(x.atom(), A::Func(F::Mod([_, n])) if n == &m) => x,
+            ref f if f.int_fn().is_some() && f.iter_args().all(|a| a.is_int()) => {
+                let args: Vec<_> = f.iter_args().map(|a| a.unwrap_int()).collect();
+                f.int_fn().unwrap()(&args).map_or_else(Expr::undef, Expr::from)
+            }
             _ => e.into(),
         }
     }
@@ -1407,7 +2021,18 @@ impl SymbolicExpr for Func {
             | F::ArcSec(x)
             | F::ArcCot(x)
             | F::ArcCsc(x)
+            | F::Re(x)
+            | F::Im(x)
+            | F::Conj(x)
+            | F::Arg(x)
+            | F::IsPrime(x)
+            | F::NextPrime(x)
+            | F::Totient(x)
+            | F::Divisors(x)
+            | F::Order(x)
             | F::Log(_, x) => slice::from_mut(x),
+            F::Mod(args) | F::ModInv(args) | F::RootOf(args) => args,
+            F::PowMod(args) => args,
         }
     }
 
@@ -1426,7 +2051,71 @@ impl SymbolicExpr for Func {
             | F::ArcSec(x)
             | F::ArcCot(x)
             | F::ArcCsc(x)
+            | F::Re(x)
+            | F::Im(x)
+            | F::Conj(x)
+            | F::Arg(x)
+            | F::IsPrime(x)
+            | F::NextPrime(x)
+            | F::Totient(x)
+            | F::Divisors(x)
+            | F::Order(x)
             | F::Log(_, x) => slice::from_ref(x),
+            F::Mod(args) | F::ModInv(args) | F::RootOf(args) => args,
+            F::PowMod(args) => args,
+        }
+    }
+}
+
+impl SymbolicExpr for Rel {
+    fn reduce(&self) -> Expr {
+        let (lhs, rhs) = (self.lhs(), self.rhs());
+        if lhs.is_undef() || rhs.is_undef() {
+            return Expr::undef();
+        }
+
+        let ord = match lhs == rhs {
+            true => Some(cmp::Ordering::Equal),
+            false => lhs.cmp_real(rhs),
+        };
+        match ord {
+            Some(ord) => Expr::from(self.op.holds(ord)),
+            None => Expr::from(self.clone()),
+        }
+    }
+
+    fn args(&self) -> &[Expr] {
+        &self.args
+    }
+    fn args_mut(&mut self) -> &mut [Expr] {
+        &mut self.args
+    }
+}
+
+impl SymbolicExpr for Logic {
+    fn reduce(&self) -> Expr {
+        match self {
+            Logic::Not(x) => match x.atom() {
+                Atom::Bool(b) => Expr::from(!b),
+                Atom::Logic(Logic::Not(p)) => p.clone(),
+                Atom::Rel(rel) => Rel::new(rel.op.negate(), rel.lhs(), rel.rhs()).reduce(),
+                _ => Expr::from(self.clone()),
+            },
+            Logic::And(args) => Logic::reduce_assoc(args, true),
+            Logic::Or(args) => Logic::reduce_assoc(args, false),
+        }
+    }
+
+    fn args(&self) -> &[Expr] {
+        match self {
+            Logic::And(args) | Logic::Or(args) => args,
+            Logic::Not(x) => slice::from_ref(x),
+        }
+    }
+    fn args_mut(&mut self) -> &mut [Expr] {
+        match self {
+            Logic::And(args) | Logic::Or(args) => args,
+            Logic::Not(x) => slice::from_mut(x),
         }
     }
 }
@@ -1453,6 +2142,20 @@ pub mod unicode {
     symbl!(frac_slash : "/");
     symbl!(pow : "^");
     symbl!(undef : "∅");
+    symbl!(inf : "∞");
+    symbl!(complex_inf : "∞̃");
+    symbl!(ne : "≠");
+    symbl!(le : "≤");
+    symbl!(ge : "≥");
+    symbl!(and : "∧");
+    symbl!(or : "∨");
+    symbl!(not : "¬");
+    symbl!(lbrack_top : "⎡");
+    symbl!(lbrack_mid : "⎢");
+    symbl!(lbrack_bot : "⎣");
+    symbl!(rbrack_top : "⎤");
+    symbl!(rbrack_mid : "⎥");
+    symbl!(rbrack_bot : "⎦");
 }
 
 #[cfg(test)]
@@ -1486,6 +2189,11 @@ mod test {
             nd(e!((2 / 3) * (x * (x + 1)) / (x + 2) * y ^ n)),
             (e!(2 * x * (x + 1) * y ^ n), e!(3 * (x + 2)))
         );
+        eq!(
+            nd(e!(x / (x + 1) ^ 2 * y ^ (-1 / 2))),
+            (e!(x), e!((x + 1) ^ 2 * y ^ (1 / 2)))
+        );
+        eq!(nd(e!(x ^ -3)), (e!(1), e!(x ^ 3)));
     }
 
     #[test]
@@ -1511,9 +2219,85 @@ mod test {
             e!(x * cos(x) * ln(x) + sin(x) * ln(x) + sin(x)).sort_args() //e!(sin(x) + x*cos(x)*ln(x) + sin(x)*ln(x))
         );
         eq!(d(e!(x ^ 2)), e!(2 * x));
+        eq!(
+            e!(arg(x)).derivative(e!(x)),
+            e!((re(x) * im(1) - im(x) * re(1)) / (re(x) ^ 2 + im(x) ^ 2))
+        );
         //eq!(d(exp(e!(sin(x)))), exp(e!(x)));
     }
 
+    #[test]
+    fn number_theory() {
+        let checks = vec![
+            (e!(isprime(97)), e!(1)),
+            (e!(isprime(91)), e!(0)),
+            (e!(isprime(7 / 2)), e!(0)),
+            (e!(nextprime(13)), e!(17)),
+            (e!(totient(36)), e!(12)),
+            (e!(totient(0)), e!(undef)),
+            (e!(modulo(-7, 3)), e!(2)),
+            (e!(modulo(x, 0)), e!(modulo(x, 0))),
+            (e!(modulo(modulo(x, 5), 5)), e!(modulo(x, 5))),
+            (e!(powmod(3, 200, 7)), e!(2)),
+            (e!(powmod(3, -1, 7)), e!(5)),
+            (e!(modinv(3, 7)), e!(5)),
+            (e!(modinv(2, 4)), e!(undef)),
+            (e!(isprime(x)), e!(isprime(x))),
+            (e!(divisors(0)), e!(undef)),
+            (e!(divisors(x)), e!(divisors(x))),
+        ];
+        for (calc, res) in checks {
+            eq!(calc.reduce(), res);
+        }
+        eq!(
+            e!(divisors(-12)).reduce(),
+            Expr::from([1, 2, 3, 4, 6, 12].map(Expr::from).to_vec())
+        );
+        eq!(e!(divisors(6)).reduce().to_string(), "{1, 2, 3, 6}");
+        eq!(e!(divisors(6)).reduce().to_latex(), "\\{1, 2, 3, 6\\}");
+    }
+
+    #[test]
+    fn relations() {
+        let checks = vec![
+            (e!(1 < 2), e!(true)),
+            (e!(2 <= 1), e!(false)),
+            (e!(pi > 3), e!(true)),
+            // too close for floats
+            (e!(pi + 10 ^ -30 > pi), e!(pi + 10 ^ -30 > pi)),
+            (e!(pi + 10 ^ -30 == pi), e!(pi + 10 ^ -30 == pi)),
+            (e!(2 * pi == pi + pi), e!(true)),
+            (e!(1 / 2 == 0.5), e!(true)),
+            (e!(x == x), e!(true)),
+            (e!(x + 1 < x + 3), e!(true)),
+            (e!(x + 1 != x + 1), e!(false)),
+            (e!(x < 3), e!(x < 3)),
+            (e!(undef < 3), e!(undef)),
+            (e!(!(x < 3)), e!(x >= 3)),
+            (e!(!!(a == b)), e!(a == b)),
+            (e!(x != 2 && true), e!(x != 2)),
+            (e!(x < 3 || false), e!(x < 3)),
+            (e!(x < 3 && false), e!(false)),
+            (e!(a || !a), e!(true)),
+            (e!((a && b) && (b && c)), e!(a && b && c)),
+            // algebra on both sides
+            (e!((x - 1 < 3) + 1), e!(x < 4)),
+            (e!(-2 * (x < 3)), e!(-2 * x > -6)),
+            (e!((2 * x >= 1) / 2), e!(x >= 1 / 2)),
+            (e!(y * (x == 3)), e!(y * x == 3 * y)),
+            (e!((x + y == 1) + (x - y == 3)), e!(2 * x == 4)),
+            (e!(y * (x < 3)), e!(undef)),
+            (e!(0 * (x < 3)), e!(undef)),
+        ];
+        for (calc, res) in checks {
+            eq!(
+                calc.reduce().sort_args(),
+                res.reduce().sort_args(),
+                "{calc}"
+            );
+        }
+    }
+
     #[test]
     fn term_const() {
         eq!(e!(2 * y).non_rational_term(), Some(e!(y)));
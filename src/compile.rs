@@ -0,0 +1,396 @@
+use std::f64::consts;
+
+use num::FromPrimitive;
+
+use crate::{
+    atom::{Atom, Expr, Func, Infinity, IntFn, Logic, Real, RelOp, SymbolicExpr, Var},
+    eval::{self, EvalError},
+    rational::Int,
+    utils::HashMap,
+};
+
+/// index of the register an instruction writes to
+type Reg = usize;
+
+#[derive(Debug, Clone, Copy)]
+enum Instr {
+    Const(f64),
+    Var(usize),
+    Add(Reg, Reg),
+    Mul(Reg, Reg),
+    Powi(Reg, i32),
+    Powf(Reg, Reg),
+    /// real root of a possibly negative base, e.g (-8)^(1/3) = -2
+    ///
+    /// the flag is set if the numerator of the exponent is odd
+    OddRoot(Reg, f64, bool),
+    Unary(fn(f64) -> f64, Reg),
+    /// integer function of up to three arguments, see [Func::int_fn]
+    IntFn(IntFn, [Reg; 3], usize),
+    /// 1 if the relation holds, 0 otherwise
+    Rel(RelOp, Reg, Reg),
+    And(Reg, Reg),
+    Or(Reg, Reg),
+}
+
+impl Instr {
+    #[inline]
+    fn apply(self, reg: impl Fn(Reg) -> f64, arg: impl Fn(usize) -> f64) -> f64 {
+        match self {
+            Instr::Const(c) => c,
+            Instr::Var(i) => arg(i),
+            Instr::Add(a, b) => reg(a) + reg(b),
+            Instr::Mul(a, b) => reg(a) * reg(b),
+            Instr::Powi(a, n) => reg(a).powi(n),
+            Instr::Powf(a, b) => reg(a).powf(reg(b)),
+            Instr::OddRoot(a, e, odd) => {
+                let res = reg(a).abs().powf(e);
+                match odd && reg(a).is_sign_negative() {
+                    true => -res,
+                    false => res,
+                }
+            }
+            Instr::Unary(f, a) => f(reg(a)),
+            Instr::IntFn(f, args, n) => args[..n]
+                .iter()
+                .map(|&a| Int::from_f64(reg(a)))
+                .collect::<Option<Vec<_>>>()
+                .and_then(|args| f(&args))
+                .map_or(f64::NAN, |res| res.f64_approx()),
+            Instr::Rel(op, a, b) => reg(a)
+                .partial_cmp(&reg(b))
+                .map_or(f64::NAN, |ord| op.holds(ord) as u8 as f64),
+            Instr::And(a, b) => (reg(a) != 0.0 && reg(b) != 0.0) as u8 as f64,
+            Instr::Or(a, b) => (reg(a) != 0.0 || reg(b) != 0.0) as u8 as f64,
+        }
+    }
+}
+
+/// flat list of instructions, evaluating an [Expr] without walking the tree
+///
+/// every instruction writes to its own register, equal subexpressions are only computed once.
+/// undefined or complex results are returned as NaN
+#[derive(Debug, Clone)]
+pub struct Tape {
+    instrs: Vec<Instr>,
+    n_vars: usize,
+}
+
+/// number of inputs evaluated at once by [Tape::eval_batch]
+const BLOCK: usize = 64;
+
+impl Tape {
+    pub fn new(e: &Expr, vars: &[Var]) -> Result<Self, EvalError> {
+        let mut lower = Lowering {
+            vars,
+            instrs: vec![],
+            cache: HashMap::default(),
+        };
+        lower.expr(e)?;
+        Ok(Tape {
+            instrs: lower.instrs,
+            n_vars: vars.len(),
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.instrs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instrs.is_empty()
+    }
+
+    /// evaluate with the given registers, avoiding an allocation per call
+    pub fn eval_with(&self, args: &[f64], regs: &mut Vec<f64>) -> f64 {
+        assert_eq!(args.len(), self.n_vars, "wrong number of arguments");
+        regs.clear();
+        for instr in &self.instrs {
+            let val = instr.apply(|r| regs[r], |i| args[i]);
+            regs.push(val);
+        }
+        regs.last().copied().unwrap_or(f64::NAN)
+    }
+
+    pub fn eval(&self, args: &[f64]) -> f64 {
+        self.eval_with(args, &mut Vec::with_capacity(self.len()))
+    }
+
+    /// evaluate for many inputs, `args[i]` holds all values of the i-th variable
+    pub fn eval_batch(&self, args: &[&[f64]]) -> Vec<f64> {
+        assert_eq!(args.len(), self.n_vars, "wrong number of arguments");
+        let n = args.first().map_or(1, |a| a.len());
+        assert!(
+            args.iter().all(|a| a.len() == n),
+            "arguments differ in length"
+        );
+
+        let mut res = Vec::with_capacity(n);
+        let mut regs = vec![[0.0; BLOCK]; self.len()];
+
+        for start in (0..n).step_by(BLOCK) {
+            let len = BLOCK.min(n - start);
+            for (i, instr) in self.instrs.iter().enumerate() {
+                let (prev, curr) = regs.split_at_mut(i);
+                for k in 0..len {
+                    curr[0][k] = instr.apply(|r| prev[r][k], |v| args[v][start + k]);
+                }
+            }
+            // the tape always ends with the result
+            res.extend_from_slice(&regs[self.len() - 1][..len]);
+        }
+        res
+    }
+}
+
+struct Lowering<'a> {
+    vars: &'a [Var],
+    instrs: Vec<Instr>,
+    cache: HashMap<Expr, Reg>,
+}
+
+impl Lowering<'_> {
+    fn push(&mut self, instr: Instr) -> Reg {
+        self.instrs.push(instr);
+        self.instrs.len() - 1
+    }
+
+    fn unary(&mut self, f: fn(f64) -> f64, e: &Expr) -> Result<Reg, EvalError> {
+        let a = self.expr(e)?;
+        Ok(self.push(Instr::Unary(f, a)))
+    }
+
+    fn fold(&mut self, args: &[Expr], op: fn(Reg, Reg) -> Instr) -> Result<Reg, EvalError> {
+        let mut regs = args.iter().map(|a| self.expr(a));
+        let first = regs
+            .next()
+            .expect("sum and product have at least one argument")?;
+        let rest: Vec<_> = regs.collect::<Result<_, _>>()?;
+        Ok(rest.into_iter().fold(first, |acc, r| self.push(op(acc, r))))
+    }
+
+    fn expr(&mut self, e: &Expr) -> Result<Reg, EvalError> {
+        if let Some(&r) = self.cache.get(e) {
+            return Ok(r);
+        }
+
+        let reg = match e.atom() {
+            Atom::Undef | Atom::ImagUnit | Atom::Infinity(Infinity::Complex) => {
+                self.push(Instr::Const(f64::NAN))
+            }
+            Atom::Rational(r) => self.push(Instr::Const(r.f64_approx())),
+            Atom::Irrational(i) => self.push(Instr::Const(i.f64_approx())),
+            Atom::Float(f) => self.push(Instr::Const(f.to_f64())),
+            Atom::Infinity(Infinity::Pos) => self.push(Instr::Const(f64::INFINITY)),
+            Atom::Infinity(Infinity::Neg) => self.push(Instr::Const(f64::NEG_INFINITY)),
+            Atom::Var(v) => match self.vars.iter().position(|x| x == v) {
+                Some(i) => self.push(Instr::Var(i)),
+                None => return Err(EvalError::UnboundVar(v.clone())),
+            },
+            Atom::Sum(sum) => self.fold(&sum.args, Instr::Add)?,
+            Atom::Prod(prod) => self.fold(&prod.args, Instr::Mul)?,
+            Atom::Pow(pow) => {
+                let base = self.expr(pow.base())?;
+                match pow.exponent().atom() {
+                    Atom::Rational(r) => match r.to_int().and_then(|n| i32::try_from(&n.0).ok()) {
+                        Some(n) => self.push(Instr::Powi(base, n)),
+                        None if r.denom().is_odd() => {
+                            self.push(Instr::OddRoot(base, r.f64_approx(), r.numer().is_odd()))
+                        }
+                        None => {
+                            let exp = self.expr(pow.exponent())?;
+                            self.push(Instr::Powf(base, exp))
+                        }
+                    },
+                    _ => {
+                        let exp = self.expr(pow.exponent())?;
+                        self.push(Instr::Powf(base, exp))
+                    }
+                }
+            }
+            Atom::Func(func) => self.func(func)?,
+            Atom::Bool(b) => self.push(Instr::Const(*b as u8 as f64)),
+            Atom::Rel(rel) => {
+                let (l, r) = (self.expr(rel.lhs())?, self.expr(rel.rhs())?);
+                self.push(Instr::Rel(rel.op, l, r))
+            }
+            Atom::Logic(Logic::Not(x)) => self.unary(|x| (x == 0.0) as u8 as f64, x)?,
+            Atom::Logic(Logic::And(args)) => self.fold(args, Instr::And)?,
+            Atom::Logic(Logic::Or(args)) => self.fold(args, Instr::Or)?,
+            Atom::List(_) => return Err(EvalError::Undef),
+        };
+
+        self.cache.insert(e.clone(), reg);
+        Ok(reg)
+    }
+
+    fn func(&mut self, func: &Func) -> Result<Reg, EvalError> {
+        use Func as F;
+
+        Ok(match func {
+            F::Sin(x) => self.unary(f64::sin, x)?,
+            F::Cos(x) => self.unary(f64::cos, x)?,
+            F::Tan(x) => self.unary(f64::tan, x)?,
+            F::Sec(x) => self.unary(|x| x.cos().recip(), x)?,
+            F::Cot(x) => self.unary(|x| x.tan().recip(), x)?,
+            F::Csc(x) => self.unary(|x| x.sin().recip(), x)?,
+            F::ArcSin(x) => self.unary(f64::asin, x)?,
+            F::ArcCos(x) => self.unary(f64::acos, x)?,
+            F::ArcTan(x) => self.unary(f64::atan, x)?,
+            F::ArcSec(x) => self.unary(|x| x.recip().acos(), x)?,
+            F::ArcCsc(x) => self.unary(|x| x.recip().asin(), x)?,
+            F::ArcCot(x) => self.unary(
+                |x| match x == 0.0 {
+                    true => consts::FRAC_PI_2,
+                    false => x.recip().atan(),
+                },
+                x,
+            )?,
+            // only real values are supported
+            F::Re(x) | F::Conj(x) => self.expr(x)?,
+            F::Im(x) => {
+                let x = self.expr(x)?;
+                self.push(Instr::Unary(|x| x * 0.0, x))
+            }
+            F::Arg(x) => self.unary(
+                |x| match x {
+                    _ if x > 0.0 => 0.0,
+                    _ if x < 0.0 => consts::PI,
+                    _ => f64::NAN,
+                },
+                x,
+            )?,
+            F::Log(base, x) if base == &Real::E => self.unary(f64::ln, x)?,
+            F::Log(base, x) => {
+                let ln = self.unary(f64::ln, x)?;
+                let scale = self.push(Instr::Const(base.f64_approx().ln().recip()));
+                self.push(Instr::Mul(ln, scale))
+            }
+            F::IsPrime(_)
+            | F::NextPrime(_)
+            | F::Totient(_)
+            | F::Mod(_)
+            | F::PowMod(_)
+            | F::ModInv(_) => {
+                let mut args = [0; 3];
+                for (reg, a) in args.iter_mut().zip(func.iter_args()) {
+                    *reg = self.expr(a)?;
+                }
+                let n = func.args().len();
+                self.push(Instr::IntFn(func.int_fn().unwrap(), args, n))
+            }
+            F::RootOf([poly, k]) => {
+                let root = eval::root_of(poly, k)?;
+                self.push(Instr::Const(root))
+            }
+            F::Order(_) | F::Divisors(_) => return Err(EvalError::Undef),
+        })
+    }
+}
+
+impl Expr {
+    /// lower the expression into a [Tape] and return a closure evaluating it
+    ///
+    /// the arguments are passed in the same order as `vars`. the registers are allocated
+    /// per call, so the closure can be shared between threads. use [Tape::eval_with] to
+    /// reuse them
+    pub fn compile(&self, vars: &[Var]) -> Result<impl Fn(&[f64]) -> f64 + Send + Sync, EvalError> {
+        let tape = Tape::new(self, vars)?;
+        Ok(move |args: &[f64]| tape.eval(args))
+    }
+
+    /// same as [Expr::compile], but the closure evaluates many inputs at once
+    ///
+    /// `args[i]` holds the values of `vars[i]`
+    pub fn compile_batch(
+        &self,
+        vars: &[Var],
+    ) -> Result<impl Fn(&[&[f64]]) -> Vec<f64> + Send + Sync, EvalError> {
+        let tape = Tape::new(self, vars)?;
+        Ok(move |args: &[&[f64]]| tape.eval_batch(args))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use assert_eq as eq;
+    use calcurs_macros::expr as e;
+
+    fn xy() -> [Var; 2] {
+        [Var::from("x"), Var::from("y")]
+    }
+
+    #[test]
+    fn compile() {
+        let close = |l: f64, r: f64| (l - r).abs() < 1e-12;
+        let exprs = [
+            e!(x ^ 2 + 3 * x * y - 1),
+            e!(sin(x) ^ 2 + cos(x) ^ 2),
+            e!(y ^ (1 / 3) + x ^ (2 / 3)),
+            e!(x ^ y + 2 ^ x),
+            e!(arc_tan(x) + arc_cot(y) + sec(y)),
+            e!((x < y || x ^ 2 >= 1 / 4) && !(y == 2)),
+            Expr::ln(e!(x + y ^ 2))
+                + Expr::log(Real::from(crate::rational::Rational::from(2)), e!(y ^ 2)),
+        ];
+        let vals = [(0.5, -8.0), (1.25, 2.0), (3.0, 0.1)];
+
+        for e in exprs {
+            let f = e.compile(&xy()).unwrap();
+            let batch = e.compile_batch(&xy()).unwrap();
+            let (xs, ys): (Vec<_>, Vec<_>) = vals.iter().copied().unzip();
+            let res = batch(&[&xs, &ys]);
+
+            for (i, (x, y)) in vals.into_iter().enumerate() {
+                let vars = [(xy()[0].clone(), x), (xy()[1].clone(), y)].into();
+                match e.evalf(&vars) {
+                    Ok(expected) => {
+                        assert!(close(f(&[x, y]), expected), "{e} at {x}, {y}");
+                        assert!(close(res[i], expected), "{e} at {x}, {y}");
+                    }
+                    Err(_) => {
+                        assert!(f(&[x, y]).is_nan(), "{e} at {x}, {y}");
+                        assert!(res[i].is_nan(), "{e} at {x}, {y}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn tape() {
+        // sin(x) is shared, x^2 uses powi
+        let e = e!(sin(x) * x ^ 2 + sin(x));
+        let tape = Tape::new(&e, &xy()).unwrap();
+        eq!(tape.len(), 5);
+        assert!(tape.instrs.iter().any(|i| matches!(i, Instr::Powi(_, 2))));
+
+        let xs: Vec<f64> = (0..200).map(|i| i as f64 * 0.1).collect();
+        let ys = vec![0.0; xs.len()];
+        let res = tape.eval_batch(&[&xs, &ys]);
+        eq!(res.len(), xs.len());
+        for (x, r) in xs.iter().zip(res) {
+            eq!(r, tape.eval(&[*x, 0.0]));
+        }
+
+        eq!(
+            Tape::new(&e!(x + z), &xy()).unwrap_err(),
+            EvalError::UnboundVar(Var::from("z"))
+        );
+        assert!(e!(x + I).compile(&xy()).unwrap()(&[1.0, 0.0]).is_nan());
+
+        // the compiled closure can be shared between threads
+        let f = e.compile(&xy()).unwrap();
+        let res = std::thread::scope(|s| {
+            let handles: Vec<_> = xs.iter().map(|x| s.spawn(|| f(&[*x, 0.0]))).collect();
+            handles
+                .into_iter()
+                .map(|h| h.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+        for (x, r) in xs.iter().zip(res) {
+            eq!(r, tape.eval(&[*x, 0.0]));
+        }
+    }
+}
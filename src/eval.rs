@@ -0,0 +1,605 @@
+use std::{cmp, collections::HashMap, fmt, hash::BuildHasher};
+
+use num::{traits::FloatConst, Complex, FromPrimitive, ToPrimitive, Zero};
+
+use crate::{
+    atom::{Atom, Expr, Func, Infinity, Irrational, Logic, Real, SymbolicExpr, Var},
+    polynomial::RatPoly,
+    rational::{Int, Rational},
+};
+
+/// error returned by [Expr::evalf]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvalError {
+    /// the expression is undefined, e.g 0 * oo
+    Undef,
+    /// no value was given for the variable
+    UnboundVar(Var),
+    /// the result is not a real number, e.g i or sqrt(-1)
+    NotReal,
+    DivByZero,
+    /// the function was evaluated outside of its domain, e.g ln(0)
+    Domain(String),
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvalError::Undef => write!(f, "expression is undefined"),
+            EvalError::UnboundVar(v) => write!(f, "no value for variable '{v}'"),
+            EvalError::NotReal => write!(f, "result is not a real number"),
+            EvalError::DivByZero => write!(f, "division by zero"),
+            EvalError::Domain(func) => write!(f, "argument outside of the domain of '{func}'"),
+        }
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+type Result<T> = std::result::Result<T, EvalError>;
+
+struct Evaluator<'a, T, S> {
+    vars: &'a HashMap<Var, T, S>,
+}
+
+impl<T: num::Float + FloatConst, S: BuildHasher> Evaluator<'_, T, S> {
+    fn rational(r: &Rational) -> T {
+        // parse numerator and denominator separately, so that precise types keep all digits
+        let parse = |s: String| T::from_str_radix(&s, 10).ok();
+        match (
+            parse(r.numer().0.to_string()),
+            parse(r.denom().0.to_string()),
+        ) {
+            // parts beyond the range of T would give inf / inf
+            (Some(n), Some(d)) if n.is_finite() && d.is_finite() => n / d,
+            _ => T::from(r.f64_approx()).unwrap_or_else(T::nan),
+        }
+    }
+
+    fn real(r: &Real) -> T {
+        match r {
+            Real::Rational(r) => Self::rational(r),
+            Real::Irrational(Irrational::E) => T::E(),
+            Real::Irrational(Irrational::PI) => T::PI(),
+            Real::Float(f) => T::from(f.to_f64()).unwrap_or_else(T::nan),
+        }
+    }
+
+    fn inv(x: T) -> Result<T> {
+        match x.is_zero() {
+            true => Err(EvalError::DivByZero),
+            false => Ok(x.recip()),
+        }
+    }
+
+    /// nan means one of the arguments was infinite, e.g oo - oo
+    fn check(x: T) -> Result<T> {
+        match x.is_nan() {
+            true => Err(EvalError::Undef),
+            false => Ok(x),
+        }
+    }
+
+    fn eval(&self, e: &Expr) -> Result<T> {
+        match e.atom() {
+            Atom::Undef => Err(EvalError::Undef),
+            Atom::Rational(r) => Ok(Self::rational(r)),
+            Atom::Irrational(i) => Ok(Self::real(&Real::Irrational(*i))),
+            Atom::Float(f) => Ok(Self::real(&Real::Float(*f))),
+            Atom::ImagUnit | Atom::Infinity(Infinity::Complex) => Err(EvalError::NotReal),
+            Atom::Infinity(Infinity::Pos) => Ok(T::infinity()),
+            Atom::Infinity(Infinity::Neg) => Ok(T::neg_infinity()),
+            Atom::Var(v) => self
+                .vars
+                .get(v)
+                .copied()
+                .ok_or_else(|| EvalError::UnboundVar(v.clone())),
+            Atom::Sum(sum) => sum
+                .args
+                .iter()
+                .try_fold(T::zero(), |acc, a| Self::check(acc + self.eval(a)?)),
+            Atom::Prod(prod) => prod
+                .args
+                .iter()
+                .try_fold(T::one(), |acc, a| Self::check(acc * self.eval(a)?)),
+            Atom::Pow(pow) => self.pow(pow.base(), pow.exponent()),
+            Atom::Func(func) => self.func(func),
+            Atom::Bool(b) => Ok(Self::boolean(*b)),
+            Atom::Rel(rel) => {
+                let (l, r) = (self.eval(rel.lhs())?, self.eval(rel.rhs())?);
+                let ord = l.partial_cmp(&r).ok_or(EvalError::Undef)?;
+                Ok(Self::boolean(rel.op.holds(ord)))
+            }
+            Atom::Logic(Logic::Not(x)) => Ok(Self::boolean(self.eval(x)?.is_zero())),
+            Atom::Logic(Logic::And(args)) => args
+                .iter()
+                .try_fold(true, |acc, a| Ok(acc & !self.eval(a)?.is_zero()))
+                .map(Self::boolean),
+            Atom::Logic(Logic::Or(args)) => args
+                .iter()
+                .try_fold(false, |acc, a| Ok(acc | !self.eval(a)?.is_zero()))
+                .map(Self::boolean),
+            Atom::List(_) => Err(EvalError::Undef),
+        }
+    }
+
+    /// true and false are evaluated to 1 and 0
+    fn boolean(b: bool) -> T {
+        match b {
+            true => T::one(),
+            false => T::zero(),
+        }
+    }
+
+    fn pow(&self, base: &Expr, exp: &Expr) -> Result<T> {
+        let b = self.eval(base)?;
+        let e = self.eval(exp)?;
+
+        if b.is_zero() && e.is_sign_negative() {
+            return Err(EvalError::DivByZero);
+        }
+        if b.is_sign_negative() && !b.is_zero() {
+            // odd roots of negative numbers are real, e.g (-8)^(1/3) = -2
+            if let Atom::Rational(r) = exp.atom() {
+                if r.denom().is_odd() {
+                    let res = (-b).powf(e);
+                    return Self::check(if r.numer().is_odd() { -res } else { res });
+                }
+            }
+            if e.fract() != T::zero() {
+                return Err(EvalError::NotReal);
+            }
+        }
+        Self::check(b.powf(e))
+    }
+
+    fn func(&self, func: &Func) -> Result<T> {
+        use Func as F;
+
+        let domain = || EvalError::Domain(func.name());
+        let unit = |x: T| -> Result<T> {
+            match x.abs() <= T::one() {
+                true => Ok(x),
+                false => Err(EvalError::NotReal),
+            }
+        };
+
+        let res = match func {
+            F::Sin(x) => self.eval(x)?.sin(),
+            F::Cos(x) => self.eval(x)?.cos(),
+            F::Tan(x) => self.eval(x)?.tan(),
+            F::Sec(x) => Self::inv(self.eval(x)?.cos())?,
+            F::Cot(x) => Self::inv(self.eval(x)?.tan())?,
+            F::Csc(x) => Self::inv(self.eval(x)?.sin())?,
+            F::ArcSin(x) => unit(self.eval(x)?)?.asin(),
+            F::ArcCos(x) => unit(self.eval(x)?)?.acos(),
+            F::ArcTan(x) => self.eval(x)?.atan(),
+            F::ArcSec(x) => unit(Self::inv(self.eval(x)?)?)?.acos(),
+            F::ArcCsc(x) => unit(Self::inv(self.eval(x)?)?)?.asin(),
+            F::ArcCot(x) => match self.eval(x)? {
+                x if x.is_zero() => T::FRAC_PI_2(),
+                x => x.recip().atan(),
+            },
+            // only real values can be evaluated
+            F::Re(x) | F::Conj(x) => self.eval(x)?,
+            F::Im(x) => {
+                self.eval(x)?;
+                T::zero()
+            }
+            F::Arg(x) => match self.eval(x)? {
+                x if x.is_zero() => return Err(domain()),
+                x if x.is_sign_negative() => T::PI(),
+                _ => T::zero(),
+            },
+            F::Log(base, x) => {
+                let (b, x) = (Self::real(base), self.eval(x)?);
+                if b.is_zero() || x.is_zero() {
+                    return Err(domain());
+                } else if b.is_sign_negative() || x.is_sign_negative() {
+                    return Err(EvalError::NotReal);
+                }
+                x.ln() * Self::inv(b.ln())?
+            }
+            F::IsPrime(_)
+            | F::NextPrime(_)
+            | F::Totient(_)
+            | F::Mod(_)
+            | F::PowMod(_)
+            | F::ModInv(_) => {
+                let args = func
+                    .iter_args()
+                    .map(|a| {
+                        let x = self.eval(a)?.to_f64();
+                        x.and_then(Int::from_f64).ok_or_else(domain)
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                let int_fn = func.int_fn().unwrap();
+                let res = int_fn(&args).ok_or_else(domain)?;
+                T::from(res.f64_approx()).unwrap_or_else(T::nan)
+            }
+            F::RootOf([poly, k]) => T::from(root_of(poly, k)?).unwrap_or_else(T::nan),
+            // an order term or a list of divisors has no value
+            F::Order(_) | F::Divisors(_) => return Err(EvalError::Undef),
+        };
+
+        match res.is_nan() {
+            true => Err(domain()),
+            false => Ok(res),
+        }
+    }
+}
+
+/// evaluates over the complex numbers, see [Expr::evalf_complex]
+struct ComplexEvaluator<'a, S> {
+    vars: &'a HashMap<Var, Complex<f64>, S>,
+}
+
+impl<S: BuildHasher> ComplexEvaluator<'_, S> {
+    /// nan means one of the arguments was infinite
+    fn check(z: Complex<f64>) -> Result<Complex<f64>> {
+        match z.is_nan() {
+            true => Err(EvalError::Undef),
+            false => Ok(z),
+        }
+    }
+
+    fn inv(z: Complex<f64>) -> Result<Complex<f64>> {
+        match z.is_zero() {
+            true => Err(EvalError::DivByZero),
+            false => Ok(z.inv()),
+        }
+    }
+
+    fn real(z: Complex<f64>) -> Result<f64> {
+        match z.im == 0.0 {
+            true => Ok(z.re),
+            false => Err(EvalError::NotReal),
+        }
+    }
+
+    fn eval(&self, e: &Expr) -> Result<Complex<f64>> {
+        let real = |x: f64| Ok(Complex::from(x));
+        match e.atom() {
+            Atom::Undef => Err(EvalError::Undef),
+            Atom::Rational(r) => real(Evaluator::<f64, S>::rational(r)),
+            Atom::Irrational(i) => real(i.f64_approx()),
+            Atom::Float(f) => real(f.to_f64()),
+            Atom::ImagUnit => Ok(Complex::i()),
+            // the complex plane has no ordered infinities
+            Atom::Infinity(_) => Err(EvalError::Undef),
+            Atom::Var(v) => self
+                .vars
+                .get(v)
+                .copied()
+                .ok_or_else(|| EvalError::UnboundVar(v.clone())),
+            Atom::Sum(sum) => sum
+                .args
+                .iter()
+                .try_fold(Complex::ZERO, |acc, a| Self::check(acc + self.eval(a)?)),
+            Atom::Prod(prod) => prod
+                .args
+                .iter()
+                .try_fold(Complex::ONE, |acc, a| Self::check(acc * self.eval(a)?)),
+            Atom::Pow(pow) => self.pow(pow.base(), pow.exponent()),
+            Atom::Func(func) => self.func(func),
+            Atom::Bool(b) => real(Evaluator::<f64, S>::boolean(*b)),
+            // relations are only defined for real values
+            Atom::Rel(rel) => {
+                let l = Self::real(self.eval(rel.lhs())?)?;
+                let r = Self::real(self.eval(rel.rhs())?)?;
+                let ord = l.partial_cmp(&r).ok_or(EvalError::Undef)?;
+                real(Evaluator::<f64, S>::boolean(rel.op.holds(ord)))
+            }
+            Atom::Logic(Logic::Not(x)) => {
+                real(Evaluator::<f64, S>::boolean(self.eval(x)?.is_zero()))
+            }
+            Atom::Logic(Logic::And(args)) => args
+                .iter()
+                .try_fold(true, |acc, a| Ok(acc & !self.eval(a)?.is_zero()))
+                .and_then(|b| real(Evaluator::<f64, S>::boolean(b))),
+            Atom::Logic(Logic::Or(args)) => args
+                .iter()
+                .try_fold(false, |acc, a| Ok(acc | !self.eval(a)?.is_zero()))
+                .and_then(|b| real(Evaluator::<f64, S>::boolean(b))),
+            Atom::List(_) => Err(EvalError::Undef),
+        }
+    }
+
+    /// the principal branch is used for non-integer exponents, e.g (-8)^(1/3) = 1 + sqrt(3)i
+    fn pow(&self, base: &Expr, exp: &Expr) -> Result<Complex<f64>> {
+        let b = self.eval(base)?;
+        if let Some(n) = exp.try_unwrap_int().and_then(|n| n.to_i32()) {
+            if b.is_zero() && n < 0 {
+                return Err(EvalError::DivByZero);
+            }
+            return Self::check(b.powi(n));
+        }
+
+        let e = self.eval(exp)?;
+        if b.is_zero() {
+            return match e.re > 0.0 {
+                true => Ok(Complex::ZERO),
+                false => Err(EvalError::DivByZero),
+            };
+        }
+        Self::check(b.powc(e))
+    }
+
+    fn func(&self, func: &Func) -> Result<Complex<f64>> {
+        use Func as F;
+
+        let domain = || EvalError::Domain(func.name());
+        let res = match func {
+            F::Sin(x) => self.eval(x)?.sin(),
+            F::Cos(x) => self.eval(x)?.cos(),
+            F::Tan(x) => self.eval(x)?.tan(),
+            F::Sec(x) => Self::inv(self.eval(x)?.cos())?,
+            F::Cot(x) => Self::inv(self.eval(x)?.tan())?,
+            F::Csc(x) => Self::inv(self.eval(x)?.sin())?,
+            F::ArcSin(x) => self.eval(x)?.asin(),
+            F::ArcCos(x) => self.eval(x)?.acos(),
+            F::ArcTan(x) => self.eval(x)?.atan(),
+            F::ArcSec(x) => Self::inv(self.eval(x)?)?.acos(),
+            F::ArcCsc(x) => Self::inv(self.eval(x)?)?.asin(),
+            F::ArcCot(x) => match self.eval(x)? {
+                x if x.is_zero() => Complex::from(std::f64::consts::FRAC_PI_2),
+                x => x.inv().atan(),
+            },
+            F::Re(x) => Complex::from(self.eval(x)?.re),
+            F::Im(x) => Complex::from(self.eval(x)?.im),
+            F::Conj(x) => self.eval(x)?.conj(),
+            F::Arg(x) => match self.eval(x)? {
+                x if x.is_zero() => return Err(domain()),
+                x => Complex::from(x.arg()),
+            },
+            F::Log(base, x) => {
+                let (b, x) = (Evaluator::<f64, S>::real(base), self.eval(x)?);
+                if b == 0.0 || x.is_zero() {
+                    return Err(domain());
+                }
+                x.ln() * Self::inv(Complex::from(b).ln())?
+            }
+            F::IsPrime(_)
+            | F::NextPrime(_)
+            | F::Totient(_)
+            | F::Mod(_)
+            | F::PowMod(_)
+            | F::ModInv(_) => {
+                let args = func
+                    .iter_args()
+                    .map(|a| {
+                        let x = Self::real(self.eval(a)?)?;
+                        Int::from_f64(x).ok_or_else(domain)
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                let int_fn = func.int_fn().unwrap();
+                Complex::from(int_fn(&args).ok_or_else(domain)?.f64_approx())
+            }
+            F::RootOf([poly, k]) => root_of_complex(poly, k)?,
+            // an order term or a list of divisors has no value
+            F::Order(_) | F::Divisors(_) => return Err(EvalError::Undef),
+        };
+
+        match res.is_nan() {
+            true => Err(domain()),
+            false => Ok(res),
+        }
+    }
+}
+
+/// all complex roots of c[0] + c[1] x + ... using the durand-kerner method
+fn poly_roots(c: &[f64]) -> Vec<Complex<f64>> {
+    let n = c.len() - 1;
+    let monic: Vec<_> = c.iter().map(|a| a / c[n]).collect();
+    let eval = |z: Complex<f64>| {
+        monic
+            .iter()
+            .rev()
+            .fold(Complex::<f64>::ZERO, |acc, &a| acc * z + a)
+    };
+
+    let mut z: Vec<_> = (0..n)
+        .map(|i| Complex::new(0.4, 0.9).powu(i as u32))
+        .collect();
+    for _ in 0..500 {
+        for i in 0..n {
+            let denom = (0..n)
+                .filter(|&j| j != i)
+                .fold(Complex::<f64>::ONE, |d, j| d * (z[i] - z[j]));
+            z[i] = z[i] - eval(z[i]) / denom;
+        }
+    }
+    z
+}
+
+/// the k-th root of a univariate polynomial with rational coefficients, see [Expr::root_of]
+pub(crate) fn root_of_complex(poly: &Expr, k: &Expr) -> Result<Complex<f64>> {
+    let domain = || EvalError::Domain("rootof".into());
+    let vars: Vec<_> = poly.variables().iter().cloned().collect();
+    let k = k
+        .try_unwrap_int()
+        .and_then(|k| k.to_u64())
+        .ok_or_else(domain)? as usize;
+    let c: Vec<_> = match vars.as_slice() {
+        [_] => RatPoly::from_expr(poly, &vars)
+            .ok_or_else(domain)?
+            .to_dense(),
+        _ => return Err(domain()),
+    };
+    let c: Vec<_> = c.iter().map(Rational::f64_approx).collect();
+    if c.len() <= k + 1 {
+        return Err(domain());
+    }
+
+    let is_real = |z: &Complex<f64>| z.im.abs() < 1e-8 * (1.0 + z.norm());
+    let (mut real, mut complex): (Vec<_>, Vec<_>) = poly_roots(&c).into_iter().partition(is_real);
+    real.iter_mut().for_each(|z| z.im = 0.0);
+    real.sort_by(|a, b| a.re.total_cmp(&b.re));
+    complex.sort_by(|a, b| a.re.total_cmp(&b.re).then(a.im.total_cmp(&b.im)));
+    Ok(real.into_iter().chain(complex).nth(k).unwrap())
+}
+
+/// the k-th root of a polynomial if it is real, see [root_of_complex]
+pub(crate) fn root_of(poly: &Expr, k: &Expr) -> Result<f64> {
+    let z = root_of_complex(poly, k)?;
+    match z.im == 0.0 {
+        true => Ok(z.re),
+        false => Err(EvalError::NotReal),
+    }
+}
+
+impl Expr {
+    /// numerically evaluate the expression, the variables are substituted with the given values
+    pub fn evalf<S: BuildHasher>(&self, vars: &HashMap<Var, f64, S>) -> Result<f64> {
+        self.evalf_with(vars)
+    }
+
+    /// compares two expressions whose difference is a real constant, e.g x + 1 < x + 3
+    ///
+    /// rational numbers are compared exactly, other constants numerically. The expressions are
+    /// only equal if their difference reduces to zero, values that are too close to be
+    /// separated by floats, e.g pi + 10^-30 and pi, can't be compared
+    pub fn cmp_real(&self, other: &Expr) -> Option<cmp::Ordering> {
+        if let (Atom::Rational(l), Atom::Rational(r)) = (self.atom(), other.atom()) {
+            return Some(l.cmp(r));
+        } else if self == other {
+            return Some(cmp::Ordering::Equal);
+        }
+        let eval = |e: &Expr| e.evalf(&HashMap::<Var, f64>::new()).ok();
+        let cmp = |l: f64, r: f64| {
+            let close = (l - r).abs() <= 1e-12 * l.abs().max(r.abs()).max(1.0);
+            match l == r || close {
+                true => None,
+                false => l.partial_cmp(&r),
+            }
+        };
+        if let (Some(l), Some(r)) = (eval(self), eval(other)) {
+            if let Some(ord) = cmp(l, r) {
+                return Some(ord);
+            }
+        }
+        let diff = (self - other).expand().reduce();
+        if diff.is_zero() {
+            return Some(cmp::Ordering::Equal);
+        }
+        cmp(eval(&diff)?, 0.0)
+    }
+
+    /// numerically evaluate the expression over the complex numbers
+    ///
+    /// unlike [Expr::evalf], roots are taken on the principal branch, e.g (-8)^(1/3) = 1 + sqrt(3)i
+    pub fn evalf_complex<S: BuildHasher>(
+        &self,
+        vars: &HashMap<Var, Complex<f64>, S>,
+    ) -> Result<Complex<f64>> {
+        ComplexEvaluator { vars }.eval(self)
+    }
+
+    /// same as [Expr::evalf] but generic over the number type, e.g for arbitrary precision floats
+    pub fn evalf_with<T, S>(&self, vars: &HashMap<Var, T, S>) -> Result<T>
+    where
+        T: num::Float + FloatConst,
+        S: BuildHasher,
+    {
+        Evaluator { vars }.eval(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use assert_eq as eq;
+    use calcurs_macros::expr as e;
+
+    fn vars(vals: &[(&str, f64)]) -> HashMap<Var, f64> {
+        vals.iter().map(|(v, x)| (Var::from(*v), *x)).collect()
+    }
+
+    #[test]
+    fn evalf() {
+        use std::f64::consts::*;
+        let close = |l: f64, r: f64| (l - r).abs() < 1e-12;
+        let xy = vars(&[("x", 0.5), ("y", -8.0)]);
+        let ev = |e: Expr| e.evalf(&xy).unwrap();
+
+        assert!(close(ev(e!(x ^ 2 + 3 * x)), 1.75));
+        assert!(close(ev(e!(y ^ (1 / 3))), -2.0));
+        assert!(close(ev(e!(y ^ (2 / 3))), 4.0));
+        assert!(close(
+            ev(e!(pi * sin(x) + cos(x))),
+            PI * 0.5f64.sin() + 0.5f64.cos()
+        ));
+        assert!(close(
+            ev(e!(sec(x) * csc(x) * cot(x))),
+            1.0 / 0.5f64.sin().powi(2)
+        ));
+        assert!(close(ev(e!(arc_sin(x) + arc_cos(x))), FRAC_PI_2));
+        assert!(close(ev(e!(arc_tan(x) + arc_cot(x))), FRAC_PI_2));
+        assert!(close(ev(e!(arc_sec(y) + arc_csc(y))), FRAC_PI_2));
+        assert!(close(ev(Expr::ln(Expr::e())), 1.0));
+        assert!(close(
+            ev(Expr::log(Real::from(Rational::from(2)), e!(8))),
+            3.0
+        ));
+        assert!(close(ev(e!(re(y) + im(y) + arg(y))), PI - 8.0));
+        assert!(close(ev(e!(0.25 * x)), 0.125));
+        // numerator and denominator are both larger than f64::MAX
+        assert!(close(ev(e!((10 ^ 400 + 1) / 10 ^ 399).reduce()), 10.0));
+        assert!(close(ev(e!(modulo(14 * x, 4) + totient(-y))), 7.0));
+        eq!(ev(e!(oo + x)), f64::INFINITY);
+        eq!(ev(e!(x < y || x ^ 2 >= 1 / 4)), 1.0);
+        eq!(ev(e!(x != 1 / 2 && !(y > 0))), 0.0);
+    }
+
+    #[test]
+    fn errors() {
+        use EvalError as E;
+        let xy = vars(&[("x", 0.0), ("y", -1.0)]);
+        let err = |e: Expr| e.evalf(&xy).unwrap_err();
+
+        eq!(err(e!(z + 1)), E::UnboundVar(Var::from("z")));
+        eq!(err(e!(y ^ (1 / 2))), E::NotReal);
+        eq!(err(e!(2 + I)), E::NotReal);
+        eq!(err(e!(x ^ -1)), E::DivByZero);
+        eq!(err(e!(cot(x))), E::DivByZero);
+        eq!(err(Expr::ln(e!(x))), E::Domain("ln".into()));
+        eq!(err(Expr::ln(e!(y))), E::NotReal);
+        eq!(err(e!(arc_sin(y - 1))), E::NotReal);
+        eq!(err(e!(arg(x))), E::Domain("arg".into()));
+        eq!(err(e!(oo + y * oo)), E::Undef);
+        eq!(err(Expr::undef()), E::Undef);
+        eq!(err(Expr::root_of(e!(x ^ 5 - x + 1), e!(1))), E::NotReal);
+    }
+
+    #[test]
+    fn evalf_complex() {
+        use num::Complex as C;
+        use std::f64::consts::*;
+        let close = |l: C<f64>, r: C<f64>| (l - r).norm() < 1e-12;
+        let z = HashMap::from([(Var::from("z"), C::new(1.0, 2.0))]);
+        let ev = |e: &Expr| e.evalf_complex(&z).unwrap();
+
+        assert!(close(ev(&e!(z ^ 2 + I)), C::new(-3.0, 5.0)));
+        assert!(close(ev(&e!(re(z) + im(z) * I)), C::new(1.0, 2.0)));
+        assert!(close(ev(&e!(conj(z) * z)), C::new(5.0, 0.0)));
+        assert!(close(ev(&e!(arg(I))), C::new(FRAC_PI_2, 0.0)));
+        assert!(close(ev(&Expr::exp(e!(pi * I))), C::new(-1.0, 0.0)));
+        // principal branch
+        assert!(close(ev(&e!((-8) ^ (1 / 3))), C::new(1.0, 3f64.sqrt())));
+        eq!(e!(1 / (z - z)).evalf_complex(&z), Err(EvalError::DivByZero));
+
+        // the real root comes first, then the complex roots by real and imaginary part
+        let p = e!(x ^ 5 - x + 1);
+        let roots: Vec<_> = (0..5)
+            .map(|k| Expr::root_of(&p, Expr::from(k)))
+            .map(|r| {
+                assert!(r.free_of(&e!(x)));
+                assert!(ev(&p.substitude(&e!(x), &r)).norm() < 1e-9);
+                ev(&r)
+            })
+            .collect();
+        eq!(roots[0].im, 0.0);
+        assert!(roots[1..].windows(2).all(|w| w[0].re <= w[1].re));
+        assert!(roots[1..].iter().all(|z| z.im != 0.0));
+    }
+}
@@ -0,0 +1,748 @@
+use num::Integer;
+
+use crate::{
+    atom::Expr,
+    polynomial::{RatPoly, VarSet},
+    rational::{Int, Rational},
+};
+
+/// dense polynomial over Q, the i-th coefficient belongs to x^i
+type QPoly = Vec<Rational>;
+/// dense polynomial over Z, the i-th coefficient belongs to x^i
+type ZPoly = Vec<Int>;
+/// dense polynomial over Z/pZ, the i-th coefficient belongs to x^i
+type FpPoly = Vec<u64>;
+
+fn deg<T>(p: &[T]) -> usize {
+    p.len().saturating_sub(1)
+}
+
+mod fp {
+    use super::*;
+
+    pub(super) fn trim(mut p: FpPoly) -> FpPoly {
+        while p.last() == Some(&0) {
+            p.pop();
+        }
+        p
+    }
+
+    pub(super) fn from_z(f: &[Int], p: u64) -> FpPoly {
+        let m = Int::from(p);
+        trim(
+            f.iter()
+                .map(|c| u64::try_from(&c.mod_floor(&m).0).unwrap())
+                .collect(),
+        )
+    }
+
+    pub(super) fn pow_u64(mut b: u64, mut e: u64, p: u64) -> u64 {
+        let mut res = 1;
+        b %= p;
+        while e > 0 {
+            if e & 1 == 1 {
+                res = res * b % p;
+            }
+            b = b * b % p;
+            e >>= 1;
+        }
+        res
+    }
+
+    pub(super) fn inv(a: u64, p: u64) -> u64 {
+        pow_u64(a, p - 2, p)
+    }
+
+    pub(super) fn add(a: &FpPoly, b: &FpPoly, p: u64) -> FpPoly {
+        let n = a.len().max(b.len());
+        let get = |f: &FpPoly, i: usize| f.get(i).copied().unwrap_or(0);
+        trim((0..n).map(|i| (get(a, i) + get(b, i)) % p).collect())
+    }
+
+    pub(super) fn sub(a: &FpPoly, b: &FpPoly, p: u64) -> FpPoly {
+        let n = a.len().max(b.len());
+        let get = |f: &FpPoly, i: usize| f.get(i).copied().unwrap_or(0);
+        trim((0..n).map(|i| (get(a, i) + p - get(b, i)) % p).collect())
+    }
+
+    pub(super) fn mul(a: &FpPoly, b: &FpPoly, p: u64) -> FpPoly {
+        if a.is_empty() || b.is_empty() {
+            return vec![];
+        }
+        let mut res = vec![0; a.len() + b.len() - 1];
+        for (i, x) in a.iter().enumerate() {
+            for (j, y) in b.iter().enumerate() {
+                res[i + j] = (res[i + j] + x * y) % p;
+            }
+        }
+        trim(res)
+    }
+
+    pub(super) fn div_rem(u: &FpPoly, v: &FpPoly, p: u64) -> (FpPoly, FpPoly) {
+        let inv_lc = inv(*v.last().expect("division by zero"), p);
+        let mut r = u.clone();
+        if r.len() < v.len() {
+            return (vec![], r);
+        }
+        let mut q = vec![0; r.len() - v.len() + 1];
+        while r.len() >= v.len() && !r.is_empty() {
+            let shift = r.len() - v.len();
+            let c = r.last().unwrap() * inv_lc % p;
+            for (i, vc) in v.iter().enumerate() {
+                r[i + shift] = (r[i + shift] + p - c * vc % p) % p;
+            }
+            q[shift] = c;
+            r.pop();
+            r = trim(r);
+        }
+        (trim(q), r)
+    }
+
+    pub(super) fn rem(u: &FpPoly, v: &FpPoly, p: u64) -> FpPoly {
+        div_rem(u, v, p).1
+    }
+
+    pub(super) fn monic(f: FpPoly, p: u64) -> FpPoly {
+        match f.last() {
+            Some(&lc) => {
+                let inv = inv(lc, p);
+                f.into_iter().map(|c| c * inv % p).collect()
+            }
+            None => f,
+        }
+    }
+
+    pub(super) fn gcd(a: &FpPoly, b: &FpPoly, p: u64) -> FpPoly {
+        let (mut u, mut v) = (a.clone(), b.clone());
+        while !v.is_empty() {
+            let r = rem(&u, &v, p);
+            u = v;
+            v = r;
+        }
+        monic(u, p)
+    }
+
+    /// returns (g, s, t) with s*a + t*b = g = gcd(a, b)
+    pub(super) fn ext_gcd(a: &FpPoly, b: &FpPoly, p: u64) -> (FpPoly, FpPoly, FpPoly) {
+        let (mut r0, mut r1) = (a.clone(), b.clone());
+        let (mut s0, mut s1) = (vec![1], vec![]);
+        let (mut t0, mut t1) = (vec![], vec![1]);
+        while !r1.is_empty() {
+            let (q, r) = div_rem(&r0, &r1, p);
+            let s = sub(&s0, &mul(&q, &s1, p), p);
+            let t = sub(&t0, &mul(&q, &t1, p), p);
+            (r0, r1) = (r1, r);
+            (s0, s1) = (s1, s);
+            (t0, t1) = (t1, t);
+        }
+        let inv = inv(*r0.last().unwrap(), p);
+        let scale = |f: FpPoly| f.into_iter().map(|c| c * inv % p).collect();
+        (scale(r0), scale(s0), scale(t0))
+    }
+
+    pub(super) fn derivative(f: &FpPoly, p: u64) -> FpPoly {
+        let d = f
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(i, c)| (i as u64 % p) * c % p)
+            .collect();
+        trim(d)
+    }
+
+    /// b^e mod f
+    pub(super) fn pow_mod(b: &FpPoly, e: &Int, f: &FpPoly, p: u64) -> FpPoly {
+        // bits of the exponent, least significant first
+        let mut bits = vec![];
+        let (mut e, two) = (e.clone(), Int::from(2));
+        while e.is_pos() {
+            bits.push(e.is_odd());
+            e = e.div_floor(&two);
+        }
+
+        let mut res = vec![1];
+        let mut b = rem(b, f, p);
+        for bit in bits {
+            if bit {
+                res = rem(&mul(&res, &b, p), f, p);
+            }
+            b = rem(&mul(&b, &b, p), f, p);
+        }
+        res
+    }
+
+    /// distinct degree factorization of a monic square-free polynomial,
+    /// returns pairs of (product of all factors of degree d, d)
+    pub(super) fn distinct_degree(f: &FpPoly, p: u64) -> Vec<(FpPoly, usize)> {
+        let mut res = vec![];
+        let mut f = f.clone();
+        let x = vec![0, 1];
+        let mut h = x.clone();
+        let mut d = 1;
+        while deg(&f) >= 2 * d {
+            h = pow_mod(&h, &Int::from(p), &f, p);
+            let g = gcd(&sub(&h, &x, p), &f, p);
+            if deg(&g) > 0 {
+                f = div_rem(&f, &g, p).0;
+                h = rem(&h, &f, p);
+                res.push((g, d));
+            }
+            d += 1;
+        }
+        if deg(&f) > 0 {
+            let d = deg(&f);
+            res.push((f, d));
+        }
+        res
+    }
+
+    /// cantor-zassenhaus split of a monic polynomial whose factors all have degree d
+    pub(super) fn equal_degree(f: &FpPoly, d: usize, p: u64, rng: &mut u64) -> Vec<FpPoly> {
+        let n = deg(f);
+        if n == d {
+            return vec![f.clone()];
+        }
+        let exp = (Int::from(p).pow(&Int::from(d as u64)).unwrap() - Int::ONE) / Int::from(2);
+
+        loop {
+            // xorshift, the factorization does not depend on the choice of a
+            let a: FpPoly = trim(
+                (0..n)
+                    .map(|_| {
+                        *rng ^= *rng << 13;
+                        *rng ^= *rng >> 7;
+                        *rng ^= *rng << 17;
+                        *rng % p
+                    })
+                    .collect(),
+            );
+            if deg(&a) == 0 {
+                continue;
+            }
+            let b = sub(&pow_mod(&a, &exp, f, p), &vec![1], p);
+            let g = gcd(&b, f, p);
+            if deg(&g) > 0 && deg(&g) < n {
+                let h = div_rem(f, &g, p).0;
+                let mut res = equal_degree(&g, d, p, rng);
+                res.extend(equal_degree(&monic(h, p), d, p, rng));
+                return res;
+            }
+        }
+    }
+
+    /// all monic irreducible factors of a square-free polynomial
+    pub(super) fn factor(f: &FpPoly, p: u64) -> Vec<FpPoly> {
+        let mut rng = 0x2545F4914F6CDD1D;
+        distinct_degree(&monic(f.clone(), p), p)
+            .into_iter()
+            .flat_map(|(g, d)| equal_degree(&g, d, p, &mut rng))
+            .collect()
+    }
+}
+
+mod z {
+    use super::*;
+
+    pub(super) fn trim(mut p: ZPoly) -> ZPoly {
+        while p.last().is_some_and(|c| c.is_zero()) {
+            p.pop();
+        }
+        p
+    }
+
+    pub(super) fn from_fp(f: &FpPoly) -> ZPoly {
+        f.iter().map(|c| Int::from(*c)).collect()
+    }
+
+    pub(super) fn mul(a: &ZPoly, b: &ZPoly) -> ZPoly {
+        if a.is_empty() || b.is_empty() {
+            return vec![];
+        }
+        let mut res = vec![Int::ZERO; a.len() + b.len() - 1];
+        for (i, x) in a.iter().enumerate() {
+            for (j, y) in b.iter().enumerate() {
+                res[i + j] += x.clone() * y;
+            }
+        }
+        trim(res)
+    }
+
+    pub(super) fn add(a: &ZPoly, b: &ZPoly) -> ZPoly {
+        let n = a.len().max(b.len());
+        let get = |p: &ZPoly, i: usize| p.get(i).cloned().unwrap_or_default();
+        trim((0..n).map(|i| get(a, i) + get(b, i)).collect())
+    }
+
+    pub(super) fn sub(a: &ZPoly, b: &ZPoly) -> ZPoly {
+        let n = a.len().max(b.len());
+        let get = |p: &ZPoly, i: usize| p.get(i).cloned().unwrap_or_default();
+        trim((0..n).map(|i| get(a, i) - get(b, i)).collect())
+    }
+
+    pub(super) fn scale(f: &ZPoly, c: &Int) -> ZPoly {
+        trim(f.iter().map(|x| x.clone() * c).collect())
+    }
+
+    /// reduce the coefficients into [0, m)
+    pub(super) fn modulo(f: &ZPoly, m: &Int) -> ZPoly {
+        trim(f.iter().map(|c| c.mod_floor(m)).collect())
+    }
+
+    /// reduce the coefficients into (-m/2, m/2]
+    pub(super) fn sym_modulo(f: &ZPoly, m: &Int) -> ZPoly {
+        let half = m.clone() / Int::from(2);
+        trim(
+            f.iter()
+                .map(|c| {
+                    let c = c.mod_floor(m);
+                    if c > half {
+                        c - m
+                    } else {
+                        c
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    pub(super) fn content(f: &ZPoly) -> Int {
+        f.iter().fold(Int::ZERO, |g, c| g.gcd(c))
+    }
+
+    /// primitive part with a positive leading coefficient
+    pub(super) fn primitive(f: &ZPoly) -> ZPoly {
+        let mut c = content(f);
+        if f.last().is_some_and(|lc| lc.is_neg()) {
+            c = -c;
+        }
+        f.iter().map(|x| x.clone() / &c).collect()
+    }
+
+    /// exact division in Z[x], [None] if `v` does not divide `u`
+    pub(super) fn div_exact(u: &ZPoly, v: &ZPoly) -> Option<ZPoly> {
+        let lcv = v.last()?;
+        let mut r = u.clone();
+        if r.len() < v.len() {
+            return r.is_empty().then(Vec::new);
+        }
+        let mut q = vec![Int::ZERO; r.len() - v.len() + 1];
+        while r.len() >= v.len() && !r.is_empty() {
+            let shift = r.len() - v.len();
+            let (c, rem) = num::Integer::div_rem(r.last().unwrap(), lcv);
+            if !rem.is_zero() {
+                return None;
+            }
+            for (i, vc) in v.iter().enumerate() {
+                r[i + shift] -= c.clone() * vc;
+            }
+            q[shift] = c;
+            r.pop();
+            r = trim(r);
+        }
+        r.is_empty().then(|| trim(q))
+    }
+
+    /// inverse of a modulo m, a must be a unit
+    pub(super) fn inv_mod(a: &Int, m: &Int) -> Int {
+        let (mut r0, mut r1) = (a.mod_floor(m), m.clone());
+        let (mut s0, mut s1) = (Int::ONE, Int::ZERO);
+        while !r1.is_zero() {
+            let q = r0.div_floor(&r1);
+            let r = r0 - q.clone() * &r1;
+            let s = s0 - q * &s1;
+            (r0, r1) = (r1, r);
+            (s0, s1) = (s1, s);
+        }
+        s0.mod_floor(m)
+    }
+}
+
+/// lift f = g0 * h0 mod p to f = g * h mod p^k, g0 has to be monic and coprime to h0
+fn hensel_lift(f: &ZPoly, g0: &FpPoly, h0: &FpPoly, p: u64, k: u32) -> (ZPoly, ZPoly) {
+    let (_, s, t) = fp::ext_gcd(g0, h0, p);
+    let (mut g, mut h) = (z::from_fp(g0), z::from_fp(h0));
+    let pi = Int::from(p);
+    let mut pj = pi.clone();
+
+    for _ in 1..k {
+        // f - g*h = p^j * e
+        let diff = z::sub(f, &z::mul(&g, &h));
+        let e: ZPoly = diff.into_iter().map(|c| c / &pj).collect();
+        let e = fp::from_z(&e, p);
+
+        // a*h + b*g = e mod p
+        let (q, a) = fp::div_rem(&fp::mul(&t, &e, p), g0, p);
+        let b = fp::add(&fp::mul(&s, &e, p), &fp::mul(&q, h0, p), p);
+
+        let next = pj.clone() * &pi;
+        g = z::modulo(&z::add(&g, &z::scale(&z::from_fp(&a), &pj)), &next);
+        h = z::modulo(&z::add(&h, &z::scale(&z::from_fp(&b), &pj)), &next);
+        pj = next;
+    }
+    (g, h)
+}
+
+/// lift the monic factors of f mod p to monic factors mod p^k
+fn hensel_lift_all(f: &ZPoly, factors: &[FpPoly], p: u64, k: u32) -> Vec<ZPoly> {
+    let m = Int::from(p).pow(&Int::from(k)).unwrap();
+    let lc = f.last().unwrap();
+    match factors {
+        [] => vec![],
+        [_] => vec![z::modulo(&z::scale(f, &z::inv_mod(lc, &m)), &m)],
+        [g0, rest @ ..] => {
+            let h0 = rest
+                .iter()
+                .fold(fp::from_z(std::slice::from_ref(lc), p), |h, u| {
+                    fp::mul(&h, u, p)
+                });
+            let (g, h) = hensel_lift(f, g0, &h0, p, k);
+            let mut res = vec![g];
+            res.extend(hensel_lift_all(&h, rest, p, k));
+            res
+        }
+    }
+}
+
+fn combinations(n: usize, k: usize) -> Vec<Vec<usize>> {
+    if k == 0 {
+        return vec![vec![]];
+    }
+    (k - 1..n)
+        .flat_map(|last| {
+            combinations(last, k - 1).into_iter().map(move |mut c| {
+                c.push(last);
+                c
+            })
+        })
+        .collect()
+}
+
+/// 3, 5, 7, 11, ...
+fn odd_primes() -> impl Iterator<Item = u64> {
+    std::iter::successors(Some(3), |&p| {
+        u64::try_from(&Int::from(p).next_prime().0).ok()
+    })
+}
+
+/// irreducible factors of a primitive square-free polynomial over Z
+fn zassenhaus(f: &ZPoly) -> Vec<ZPoly> {
+    if deg(f) <= 1 {
+        return vec![f.clone()];
+    }
+    let lc = f.last().unwrap().clone();
+
+    // choose the prime with the fewest modular factors, to speed up the recombination
+    //
+    // only the finitely many primes dividing lc(f) or the discriminant of f are skipped
+    let mut candidates = odd_primes().filter_map(|p| {
+        let fp = fp::from_z(f, p);
+        let square_free = deg(&fp::gcd(&fp, &fp::derivative(&fp, p), p)) == 0;
+        (deg(&fp) == deg(f) && square_free).then(|| (p, fp::factor(&fp, p)))
+    });
+    let Some(mut best) = candidates.next() else {
+        return vec![f.clone()];
+    };
+    for c in candidates.take(2) {
+        if c.1.len() < best.1.len() {
+            best = c;
+        }
+    }
+    let (p, factors) = best;
+    if factors.len() == 1 {
+        return vec![f.clone()];
+    }
+
+    // mignotte bound for the coefficients of factors of f
+    let norm =
+        num_integer::Roots::sqrt(&f.iter().fold(Int::ZERO, |s, c| s + c.clone() * c)) + Int::ONE;
+    let bound =
+        Int::from(2) * lc.abs() * Int::from(2).pow(&Int::from(deg(f) as u64)).unwrap() * norm;
+    let (mut k, mut m) = (1, Int::from(p));
+    while m <= bound {
+        k += 1;
+        m *= Int::from(p);
+    }
+
+    let mut lifted = hensel_lift_all(f, &factors, p, k);
+    let mut res = vec![];
+    let mut f = f.clone();
+    let mut s = 1;
+    'outer: while 2 * s <= lifted.len() {
+        for subset in combinations(lifted.len(), s) {
+            let lc = f.last().unwrap().clone();
+            let g = subset
+                .iter()
+                .fold(vec![lc], |g, &i| z::modulo(&z::mul(&g, &lifted[i]), &m));
+            let g = z::primitive(&z::sym_modulo(&g, &m));
+            if let Some(q) = z::div_exact(&f, &g) {
+                res.push(g);
+                f = q;
+                for i in subset.into_iter().rev() {
+                    lifted.remove(i);
+                }
+                continue 'outer;
+            }
+        }
+        s += 1;
+    }
+    res.push(z::primitive(&f));
+    res
+}
+
+/// yun's square-free decomposition, returns monic a_i, such that f = lc(f) * a_1 * a_2^2 * ...
+fn square_free(f: &RatPoly) -> Vec<RatPoly> {
+    let df = f.derivative(0);
+    let a0 = f.gcd_uv(&df);
+    let mut b = f.div_rem(&a0).0;
+    let mut c = df.div_rem(&a0).0;
+    let mut d = c.sub(&b.derivative(0));
+
+    let mut res = vec![];
+    while !b.is_const() {
+        let a = b.gcd_uv(&d);
+        b = b.div_rem(&a).0;
+        c = d.div_rem(&a).0;
+        d = c.sub(&b.derivative(0));
+        res.push(a);
+    }
+    res
+}
+
+/// primitive integer polynomial with the same roots
+fn q_to_z(f: &QPoly) -> ZPoly {
+    let denom = f.iter().fold(Int::ONE, |l, c| l.lcm(&c.denom()));
+    let f: ZPoly = f
+        .iter()
+        .map(|c| {
+            (c.clone() * Rational::from(denom.clone()))
+                .to_int()
+                .unwrap()
+        })
+        .collect();
+    z::primitive(&f)
+}
+
+pub(crate) fn z_to_expr(f: &ZPoly, x: &Expr) -> Expr {
+    let f: QPoly = f.iter().map(|c| c.clone().into()).collect();
+    RatPoly::from_dense(&f).to_expr(std::slice::from_ref(x))
+}
+
+/// irreducible primitive factors over Z with their multiplicity, ordered by multiplicity,
+/// then by degree
+pub(crate) fn factor_list(f: &RatPoly) -> Vec<(ZPoly, u32)> {
+    let mut res = vec![];
+    for (i, a) in square_free(f).iter().enumerate() {
+        let mut factors = zassenhaus(&q_to_z(&a.to_dense()));
+        factors.sort_by(|g, h| deg(g).cmp(&deg(h)).then_with(|| g.cmp(h)));
+        res.extend(factors.into_iter().map(|g| (g, i as u32 + 1)));
+    }
+    res
+}
+
+/// term c(x) / f(x)^k of a partial fraction decomposition, f is irreducible and deg(c) < deg(f)
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PartialFraction {
+    pub numer: Expr,
+    pub denom: Expr,
+    pub power: u32,
+}
+
+impl PartialFraction {
+    pub fn to_expr(&self) -> Expr {
+        let power = Expr::from(Int::from(self.power));
+        self.numer.clone() / Expr::pow(&self.denom, power)
+    }
+}
+
+impl Expr {
+    /// polynomial part and partial fractions of a rational function in `x`
+    pub(crate) fn apart_list(&self, x: &Expr) -> Option<(Expr, Vec<PartialFraction>)> {
+        let vars = std::slice::from_ref(x);
+        let e = self.rationalize();
+        let n = RatPoly::from_expr(&e.numerator(), vars)?;
+        let d = RatPoly::from_expr(&e.denominator(), vars)?;
+        let to_expr = |p: &RatPoly| p.to_expr(vars);
+
+        let (poly, r) = n.div_rem(&d);
+        if d.is_const() || r.is_zero() {
+            return Some((to_expr(&poly), vec![]));
+        }
+
+        // d = c * prod p_i, with p_i = f_i^k_i
+        let one = RatPoly::constant(1, Rational::ONE);
+        let factors: Vec<(RatPoly, u32)> = factor_list(&d)
+            .into_iter()
+            .filter(|(f, _)| deg(f) > 0)
+            .map(|(f, k)| {
+                let f: QPoly = f.into_iter().map(Rational::from).collect();
+                (RatPoly::from_dense(&f), k)
+            })
+            .collect();
+        let powers: Vec<RatPoly> = factors
+            .iter()
+            .map(|(f, k)| (0..*k).fold(one.clone(), |p, _| p.mul(f)))
+            .collect();
+        let prod = powers.iter().fold(one, |p, f| p.mul(f));
+        let c = d.div_exact(&prod)?;
+        let r = r.div_exact(&c)?;
+
+        let mut fractions = vec![];
+        for ((f, k), p) in factors.iter().zip(&powers) {
+            // r / prod = a / p + ..., where a = r * (prod / p)^-1 mod p
+            let cofactor = prod.div_exact(p)?;
+            let (_, s) = cofactor.ext_gcd_uv(p);
+            let mut a = r.mul(&s).div_rem(p).1;
+
+            // a / f^k = sum_i c_i / f^i, the c_i are the digits of a in base f
+            let mut digits = vec![];
+            for i in (1..=*k).rev() {
+                let (quot, rem) = a.div_rem(f);
+                if !rem.is_zero() {
+                    digits.push(PartialFraction {
+                        numer: to_expr(&rem),
+                        denom: to_expr(f),
+                        power: i,
+                    });
+                }
+                a = quot;
+            }
+            fractions.extend(digits.into_iter().rev());
+        }
+        Some((to_expr(&poly), fractions))
+    }
+
+    /// partial fraction decomposition of a rational function in `x` over Q
+    ///
+    /// e.g 1 / (x^2 - 1) -> 1 / (2 * (x - 1)) - 1 / (2 * (x + 1)),
+    /// returns the expression unchanged if it is not a rational function in `x`
+    pub fn apart(&self, x: &Expr) -> Expr {
+        match self.apart_list(x) {
+            Some((poly, fractions)) => fractions
+                .iter()
+                .fold(poly, |sum, frac| sum + frac.to_expr()),
+            None => self.clone(),
+        }
+    }
+
+    /// factorization of a univariate polynomial with rational coefficients into
+    /// irreducible factors over Z, e.g x^4 - 1 -> (x - 1)(x + 1)(x^2 + 1)
+    ///
+    /// returns the expression unchanged if it is not such a polynomial in `vars`
+    pub fn factor(&self, vars: &VarSet) -> Expr {
+        let vars: Vec<_> = vars.iter().cloned().collect();
+        let [x] = vars.as_slice() else {
+            return self.clone();
+        };
+        let Some(poly) = RatPoly::from_expr(self, &vars) else {
+            return self.clone();
+        };
+        if poly.degree(0).is_none_or(|n| n == 0) {
+            return poly.to_expr(&vars);
+        }
+
+        let lc = poly.to_dense().last().unwrap().clone();
+
+        let mut res = Expr::one();
+        let mut lc_factors = Rational::ONE;
+        for (g, mult) in factor_list(&poly) {
+            let mult = Int::from(mult);
+            let lc_g = Rational::from(g.last().unwrap().clone());
+            lc_factors *= lc_g.pow_basic(mult.clone().into()).unwrap();
+            res *= Expr::pow(z_to_expr(&g, x), Expr::from(mult));
+        }
+        Expr::from(lc / lc_factors) * res
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use assert_eq as eq;
+    use calcurs_macros::expr as e;
+
+    #[test]
+    fn square_free() {
+        let dense = |c: &[i64]| {
+            let c: QPoly = c.iter().map(|c| Rational::from(*c)).collect();
+            RatPoly::from_dense(&c)
+        };
+        // x^3 - 2x^2 + x = x * (x - 1)^2
+        eq!(
+            super::square_free(&dense(&[0, 1, -2, 1])),
+            vec![dense(&[0, 1]), dense(&[-1, 1])]
+        );
+    }
+
+    #[test]
+    fn apart() {
+        let x = &e!(x);
+        let checks = [
+            (e!(1 / (x ^ 2 - 1)), e!(1 / 2 / (x - 1) - 1 / 2 / (x + 1))),
+            (
+                e!((x ^ 3 + 2) / (x ^ 2 - 1)),
+                e!(x + 3 / 2 / (x - 1) - 1 / 2 / (x + 1)),
+            ),
+            (
+                e!(1 / (x ^ 3 - 2 * x ^ 2 + x)),
+                e!(1 / x - 1 / (x - 1) + 1 / (x - 1) ^ 2),
+            ),
+            (
+                e!((x + 2) / (x ^ 3 + x)),
+                e!(2 / x + (1 - 2 * x) / (x ^ 2 + 1)),
+            ),
+            (
+                e!(1 / ((x ^ 2 + 1) ^ 2 * (2 * x + 1))),
+                e!(16 / 25 / (2 * x + 1)
+                    + (4 / 25 - 8 / 25 * x) / (x ^ 2 + 1)
+                    + (1 / 5 - 2 / 5 * x) / (x ^ 2 + 1)
+                    ^ 2),
+            ),
+            (e!(1 / x + 1 / (x + 1)), e!(1 / x + 1 / (x + 1))),
+            (e!((x ^ 2 - 1) / (x + 1)), e!(x - 1)),
+            (e!(x / y), e!(x / y)),
+            (e!(sin(x) / x), e!(sin(x) / x)),
+        ];
+
+        for (e, res) in checks {
+            eq!(e.apart(x).sort_args(), res.sort_args(), "{e}");
+        }
+    }
+
+    #[test]
+    fn factor() {
+        let x = &VarSet::from(e!(x));
+        let checks = [
+            (e!(x ^ 4 - 1), e!((x - 1) * (x + 1) * (x ^ 2 + 1))),
+            (e!(2 * x ^ 2 - 2), e!(2 * (x - 1) * (x + 1))),
+            (e!(x ^ 2 / 2 - 1 / 2), e!((x - 1) * (x + 1) / 2)),
+            (e!(x ^ 3 - 2 * x ^ 2 + x), e!(x * (x - 1) ^ 2)),
+            (e!(6 * x ^ 2 + 5 * x + 1), e!((2 * x + 1) * (3 * x + 1))),
+            (e!(1 - x ^ 2), e!(-(x - 1) * (x + 1))),
+            (e!(x ^ 4 + 1), e!(x ^ 4 + 1)),
+            (
+                e!((x ^ 2 + 1) ^ 2 * (x ^ 3 + 2) * (3 * x - 5)).expand(),
+                e!((3 * x - 5) * (x ^ 3 + 2) * (x ^ 2 + 1) ^ 2),
+            ),
+            (
+                e!((x ^ 4 - 2) * (x ^ 4 + x + 1) * (x ^ 2 - 3)).expand(),
+                e!((x ^ 2 - 3) * (x ^ 4 - 2) * (x ^ 4 + x + 1)),
+            ),
+            (e!(x + 1), e!(x + 1)),
+            (e!(3), e!(3)),
+            (e!(sin(x)), e!(sin(x))),
+        ];
+
+        for (poly, factored) in checks {
+            eq!(poly.factor(x), factored, "{poly}");
+        }
+
+        // the leading coefficient is divisible by every odd prime up to 97
+        let p = (3..98)
+            .filter(|n| (2..*n).all(|d| n % d != 0))
+            .fold(Expr::one(), |p, n| p * Expr::from(n));
+        let f = &p * e!(x ^ 2) + e!(1);
+        eq!(f.factor(x), f);
+        let f = (&p * e!(x) + e!(1)) * e!(x - 1);
+        eq!(f.expand().factor(x), f);
+    }
+}
@@ -0,0 +1,128 @@
+use std::{fmt, ops};
+
+use ordered_float::OrderedFloat;
+use serde::{Deserialize, Serialize};
+
+use crate::rational::Rational;
+
+/// inexact floating point number
+///
+/// the number of printed digits is taken from the formatter, e.g `format!("{:.3}", expr)`
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(from = "f64", into = "f64")]
+#[repr(transparent)]
+pub struct Float(pub(crate) OrderedFloat<f64>);
+
+impl Float {
+    pub const ZERO: Float = Float(OrderedFloat(0.0));
+    pub const ONE: Float = Float(OrderedFloat(1.0));
+
+    pub fn new(v: f64) -> Self {
+        Float(OrderedFloat(v))
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.0 .0
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.to_f64() == 0.0
+    }
+    pub fn is_neg(&self) -> bool {
+        self.to_f64() < 0.0
+    }
+    pub fn is_pos(&self) -> bool {
+        self.to_f64() > 0.0
+    }
+    pub fn is_int(&self) -> bool {
+        self.to_f64().fract() == 0.0
+    }
+    pub fn is_finite(&self) -> bool {
+        self.to_f64().is_finite()
+    }
+
+    pub fn abs(self) -> Self {
+        Float::new(self.to_f64().abs())
+    }
+
+    /// returns [None] if the result is not a real number, e.g (-1.0)^0.5
+    pub fn pow(self, rhs: Self) -> Option<Self> {
+        let res = self.to_f64().powf(rhs.to_f64());
+        (!res.is_nan()).then_some(Float::new(res))
+    }
+}
+
+impl From<f64> for Float {
+    fn from(value: f64) -> Self {
+        Float::new(value)
+    }
+}
+impl From<Float> for f64 {
+    fn from(value: Float) -> Self {
+        value.to_f64()
+    }
+}
+impl From<&Rational> for Float {
+    fn from(value: &Rational) -> Self {
+        Float::new(value.f64_approx())
+    }
+}
+
+impl fmt::Display for Float {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match f.precision() {
+            Some(digits) => write!(f, "{:.*}", digits, self.to_f64()),
+            // debug formatting always keeps the decimal point, e.g 1.0
+            None => write!(f, "{:?}", self.to_f64()),
+        }
+    }
+}
+impl fmt::Debug for Float {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.to_f64())
+    }
+}
+
+impl ops::Neg for Float {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Float(-self.0)
+    }
+}
+impl ops::Add for Float {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Float(self.0 + rhs.0)
+    }
+}
+impl ops::Mul for Float {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Float(self.0 * rhs.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use assert_eq as eq;
+
+    #[test]
+    fn display() {
+        eq!(Float::new(0.5).to_string(), "0.5");
+        eq!(Float::new(2.0).to_string(), "2.0");
+        eq!(format!("{:.3}", Float::new(1.0 / 3.0)), "0.333");
+        eq!(format!("{:.0}", Float::new(2.5e3)), "2500");
+    }
+
+    #[test]
+    fn serde() {
+        let f = Float::new(-1.25);
+        let json = serde_json::to_string(&f).unwrap();
+        eq!(json, "-1.25");
+        eq!(serde_json::from_str::<Float>(&json).unwrap(), f);
+    }
+}
@@ -0,0 +1,778 @@
+use crate::{
+    atom::{Atom, Expr, Real, RelOp},
+    parser::{ParseError, ParseErrorKind, Span},
+    rational::Int,
+};
+use std::str::FromStr;
+
+type Result<T> = std::result::Result<T, ParseError>;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Num(String),
+    Float(String),
+    Letter(char),
+    Cmd(String),
+    Op(char),
+    LBrace,
+    RBrace,
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    /// \{ and \} around a list
+    LSet,
+    RSet,
+    Comma,
+}
+
+/// commands that only affect spacing or delimiter sizes
+const IGNORED_CMDS: &[&str] = &[",", ";", ":", "!", " ", "quad", "qquad", "left", "right"];
+
+fn tokenize(src: &str) -> Result<Vec<(Token, Span)>> {
+    let mut tokens = vec![];
+    let mut chars = src.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        let tok = match c {
+            c if c.is_whitespace() => continue,
+            '\\' => {
+                let mut end = start + 1;
+                match chars.peek() {
+                    Some(&(_, c)) if c.is_ascii_alphabetic() => {
+                        while let Some((i, c)) = chars.next_if(|(_, c)| c.is_ascii_alphabetic()) {
+                            end = i + c.len_utf8();
+                        }
+                    }
+                    Some(&(i, c)) => {
+                        chars.next();
+                        end = i + c.len_utf8();
+                    }
+                    None => (),
+                }
+
+                match &src[start + 1..end] {
+                    cmd if IGNORED_CMDS.contains(&cmd) => continue,
+                    "cdot" | "times" => Token::Op('*'),
+                    "div" => Token::Op('/'),
+                    "neq" | "ne" => Token::Op('≠'),
+                    "leq" | "le" => Token::Op('≤'),
+                    "geq" | "ge" => Token::Op('≥'),
+                    "land" | "wedge" => Token::Op('∧'),
+                    "lor" | "vee" => Token::Op('∨'),
+                    "neg" | "lnot" => Token::Op('¬'),
+                    "{" => Token::LSet,
+                    "}" => Token::RSet,
+                    cmd => Token::Cmd(cmd.to_string()),
+                }
+            }
+            '0'..='9' | '.' => {
+                let mut end = start + 1;
+                while let Some((i, c)) = chars.next_if(|(_, c)| c.is_ascii_digit() || *c == '.') {
+                    end = i + c.len_utf8();
+                }
+                let lit = src[start..end].to_string();
+                if lit.contains('.') {
+                    Token::Float(lit)
+                } else {
+                    Token::Num(lit)
+                }
+            }
+            c if c.is_alphabetic() => Token::Letter(c),
+            '+' | '-' | '*' | '/' | '^' | '_' => Token::Op(c),
+            '=' | '<' | '>' | '≠' | '≤' | '≥' | '∧' | '∨' | '¬' => Token::Op(c),
+            ',' => Token::Comma,
+            '{' => Token::LBrace,
+            '}' => Token::RBrace,
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '[' => Token::LBracket,
+            ']' => Token::RBracket,
+            _ => {
+                let span = start..start + c.len_utf8();
+                return Err(ParseError::new(ParseErrorKind::UnexpectedChar(c), span));
+            }
+        };
+
+        let end = chars.peek().map(|(i, _)| *i).unwrap_or(src.len());
+        tokens.push((tok, start..end));
+    }
+
+    Ok(tokens)
+}
+
+fn func_by_name(name: &str) -> Option<fn(Expr) -> Expr> {
+    Some(match name {
+        "sin" => Expr::sin,
+        "cos" => Expr::cos,
+        "tan" => Expr::tan,
+        "sec" => Expr::sec,
+        "cot" => Expr::cot,
+        "csc" => Expr::csc,
+        "arcsin" => Expr::arc_sin,
+        "arccos" => Expr::arc_cos,
+        "arctan" => Expr::arc_tan,
+        "arcsec" => Expr::arc_sec,
+        "arccot" => Expr::arc_cot,
+        "arccsc" => Expr::arc_csc,
+        "ln" => Expr::ln,
+        "exp" => Expr::exp,
+        "Re" => Expr::re,
+        "Im" => Expr::im,
+        "arg" => Expr::arg,
+        "isprime" => Expr::isprime,
+        "nextprime" => Expr::nextprime,
+        "totient" => Expr::totient,
+        "divisors" => Expr::divisors,
+        "O" => Expr::order,
+        _ => return None,
+    })
+}
+
+type MultiFunc = fn(&[Expr]) -> Expr;
+
+/// functions of several arguments and their arity, written as \operatorname{name}(a, b, ...)
+fn multi_func_by_name(name: &str) -> Option<(usize, MultiFunc)> {
+    Some(match name {
+        "mod" => (2, |a| Expr::modulo(&a[0], &a[1])),
+        "modinv" => (2, |a| Expr::modinv(&a[0], &a[1])),
+        "powmod" => (3, |a| Expr::powmod(&a[0], &a[1], &a[2])),
+        "rootof" => (2, |a| Expr::root_of(&a[0], &a[1])),
+        _ => return None,
+    })
+}
+
+fn rel_op(tok: &Token) -> Option<RelOp> {
+    Some(match tok {
+        Token::Op('=') => RelOp::Eq,
+        Token::Op('≠') => RelOp::Ne,
+        Token::Op('<') => RelOp::Lt,
+        Token::Op('≤') => RelOp::Le,
+        Token::Op('>') => RelOp::Gt,
+        Token::Op('≥') => RelOp::Ge,
+        _ => return None,
+    })
+}
+
+/// the inverse of a trigonometric function, e.g. \cos^{-1} is arccos
+fn inverse_trig(name: &str) -> Option<fn(Expr) -> Expr> {
+    Some(match name {
+        "sin" => Expr::arc_sin,
+        "cos" => Expr::arc_cos,
+        "tan" => Expr::arc_tan,
+        "sec" => Expr::arc_sec,
+        "cot" => Expr::arc_cot,
+        "csc" => Expr::arc_csc,
+        _ => return None,
+    })
+}
+
+struct LatexParser<'a> {
+    src: &'a str,
+    tokens: Vec<(Token, Span)>,
+    pos: usize,
+}
+
+impl LatexParser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(t, _)| t)
+    }
+
+    fn span(&self) -> Span {
+        match self.tokens.get(self.pos) {
+            Some((_, s)) => s.clone(),
+            None => self.src.len()..self.src.len(),
+        }
+    }
+
+    fn next(&mut self) -> Option<(Token, Span)> {
+        let t = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        t
+    }
+
+    fn unexpected(&self) -> ParseError {
+        match self.tokens.get(self.pos) {
+            Some((_, span)) => ParseError::new(
+                ParseErrorKind::UnexpectedToken(self.src[span.clone()].to_string()),
+                span.clone(),
+            ),
+            None => ParseError::new(ParseErrorKind::UnexpectedEof, self.span()),
+        }
+    }
+
+    fn eat(&mut self, tok: &Token) -> bool {
+        if self.peek() == Some(tok) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, tok: Token) -> Result<()> {
+        match self.eat(&tok) {
+            true => Ok(()),
+            false => Err(self.unexpected()),
+        }
+    }
+
+    /// consumes ^{-1} or ^-1
+    fn eat_inverse(&mut self) -> bool {
+        use Token as T;
+        let (minus, one) = (T::Op('-'), T::Num("1".into()));
+        let braced = [T::Op('^'), T::LBrace, minus.clone(), one.clone(), T::RBrace];
+        let plain = [T::Op('^'), minus, one];
+
+        for toks in [&braced[..], &plain] {
+            let rest = self.tokens[self.pos..].iter().map(|(t, _)| t);
+            if rest.take(toks.len()).eq(toks) {
+                self.pos += toks.len();
+                return true;
+            }
+        }
+        false
+    }
+
+    fn starts_factor(&self) -> bool {
+        matches!(
+            self.peek(),
+            Some(
+                Token::Num(_)
+                    | Token::Float(_)
+                    | Token::Letter(_)
+                    | Token::Cmd(_)
+                    | Token::LParen
+                    | Token::LBrace
+                    | Token::LBracket
+                    | Token::LSet
+            )
+        )
+    }
+
+    fn starts_func(&self) -> bool {
+        match self.peek() {
+            Some(Token::Cmd(cmd)) => {
+                func_by_name(cmd).is_some() || cmd == "log" || cmd == "operatorname"
+            }
+            _ => false,
+        }
+    }
+
+    /// a \lor b, with \land binding tighter, then relations, then sums
+    fn parse_expr(&mut self) -> Result<Expr> {
+        let mut lhs = self.parse_and()?;
+        while self.eat(&Token::Op('∨')) {
+            lhs = Expr::or(lhs, self.parse_and()?);
+        }
+        Ok(lhs)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr> {
+        let mut lhs = self.parse_rel()?;
+        while self.eat(&Token::Op('∧')) {
+            lhs = Expr::and(lhs, self.parse_rel()?);
+        }
+        Ok(lhs)
+    }
+
+    fn parse_rel(&mut self) -> Result<Expr> {
+        let mut lhs = self.parse_sum()?;
+        while let Some(op) = self.peek().and_then(rel_op) {
+            self.pos += 1;
+            lhs = Expr::rel(op, lhs, self.parse_sum()?);
+        }
+        Ok(lhs)
+    }
+
+    fn parse_sum(&mut self) -> Result<Expr> {
+        let mut lhs = self.parse_term()?;
+        loop {
+            if self.eat(&Token::Op('+')) {
+                lhs = Expr::add(lhs, self.parse_term()?);
+            } else if self.eat(&Token::Op('-')) {
+                lhs = Expr::sub(lhs, self.parse_term()?);
+            } else {
+                return Ok(lhs);
+            }
+        }
+    }
+
+    fn parse_term(&mut self) -> Result<Expr> {
+        let mut lhs = self.parse_unary()?;
+        loop {
+            if self.eat(&Token::Op('*')) {
+                lhs = Expr::mul(lhs, self.parse_unary()?);
+            } else if self.eat(&Token::Op('/')) {
+                lhs = Expr::div(lhs, self.parse_unary()?);
+            } else if self.starts_factor() {
+                // implicit multiplication, e.g. 2xy or 2\sin(x)
+                lhs = Expr::mul(lhs, self.parse_power()?);
+            } else {
+                return Ok(lhs);
+            }
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr> {
+        if self.eat(&Token::Op('-')) {
+            Ok(Expr::mul(Expr::min_one(), self.parse_unary()?))
+        } else if self.eat(&Token::Op('+')) {
+            self.parse_unary()
+        } else if self.eat(&Token::Op('¬')) {
+            Ok(Expr::not(self.parse_unary()?))
+        } else {
+            self.parse_power()
+        }
+    }
+
+    fn parse_power(&mut self) -> Result<Expr> {
+        let base = self.parse_primary()?;
+        if self.eat(&Token::Op('^')) {
+            let expon = self.parse_script()?;
+            Ok(Expr::pow(base, expon))
+        } else {
+            Ok(base)
+        }
+    }
+
+    /// argument of ^, _, \frac, ... either a {group} or a single token
+    fn parse_script(&mut self) -> Result<Expr> {
+        match self.tokens.get(self.pos).cloned() {
+            Some((Token::LBrace, _)) => {
+                self.pos += 1;
+                let e = self.parse_expr()?;
+                self.expect(Token::RBrace)?;
+                Ok(e)
+            }
+            Some((Token::Num(n), span)) if n.len() > 1 => {
+                // x^23 is x^2·3
+                let (d, rest) = n.split_at(1);
+                self.tokens[self.pos] = (Token::Num(rest.into()), span.start + 1..span.end);
+                Ok(Expr::rational(Int::from_str(d).unwrap()))
+            }
+            Some((Token::Op('-'), _)) => {
+                self.pos += 1;
+                Ok(Expr::mul(Expr::min_one(), self.parse_script()?))
+            }
+            _ => self.parse_primary(),
+        }
+    }
+
+    /// raw text of a subscript, e.g. x_1 or x_{12}
+    fn parse_subscript_name(&mut self) -> Result<String> {
+        match self.next() {
+            Some((Token::LBrace, open)) => {
+                let start = open.end;
+                let mut depth = 1;
+                while let Some((tok, span)) = self.next() {
+                    match tok {
+                        Token::LBrace => depth += 1,
+                        Token::RBrace if depth == 1 => {
+                            let name: String = self.src[start..span.start]
+                                .chars()
+                                .filter(|c| !c.is_whitespace())
+                                .collect();
+                            return Ok(name);
+                        }
+                        Token::RBrace => depth -= 1,
+                        _ => (),
+                    }
+                }
+                Err(ParseError::new(ParseErrorKind::UnexpectedEof, self.span()))
+            }
+            Some((Token::Num(n), span)) => {
+                let (d, rest) = n.split_at(1);
+                if !rest.is_empty() {
+                    self.pos -= 1;
+                    self.tokens[self.pos] = (Token::Num(rest.into()), span.start + 1..span.end);
+                }
+                Ok(d.to_string())
+            }
+            Some((Token::Letter(c), _)) => Ok(c.to_string()),
+            _ => {
+                self.pos -= 1;
+                Err(self.unexpected())
+            }
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr> {
+        let Some((tok, span)) = self.next() else {
+            return Err(ParseError::new(ParseErrorKind::UnexpectedEof, self.span()));
+        };
+
+        match tok {
+            Token::Num(n) => match Int::from_str(&n) {
+                Ok(n) => Ok(Expr::rational(n)),
+                Err(_) => Err(ParseError::new(ParseErrorKind::InvalidNumber(n), span)),
+            },
+            Token::Float(lit) => match f64::from_str(&lit) {
+                Ok(f) => Ok(Expr::float(f)),
+                Err(_) => Err(ParseError::new(ParseErrorKind::InvalidNumber(lit), span)),
+            },
+            Token::Letter(c) => {
+                if self.eat(&Token::Op('_')) {
+                    let sub = self.parse_subscript_name()?;
+                    Ok(Expr::var(&format!("{c}_{sub}")))
+                } else if c == 'e' {
+                    Ok(Expr::e())
+                } else if c == 'i' {
+                    Ok(Expr::i())
+                } else {
+                    Ok(Expr::var(&c.to_string()))
+                }
+            }
+            Token::LParen => {
+                let e = self.parse_expr()?;
+                self.expect(Token::RParen)?;
+                Ok(e)
+            }
+            Token::LBracket => {
+                let e = self.parse_expr()?;
+                self.expect(Token::RBracket)?;
+                Ok(e)
+            }
+            Token::LBrace => {
+                let e = self.parse_expr()?;
+                self.expect(Token::RBrace)?;
+                Ok(e)
+            }
+            Token::LSet => {
+                let items = self.parse_list(Token::RSet)?;
+                Ok(Expr::from(items))
+            }
+            Token::Cmd(cmd) => self.parse_cmd(cmd, span),
+            _ => Err(ParseError::new(ParseErrorKind::ExpectedOperand, span)),
+        }
+    }
+
+    fn parse_cmd(&mut self, cmd: String, span: Span) -> Result<Expr> {
+        match cmd.as_str() {
+            "pi" => Ok(Expr::pi()),
+            "overline" => Ok(Expr::conj(self.parse_script()?)),
+            "frac" | "dfrac" | "tfrac" => {
+                let n = self.parse_script()?;
+                let d = self.parse_script()?;
+                Ok(Expr::div(n, d))
+            }
+            "sqrt" => {
+                let index = match self.eat(&Token::LBracket) {
+                    true => {
+                        let n = self.parse_expr()?;
+                        self.expect(Token::RBracket)?;
+                        Some(n)
+                    }
+                    false => None,
+                };
+                let radicand = self.parse_script()?;
+                Ok(match index {
+                    Some(n) => Expr::pow(radicand, Expr::div(Expr::one(), n)),
+                    None => Expr::sqrt(radicand),
+                })
+            }
+            "log" => {
+                let base = match self.eat(&Token::Op('_')) {
+                    true => {
+                        let base_span = self.span();
+                        let base = self.parse_script()?;
+                        match base.atom() {
+                            Atom::Rational(r) => Real::Rational(r.clone()),
+                            Atom::Irrational(i) => Real::Irrational(*i),
+                            _ => {
+                                return Err(ParseError::new(
+                                    ParseErrorKind::InvalidLogBase,
+                                    base_span,
+                                ))
+                            }
+                        }
+                    }
+                    false => Real::Rational(10.into()),
+                };
+                self.parse_func_call(|x| Expr::log(base.clone(), x))
+            }
+            "operatorname" => {
+                let name = self.parse_subscript_name()?;
+                if let Some((n_args, func)) = multi_func_by_name(&name) {
+                    let start = self.span();
+                    self.expect(Token::LParen)?;
+                    let args = self.parse_list(Token::RParen)?;
+                    if args.len() != n_args {
+                        let kind = ParseErrorKind::WrongArgCount {
+                            func: name,
+                            expected: n_args,
+                            found: args.len(),
+                        };
+                        return Err(ParseError::new(kind, span.start..start.start));
+                    }
+                    return Ok(func(&args));
+                }
+                match func_by_name(&name) {
+                    Some(func) => self.parse_named_func(&name, func),
+                    None => Err(ParseError::new(ParseErrorKind::UnknownFunction(name), span)),
+                }
+            }
+            "text" => {
+                // \_ is an escaped underscore
+                let text = self.parse_subscript_name()?.replace("\\_", "_");
+                Ok(match text.as_str() {
+                    "undefined" => Expr::undef(),
+                    "true" => Expr::from(true),
+                    "false" => Expr::from(false),
+                    _ => Expr::var(&text),
+                })
+            }
+            "infty" => Ok(Expr::inf()),
+            "tilde" => match self.parse_script()? {
+                e if e == Expr::inf() => Ok(Expr::complex_inf()),
+                _ => Err(ParseError::new(
+                    ParseErrorKind::Unsupported("accents"),
+                    span,
+                )),
+            },
+            _ => match func_by_name(&cmd) {
+                Some(func) => self.parse_named_func(&cmd, func),
+                None => Err(ParseError::new(ParseErrorKind::UnknownCommand(cmd), span)),
+            },
+        }
+    }
+
+    /// comma separated expressions up to and including `close`
+    fn parse_list(&mut self, close: Token) -> Result<Vec<Expr>> {
+        let mut items = vec![];
+        if self.eat(&close) {
+            return Ok(items);
+        }
+        loop {
+            items.push(self.parse_expr()?);
+            if !self.eat(&Token::Comma) {
+                break;
+            }
+        }
+        self.expect(close)?;
+        Ok(items)
+    }
+
+    /// like [Self::parse_func_call], but \sin^{-1} is read as arcsin
+    fn parse_named_func(&mut self, name: &str, func: fn(Expr) -> Expr) -> Result<Expr> {
+        match inverse_trig(name) {
+            Some(inv) if self.eat_inverse() => self.parse_func_call(inv),
+            _ => self.parse_func_call(func),
+        }
+    }
+
+    /// \sin(x), \sin^{2}(x) or \sin 2x
+    fn parse_func_call(&mut self, func: impl Fn(Expr) -> Expr) -> Result<Expr> {
+        let expon = match self.eat(&Token::Op('^')) {
+            true => Some(self.parse_script()?),
+            false => None,
+        };
+
+        let arg = if self.peek() == Some(&Token::LParen) {
+            self.parse_primary()?
+        } else {
+            let mut arg = self.parse_power()?;
+            while self.starts_factor() && !self.starts_func() {
+                arg = Expr::mul(arg, self.parse_power()?);
+            }
+            arg
+        };
+
+        Ok(match expon {
+            Some(e) => Expr::pow(func(arg), e),
+            None => func(arg),
+        })
+    }
+}
+
+impl Expr {
+    /// parses a subset of LaTeX math, e.g. `\frac{1}{2}\sin^{2}(x) + \sqrt[3]{y}`
+    pub fn from_latex(src: &str) -> Result<Expr> {
+        let mut parser = LatexParser {
+            src,
+            tokens: tokenize(src)?,
+            pos: 0,
+        };
+
+        let e = parser.parse_expr()?;
+        if parser.peek().is_some() {
+            return Err(parser.unexpected());
+        }
+        Ok(e)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use assert_eq as eq;
+    use calcurs_macros::expr as e;
+
+    fn parse(src: &str) -> Expr {
+        Expr::from_latex(src).unwrap()
+    }
+
+    #[test]
+    fn basic() {
+        eq!(parse("x + y"), e!(x + y));
+        eq!(parse("2xy"), e!(2 * x * y));
+        eq!(parse("-x^2"), e!(-(x ^ 2)));
+        eq!(parse(r"a \cdot b - c \times d"), e!(a * b - c * d));
+        eq!(parse("x^23"), e!(x ^ 2 * 3));
+        eq!(parse("x^{23}"), e!(x ^ 23));
+        eq!(parse(r"\frac{x + 1}{2y}"), e!((x + 1) / (2 * y)));
+        eq!(parse(r"\frac12"), e!(1 / 2));
+        eq!(parse(r"\sqrt{x}"), e!(x ^ (1 / 2)));
+        eq!(parse(r"\sqrt[3]{x + 1}"), e!((x + 1) ^ (1 / 3)));
+        eq!(parse(r"\pi r^2"), e!(pi * r ^ 2));
+        eq!(parse("e^{2x}"), e!(exp(2 * x)));
+        eq!(parse(r"x_1 + x_{12}"), Expr::var("x_1") + Expr::var("x_12"));
+        eq!(parse(r"\left(a + b\right)(c + d)"), e!((a + b) * (c + d)));
+    }
+
+    #[test]
+    fn functions() {
+        eq!(parse(r"\sin(x)\cos(x)"), e!(sin(x) * cos(x)));
+        eq!(parse(r"\sin x \cos x"), e!(sin(x) * cos(x)));
+        eq!(parse(r"\tan 2x"), e!(tan(2 * x)));
+        eq!(parse(r"\arcsin(x) + 1"), e!(arc_sin(x) + 1));
+        eq!(parse(r"\sin^{2}(x)"), e!(sin(x) ^ 2));
+        eq!(parse(r"\ln x"), e!(ln(x)));
+        eq!(parse(r"\log(x)"), e!(log10(x)));
+        eq!(
+            parse(r"\log_{2}(x)"),
+            Expr::log(Real::Rational(2.into()), e!(x))
+        );
+        eq!(parse(r"\log_e x"), e!(ln(x)));
+        eq!(parse(r"\operatorname{arcsec}(x)"), e!(arc_sec(x)));
+        eq!(parse(r"\cos^{-1} x"), e!(arc_cos(x)));
+        eq!(
+            parse(r"\sin^-1(x) + \tan^{-1}2x"),
+            e!(arc_sin(x) + arc_tan(2 * x))
+        );
+        eq!(parse(r"\operatorname{sec}^{-1}(x)"), e!(arc_sec(x)));
+        eq!(parse(r"\sin^{-2}(x)"), e!(sin(x) ^ -2));
+        eq!(parse(r"\ln^{-1}(x)"), e!(ln(x) ^ -1));
+    }
+
+    #[test]
+    fn text() {
+        eq!(parse(r"\text{undefined}"), e!(undef));
+        eq!(parse(r"x + \text{undefined}"), e!(x + undef));
+        eq!(parse(r"\text{true}"), e!(true));
+        eq!(parse(r"2\text{speed}"), Expr::var("speed") * e!(2));
+    }
+
+    #[test]
+    fn round_trip() {
+        let exprs = vec![
+            e!(2 / 3),
+            e!(pi),
+            e!(y + -x),
+            e!(a + b * c),
+            e!(2 * x * y),
+            e!((x + y) / (x * y)),
+            e!((x + y) * (a + b)),
+            e!(2 * x ^ (a + b)),
+            e!(x ^ y ^ z),
+            e!((x + 1) ^ (1 / 3)),
+            e!(x ^ (3 / 2)),
+            e!(exp(x)),
+            e!(arc_tan(x) * arc_cot(x) + arc_csc(x)),
+            e!(sec(x) * cot(x) * csc(x)),
+            e!(log10(x) + ln(x)),
+            e!(sin(x) ^ 2 * cos(x)),
+            e!(sin(x) ^ (x + y) * tan(x)),
+            e!(2 + 3 * I),
+            e!(re(x) + im(x) * I),
+            e!(conj(x + 1) * arg(x)),
+            e!(x + oo),
+            e!(-oo),
+            e!(zoo * x),
+            e!(0.5 * x - 1.25),
+        ];
+
+        for e in exprs {
+            eq!(parse(&e.to_latex()), e, "{}", e.to_latex());
+        }
+    }
+
+    #[test]
+    fn round_trip_atoms() {
+        let exprs = vec![
+            e!(undef),
+            e!(-7),
+            e!(1 / 3),
+            e!(pi),
+            Expr::e(),
+            e!(1.5),
+            e!(I),
+            e!(oo),
+            e!(-oo),
+            e!(zoo),
+            e!(x),
+            e!(e + i),
+            Expr::var("x_12"),
+            Expr::var("speed"),
+            Expr::var("_x"),
+            e!(a + b),
+            e!(a * b),
+            e!(a ^ b),
+            e!(true),
+            e!(false),
+            e!(x < 1),
+            e!(x <= 1),
+            e!(x > 1),
+            e!(x >= 1),
+            e!(x == 1),
+            e!(x != 1),
+            e!(x > 1 && y < 2),
+            e!(x < 1 || !(y < 2)),
+            e!(!x && (y || z)),
+            Expr::from([1, 2, 3].map(Expr::from).to_vec()),
+            Expr::from(vec![]),
+            e!(isprime(x) + nextprime(x) + totient(x) + divisors(x)),
+            e!(modulo(x, 3) + modinv(x, 7) + powmod(x, 2, 5)),
+            e!(root_of(x ^ 5 - x + 1, 0)),
+            e!(order(x ^ 3)),
+            e!(log(2, x)),
+            e!(sin(x) + arc_sin(x) + cos(x) + arc_cos(x)),
+            e!(re(x) + im(x) + conj(x) + arg(x)),
+        ];
+
+        for e in exprs {
+            eq!(parse(&e.to_latex()), e, "{}", e.to_latex());
+        }
+    }
+
+    #[test]
+    fn errors() {
+        use ParseErrorKind as K;
+        let err = |src: &str| Expr::from_latex(src).unwrap_err();
+
+        eq!(
+            err(r"x + \foo{y}"),
+            ParseError::new(K::UnknownCommand("foo".into()), 4..8)
+        );
+        eq!(
+            err(r"\frac{x}{y"),
+            ParseError::new(K::UnexpectedEof, 10..10)
+        );
+        eq!(
+            err(r"\log_{x}(y)"),
+            ParseError::new(K::InvalidLogBase, 5..6)
+        );
+        eq!(err("x + $"), ParseError::new(K::UnexpectedChar('$'), 4..5));
+        eq!(
+            err(r"\operatorname{mod}(x)"),
+            ParseError::new(
+                K::WrongArgCount {
+                    func: "mod".into(),
+                    expected: 2,
+                    found: 1
+                },
+                0..18
+            )
+        );
+    }
+}
@@ -4,15 +4,27 @@ pub extern crate self as calcu_rs;
 
 pub mod algos;
 pub mod atom;
+pub mod compile;
+pub mod eval;
+pub mod factor;
+pub mod float;
+pub mod latex;
+pub mod limit;
+pub mod matrix;
+pub mod parser;
 pub mod polynomial;
 pub mod rational;
+pub mod rewrite;
 pub mod rubi;
+pub mod series;
+pub mod solve;
 pub mod sym_fmt;
 pub mod transforms;
 pub mod utils;
 
 pub use atom::{Expr, SymbolicExpr};
 pub use calcurs_macros::expr;
+pub use calcurs_wolfram as wolfram;
 
 pub mod prelude {
     pub use crate::atom::{Expr, Irrational, Pow, Prod, Sum, SymbolicExpr};
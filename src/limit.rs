@@ -0,0 +1,416 @@
+use std::{cell::Cell, cmp::Ordering};
+
+use num::ToPrimitive;
+
+use crate::{
+    atom::{Atom, Expr, Func, Infinity, SymbolicExpr},
+    polynomial::VarSet,
+    rational::Rational,
+    series,
+    solve::fresh_var,
+};
+
+/// the side from which `x` approaches a finite point, see [Expr::limit]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dir {
+    /// from below, x -> x0-
+    Left,
+    /// from above, x -> x0+
+    Right,
+    /// both one-sided limits have to agree
+    Both,
+}
+
+/// the sign of a real constant or infinity
+fn sign(e: &Expr) -> Option<Ordering> {
+    match e.atom() {
+        Atom::Infinity(Infinity::Pos) => Some(Ordering::Greater),
+        Atom::Infinity(Infinity::Neg) => Some(Ordering::Less),
+        _ => e.cmp_real(&Expr::zero()),
+    }
+}
+
+/// oo or -oo with the sign of `e`
+fn signed_inf(e: &Expr) -> Option<Expr> {
+    match sign(e)? {
+        Ordering::Greater => Some(Expr::inf()),
+        Ordering::Less => Some(Expr::neg_inf()),
+        Ordering::Equal => None,
+    }
+}
+
+/// nested applications of l'hopital's rule before giving up
+const MAX_LHOPITAL: u32 = 4;
+
+/// true if `e` stays within a finite interval for all real arguments, e.g sin(1/t)
+fn is_bounded(e: &Expr) -> bool {
+    use Func as F;
+    match e.atom() {
+        Atom::Func(F::Sin(_) | F::Cos(_) | F::ArcTan(_) | F::ArcCot(_)) => true,
+        Atom::Pow(pow) => is_bounded(pow.base()) && pow.exponent().is_pos(),
+        _ => false,
+    }
+}
+
+/// limits for t -> 0+
+struct Limit {
+    t: Expr,
+    ln_t: Expr,
+    /// the current nesting of [Limit::lhopital]
+    depth: Cell<u32>,
+}
+
+impl Limit {
+    fn lim(&self, e: &Expr) -> Option<Expr> {
+        // the leading coefficient can contain iterated logarithms, e.g ln(ln(t))
+        let leading = series::terms_at_zero(e, &self.t).and_then(|terms| self.leading(&terms));
+        let res = match leading {
+            Some(res) => res,
+            None => self.lim_rec(e)?,
+        };
+        let indeterminate = matches!(res.atom(), Atom::Undef | Atom::Infinity(Infinity::Complex));
+        (!indeterminate).then_some(res)
+    }
+
+    /// the leading term c ln(t)^m of a polynomial in ln(t)
+    fn ln_lead(&self, p: &Expr) -> Option<(i64, Expr)> {
+        let p = p.expand();
+        let vars = VarSet::from(self.ln_t.clone());
+        let view = p.as_polynomial_view(&vars);
+        if !view.check() {
+            return None;
+        }
+        match view.degree_of(&self.ln_t) {
+            Some(m) if !m.is_zero() => Some((m.to_i64()?, view.leading_coeff_of(&self.ln_t)?)),
+            _ => Some((0, p)),
+        }
+    }
+
+    /// the dominant part c t^k ln(t)^m of a series in t, where the coefficient of t^k is a
+    /// rational function in ln(t). c is multiplied by (-1)^m, so that its sign is the sign of
+    /// the series for t -> 0+
+    fn dominant(&self, terms: &[(Rational, Expr)]) -> Option<(Rational, i64, Expr)> {
+        let (k, c) = terms.first()?;
+        let c = c.rationalize();
+        let (m1, c1) = self.ln_lead(&c.numerator())?;
+        let (m2, c2) = self.ln_lead(&c.denominator())?;
+        let (m, lead) = (m1 - m2, (c1 / c2).reduce());
+        if !lead.free_of(&self.t) {
+            return None;
+        }
+        let lead = match m % 2 {
+            0 => lead,
+            _ => Expr::min_one() * lead,
+        };
+        Some((k.clone(), m, lead))
+    }
+
+    /// the limit of a series in t
+    fn leading(&self, terms: &[(Rational, Expr)]) -> Option<Expr> {
+        if terms.is_empty() || terms[0].0.is_pos() {
+            // t^k ln(t)^m -> 0 for k > 0
+            return Some(Expr::zero());
+        }
+        match self.dominant(terms)? {
+            (k, 0, c) if k.is_zero() => Some(c),
+            (k, m, _) if k.is_zero() && m < 0 => Some(Expr::zero()),
+            (_, _, c) => signed_inf(&c),
+        }
+    }
+
+    /// the limit of `e` from the limits of its arguments
+    fn lim_rec(&self, e: &Expr) -> Option<Expr> {
+        use Atom as A;
+        if e.free_of(&self.t) {
+            return Some(e.clone());
+        }
+
+        match e.atom() {
+            A::Sum(sum) => self.sum(&sum.args),
+            A::Prod(prod) => self.prod(&prod.args),
+            A::Pow(pow) => {
+                let (b, p) = (pow.base(), pow.exponent());
+                if b.is_e() {
+                    Some(Expr::exp(self.lim(p)?))
+                } else if p.free_of(&self.t) {
+                    let b = self.lim(b)?;
+                    // the sign of b is unknown for 0^-n
+                    (!b.is_zero()).then(|| Expr::pow(b, p))
+                } else {
+                    // b^p = exp(p * ln(b))
+                    self.lim(&Expr::pow_raw(Expr::e(), Expr::mul_raw(p, Expr::ln(b))))
+                }
+            }
+            A::Func(f) => self.func(f),
+            _ => None,
+        }
+    }
+
+    fn func(&self, f: &Func) -> Option<Expr> {
+        use Func as F;
+        match f {
+            F::Log(base, a) => {
+                let a = self.lim(a)?;
+                if base.f64_approx() <= 1.0 {
+                    None
+                } else if a.is_zero() {
+                    Some(Expr::neg_inf())
+                } else if a.is_infinity() {
+                    (sign(&a)? == Ordering::Greater).then(Expr::inf)
+                } else {
+                    Some(Expr::log(base.clone(), a).reduce())
+                }
+            }
+            F::ArcTan(a) | F::ArcCot(a) => {
+                let a = self.lim(a)?;
+                if !a.is_infinity() {
+                    let mut f = f.clone();
+                    f.args_mut()[0] = a;
+                    return Some(Atom::Func(f).reduce());
+                }
+                let half_pi = Expr::pi() / Expr::two();
+                match (f, sign(&a)?) {
+                    (F::ArcCot(_), _) => Some(Expr::zero()),
+                    (_, Ordering::Less) => Some(Expr::min_one() * half_pi),
+                    _ => Some(half_pi),
+                }
+            }
+            // continuous at finite arguments
+            _ => {
+                let mut f = f.clone();
+                for a in f.args_mut() {
+                    *a = self.lim(a)?;
+                    if a.is_infinity() {
+                        return None;
+                    }
+                }
+                Some(Atom::Func(f).reduce())
+            }
+        }
+    }
+
+    fn sum(&self, args: &[Expr]) -> Option<Expr> {
+        let lims = args
+            .iter()
+            .map(|a| self.lim(a))
+            .collect::<Option<Vec<_>>>()?;
+        let res = lims.iter().fold(Expr::zero(), |res, l| res + l).reduce();
+        if !res.is_undef() {
+            return Some(res);
+        }
+        // oo - oo: a_1 + ... + a_n = a_j (1 + r) with r = sum of a_i / a_j, i != j
+        for (j, a) in args.iter().enumerate() {
+            if !lims[j].is_infinity() {
+                continue;
+            }
+            let r = args
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != j)
+                .fold(Expr::zero(), |r, (_, b)| r + b / a);
+            // e.g exp(1/t) / exp(2/t) = exp(-1/t)
+            let Some(r) = self.lim(&r.expand().reduce()) else {
+                continue;
+            };
+            if !r.is_infinity() {
+                return signed_inf(&((Expr::one() + r) * &lims[j]).reduce());
+            }
+        }
+        None
+    }
+
+    /// exponentials dominate powers and logarithms, the product is split into p * exp(g)
+    fn prod(&self, args: &[Expr]) -> Option<Expr> {
+        let (exps, rest): (Vec<_>, Vec<_>) = args
+            .iter()
+            .partition(|a| a.base().is_e() && !a.exponent().free_of(&self.t));
+        let p = rest.into_iter().fold(Expr::one(), |p, a| p * a);
+        let p_terms = series::terms_at_zero(&p, &self.t);
+
+        if exps.is_empty() || p_terms.is_none() {
+            return self.prod_rec(args);
+        }
+        let g = exps.into_iter().fold(Expr::zero(), |g, a| g + a.exponent());
+        let p_terms = p_terms.unwrap();
+
+        let g = self.lim(&g)?;
+        match g.atom() {
+            Atom::Infinity(Infinity::Neg) => Some(Expr::zero()),
+            Atom::Infinity(Infinity::Pos) => signed_inf(&self.dominant(&p_terms)?.2),
+            _ => Some(Expr::exp(g) * self.leading(&p_terms)?),
+        }
+    }
+
+    /// the limit of a product from the limits of its factors
+    ///
+    /// bounded factors vanish if the rest goes to zero, e.g sin(1/t) * t. For 0 * oo the
+    /// infinite and the vanishing factors are compared with [Limit::lhopital]
+    fn prod_rec(&self, args: &[Expr]) -> Option<Expr> {
+        let (bounded, rest): (Vec<_>, Vec<_>) = args.iter().partition(|a| is_bounded(a));
+        let lims: Vec<_> = rest.iter().map(|a| self.lim(a)).collect::<Option<_>>()?;
+        let res = lims.iter().fold(Expr::one(), |res, l| res * l).reduce();
+        if !bounded.is_empty() {
+            return match res.is_zero() {
+                true => Some(res),
+                false => bounded
+                    .iter()
+                    .try_fold(res, |res, a| Some(res * self.lim(a)?)),
+            };
+        } else if !res.is_undef() {
+            return Some(res);
+        }
+
+        // 0 * oo = f / g with f, g -> oo
+        let (mut f, mut zeros, mut c) = (Expr::one(), Expr::one(), Expr::one());
+        for (a, l) in rest.into_iter().zip(lims) {
+            if l.is_infinity() {
+                f *= a;
+            } else if l.is_zero() {
+                zeros *= a;
+            } else {
+                c *= l;
+            }
+        }
+        let g = (Expr::one() / zeros).reduce();
+        Some((c * self.lhopital(&f, &g)?).reduce())
+    }
+
+    /// the limit of f / g for f, g -> oo by l'hopital's rule: lim f / g = lim f' / g'
+    ///
+    /// this compares the growth of logarithms, e.g ln(ln(1/t)) / ln(1/t) -> 0
+    fn lhopital(&self, f: &Expr, g: &Expr) -> Option<Expr> {
+        let depth = self.depth.get();
+        if depth >= MAX_LHOPITAL {
+            return None;
+        }
+        let df = f.derivative(&self.t).reduce();
+        let dg = g.derivative(&self.t).reduce();
+        self.depth.set(depth + 1);
+        let res = self.lim(&(df / dg).reduce());
+        self.depth.set(depth);
+        res
+    }
+}
+
+impl Expr {
+    /// the limit of [self] for `x` -> `x0`
+    ///
+    /// `x0` can be oo or -oo, the direction is then ignored. The limit is computed from the
+    /// leading term of the series at `x0`, expressions without a series are handled by taking
+    /// the limit of their arguments, where exponentials dominate powers and logarithms.
+    /// Bounded factors vanish against factors that go to zero, e.g sin(x) / x at oo, and
+    /// other products 0 * oo are resolved with l'hopital's rule, e.g ln(ln(x)) / ln(x) at oo.
+    ///
+    /// growth classes are not compared in general (gruntz algorithm), so e.g
+    /// exp(ln(x)^2) / x^10 and exp(exp(x) + exp(-x)) - exp(exp(x)) at oo return [None].
+    /// Returns [None] if the limit does not exist or can't be determined
+    pub fn limit(&self, x: &Expr, x0: &Expr, dir: Dir) -> Option<Expr> {
+        if !x.is_var() {
+            return None;
+        }
+        let t = fresh_var(&[self.clone(), x0.clone()], "t");
+        let sub = match x0.atom() {
+            Atom::Infinity(Infinity::Pos) => Expr::pow(&t, Expr::min_one()),
+            Atom::Infinity(Infinity::Neg) => Expr::min_one() / &t,
+            Atom::Infinity(Infinity::Complex) | Atom::Undef => return None,
+            _ if !x0.free_of(x) => return None,
+            _ => match dir {
+                Dir::Right => x0 + &t,
+                Dir::Left => x0 - &t,
+                Dir::Both => {
+                    let right = self.limit(x, x0, Dir::Right)?;
+                    let left = self.limit(x, x0, Dir::Left)?;
+                    return (left == right).then_some(right);
+                }
+            },
+        };
+        let lim = Limit {
+            ln_t: Expr::ln(&t),
+            t,
+            depth: Cell::new(0),
+        };
+        lim.lim(&self.substitude(x, &sub))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use assert_eq as eq;
+    use calcurs_macros::expr as e;
+
+    fn lim(e: Expr, x0: Expr, dir: Dir) -> Option<Expr> {
+        e.limit(&e!(x), &x0, dir)
+    }
+
+    #[test]
+    fn indeterminate() {
+        eq!(lim(e!(sin(x) / x), e!(0), Dir::Both), Some(e!(1)));
+        eq!(
+            lim(e!((1 - cos(x)) / x ^ 2), e!(0), Dir::Both),
+            Some(e!(1 / 2))
+        );
+        eq!(
+            lim(e!((x ^ 2 - 1) / (x - 1)), e!(1), Dir::Both),
+            Some(e!(2))
+        );
+        eq!(lim(e!((1 + 1 / x) ^ x), e!(oo), Dir::Both), Some(Expr::e()));
+        eq!(
+            lim(e!((1 + a / x) ^ x), e!(oo), Dir::Both),
+            Some(Expr::exp(e!(a)))
+        );
+        eq!(lim(e!(x * ln(x)), e!(0), Dir::Right), Some(e!(0)));
+        eq!(lim(e!(x ^ x), e!(0), Dir::Right), Some(e!(1)));
+        eq!(
+            lim(e!(sqrt(x ^ 2 + x) - x), e!(oo), Dir::Both),
+            Some(e!(1 / 2))
+        );
+        eq!(
+            lim(e!((3 * x ^ 2 + 1) / (2 * x ^ 2 - x)), e!(oo), Dir::Both),
+            Some(e!(3 / 2))
+        );
+        eq!(lim(e!(x ^ 2), e!(3), Dir::Both), Some(e!(9)));
+        // bounded times zero
+        eq!(lim(e!(sin(x) / x), e!(oo), Dir::Both), Some(e!(0)));
+        eq!(lim(e!(x * sin(1 / x)), e!(0), Dir::Both), Some(e!(0)));
+    }
+
+    #[test]
+    fn infinite() {
+        eq!(lim(e!(1 / x), e!(0), Dir::Right), Some(e!(oo)));
+        eq!(lim(e!(1 / x), e!(0), Dir::Left), Some(e!(-oo)));
+        eq!(lim(e!(1 / x ^ 2), e!(0), Dir::Both), Some(e!(oo)));
+        eq!(lim(e!(ln(x)), e!(0), Dir::Right), Some(e!(-oo)));
+        eq!(lim(e!(x / ln(x)), e!(oo), Dir::Both), Some(e!(oo)));
+        eq!(lim(e!(ln(x) / x), e!(oo), Dir::Both), Some(e!(0)));
+        eq!(lim(e!(tan(x)), e!(pi / 2), Dir::Left), Some(e!(oo)));
+        eq!(lim(e!(arc_tan(x)), e!(-oo), Dir::Both), Some(e!(-pi / 2)));
+        // growth of iterated logarithms
+        eq!(lim(e!(ln(ln(x)) / ln(x)), e!(oo), Dir::Both), Some(e!(0)));
+        eq!(lim(e!(ln(x) / ln(ln(x))), e!(oo), Dir::Both), Some(e!(oo)));
+    }
+
+    #[test]
+    fn exponential() {
+        eq!(lim(e!(exp(x) / x ^ 3), e!(oo), Dir::Both), Some(e!(oo)));
+        eq!(lim(e!(x ^ 5 * exp(-x)), e!(oo), Dir::Both), Some(e!(0)));
+        eq!(lim(e!(exp(x) - x), e!(oo), Dir::Both), Some(e!(oo)));
+        eq!(
+            lim(e!(exp(x) - exp(2 * x)), e!(oo), Dir::Both),
+            Some(e!(-oo))
+        );
+        eq!(lim(e!(exp(1 / x)), e!(0), Dir::Right), Some(e!(oo)));
+        eq!(lim(e!(exp(1 / x)), e!(0), Dir::Left), Some(e!(0)));
+        eq!(
+            lim(e!(exp(-1 / x ^ 2) / x ^ 10), e!(0), Dir::Both),
+            Some(e!(0))
+        );
+    }
+
+    #[test]
+    fn no_limit() {
+        eq!(lim(e!(1 / x), e!(0), Dir::Both), None);
+        eq!(lim(e!(sin(1 / x)), e!(0), Dir::Both), None);
+        eq!(lim(e!(sin(x)), e!(oo), Dir::Both), None);
+    }
+}
@@ -0,0 +1,851 @@
+use std::{fmt, ops};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    atom::{Atom, Expr, Sum, SymbolicExpr},
+    polynomial::RatPoly,
+    rational::{Int, Rational},
+    solve,
+    sym_fmt::{self, FmtAtom},
+};
+
+/// a dense matrix of expressions, stored row by row
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+#[serde(try_from = "Vec<Vec<Expr>>", into = "Vec<Vec<Expr>>")]
+pub struct Matrix {
+    rows: usize,
+    cols: usize,
+    data: Vec<Expr>,
+}
+
+fn is_zero(e: &Expr) -> bool {
+    e.expand().reduce().is_zero()
+}
+
+/// lhs / rhs where rhs is known to divide lhs
+fn exact_div(lhs: &Expr, rhs: &Expr) -> Expr {
+    let lhs = lhs.expand().reduce();
+    if rhs.is_one() {
+        return lhs;
+    }
+    Expr::cancel_poly(&lhs, rhs)
+        .unwrap_or_else(|| lhs / rhs)
+        .expand()
+        .reduce()
+}
+
+fn terms(e: &Expr) -> &[Expr] {
+    match e.atom() {
+        Atom::Sum(Sum { args }) => args,
+        _ => std::slice::from_ref(e),
+    }
+}
+
+/// the gcd of the integer coefficients of the terms of `e`, one if a coefficient is a fraction
+fn int_content(e: &Expr) -> Int {
+    terms(e).iter().fold(Int::ZERO, |g, t| {
+        let c = match t.atom() {
+            Atom::Rational(r) => r.clone(),
+            _ => t.rational_coeff().unwrap_or(Rational::ONE),
+        };
+        match c.is_int() {
+            true => g.gcd(&c.numer()),
+            false => Int::ONE,
+        }
+    })
+}
+
+/// bring an entry into the form numer / denom, where numer and denom are expanded
+/// and have no common polynomial or integer factor
+pub(crate) fn simplify(e: &Expr) -> Expr {
+    let e = e.reduce().rationalize();
+    let mut numer = e.numerator().expand().reduce();
+    let mut denom = e.denominator().expand().reduce();
+    if denom.is_one() {
+        return numer;
+    }
+    // e.g (2k + 2*2^(1/2))/2, which cancel_poly doesn't see as a polynomial
+    let g = int_content(&numer).gcd(&int_content(&denom));
+    if g > Int::ONE {
+        let div = |e: &Expr| {
+            terms(e)
+                .iter()
+                .fold(Expr::zero(), |sum, t| sum + t / Expr::from(g.clone()))
+                .reduce()
+        };
+        (numer, denom) = (div(&numer), div(&denom));
+        if denom.is_one() {
+            return numer;
+        }
+    }
+    Expr::cancel_poly(&numer, &denom)
+        .unwrap_or_else(|| numer / denom)
+        .reduce()
+}
+
+impl Matrix {
+    /// a `rows` x `cols` matrix from its entries in row-major order
+    pub fn new(rows: usize, cols: usize, data: Vec<Expr>) -> Self {
+        assert_eq!(rows * cols, data.len(), "matrix dimensions don't match");
+        Self { rows, cols, data }
+    }
+
+    /// returns [None] if the rows don't have the same length
+    pub fn from_rows(rows: Vec<Vec<Expr>>) -> Option<Self> {
+        let n_rows = rows.len();
+        let n_cols = rows.first().map_or(0, Vec::len);
+        if rows.iter().any(|r| r.len() != n_cols) {
+            return None;
+        }
+        Some(Self::new(
+            n_rows,
+            n_cols,
+            rows.into_iter().flatten().collect(),
+        ))
+    }
+
+    /// a column vector
+    pub fn column(entries: Vec<Expr>) -> Self {
+        Self::new(entries.len(), 1, entries)
+    }
+
+    pub fn zeros(rows: usize, cols: usize) -> Self {
+        Self::new(rows, cols, vec![Expr::zero(); rows * cols])
+    }
+
+    pub fn identity(n: usize) -> Self {
+        let mut id = Self::zeros(n, n);
+        (0..n).for_each(|i| id[(i, i)] = Expr::one());
+        id
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.rows == self.cols
+    }
+
+    pub fn row(&self, i: usize) -> &[Expr] {
+        &self.data[i * self.cols..(i + 1) * self.cols]
+    }
+
+    pub fn iter_rows(&self) -> impl Iterator<Item = &[Expr]> {
+        (0..self.rows).map(|i| self.row(i))
+    }
+
+    pub fn map(&self, f: impl FnMut(&Expr) -> Expr) -> Self {
+        Self::new(self.rows, self.cols, self.data.iter().map(f).collect())
+    }
+
+    pub fn scale(&self, c: &Expr) -> Self {
+        self.map(|e| simplify(&(c * e)))
+    }
+
+    pub fn transpose(&self) -> Self {
+        let data = (0..self.cols)
+            .flat_map(|j| (0..self.rows).map(move |i| (i, j)))
+            .map(|idx| self[idx].clone())
+            .collect();
+        Self::new(self.cols, self.rows, data)
+    }
+
+    /// returns [None] if the matrix is not square
+    pub fn trace(&self) -> Option<Expr> {
+        if !self.is_square() {
+            return None;
+        }
+        let tr = (0..self.rows).fold(Expr::zero(), |sum, i| sum + &self[(i, i)]);
+        Some(simplify(&tr))
+    }
+
+    fn swap_rows(&mut self, i: usize, j: usize) {
+        if i != j {
+            for k in 0..self.cols {
+                self.data.swap(i * self.cols + k, j * self.cols + k);
+            }
+        }
+    }
+
+    /// multiply every row by the product of the denominators of its entries,
+    /// returns the product of all factors
+    fn clear_denominators(&mut self) -> Expr {
+        let mut scale = Expr::one();
+        for i in 0..self.rows {
+            let mut denoms: Vec<Expr> = vec![];
+            for j in 0..self.cols {
+                let e = simplify(&self[(i, j)]);
+                let d = e.denominator();
+                if !d.is_one() && !denoms.contains(&d) {
+                    denoms.push(d);
+                }
+                self[(i, j)] = e;
+            }
+            let lcm = denoms.into_iter().fold(Expr::one(), Expr::mul);
+            if !lcm.is_one() {
+                for j in 0..self.cols {
+                    self[(i, j)] = simplify(&(&self[(i, j)] * &lcm));
+                }
+                scale *= lcm;
+            }
+        }
+        scale
+    }
+
+    /// fraction-free gauss-jordan elimination (Bareiss)
+    ///
+    /// afterwards every pivot equals the last pivot `d` and the other entries of the
+    /// pivot columns are zero. Every entry stays a minor of the original matrix, so
+    /// the divisions by the previous pivot are exact. Returns the pivot columns, `d` and
+    /// whether an odd number of rows was swapped
+    fn bareiss(&mut self) -> (Vec<usize>, Expr, bool) {
+        if let Some(res) = self.bareiss_poly() {
+            return res;
+        }
+        let mut pivots = vec![];
+        let mut prev = Expr::one();
+        let mut odd_swaps = false;
+
+        for c in 0..self.cols {
+            let r = pivots.len();
+            let Some(p) = (r..self.rows).find(|&i| !is_zero(&self[(i, c)])) else {
+                continue;
+            };
+            if p != r {
+                self.swap_rows(r, p);
+                odd_swaps = !odd_swaps;
+            }
+            let pivot = self[(r, c)].clone();
+            for i in (0..self.rows).filter(|&i| i != r) {
+                let factor = self[(i, c)].clone();
+                for j in (0..self.cols).filter(|&j| j != c) {
+                    let minor = &pivot * &self[(i, j)] - &factor * &self[(r, j)];
+                    self[(i, j)] = exact_div(&minor, &prev);
+                }
+                self[(i, c)] = Expr::zero();
+            }
+            prev = pivot;
+            pivots.push(c);
+        }
+        (pivots, prev, odd_swaps)
+    }
+
+    /// [Matrix::bareiss] for polynomial entries, which are converted to [RatPoly] once, so
+    /// the exact divisions don't have to cancel a gcd
+    ///
+    /// returns [None] if an entry is not a polynomial in plain variables. Radicals or
+    /// functions as variables would lose their relations, e.g 2^(1/2) * 2^(1/2) = 2
+    fn bareiss_poly(&mut self) -> Option<(Vec<usize>, Expr, bool)> {
+        let mut vars: Vec<_> = self.data.iter().flat_map(Expr::variables).collect();
+        vars.sort();
+        vars.dedup();
+        if !vars.iter().all(|v| v.is_var()) {
+            return None;
+        }
+        let mut data = self
+            .data
+            .iter()
+            .map(|e| RatPoly::from_expr(e, &vars))
+            .collect::<Option<Vec<_>>>()?;
+
+        let idx = |i: usize, j: usize| i * self.cols + j;
+        let mut pivots = vec![];
+        let mut prev = RatPoly::constant(vars.len(), Rational::ONE);
+        let mut odd_swaps = false;
+
+        for c in 0..self.cols {
+            let r = pivots.len();
+            let Some(p) = (r..self.rows).find(|&i| !data[idx(i, c)].is_zero()) else {
+                continue;
+            };
+            if p != r {
+                (0..self.cols).for_each(|k| data.swap(idx(r, k), idx(p, k)));
+                odd_swaps = !odd_swaps;
+            }
+            let pivot = data[idx(r, c)].clone();
+            for i in (0..self.rows).filter(|&i| i != r) {
+                let factor = data[idx(i, c)].clone();
+                for j in (0..self.cols).filter(|&j| j != c) {
+                    let minor = pivot
+                        .mul(&data[idx(i, j)])
+                        .sub(&factor.mul(&data[idx(r, j)]));
+                    data[idx(i, j)] = minor.div_exact(&prev)?;
+                }
+                data[idx(i, c)] = RatPoly::zero(vars.len());
+            }
+            prev = pivot;
+            pivots.push(c);
+        }
+
+        self.data = data.iter().map(|p| p.to_expr(&vars)).collect();
+        Some((pivots, prev.to_expr(&vars), odd_swaps))
+    }
+
+    /// determinant with fraction-free gaussian elimination (Bareiss)
+    ///
+    /// returns [None] if the matrix is not square
+    pub fn det(&self) -> Option<Expr> {
+        if !self.is_square() {
+            return None;
+        }
+        let mut m = self.clone();
+        let scale = m.clear_denominators();
+        let (pivots, d, odd_swaps) = m.bareiss();
+        Some(match (pivots.len() == self.rows, odd_swaps) {
+            (false, _) => Expr::zero(),
+            (true, false) => simplify(&(d / scale)),
+            (true, true) => simplify(&(Expr::min_one() * d / scale)),
+        })
+    }
+
+    pub fn rank(&self) -> usize {
+        let mut m = self.clone();
+        m.clear_denominators();
+        m.bareiss().0.len()
+    }
+
+    /// reduced row echelon form and its pivot columns
+    pub fn rref(&self) -> (Matrix, Vec<usize>) {
+        let mut m = self.clone();
+        m.clear_denominators();
+        let (pivots, d, _) = m.bareiss();
+        for r in 0..pivots.len() {
+            for j in 0..m.cols {
+                m[(r, j)] = match pivots.get(r) == Some(&j) {
+                    true => Expr::one(),
+                    false => simplify(&(&m[(r, j)] / &d)),
+                };
+            }
+        }
+        (m, pivots)
+    }
+
+    /// returns [None] if the matrix is not square or singular
+    pub fn inverse(&self) -> Option<Matrix> {
+        if !self.is_square() {
+            return None;
+        }
+        let n = self.rows;
+        let mut aug = Self::zeros(n, 2 * n);
+        for i in 0..n {
+            for j in 0..n {
+                aug[(i, j)] = self[(i, j)].clone();
+            }
+            aug[(i, n + i)] = Expr::one();
+        }
+
+        // singular if a pivot is in the right half
+        let (rref, pivots) = aug.rref();
+        if !pivots.iter().copied().eq(0..n) {
+            return None;
+        }
+        let data = (0..n).flat_map(|i| rref.row(i)[n..].to_vec()).collect();
+        Some(Self::new(n, n, data))
+    }
+
+    /// a basis of the solutions of self * v = 0 as column vectors
+    pub fn nullspace(&self) -> Vec<Matrix> {
+        let (rref, pivots) = self.rref();
+        (0..self.cols)
+            .filter(|c| !pivots.contains(c))
+            .map(|free| {
+                let mut v = vec![Expr::zero(); self.cols];
+                v[free] = Expr::one();
+                for (r, &c) in pivots.iter().enumerate() {
+                    v[c] = simplify(&(Expr::min_one() * &rref[(r, free)]));
+                }
+                Self::column(v)
+            })
+            .collect()
+    }
+
+    /// coefficients of det(x*I - self), starting with the leading one
+    ///
+    /// uses the division-free algorithm of Berkowitz: with self = \[\[a, r\], \[c, A\]\],
+    /// the coefficients are the product of the toeplitz matrix with the first column
+    /// (1, -a, -r*c, -r*A*c, -r*A^2*c, ...) and the coefficients of A
+    fn berkowitz(&self) -> Vec<Expr> {
+        let n = self.rows;
+        if n == 0 {
+            return vec![Expr::one()];
+        }
+
+        let sub = Self::new(
+            n - 1,
+            n - 1,
+            (1..n).flat_map(|i| self.row(i)[1..].to_vec()).collect(),
+        );
+        let r = &self.row(0)[1..];
+        let mut v: Vec<Expr> = (1..n).map(|i| self[(i, 0)].clone()).collect();
+
+        let neg = |e: Expr| simplify(&(Expr::min_one() * e));
+        let mut col = vec![Expr::one(), neg(self[(0, 0)].clone())];
+        for _ in 1..n {
+            let rv = r
+                .iter()
+                .zip(&v)
+                .fold(Expr::zero(), |sum, (a, b)| sum + a * b);
+            col.push(neg(rv));
+            v = (&sub * &Self::column(v)).data;
+        }
+
+        let coeffs = sub.berkowitz();
+        (0..=n)
+            .map(|i| {
+                let c = (0..n)
+                    .filter(|&j| j <= i)
+                    .fold(Expr::zero(), |sum, j| sum + &col[i - j] * &coeffs[j]);
+                simplify(&c)
+            })
+            .collect()
+    }
+
+    /// the characteristic polynomial det(x*I - self)
+    ///
+    /// returns [None] if the matrix is not square
+    pub fn charpoly(&self, x: &Expr) -> Option<Expr> {
+        if !self.is_square() {
+            return None;
+        }
+        let n = self.rows;
+        let p = self
+            .berkowitz()
+            .into_iter()
+            .enumerate()
+            .fold(Expr::zero(), |sum, (i, c)| {
+                sum + c * Expr::pow(x, Expr::from((n - i) as u64))
+            });
+        Some(p.expand().reduce())
+    }
+
+    /// the eigenvalues with their algebraic multiplicity
+    ///
+    /// diagonal entries that are roots of the characteristic polynomial are divided out
+    /// first, so e.g triangular matrices keep their entries as eigenvalues. The remaining
+    /// roots are found with [Expr::solve_poly]. Returns [None] if the matrix is not square
+    /// or the roots can't be expressed
+    pub fn eigenvals(&self) -> Option<Vec<(Expr, u32)>> {
+        let lambda = solve::fresh_var(&self.data, "λ");
+        let mut p = self.charpoly(&lambda)?;
+
+        let mut vals: Vec<(Expr, u32)> = vec![];
+        for i in 0..self.rows {
+            let d = &self[(i, i)];
+            if vals.iter().any(|(v, _)| v == d) {
+                continue;
+            }
+            let mut mult = 0;
+            while !p.free_of(&lambda) && is_zero(&p.substitude(&lambda, d)) {
+                p = Expr::poly_div(&p, &(&lambda - d), &lambda)?
+                    .0
+                    .expand()
+                    .reduce();
+                mult += 1;
+            }
+            if mult > 0 {
+                vals.push((d.clone(), mult));
+            }
+        }
+
+        for (r, m) in p.solve_poly(&lambda)? {
+            let r = simplify(&r);
+            match vals.iter_mut().find(|(v, _)| v == &r) {
+                Some((_, mult)) => *mult += m,
+                None => vals.push((r, m)),
+            }
+        }
+        Some(vals)
+    }
+
+    /// the eigenvalues with their algebraic multiplicity and a basis of their eigenspace,
+    /// the nullspace of self - λI
+    ///
+    /// returns [None] if the eigenvalues can't be computed or an eigenspace can't be found,
+    /// e.g because the zero test fails for an eigenvalue with radicals
+    pub fn eigenvects(&self) -> Option<Vec<(Expr, u32, Vec<Matrix>)>> {
+        self.eigenvals()?
+            .into_iter()
+            .map(|(val, mult)| {
+                let shifted = self - &Self::identity(self.rows).scale(&val);
+                let basis = shifted.nullspace();
+                (!basis.is_empty()).then_some((val, mult, basis))
+            })
+            .collect()
+    }
+
+    /// (P, D) with self = P * D * P^-1, where D is diagonal with the eigenvalues on its
+    /// diagonal and the columns of P are the corresponding eigenvectors
+    ///
+    /// returns [None] if the matrix is not diagonalizable or the eigenvectors can't be computed
+    pub fn diagonalize(&self) -> Option<(Matrix, Matrix)> {
+        let n = self.rows;
+        let mut vals = vec![];
+        let mut vecs = vec![];
+        for (val, mult, basis) in self.eigenvects()? {
+            if basis.len() != mult as usize {
+                return None;
+            }
+            for v in basis {
+                vals.push(val.clone());
+                vecs.push(v.data);
+            }
+        }
+
+        let p = Self::from_rows(vecs)?.transpose();
+        let mut d = Self::zeros(n, n);
+        vals.into_iter()
+            .enumerate()
+            .for_each(|(i, val)| d[(i, i)] = val);
+        Some((p, d))
+    }
+
+    pub fn fmt_ast(&self) -> FmtAtom {
+        FmtAtom::Matrix(
+            self.iter_rows()
+                .map(|r| r.iter().map(|e| e.fmt_ast()).collect())
+                .collect(),
+        )
+    }
+
+    pub fn to_latex(&self) -> String {
+        self.fmt_ast().fmt_with::<sym_fmt::LatexFmt>().to_string()
+    }
+}
+
+impl TryFrom<Vec<Vec<Expr>>> for Matrix {
+    type Error = String;
+
+    fn try_from(rows: Vec<Vec<Expr>>) -> Result<Self, Self::Error> {
+        Self::from_rows(rows).ok_or_else(|| "matrix rows of different length".into())
+    }
+}
+
+impl From<Matrix> for Vec<Vec<Expr>> {
+    fn from(m: Matrix) -> Self {
+        m.iter_rows().map(<[Expr]>::to_vec).collect()
+    }
+}
+
+impl ops::Index<(usize, usize)> for Matrix {
+    type Output = Expr;
+
+    fn index(&self, (i, j): (usize, usize)) -> &Self::Output {
+        assert!(i < self.rows && j < self.cols, "index out of bounds");
+        &self.data[i * self.cols + j]
+    }
+}
+
+impl ops::IndexMut<(usize, usize)> for Matrix {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut Self::Output {
+        assert!(i < self.rows && j < self.cols, "index out of bounds");
+        &mut self.data[i * self.cols + j]
+    }
+}
+
+impl Matrix {
+    fn zip_with(&self, rhs: &Matrix, f: impl Fn(&Expr, &Expr) -> Expr) -> Matrix {
+        assert_eq!(
+            (self.rows, self.cols),
+            (rhs.rows, rhs.cols),
+            "matrix dimensions don't match"
+        );
+        let data = self
+            .data
+            .iter()
+            .zip(&rhs.data)
+            .map(|(a, b)| simplify(&f(a, b)))
+            .collect();
+        Matrix::new(self.rows, self.cols, data)
+    }
+}
+
+impl ops::Add for &Matrix {
+    type Output = Matrix;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        self.zip_with(rhs, |a, b| a + b)
+    }
+}
+
+impl ops::Sub for &Matrix {
+    type Output = Matrix;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.zip_with(rhs, |a, b| a - b)
+    }
+}
+
+impl ops::Mul for &Matrix {
+    type Output = Matrix;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        assert_eq!(self.cols, rhs.rows, "matrix dimensions don't match");
+        let data = (0..self.rows)
+            .flat_map(|i| (0..rhs.cols).map(move |j| (i, j)))
+            .map(|(i, j)| {
+                let dot = (0..self.cols)
+                    .map(|k| &self[(i, k)] * &rhs[(k, j)])
+                    .fold(Expr::zero(), |sum, t| sum + t);
+                simplify(&dot)
+            })
+            .collect();
+        Matrix::new(self.rows, rhs.cols, data)
+    }
+}
+
+macro_rules! impl_owned_op {
+    ($($op:ident, $fn:ident);*) => {$(
+        impl ops::$op for Matrix {
+            type Output = Matrix;
+
+            fn $fn(self, rhs: Self) -> Self::Output {
+                ops::$op::$fn(&self, &rhs)
+            }
+        }
+    )*};
+}
+impl_owned_op!(Add, add; Sub, sub; Mul, mul);
+
+impl fmt::Display for Matrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.fmt_ast(), f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use assert_eq as eq;
+    use calcurs_macros::expr as e;
+
+    fn m<const R: usize, const C: usize>(rows: [[Expr; C]; R]) -> Matrix {
+        Matrix::from_rows(rows.into_iter().map(Vec::from).collect()).unwrap()
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a = m([[e!(1), e!(x)], [e!(0), e!(2)]]);
+        let b = m([[e!(y), e!(1)], [e!(1), e!(x)]]);
+        eq!(&a + &b, m([[e!(y + 1), e!(x + 1)], [e!(1), e!(x + 2)]]));
+        eq!(&a - &a, Matrix::zeros(2, 2));
+        eq!(&a * &b, m([[e!(y + x), e!(1 + x ^ 2)], [e!(2), e!(2 * x)]]));
+        eq!(&a * &Matrix::identity(2), a);
+        eq!(a.transpose(), m([[e!(1), e!(0)], [e!(x), e!(2)]]));
+        eq!(b.trace(), Some(e!(x + y)));
+        eq!(Matrix::zeros(2, 3).trace(), None);
+    }
+
+    #[test]
+    fn det() {
+        eq!(
+            m([[e!(a), e!(b)], [e!(c), e!(d)]]).det(),
+            Some(e!(a * d - b * c))
+        );
+        eq!(
+            m([
+                [e!(2), e!(0), e!(1)],
+                [e!(1), e!(3), e!(2)],
+                [e!(1), e!(1), e!(2)]
+            ])
+            .det(),
+            Some(e!(6))
+        );
+        // needs a row swap
+        eq!(m([[e!(0), e!(1)], [e!(1), e!(0)]]).det(), Some(e!(-1)));
+        eq!(m([[e!(x), e!(x)], [e!(1), e!(1)]]).det(), Some(e!(0)));
+        eq!(Matrix::zeros(2, 3).det(), None);
+        // denominators are cleared before the elimination
+        eq!(
+            m([[e!(1 / 2), e!(1 / 3)], [e!(1), e!(1)]]).det(),
+            Some(e!(1 / 6))
+        );
+        let a = m([[e!(1 / x), e!(1)], [e!(1), e!(x)]]);
+        eq!(a.det(), Some(e!(0)));
+        eq!(a.rank(), 1);
+        eq!(a.rref(), (m([[e!(1), e!(x)], [e!(0), e!(0)]]), vec![0]));
+    }
+
+    #[test]
+    fn det_symbolic() {
+        let vars = (0..16).map(|i| Expr::var(&format!("a{i}"))).collect();
+        let a = Matrix::new(4, 4, vars);
+        let start = std::time::Instant::now();
+        let det = a.det().unwrap();
+        let elapsed = start.elapsed();
+        // det(-A) = det(A) for even n, the constant term of det(x*I - A)
+        eq!(
+            Some(det),
+            a.charpoly(&e!(x))
+                .map(|p| p.substitude(&e!(x), &e!(0)).reduce())
+        );
+        assert!(elapsed.as_secs() < 5, "det took {elapsed:?}");
+    }
+
+    #[test]
+    fn inverse() {
+        let a = m([[e!(a), e!(b)], [e!(c), e!(d)]]);
+        let inv = a.inverse().unwrap();
+        eq!(&a * &inv, Matrix::identity(2));
+        eq!(
+            m([[e!(2), e!(1)], [e!(1), e!(1)]]).inverse(),
+            Some(m([[e!(1), e!(-1)], [e!(-1), e!(2)]]))
+        );
+        eq!(m([[e!(1), e!(2)], [e!(2), e!(4)]]).inverse(), None);
+        eq!(Matrix::zeros(0, 0).inverse(), Some(Matrix::zeros(0, 0)));
+        eq!(Matrix::zeros(2, 3).inverse(), None);
+    }
+
+    #[test]
+    fn rref() {
+        let a = m([
+            [e!(1), e!(2), e!(3)],
+            [e!(2), e!(4), e!(6)],
+            [e!(1), e!(0), e!(1)],
+        ]);
+        eq!(a.rank(), 2);
+        eq!(
+            a.rref(),
+            (
+                m([
+                    [e!(1), e!(0), e!(1)],
+                    [e!(0), e!(1), e!(1)],
+                    [e!(0), e!(0), e!(0)]
+                ]),
+                vec![0, 1]
+            )
+        );
+        eq!(
+            a.nullspace(),
+            vec![Matrix::column(vec![e!(-1), e!(-1), e!(1)])]
+        );
+        eq!(
+            m([[e!(a), e!(a * b)]]).rref(),
+            (m([[e!(1), e!(b)]]), vec![0])
+        );
+        eq!(Matrix::identity(3).rank(), 3);
+        eq!(Matrix::identity(3).nullspace(), vec![]);
+    }
+
+    #[test]
+    fn charpoly() {
+        let x = &e!(x);
+        eq!(
+            m([[e!(a), e!(b)], [e!(c), e!(d)]]).charpoly(x),
+            Some(e!(x ^ 2 - (a + d) * x + a * d - b * c).expand().reduce())
+        );
+        let a = m([
+            [e!(1), e!(2), e!(a)],
+            [e!(0), e!(b), e!(3)],
+            [e!(4), e!(1), e!(1)],
+        ]);
+        let char_det = (&Matrix::identity(3).scale(x) - &a).det().unwrap();
+        eq!(a.charpoly(x), Some(char_det));
+        eq!(Matrix::zeros(2, 3).charpoly(x), None);
+    }
+
+    #[test]
+    fn eigen() {
+        let a = m([[e!(2), e!(1)], [e!(1), e!(2)]]);
+        eq!(a.eigenvals(), Some(vec![(e!(3), 1), (e!(1), 1)]));
+        eq!(
+            a.eigenvects(),
+            Some(vec![
+                (e!(3), 1, vec![Matrix::column(vec![e!(1), e!(1)])]),
+                (e!(1), 1, vec![Matrix::column(vec![e!(-1), e!(1)])]),
+            ])
+        );
+        eq!(
+            m([[e!(a), e!(1)], [e!(0), e!(b)]]).eigenvals(),
+            Some(vec![(e!(a), 1), (e!(b), 1)])
+        );
+        eq!(
+            Matrix::identity(3).eigenvects(),
+            Some(vec![(
+                e!(1),
+                3,
+                (0..3)
+                    .map(|i| Matrix::column((0..3).map(|j| Expr::from((i == j) as u32)).collect()))
+                    .collect()
+            )])
+        );
+
+        let tri = m([
+            [e!(k), e!(1), e!(0)],
+            [e!(1), e!(k), e!(1)],
+            [e!(0), e!(1), e!(k)],
+        ]);
+        eq!(
+            tri.eigenvals(),
+            Some(vec![
+                (e!(k), 1),
+                (e!(k + 2 ^ (1 / 2)).reduce(), 1),
+                (e!(k - 2 ^ (1 / 2)).reduce(), 1),
+            ])
+        );
+
+        // damped oscillator x'' + c x' + k x = 0
+        let lambda = &e!(λ);
+        let osc = m([[e!(0), e!(1)], [e!(-k), e!(-c)]]);
+        let p = osc.charpoly(lambda).unwrap();
+        eq!(p, e!(λ ^ 2 + c * λ + k).expand().reduce());
+        let vals = osc.eigenvals().unwrap();
+        eq!(vals.len(), 2);
+        for (v, _) in vals {
+            assert!(is_zero(&p.substitude(lambda, &v)), "{v}");
+        }
+    }
+
+    #[test]
+    fn diagonalize() {
+        let check = |a: Matrix| {
+            let (p, d) = a.diagonalize().unwrap();
+            eq!(&(&p * &d) * &p.inverse().unwrap(), a);
+        };
+        check(m([[e!(2), e!(1)], [e!(1), e!(2)]]));
+        check(m([[e!(0), e!(-1)], [e!(1), e!(0)]]));
+        check(m([[e!(a), e!(1)], [e!(0), e!(b)]]));
+        check(m([[e!(0), e!(1)], [e!(-k), e!(-c)]]));
+        check(m([
+            [e!(1), e!(0), e!(0)],
+            [e!(0), e!(2), e!(1)],
+            [e!(0), e!(1), e!(2)],
+        ]));
+        // a jordan block is not diagonalizable
+        let jordan = m([[e!(1), e!(1)], [e!(0), e!(1)]]);
+        eq!(
+            jordan.eigenvects(),
+            Some(vec![(e!(1), 2, vec![Matrix::column(vec![e!(1), e!(0)])])])
+        );
+        eq!(jordan.diagonalize(), None);
+    }
+
+    #[test]
+    fn serde() {
+        let a = m([[e!(1), e!(x ^ 2)], [e!(sin(y)), e!(2 / 3)]]);
+        let json = serde_json::to_string(&a).unwrap();
+        eq!(serde_json::from_str::<Matrix>(&json).unwrap(), a);
+        let ragged = serde_json::to_string(&vec![vec![e!(1)], vec![]]).unwrap();
+        assert!(serde_json::from_str::<Matrix>(&ragged).is_err());
+    }
+
+    #[test]
+    fn fmt() {
+        let a = m([[e!(1), e!(x ^ 2)], [e!(y + 1), e!(2)]]);
+        eq!(a.to_string(), "⎡1      x^2⎤\n⎣y + 1  2  ⎦");
+        eq!(m([[e!(1), e!(x)]]).to_string(), "[1  x]");
+        eq!(
+            m([[e!(1), e!(0)], [e!(0), e!(1)], [e!(a), e!(b)]]).to_string(),
+            "⎡1  0⎤\n⎢0  1⎥\n⎣a  b⎦"
+        );
+        eq!(
+            a.to_latex(),
+            r"\begin{bmatrix}1 & x^{2} \\ y + 1 & 2\end{bmatrix}"
+        );
+    }
+}
@@ -0,0 +1,908 @@
+use crate::{
+    atom::{Expr, RelOp},
+    rational::Int,
+};
+use std::{fmt, iter::Peekable, ops, str::CharIndices, str::FromStr};
+
+/// byte range into the parsed source
+pub type Span = ops::Range<usize>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    UnexpectedChar(char),
+    UnexpectedToken(String),
+    UnexpectedEof,
+    ExpectedOperand,
+    ExpectedUnaryOp,
+    InvalidNumber(String),
+    UnknownFunction(String),
+    UnknownCommand(String),
+    InvalidLogBase,
+    WrongArgCount {
+        func: String,
+        expected: usize,
+        found: usize,
+    },
+    PlaceHolder,
+    Unsupported(&'static str),
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use ParseErrorKind as K;
+        match self {
+            K::UnexpectedChar(c) => write!(f, "unexpected character '{c}'"),
+            K::UnexpectedToken(t) => write!(f, "unexpected token '{t}'"),
+            K::UnexpectedEof => write!(f, "unexpected end of input"),
+            K::ExpectedOperand => write!(f, "bad expression"),
+            K::ExpectedUnaryOp => write!(f, "expected unary operator"),
+            K::InvalidNumber(n) => write!(f, "invalid number '{n}'"),
+            K::UnknownFunction(func) => write!(f, "unknown function '{func}'"),
+            K::UnknownCommand(cmd) => write!(f, "unknown command '\\{cmd}'"),
+            K::InvalidLogBase => write!(f, "logarithm base must be a real constant"),
+            K::WrongArgCount {
+                func,
+                expected,
+                found,
+            } => write!(
+                f,
+                "function '{func}' takes {expected} argument(s), found {found}"
+            ),
+            K::PlaceHolder => write!(
+                f,
+                "placeholder not allowed in expressions, only in patterns"
+            ),
+            K::Unsupported(what) => write!(f, "{what} are not supported"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    pub span: Span,
+}
+
+impl ParseError {
+    pub(crate) fn new(kind: ParseErrorKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {}..{}", self.kind, self.span.start, self.span.end)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+type Result<T> = std::result::Result<T, ParseError>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OpKind {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+impl OpKind {
+    fn precedence(&self) -> i32 {
+        match self {
+            OpKind::Or => 1,
+            OpKind::And => 2,
+            OpKind::Eq | OpKind::Ne | OpKind::Lt | OpKind::Le | OpKind::Gt | OpKind::Ge => 3,
+            OpKind::Add | OpKind::Sub => 4,
+            OpKind::Mul | OpKind::Div => 5,
+            OpKind::Pow => 6,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Int(String),
+    Float(String),
+    Ident(String),
+    Op(OpKind),
+    LParen,
+    RParen,
+    Comma,
+    Question,
+    Not,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Int(s) | Token::Float(s) | Token::Ident(s) => write!(f, "{s}"),
+            Token::Op(OpKind::Add) => write!(f, "+"),
+            Token::Op(OpKind::Sub) => write!(f, "-"),
+            Token::Op(OpKind::Mul) => write!(f, "*"),
+            Token::Op(OpKind::Div) => write!(f, "/"),
+            Token::Op(OpKind::Pow) => write!(f, "^"),
+            Token::Op(OpKind::Eq) => write!(f, "=="),
+            Token::Op(OpKind::Ne) => write!(f, "!="),
+            Token::Op(OpKind::Lt) => write!(f, "<"),
+            Token::Op(OpKind::Le) => write!(f, "<="),
+            Token::Op(OpKind::Gt) => write!(f, ">"),
+            Token::Op(OpKind::Ge) => write!(f, ">="),
+            Token::Op(OpKind::And) => write!(f, "&&"),
+            Token::Op(OpKind::Or) => write!(f, "||"),
+            Token::LParen => write!(f, "("),
+            Token::RParen => write!(f, ")"),
+            Token::Comma => write!(f, ","),
+            Token::Question => write!(f, "?"),
+            Token::Not => write!(f, "!"),
+        }
+    }
+}
+
+struct Lexer<'a> {
+    src: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(src: &'a str) -> Self {
+        Self {
+            src,
+            chars: src.char_indices().peekable(),
+        }
+    }
+
+    fn pos(&mut self) -> usize {
+        self.chars.peek().map(|(i, _)| *i).unwrap_or(self.src.len())
+    }
+
+    fn eat_while(&mut self, f: impl Fn(char) -> bool) {
+        while self.chars.next_if(|(_, c)| f(*c)).is_some() {}
+    }
+
+    fn number(&mut self, start: usize) -> Token {
+        self.eat_while(|c| c.is_ascii_digit());
+        let mut is_float = false;
+
+        if self.chars.next_if(|(_, c)| *c == '.').is_some() {
+            is_float = true;
+            self.eat_while(|c| c.is_ascii_digit());
+        }
+
+        // only treat e as an exponent if digits follow, e.g. 1e5 or 1e-5
+        let rest = &self.src[self.pos()..];
+        let mut exp = rest.chars();
+        if matches!(exp.next(), Some('e' | 'E')) {
+            let digit_at = match exp.next() {
+                Some('+' | '-') => 2,
+                _ => 1,
+            };
+            if rest[digit_at..].starts_with(|c: char| c.is_ascii_digit()) {
+                is_float = true;
+                for _ in 0..digit_at {
+                    self.chars.next();
+                }
+                self.eat_while(|c| c.is_ascii_digit());
+            }
+        }
+
+        let lit = self.src[start..self.pos()].to_string();
+        match is_float {
+            true => Token::Float(lit),
+            false => Token::Int(lit),
+        }
+    }
+
+    fn tokenize(mut self) -> Result<Vec<(Token, Span)>> {
+        let mut tokens = vec![];
+
+        while let Some(&(start, c)) = self.chars.peek() {
+            if c.is_whitespace() {
+                self.chars.next();
+                continue;
+            }
+
+            let tok = if c.is_ascii_digit() {
+                self.number(start)
+            } else if c.is_alphabetic() || c == '_' {
+                self.eat_while(|c| c.is_alphanumeric() || c == '_');
+                Token::Ident(self.src[start..self.pos()].to_string())
+            } else {
+                self.chars.next();
+                // multi character operators first, e.g <= would be lexed as <
+                let next = self.chars.peek().map(|(_, c)| *c);
+                let two = match (c, next) {
+                    ('=', Some('=')) => Some(Token::Op(OpKind::Eq)),
+                    ('!', Some('=')) => Some(Token::Op(OpKind::Ne)),
+                    ('<', Some('=')) => Some(Token::Op(OpKind::Le)),
+                    ('>', Some('=')) => Some(Token::Op(OpKind::Ge)),
+                    ('&', Some('&')) => Some(Token::Op(OpKind::And)),
+                    ('|', Some('|')) => Some(Token::Op(OpKind::Or)),
+                    _ => None,
+                };
+                if let Some(tok) = two {
+                    self.chars.next();
+                    tokens.push((tok, start..self.pos()));
+                    continue;
+                }
+
+                match c {
+                    '+' => Token::Op(OpKind::Add),
+                    '-' => Token::Op(OpKind::Sub),
+                    '*' => Token::Op(OpKind::Mul),
+                    '/' => Token::Op(OpKind::Div),
+                    '^' => Token::Op(OpKind::Pow),
+                    '<' => Token::Op(OpKind::Lt),
+                    '>' => Token::Op(OpKind::Gt),
+                    '!' => Token::Not,
+                    '(' => Token::LParen,
+                    ')' => Token::RParen,
+                    ',' => Token::Comma,
+                    '?' => Token::Question,
+                    _ => {
+                        let span = start..start + c.len_utf8();
+                        return Err(ParseError::new(ParseErrorKind::UnexpectedChar(c), span));
+                    }
+                }
+            };
+
+            tokens.push((tok, start..self.pos()));
+        }
+
+        Ok(tokens)
+    }
+}
+
+/// intermediate tree, mirrors the one built by [calcurs_macros::expr]
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Num(Int),
+    Float(f64),
+    Symbol(String),
+    Binary(OpKind, Box<Node>, Box<Node>),
+    Func(String, Span, Vec<Node>),
+    Not(Box<Node>),
+    Bool(bool),
+    Infinity { sign: i8 },
+    Undef,
+    PlaceHolder(Span),
+}
+
+struct Parser {
+    tokens: Vec<(Token, Span)>,
+    pos: usize,
+    src_len: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(t, _)| t)
+    }
+
+    fn span(&self) -> Span {
+        match self.tokens.get(self.pos) {
+            Some((_, s)) => s.clone(),
+            None => self.src_len..self.src_len,
+        }
+    }
+
+    fn next(&mut self) -> Option<(Token, Span)> {
+        let t = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        t
+    }
+
+    fn unexpected(&self) -> ParseError {
+        match self.peek() {
+            Some(t) => ParseError::new(ParseErrorKind::UnexpectedToken(t.to_string()), self.span()),
+            None => ParseError::new(ParseErrorKind::UnexpectedEof, self.span()),
+        }
+    }
+
+    fn expect(&mut self, tok: Token) -> Result<()> {
+        if self.peek() == Some(&tok) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    fn parse_expr(&mut self) -> Result<Node> {
+        self.parse_bin_expr(1)
+    }
+
+    fn parse_operand(&mut self) -> Result<Node> {
+        let span = self.span();
+        match self.next() {
+            Some((Token::Ident(id), span)) => {
+                if id == "oo" {
+                    Ok(Node::Infinity { sign: 1 })
+                } else if id == "zoo" {
+                    Ok(Node::Infinity { sign: 0 })
+                } else if id == "undef" {
+                    Ok(Node::Undef)
+                } else if id == "true" || id == "false" {
+                    Ok(Node::Bool(id == "true"))
+                } else if self.peek() == Some(&Token::LParen) {
+                    self.pos += 1;
+                    let mut args = vec![];
+                    while self.peek() != Some(&Token::RParen) {
+                        args.push(self.parse_expr()?);
+                        if self.peek() != Some(&Token::Comma) {
+                            break;
+                        }
+                        self.pos += 1;
+                    }
+                    self.expect(Token::RParen)?;
+                    Ok(Node::Func(id, span, args))
+                } else {
+                    Ok(Node::Symbol(id))
+                }
+            }
+            Some((Token::Int(lit), span)) => match Int::from_str(&lit) {
+                Ok(n) => Ok(Node::Num(n)),
+                Err(_) => Err(ParseError::new(ParseErrorKind::InvalidNumber(lit), span)),
+            },
+            Some((Token::Float(lit), span)) => match f64::from_str(&lit) {
+                Ok(f) => Ok(Node::Float(f)),
+                Err(_) => Err(ParseError::new(ParseErrorKind::InvalidNumber(lit), span)),
+            },
+            Some((Token::LParen, _)) => {
+                let e = self.parse_expr()?;
+                self.expect(Token::RParen)?;
+                Ok(e)
+            }
+            _ => Err(ParseError::new(ParseErrorKind::ExpectedOperand, span)),
+        }
+    }
+
+    fn parse_unary_expr(&mut self) -> Result<Node> {
+        match self.peek() {
+            Some(Token::Op(OpKind::Sub)) => {
+                self.pos += 1;
+                let operand = self.parse_operand()?;
+                Ok(if let Node::Num(n) = operand {
+                    Node::Num(-n)
+                } else {
+                    let min_one = Node::Num(Int::MINUS_ONE);
+                    Node::Binary(OpKind::Mul, min_one.into(), operand.into())
+                })
+            }
+            Some(Token::Op(_)) => Err(ParseError::new(
+                ParseErrorKind::ExpectedUnaryOp,
+                self.span(),
+            )),
+            Some(Token::Not) => {
+                self.pos += 1;
+                Ok(Node::Not(self.parse_unary_expr()?.into()))
+            }
+            Some(Token::Question) => {
+                let start = self.span().start;
+                self.pos += 1;
+                match self.next() {
+                    Some((Token::Ident(_), s)) => Ok(Node::PlaceHolder(start..s.end)),
+                    _ => {
+                        self.pos -= 1;
+                        Err(self.unexpected())
+                    }
+                }
+            }
+            _ => self.parse_operand(),
+        }
+    }
+
+    fn parse_bin_expr(&mut self, prec_in: i32) -> Result<Node> {
+        let mut expr = self.parse_unary_expr()?;
+
+        while let Some(&Token::Op(op)) = self.peek() {
+            if op.precedence() < prec_in {
+                break;
+            }
+            self.pos += 1;
+            let rhs = self.parse_bin_expr(op.precedence() + 1)?;
+            expr = Node::Binary(op, expr.into(), rhs.into());
+        }
+
+        Ok(expr)
+    }
+}
+
+fn lower_func(name: &str, span: Span, args: Vec<Expr>) -> Result<Expr> {
+    // the base of log must be a real constant, e.g log(2, x)
+    if name == "log" {
+        let [base, x] = <[Expr; 2]>::try_from(args).map_err(|args| {
+            let kind = ParseErrorKind::WrongArgCount {
+                func: name.to_string(),
+                expected: 2,
+                found: args.len(),
+            };
+            ParseError::new(kind, span.clone())
+        })?;
+        return match base.try_as_real() {
+            Some(base) => Ok(Expr::log(base, x)),
+            None => Err(ParseError::new(ParseErrorKind::InvalidLogBase, span)),
+        };
+    }
+
+    let unary: Option<fn(Expr) -> Expr> = match name {
+        "sin" => Some(Expr::sin),
+        "cos" => Some(Expr::cos),
+        "tan" => Some(Expr::tan),
+        "sec" => Some(Expr::sec),
+        "cot" => Some(Expr::cot),
+        "csc" => Some(Expr::csc),
+        "arc_sin" => Some(Expr::arc_sin),
+        "arc_cos" => Some(Expr::arc_cos),
+        "arc_tan" => Some(Expr::arc_tan),
+        "arc_sec" => Some(Expr::arc_sec),
+        "arc_cot" => Some(Expr::arc_cot),
+        "arc_csc" => Some(Expr::arc_csc),
+        "exp" => Some(Expr::exp),
+        "ln" => Some(Expr::ln),
+        "log10" => Some(Expr::log10),
+        "sqrt" => Some(Expr::sqrt),
+        "re" => Some(Expr::re),
+        "im" => Some(Expr::im),
+        "conj" => Some(Expr::conj),
+        "arg" => Some(Expr::arg),
+        "isprime" => Some(Expr::isprime),
+        "nextprime" => Some(Expr::nextprime),
+        "totient" => Some(Expr::totient),
+        "divisors" => Some(Expr::divisors),
+        "order" => Some(Expr::order),
+        "not" => Some(Expr::not),
+        _ => None,
+    };
+    let binary: Option<fn(Expr, Expr) -> Expr> = match name {
+        "add" => Some(Expr::add),
+        "sub" => Some(Expr::sub),
+        "mul" => Some(Expr::mul),
+        "div" => Some(Expr::div),
+        "pow" => Some(Expr::pow),
+        "add_raw" => Some(Expr::add_raw),
+        "mul_raw" => Some(Expr::mul_raw),
+        "div_raw" => Some(Expr::div_raw),
+        "pow_raw" => Some(Expr::pow_raw),
+        "and" => Some(Expr::and),
+        "or" => Some(Expr::or),
+        "modulo" => Some(Expr::modulo),
+        "modinv" => Some(Expr::modinv),
+        "root_of" => Some(Expr::root_of),
+        _ => None,
+    };
+    let ternary: Option<fn(Expr, Expr, Expr) -> Expr> = match name {
+        "powmod" => Some(Expr::powmod),
+        _ => None,
+    };
+
+    let expected = match (unary, binary, ternary) {
+        (Some(_), _, _) => 1,
+        (_, Some(_), _) => 2,
+        (_, _, Some(_)) => 3,
+        _ => {
+            return Err(ParseError::new(
+                ParseErrorKind::UnknownFunction(name.to_string()),
+                span,
+            ))
+        }
+    };
+
+    if args.len() != expected {
+        let kind = ParseErrorKind::WrongArgCount {
+            func: name.to_string(),
+            expected,
+            found: args.len(),
+        };
+        return Err(ParseError::new(kind, span));
+    }
+
+    let mut args = args.into_iter();
+    let mut arg = || args.next().unwrap();
+    Ok(match (unary, binary, ternary) {
+        (Some(f), _, _) => f(arg()),
+        (_, Some(f), _) => f(arg(), arg()),
+        (_, _, Some(f)) => f(arg(), arg(), arg()),
+        _ => unreachable!(),
+    })
+}
+
+fn lower(n: Node) -> Result<Expr> {
+    Ok(match n {
+        Node::Num(n) => Expr::rational(n),
+        Node::Symbol(s) if s == "pi" => Expr::pi(),
+        Node::Symbol(s) if s == "I" => Expr::i(),
+        Node::Symbol(s) => Expr::from(s),
+        Node::Undef => Expr::undef(),
+        Node::Bool(b) => Expr::from(b),
+        Node::Not(x) => Expr::not(lower(*x)?),
+        Node::Binary(op, lhs, rhs) => {
+            let (lhs, rhs) = (lower(*lhs)?, lower(*rhs)?);
+            match op {
+                OpKind::Add => Expr::add(lhs, rhs),
+                OpKind::Sub => Expr::sub(lhs, rhs),
+                OpKind::Mul => Expr::mul(lhs, rhs),
+                OpKind::Div => Expr::div(lhs, rhs),
+                OpKind::Pow => Expr::pow(lhs, rhs),
+                OpKind::And => Expr::and(lhs, rhs),
+                OpKind::Or => Expr::or(lhs, rhs),
+                OpKind::Eq => Expr::rel(RelOp::Eq, lhs, rhs),
+                OpKind::Ne => Expr::rel(RelOp::Ne, lhs, rhs),
+                OpKind::Lt => Expr::rel(RelOp::Lt, lhs, rhs),
+                OpKind::Le => Expr::rel(RelOp::Le, lhs, rhs),
+                OpKind::Gt => Expr::rel(RelOp::Gt, lhs, rhs),
+                OpKind::Ge => Expr::rel(RelOp::Ge, lhs, rhs),
+            }
+        }
+        Node::Func(name, span, args) => {
+            let args = args.into_iter().map(lower).collect::<Result<Vec<_>>>()?;
+            lower_func(&name, span, args)?
+        }
+        Node::PlaceHolder(span) => return Err(ParseError::new(ParseErrorKind::PlaceHolder, span)),
+        Node::Float(f) => Expr::float(f),
+        Node::Infinity { sign } => match sign {
+            1 => Expr::inf(),
+            -1 => Expr::neg_inf(),
+            _ => Expr::complex_inf(),
+        },
+    })
+}
+
+impl Expr {
+    /// parses `src` at runtime, accepting the same grammar as [calcurs_macros::expr]
+    pub fn parse(src: &str) -> Result<Expr> {
+        let tokens = Lexer::new(src).tokenize()?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            src_len: src.len(),
+        };
+
+        let node = parser.parse_expr()?;
+        if parser.peek().is_some() {
+            return Err(parser.unexpected());
+        }
+        lower(node)
+    }
+}
+
+impl FromStr for Expr {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Expr::parse(s)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use assert_eq as eq;
+    use calcurs_macros::expr as e;
+
+    macro_rules! parity {
+        ($([$($t:tt)+]),* $(,)?) => {$(
+            eq!(
+                Expr::parse(stringify!($($t)+)),
+                Ok(e!($($t)+)),
+                "{}",
+                stringify!($($t)+)
+            );
+        )*};
+    }
+
+    #[test]
+    fn macro_parity() {
+        parity!(
+            [-x ^ 2],
+            [-(2) * x],
+            [2 ^ 3 ^ 2],
+            [a - b - c],
+            [add_raw(-x - y, x)],
+            [mul_raw(sin(x) * sin(y), 1 / sin(x))],
+            [arc_tan(x) + arc_sin(pi * x)],
+            [sqrt(log10(x)) + exp(ln(x))],
+            [pow(x, sub(y, 1))],
+            [x * (2 + (1 + x) ^ 2)],
+            [2 * x + (x + 1) ^ 2 * x],
+            [(x + (1 + x) ^ 2) ^ 2],
+            [x ^ 2 + 2 * x * (1 + x) ^ 2 + (1 + x) ^ 4],
+            [(x + 2) * (x + 3) * (x + 4)],
+            [x],
+            [x ^ 3 + 9 * x ^ 2 + 26 * x + 24],
+            [(x + 1) ^ 2 + (y + 1) ^ 2],
+            [y ^ 2 + 2 * y + x ^ 2 + 2 * x + 2],
+            [((x + 2) ^ 2 + 3) ^ 2],
+            [x ^ 4 + 8 * x ^ 3 + 30 * x ^ 2 + 56 * x + 49],
+            [a * (b + c) * (d + e)],
+            [a * b * (d + e) + a * c * (d + e)],
+            [(x + y) / (x * y)],
+            [x / (x * y) + y / (x * y)],
+            [(1 + 1 / x) ^ 2],
+            [((x + 1) / x) ^ 2],
+            [(1 + 1 / x) ^ (1 / 2)],
+            [((x + 1) / x) ^ (1 / 2)],
+            [6 * x * y ^ 3],
+            [2 * x ^ 2 * y * z],
+            [2 * x * y],
+            [3 * y ^ 2],
+            [x * z],
+            [a * (x + y)],
+            [x + y],
+            [a],
+            [1],
+            [x - y],
+            [a * (x - y)],
+            [(x ^ 2 + x * y) ^ 3],
+            [x ^ 3 * (x + y) ^ 3],
+            [a * (b + b * x)],
+            [a * b * (1 + x)],
+            [2 ^ (1 / 2) + 2],
+            [2 ^ (1 / 2) * (1 + 2 ^ (1 / 2))],
+            [a * b * x + a * c * x + b * c * x],
+            [x * (a * b + c * (a + b))],
+            [a / x + b / x],
+            [c * x * sin(x) / 2],
+            [c / 2],
+            [x * sin(x)],
+            [2 * x],
+            [1 + 2],
+            [3],
+            [a + undef],
+            [undef],
+            [a + (b + c)],
+            [0 - 2 * b],
+            [(2 - 4) * b],
+            [a + 0],
+            [0 + a],
+            [x + 0],
+            [0 + x],
+            [0 - x],
+            [(4 - 5) * x],
+            [x - 0],
+            [3 - 2],
+            [x * 0],
+            [0],
+            [0 * x],
+            [x * 1],
+            [1 * x],
+            [0 ^ 0],
+            [0 ^ 1],
+            [0 ^ 314],
+            [1 ^ 0],
+            [314 ^ 0],
+            [314 ^ 1],
+            [314],
+            [x ^ 1],
+            [1 ^ x],
+            [1 ^ 314],
+            [3 ^ 3],
+            [27],
+            [a - b],
+            [a + ((2 - 3) * b)],
+            [a / b],
+            [a * b ^ (2 - 3)],
+            [(x ^ (1 / 2) ^ (1 / 2)) ^ 8],
+            [x ^ 2],
+            [x + x],
+            [2 * x + y + x],
+            [3 * x + y],
+            [sin(0)],
+            [sin(-x)],
+            [-1 * sin(x)],
+            [cos(-x)],
+            [cos(x)],
+            [x * y / (y * x)],
+            [(sin(2 * x) - 2 * sin(x) * cos(x))],
+            [sin(a + b)],
+            [sin(a) * cos(b) + cos(a) * sin(b)],
+            [cos(a + b)],
+            [cos(a) * cos(b) - sin(a) * sin(b)],
+            [sin(2 * (x + y))],
+            [2 * cos(x) * sin(x) * (cos(y) ^ 2 - sin(y) ^ 2)
+                + (cos(x) ^ 2 - sin(x) ^ 2) * 2 * cos(y) * sin(y)],
+            [sin((x + y) ^ 2)],
+            [sin(x ^ 2)
+                * ((cos(x * y) ^ 2 - sin(x * y) ^ 2) * cos(y ^ 2)
+                    - 2 * cos(x * y) * sin(x * y) * sin(y ^ 2))
+                + cos(x ^ 2)
+                    * (2 * cos(x * y) * sin(x * y) * cos(y ^ 2)
+                        + (cos(x * y) ^ 2 - sin(x * y) ^ 2) * sin(y ^ 2))],
+            [exp(2 * w * x + 3 * y * z)],
+            [exp(w * x)],
+            [2],
+            [exp(y * z)],
+            [exp(2 * (x + y))],
+            [exp(x)],
+            [exp(y)],
+            [exp((x + y) ^ 2)],
+            [exp(x ^ 2)],
+            [exp(x * y)],
+            [exp(y ^ 2)],
+            [ln((w * x) ^ a) + ln(y ^ b * z)],
+            [a * ln(w) + a * ln(x) + b * ln(y) + ln(z)],
+            [((a + b) * c + (a + b) * d) / (a * e + b * e)],
+            [(c + d) / e],
+            [(a * (a + b) - a ^ 2 - a * b + r * s + r * t) / r ^ 2],
+            [(s + t) / r],
+            [a + b],
+            [b * c + a],
+            [sin(x) * cos(x)],
+            [a * x ^ 2 + b * x + c + 3],
+            [x ^ 3 + 3 * x ^ 2 * y + 3 * x * y ^ 2 + y ^ 3],
+            [y],
+            [3 * x * (x + 1) * y ^ 2 * z ^ n],
+            [x + 1],
+            [z ^ n],
+            [2 ^ (1 / 2) * x ^ 2 + 3 ^ (1 / 2) * x + 5 ^ (1 / 2)],
+            [2 ^ (1 / 2)],
+            [3 ^ (1 / 2)],
+            [5 ^ (1 / 2)],
+            [(2 / 3) * (x * (x + 1)) / (x + 2) * y ^ n],
+            [2 * x * (x + 1) * y ^ n],
+            [3 * (x + 2)],
+            [sin(x)],
+            [x * exp(x)],
+            [exp(x) * (1 + x)],
+            [ln(x)],
+            [1 / x],
+            [-1 / x ^ 2],
+            [tan(x)],
+            [sec(x) ^ 2],
+            [arc_tan(x)],
+            [1 / (x ^ 2 + 1)],
+            [x * ln(x) * sin(x)],
+            [x * cos(x) * ln(x) + sin(x) * ln(x) + sin(x)],
+            [sin(x) + x * cos(x) * ln(x) + sin(x) * ln(x)],
+            [2 * y],
+            [x * y],
+            [y * x],
+            [x * (1 + 2)],
+            [2 * x ^ 3],
+            [(x + 1) * (x + 3)],
+            [x * (x + 3)],
+            [a * x ^ 2 * y ^ 2],
+            [a * (x ^ 2 + y ^ 2)],
+            [x ^ 3],
+            [x ^ 3 * x ^ 4],
+            [3 * w * x ^ 2 * y ^ 3 * z ^ 4],
+            [z],
+            [a ^ 2 * x * b * x ^ 2],
+            [a ^ 2 * b],
+            [a ^ 2 * x ^ 2 * x ^ 4],
+            [3 * x ^ 2 + 4 * x + 5],
+            [a * x ^ 2 + b * x + c],
+            [x * (x ^ 2 + 1)],
+            [x ^ 2 * (x ^ 4 + 1)],
+            [2 * x ^ 2 * y * z ^ 3 + w * x * z ^ 6],
+            [b],
+            [c],
+            [3 * x * y ^ 2 + 5 * x ^ 2 * y + 7 * x + 9],
+            [3 * y ^ 2 + 7],
+            [5 * y],
+            [3 * x * y ^ 2 + 5 * x ^ 2 * y + 7 * x ^ 2 * y ^ 3 + 9],
+            [5 * y + 7 * y ^ 3],
+            [a * (x ^ 2 + 1) ^ 2 + (x ^ 2 + 1)],
+            [x ^ 2 + 1],
+            [2 * a * x * y + 3 * b * x * y + 4 * a * x + 5 * b * x],
+            [(2 * a + 3 * b) * x * y + (4 * a + 5 * b) * x],
+            [a + b * c],
+            [2 * x ^ (a + b)],
+            [2 * x ^ (2 * a)],
+            [a * b],
+            [(x + y) * (a + b)],
+            [3 * (a + b)],
+            [x * (a + b)],
+            [(a + b) * x],
+            [x ^ (a + b)],
+            [y + -x],
+            [y * 1 / x],
+            [3 * 1 / x],
+            [(1 + x) ^ 2],
+            [2 * pi],
+            [3 + 1 / 6 * pi],
+            [sin(x) ^ 3 * tan(x)],
+            [sin(x) ^ (x + y) * tan(x)],
+            [sin(x) ^ x * tan(x)],
+            [3 * sin(x) ^ pi * cos(x) ^ 3],
+            [sin(x) * sin(x)],
+            [x ^ y ^ z],
+            [x ^ (y ^ z)],
+            [2 + 3 * I],
+            [re(x) + im(x) * I],
+            [conj(x) * arg(x)],
+            [x + oo],
+            [-oo - 1],
+            [1 / zoo],
+            [0.5 * x],
+            [-1.5 * x ^ 2.0 + 3],
+            [x < 3],
+            [x + 1 <= 2 * y],
+            [x ^ 2 > 0 && x != 1],
+            [a >= b || a == c],
+            [!(x < 1) && true || false],
+            [!x || !!y],
+            [a < b == (c > d)],
+            [divisors(12) + divisors(x)],
+            [log(2, x) + log(pi, x ^ 2) - log(0.5, y)],
+            [modulo(x, 7) * modinv(3, 7) + powmod(x, 2, 5)],
+            [isprime(x) + nextprime(x) + totient(x)],
+            [root_of(x ^ 5 - x + 1, 0) + order(x ^ 3)],
+            [and(x < 1, not(y)) || or(true, false)],
+            [div_raw(x, pow_raw(x, 2)) + sub(x, 1)],
+            [sec(x) + cot(x) + csc(x) + arc_cos(x) + arc_sec(x) + arc_cot(x) + arc_csc(x)],
+        );
+    }
+
+    #[test]
+    fn whitespace() {
+        eq!("x*y^2".parse::<Expr>(), Ok(e!(x * y ^ 2)));
+        eq!("  sin( x )\n+ 1 ".parse::<Expr>(), Ok(e!(sin(x) + 1)));
+        eq!(
+            "powmod(x, 2, modulo(y, 7))".parse::<Expr>(),
+            Ok(e!(powmod(x, 2, modulo(y, 7))))
+        );
+    }
+
+    #[test]
+    fn floats() {
+        eq!(Expr::parse("2.5e-1 * x"), Ok(e!(0.25 * x)));
+        eq!(Expr::parse("1e400"), Ok(Expr::inf()));
+    }
+
+    #[test]
+    fn big_int() {
+        let n = "123456789012345678901234567890";
+        eq!(
+            Expr::parse(n),
+            Ok(Expr::rational(n.parse::<Int>().unwrap()))
+        );
+    }
+
+    #[test]
+    fn errors() {
+        use ParseErrorKind as K;
+        let err = |src: &str| Expr::parse(src).unwrap_err();
+
+        eq!(err("x + $"), ParseError::new(K::UnexpectedChar('$'), 4..5));
+        eq!(err("x +"), ParseError::new(K::ExpectedOperand, 3..3));
+        eq!(err("(x + y"), ParseError::new(K::UnexpectedEof, 6..6));
+        eq!(
+            err("x y"),
+            ParseError::new(K::UnexpectedToken("y".into()), 2..3)
+        );
+        eq!(err("* x"), ParseError::new(K::ExpectedUnaryOp, 0..1));
+        eq!(err("x = 1"), ParseError::new(K::UnexpectedChar('='), 2..3));
+        eq!(err("x & y"), ParseError::new(K::UnexpectedChar('&'), 2..3));
+        eq!(err("x + ?a"), ParseError::new(K::PlaceHolder, 4..6));
+        eq!(
+            err("1 + foo(x)"),
+            ParseError::new(K::UnknownFunction("foo".into()), 4..7)
+        );
+        eq!(err("log(x, y)"), ParseError::new(K::InvalidLogBase, 0..3));
+        eq!(
+            err("mod(x, y)"),
+            ParseError::new(K::UnknownFunction("mod".into()), 0..3)
+        );
+        eq!(
+            err("sin(x, y)"),
+            ParseError::new(
+                K::WrongArgCount {
+                    func: "sin".into(),
+                    expected: 1,
+                    found: 2
+                },
+                0..3
+            )
+        );
+    }
+}
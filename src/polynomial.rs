@@ -1,5 +1,7 @@
+use std::collections::{BTreeMap, BTreeSet};
+
 use crate::{
-    atom::{Atom, Expr, Prod, Sum},
+    atom::{Atom, Expr, Prod, Sum, SymbolicExpr},
     rational::{Int, Rational},
     utils::HashMap,
 };
@@ -73,7 +75,7 @@ impl VarPow {
         self.var_deg
             .iter()
             .map(|(_, d)| d)
-            .fold(0, |sum, d| sum + d)
+            .fold(Int::ZERO, |sum, d| sum + d)
     }
 
     fn find(&self, v: &GVar) -> Option<&Degree> {
@@ -88,7 +90,7 @@ impl VarPow {
             .find_map(|(var, d)| if var == v { Some(d) } else { None })
     }
 
-    fn as_expr(self) -> Expr {
+    fn into_expr(self) -> Expr {
         self.var_deg
             .into_iter()
             .map(|(v, d)| Expr::pow(v, Expr::from(d)))
@@ -154,7 +156,7 @@ impl GPE {
         self.sort_by_degree();
         self.terms
             .into_iter()
-            .map(|(c, vp)| c * vp.as_expr())
+            .map(|(c, vp)| c * vp.into_expr())
             .fold(Expr::zero(), |sum, term| sum + term)
     }
 }
@@ -178,8 +180,15 @@ impl<'a> MonomialView<'a> {
         }
 
         match self.monom.atom() {
-            A::Undef => return false,
-            A::Irrational(_) | A::Rational(_) | A::Var(_) | A::Sum(_) => (),
+            A::Undef | A::Rel(_) | A::Logic(_) | A::List(_) => return false,
+            A::Irrational(_)
+            | A::Rational(_)
+            | A::Float(_)
+            | A::ImagUnit
+            | A::Infinity(_)
+            | A::Var(_)
+            | A::Bool(_)
+            | A::Sum(_) => (),
             A::Prod(Prod { args }) => {
                 for a in args {
                     if !a.as_monomial_view(self.vars).check() {
@@ -197,7 +206,7 @@ impl<'a> MonomialView<'a> {
                 }
                 _ => (),
             },
-            A::Func(_) => todo!(),
+            A::Func(_) => (),
         }
         self.monom.free_of_set(self.vars.iter())
     }
@@ -213,7 +222,7 @@ impl<'a> MonomialView<'a> {
         }
         if self.vars.has(self.monom) {
             let v = self.monom;
-            return Some((Expr::one(), [(v.clone(), 1)].into()));
+            return Some((Expr::one(), [(v.clone(), Int::ONE)].into()));
         }
 
         match self.monom.atom() {
@@ -232,7 +241,7 @@ impl<'a> MonomialView<'a> {
                     // TODO: negative exponent?
                     A::Rational(r) if r.is_int() && r >= &Rational::ONE => {
                         let (c, mut d) = pow.base().as_monomial_view(self.vars).coeff()?;
-                        d.pow(&r.to_int().unwrap().into());
+                        d.pow(&r.to_int().unwrap());
                         return Some((Expr::pow(c, pow.exponent()), d));
                         //if self.vars.has(pow.base()) {
                         //    let v = pow.base();
@@ -297,8 +306,8 @@ impl<'a> PolynomialView<'a> {
 
     pub fn degree_of(&self, v: &GVar) -> Option<Int> {
         self.coeffs()
-            .into_iter()
-            .filter_map(|(d, _)| d.degree_of(v).cloned())
+            .into_keys()
+            .filter_map(|d| d.degree_of(v).cloned())
             .reduce(std::cmp::max)
     }
 
@@ -311,7 +320,7 @@ impl<'a> PolynomialView<'a> {
             if let Some(d) = d.degree_of(v) {
                 Some((d.clone(), c))
             } else if d.is_const() {
-                Some((0, c))
+                Some((Int::ZERO, c))
             } else {
                 None
             }
@@ -336,7 +345,7 @@ impl<'a> PolynomialView<'a> {
         if let A::Sum(Sum { args }) = self.poly.atom() {
             if self.vars.has(self.poly) {
                 let v = self.poly;
-                coeffs.insert([(v.clone(), 0)].into(), Expr::one());
+                coeffs.insert([(v.clone(), Int::ONE)].into(), Expr::one());
                 return coeffs;
             }
 
@@ -419,6 +428,492 @@ impl<'a> PolynomialView<'a> {
     }
 }
 
+/// exponents of a term, indexed like the variables the polynomial was created from
+type Exponents = Vec<u64>;
+
+/// sparse polynomial in multiple variables with rational coefficients
+///
+/// the terms are ordered lexicographically, so the first variable is the main variable
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct RatPoly {
+    n_vars: usize,
+    terms: BTreeMap<Exponents, Rational>,
+}
+
+impl RatPoly {
+    pub(crate) fn zero(n_vars: usize) -> Self {
+        Self {
+            n_vars,
+            terms: Default::default(),
+        }
+    }
+
+    pub(crate) fn constant(n_vars: usize, c: Rational) -> Self {
+        Self::monomial(vec![0; n_vars], c)
+    }
+
+    fn monomial(exps: Exponents, c: Rational) -> Self {
+        let mut res = Self::zero(exps.len());
+        res.add_term(exps, c);
+        res
+    }
+
+    /// returns [None] if `e` is not a polynomial in `vars` with rational coefficients
+    pub(crate) fn from_expr(e: &Expr, vars: &[GVar]) -> Option<Self> {
+        let set = VarSet::new(vars);
+        let e = e.expand().reduce();
+        let view = e.as_polynomial_view(&set);
+        if !view.check() {
+            return None;
+        }
+
+        let mut res = Self::zero(vars.len());
+        for (vp, c) in view.coeffs() {
+            let Atom::Rational(c) = c.atom() else {
+                return None;
+            };
+            let exps = vars
+                .iter()
+                .map(|v| match vp.degree_of(v) {
+                    Some(d) => u64::try_from(&d.0).ok(),
+                    None => Some(0),
+                })
+                .collect::<Option<_>>()?;
+            res.add_term(exps, c.clone());
+        }
+        Some(res)
+    }
+
+    /// univariate polynomial from its coefficients, the i-th coefficient belongs to x^i
+    pub(crate) fn from_dense(coeffs: &[Rational]) -> Self {
+        let mut res = Self::zero(1);
+        for (i, c) in coeffs.iter().enumerate() {
+            res.add_term(vec![i as u64], c.clone());
+        }
+        res
+    }
+
+    /// coefficients of a univariate polynomial, the i-th coefficient belongs to x^i
+    pub(crate) fn to_dense(&self) -> Vec<Rational> {
+        assert_eq!(self.n_vars, 1, "polynomial is not univariate");
+        let n = self.degree(0).map_or(0, |d| d as usize + 1);
+        let mut res = vec![Rational::ZERO; n];
+        for (e, c) in &self.terms {
+            res[e[0] as usize] = c.clone();
+        }
+        res
+    }
+
+    pub(crate) fn to_expr(&self, vars: &[GVar]) -> Expr {
+        let mut gpe = GPE::default();
+        for (exps, c) in &self.terms {
+            let vp = vars
+                .iter()
+                .zip(exps)
+                .filter(|(_, d)| **d != 0)
+                .map(|(v, d)| (v.clone(), Int::from(*d)));
+            gpe.add(c.clone().into(), vp.into());
+        }
+        gpe.into_expr()
+    }
+
+    pub(crate) fn is_zero(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub(crate) fn is_const(&self) -> bool {
+        self.terms.keys().all(|e| e.iter().all(|d| *d == 0))
+    }
+
+    fn add_term(&mut self, exps: Exponents, c: Rational) {
+        if c.is_zero() {
+            return;
+        }
+        let coeff = self.terms.entry(exps.clone()).or_default();
+        *coeff += c;
+        if coeff.is_zero() {
+            self.terms.remove(&exps);
+        }
+    }
+
+    /// the largest term in lexicographic order
+    fn lead(&self) -> Option<(&Exponents, &Rational)> {
+        self.terms.last_key_value()
+    }
+
+    pub(crate) fn degree(&self, var: usize) -> Option<u64> {
+        self.terms.keys().map(|e| e[var]).max()
+    }
+
+    /// coefficient of `x^deg`, where `x` is the variable at index `var`
+    fn coeff(&self, var: usize, deg: u64) -> Self {
+        let mut res = Self::zero(self.n_vars);
+        for (e, c) in self.terms.iter().filter(|(e, _)| e[var] == deg) {
+            let mut e = e.clone();
+            e[var] = 0;
+            res.add_term(e, c.clone());
+        }
+        res
+    }
+
+    fn coeffs(&self, var: usize) -> impl Iterator<Item = Self> + '_ {
+        let degs: BTreeSet<_> = self.terms.keys().map(|e| e[var]).collect();
+        degs.into_iter().map(move |d| self.coeff(var, d))
+    }
+
+    fn leading_coeff(&self, var: usize) -> Self {
+        match self.degree(var) {
+            Some(d) => self.coeff(var, d),
+            None => Self::zero(self.n_vars),
+        }
+    }
+
+    /// multiply by `x^deg`, where `x` is the variable at index `var`
+    fn shift(&self, var: usize, deg: u64) -> Self {
+        let mut res = Self::zero(self.n_vars);
+        for (e, c) in &self.terms {
+            let mut e = e.clone();
+            e[var] += deg;
+            res.terms.insert(e, c.clone());
+        }
+        res
+    }
+
+    pub(crate) fn scale(&self, s: &Rational) -> Self {
+        let mut res = Self::zero(self.n_vars);
+        for (e, c) in &self.terms {
+            res.add_term(e.clone(), c.clone() * s);
+        }
+        res
+    }
+
+    pub(crate) fn add(&self, rhs: &Self) -> Self {
+        let mut res = self.clone();
+        for (e, c) in &rhs.terms {
+            res.add_term(e.clone(), c.clone());
+        }
+        res
+    }
+
+    pub(crate) fn sub(&self, rhs: &Self) -> Self {
+        self.add(&rhs.scale(&Rational::MINUS_ONE))
+    }
+
+    pub(crate) fn mul(&self, rhs: &Self) -> Self {
+        let mut res = Self::zero(self.n_vars);
+        for (e1, c1) in &self.terms {
+            for (e2, c2) in &rhs.terms {
+                let e = e1.iter().zip(e2).map(|(d1, d2)| d1 + d2).collect();
+                res.add_term(e, c1.clone() * c2);
+            }
+        }
+        res
+    }
+
+    /// derivative w.r.t the variable at index `var`
+    pub(crate) fn derivative(&self, var: usize) -> Self {
+        let mut res = Self::zero(self.n_vars);
+        for (e, c) in self.terms.iter().filter(|(e, _)| e[var] > 0) {
+            let mut e = e.clone();
+            let c = c.clone() * Rational::from(e[var]);
+            e[var] -= 1;
+            res.add_term(e, c);
+        }
+        res
+    }
+
+    fn pow(&self, n: u64) -> Self {
+        (0..n).fold(Self::constant(self.n_vars, Rational::ONE), |p, _| {
+            p.mul(self)
+        })
+    }
+
+    /// multivariate division by the leading term, returns (quotient, remainder)
+    ///
+    /// for univariate polynomials this is the euclidean division
+    pub(crate) fn div_rem(&self, v: &Self) -> (Self, Self) {
+        let (lv_e, lv_c) = v.lead().expect("division by zero");
+        let mut q = Self::zero(self.n_vars);
+        let mut r = Self::zero(self.n_vars);
+        let mut p = self.clone();
+
+        while let Some((e, c)) = p.lead().map(|(e, c)| (e.clone(), c.clone())) {
+            if e.iter().zip(lv_e).all(|(d1, d2)| d1 >= d2) {
+                let e = e.iter().zip(lv_e).map(|(d1, d2)| d1 - d2).collect();
+                let t = Self::monomial(e, c / lv_c);
+                p = p.sub(&t.mul(v));
+                q = q.add(&t);
+            } else {
+                p.terms.remove(&e);
+                r.add_term(e, c);
+            }
+        }
+        (q, r)
+    }
+
+    /// returns [None] if `v` does not divide [self]
+    pub(crate) fn div_exact(&self, v: &Self) -> Option<Self> {
+        let (q, r) = self.div_rem(v);
+        r.is_zero().then_some(q)
+    }
+
+    /// returns (q, r), such that lc(v)^(deg(u) - deg(v) + 1) * u = q * v + r,
+    /// where deg and lc are taken w.r.t the variable at index `var`
+    pub(crate) fn pseudo_div(&self, v: &Self, var: usize) -> (Self, Self) {
+        let n = v.degree(var).expect("division by zero");
+        let lcv = v.leading_coeff(var);
+        let delta = match self.degree(var) {
+            Some(m) if m >= n => m - n + 1,
+            _ => return (Self::zero(self.n_vars), self.clone()),
+        };
+
+        let mut q = Self::zero(self.n_vars);
+        let mut r = self.clone();
+        let mut steps = 0;
+        while let Some(m) = r.degree(var).filter(|m| *m >= n) {
+            let lcr = r.leading_coeff(var);
+            q = lcv.mul(&q).add(&lcr.shift(var, m - n));
+            r = lcv.mul(&r).sub(&lcr.mul(v).shift(var, m - n));
+            steps += 1;
+        }
+        let s = lcv.pow(delta - steps);
+        (s.mul(&q), s.mul(&r))
+    }
+
+    pub(crate) fn pseudo_rem(&self, v: &Self, var: usize) -> Self {
+        self.pseudo_div(v, var).1
+    }
+
+    /// view `u` and `v` as polynomials in `x`, with polynomial coefficients in the remaining
+    /// variables. `x` is the first variable of the returned list
+    fn with_main_var(u: &Expr, v: &Expr, x: &GVar) -> Option<(Self, Self, Vec<GVar>)> {
+        let set = VarSet::from(x.clone());
+        let coeffs = |e: &Expr| {
+            // only expand if needed, as it would destroy generalized variables like (x^2 + 1)
+            if e.as_polynomial_view(&set).check() {
+                return Some(e.as_polynomial_view(&set).coeffs_of(x));
+            }
+            let e = e.expand().reduce();
+            let poly = e.as_polynomial_view(&set);
+            poly.check().then(|| poly.coeffs_of(x))
+        };
+        let (cu, cv) = (coeffs(u)?, coeffs(v)?);
+
+        let mut vars: Vec<_> = cu
+            .values()
+            .chain(cv.values())
+            .flat_map(|c| c.variables())
+            .filter(|v| v != x)
+            .collect();
+        vars.sort();
+        vars.dedup();
+        vars.insert(0, x.clone());
+
+        let to_poly = |coeffs: HashMap<Degree, Coeff>| {
+            coeffs
+                .into_iter()
+                .try_fold(Self::zero(vars.len()), |p, (d, c)| {
+                    let c = Self::from_expr(&c, &vars)?;
+                    Some(p.add(&c.shift(0, u64::try_from(&d.0).ok()?)))
+                })
+        };
+        Some((to_poly(cu)?, to_poly(cv)?, vars))
+    }
+
+    /// gcd of the coefficients in `var`
+    fn content(&self, var: usize) -> Self {
+        self.coeffs(var).fold(Self::zero(self.n_vars), |g, c| {
+            Self::gcd_rec(&g, &c, var + 1)
+        })
+    }
+
+    /// subresultant prs, where `var` is the main variable and the coefficients
+    /// are polynomials in the following variables
+    fn gcd_rec(u: &Self, v: &Self, var: usize) -> Self {
+        if u.is_zero() {
+            return v.clone();
+        } else if v.is_zero() {
+            return u.clone();
+        }
+
+        if var == u.n_vars {
+            let c = |p: &Self| p.terms.values().next().cloned().unwrap_or_default();
+            return Self::constant(u.n_vars, rational_gcd(&c(u), &c(v)));
+        }
+
+        let (u, v) = match u.degree(var) >= v.degree(var) {
+            true => (u, v),
+            false => (v, u),
+        };
+        let (cont_u, cont_v) = (u.content(var), v.content(var));
+        let d = Self::gcd_rec(&cont_u, &cont_v, var + 1);
+
+        let exact = "subresultant division is exact";
+        let mut u = u.div_exact(&cont_u).expect(exact);
+        let mut v = v.div_exact(&cont_v).expect(exact);
+        let one = Self::constant(u.n_vars, Rational::ONE);
+        let (mut g, mut h) = (one.clone(), one);
+
+        while !v.is_zero() {
+            let delta = u.degree(var).unwrap_or(0) - v.degree(var).unwrap_or(0);
+            let r = u.pseudo_rem(&v, var);
+            u = v;
+            v = r.div_exact(&g.mul(&h.pow(delta))).expect(exact);
+            g = u.leading_coeff(var);
+            if delta > 0 {
+                h = g.pow(delta).div_exact(&h.pow(delta - 1)).expect(exact);
+            }
+        }
+
+        let cont_u = u.content(var);
+        d.mul(&u.div_exact(&cont_u).expect(exact))
+    }
+
+    /// gcd in Z[x1, .., xn], normalized to a positive leading coefficient
+    pub(crate) fn gcd(&self, rhs: &Self) -> Self {
+        let g = Self::gcd_rec(self, rhs, 0);
+        match g.lead() {
+            Some((_, c)) if c.is_neg() => g.scale(&Rational::MINUS_ONE),
+            _ => g,
+        }
+    }
+
+    /// gcd in Q[x] using the euclidean algorithm, the result is monic
+    pub(crate) fn gcd_uv(&self, rhs: &Self) -> Self {
+        let (mut u, mut v) = (self.clone(), rhs.clone());
+        while !v.is_zero() {
+            let (_, r) = u.div_rem(&v);
+            u = v;
+            v = r;
+        }
+        match u.lead() {
+            Some((_, c)) => u.scale(&c.clone().inverse().unwrap()),
+            None => u,
+        }
+    }
+
+    /// extended euclidean algorithm in Q[x], returns (g, s) with g = gcd(self, rhs) monic
+    /// and s * self = g mod rhs
+    pub(crate) fn ext_gcd_uv(&self, rhs: &Self) -> (Self, Self) {
+        let (mut r0, mut r1) = (self.clone(), rhs.clone());
+        let (mut s0, mut s1) = (Self::constant(1, Rational::ONE), Self::zero(1));
+        while !r1.is_zero() {
+            let (q, r) = r0.div_rem(&r1);
+            let s = s0.sub(&q.mul(&s1));
+            (r0, r1) = (r1, r);
+            (s0, s1) = (s1, s);
+        }
+        // make the gcd monic
+        let inv = r0
+            .lead()
+            .expect("gcd of zero polynomials")
+            .1
+            .clone()
+            .inverse()
+            .unwrap();
+        (r0.scale(&inv), s0.scale(&inv))
+    }
+}
+
+/// gcd(a/b, c/d) = gcd(a, c) / lcm(b, d)
+fn rational_gcd(lhs: &Rational, rhs: &Rational) -> Rational {
+    let n = lhs.numer().gcd(&rhs.numer());
+    let d = num::Integer::lcm(&lhs.denom(), &rhs.denom());
+    Rational::new(n, d)
+}
+
+impl Expr {
+    /// greatest common divisor of two polynomials in `vars`
+    ///
+    /// univariate polynomials are treated as elements of Q\[x\] and the gcd is monic,
+    /// multivariate ones as elements of Z\[x1, .., xn\] with a positive leading coefficient.
+    ///
+    /// returns [None] if `a` or `b` is not a polynomial in `vars` with rational coefficients
+    pub fn poly_gcd(a: &Expr, b: &Expr, vars: &VarSet) -> Option<Expr> {
+        let vars: Vec<_> = vars.iter().cloned().collect();
+        let u = RatPoly::from_expr(a, &vars)?;
+        let v = RatPoly::from_expr(b, &vars)?;
+        let gcd = match vars.len() {
+            1 => u.gcd_uv(&v),
+            _ => u.gcd(&v),
+        };
+        Some(gcd.to_expr(&vars))
+    }
+
+    /// division of polynomials in `x` with remainder, returns (quotient, remainder)
+    ///
+    /// the coefficients are treated as elements of a field,
+    /// so the quotient can contain fractions in the other variables:
+    ///
+    /// x^2 / (a*x + 1) -> (x/a - 1/a^2, 1/a^2)
+    ///
+    /// returns [None] if `u` or `v` is not a polynomial in `x` or if `v` is zero
+    pub fn poly_div(u: &Expr, v: &Expr, x: &Expr) -> Option<(Expr, Expr)> {
+        let (pu, pv, vars) = RatPoly::with_main_var(u, v, x)?;
+        let delta = match (pu.degree(0), pv.degree(0)?) {
+            (Some(m), n) if m >= n => m - n + 1,
+            _ => return Some((Expr::zero(), u.clone())),
+        };
+        let (q, r) = pu.pseudo_div(&pv, 0);
+
+        // lc(v)^delta * u = q * v + r
+        let lc = pv.leading_coeff(0).pow(delta);
+        if lc.is_const() {
+            let inv = lc.lead()?.1.clone().inverse()?;
+            return Some((q.scale(&inv).to_expr(&vars), r.scale(&inv).to_expr(&vars)));
+        }
+
+        let lc = lc.to_expr(&vars);
+        let div_lc = |p: RatPoly| {
+            let degs: BTreeSet<_> = p.terms.keys().map(|e| e[0]).collect();
+            degs.into_iter()
+                .map(|d| {
+                    let c = p.coeff(0, d).to_expr(&vars);
+                    let c = Expr::cancel_poly(&c, &lc).unwrap_or_else(|| c / &lc);
+                    c * Expr::pow(x, Expr::from(d))
+                })
+                .fold(Expr::zero(), |sum, t| sum + t)
+        };
+        Some((div_lc(q), div_lc(r)))
+    }
+
+    /// pseudo-division of polynomials in `x`, returns (quotient, remainder)
+    ///
+    /// lc(v)^(deg(u) - deg(v) + 1) * u = q * v + r, where the coefficients can be polynomials
+    /// in other variables, e.g x^2 / (a*x + 1) -> (a*x - 1, 1)
+    ///
+    /// returns [None] if `u` or `v` is not a polynomial in `x` or if `v` is zero
+    pub fn poly_pseudo_div(u: &Expr, v: &Expr, x: &Expr) -> Option<(Expr, Expr)> {
+        let (pu, pv, vars) = RatPoly::with_main_var(u, v, x)?;
+        pv.degree(0)?;
+        let (q, r) = pu.pseudo_div(&pv, 0);
+        Some((q.to_expr(&vars), r.to_expr(&vars)))
+    }
+
+    /// divide numerator and denominator by their polynomial gcd
+    ///
+    /// returns [None] if they are not polynomials or have no common factor
+    pub(crate) fn cancel_poly(numer: &Expr, denom: &Expr) -> Option<Expr> {
+        let mut vars: Vec<_> = numer
+            .variables()
+            .into_iter()
+            .chain(denom.variables())
+            .collect();
+        vars.sort();
+        vars.dedup();
+
+        let n = RatPoly::from_expr(numer, &vars)?;
+        let d = RatPoly::from_expr(denom, &vars)?;
+        let gcd = n.gcd(&d);
+        if gcd.is_const() {
+            return None;
+        }
+        let n = n.div_exact(&gcd)?.to_expr(&vars);
+        let d = d.div_exact(&gcd)?.to_expr(&vars);
+        Some(n / d)
+    }
+}
+
 #[cfg(test)]
 mod monomial_uv {
     use calcurs_macros::expr as e;
@@ -572,8 +1067,11 @@ mod polynomial_uv {
         let poly = u.as_polynomial_view(&vars);
         assert!(poly.check());
         assert_eq!(poly.degree(), Some(2.into()));
-        assert_eq!(poly.coeffs_of_deg(&e!(x ^ 2 + 1), &1), None);
-        assert_eq!(poly.coeffs_of_deg(&e!(x ^ 2 + 1), &0), Some(e!(x ^ 2 + 1)));
+        assert_eq!(poly.coeffs_of_deg(&e!(x ^ 2 + 1), &Int::ONE), None);
+        assert_eq!(
+            poly.coeffs_of_deg(&e!(x ^ 2 + 1), &Int::ZERO),
+            Some(e!(x ^ 2 + 1))
+        );
     }
 
     #[test]
@@ -587,3 +1085,141 @@ mod polynomial_uv {
         )
     }
 }
+
+#[cfg(test)]
+mod poly_gcd {
+    use calcurs_macros::expr as e;
+
+    use super::*;
+    use assert_eq as eq;
+
+    fn gcd(a: Expr, b: Expr, vars: impl Into<VarSet>) -> Option<Expr> {
+        Expr::poly_gcd(&a, &b, &vars.into())
+    }
+
+    #[test]
+    fn univariate() {
+        eq!(gcd(e!(x ^ 2 - 1), e!(x + 1), e!(x)), Some(e!(x + 1)));
+        eq!(
+            gcd(e!(x ^ 2 + 2 * x + 1), e!(x ^ 2 - 1), e!(x)),
+            Some(e!(x + 1))
+        );
+        eq!(
+            gcd(e!(2 * x ^ 2 - 2), e!(4 * x + 4), e!(x)),
+            Some(e!(x + 1))
+        );
+        eq!(
+            gcd(e!(x ^ 3 - x), e!((x - 1) ^ 2 * (x + 2)), e!(x)),
+            Some(e!(x - 1))
+        );
+        eq!(gcd(e!(x ^ 2 + 1), e!(x + 1), e!(x)), Some(e!(1)));
+        eq!(gcd(e!(3 * x), e!(0), e!(x)), Some(e!(x)));
+        eq!(gcd(e!(x ^ (1 / 2)), e!(x), e!(x)), None);
+        eq!(gcd(e!(a * x), e!(x), e!(x)), None);
+    }
+
+    #[test]
+    fn multivariate() {
+        let xy = [e!(x), e!(y)];
+        eq!(
+            gcd(e!(x ^ 2 - y ^ 2), e!(x ^ 2 + 2 * x * y + y ^ 2), xy.clone()),
+            Some(e!(x + y))
+        );
+        eq!(
+            gcd(e!(6 * x * y + 4 * y), e!(2 * y ^ 2), xy.clone()),
+            Some(e!(2 * y))
+        );
+        eq!(
+            gcd(
+                e!((x * y + 1) * (x - y) ^ 2).expand(),
+                e!((x * y + 1) * (x + y)).expand(),
+                xy.clone()
+            ),
+            Some(e!(x * y + 1))
+        );
+        eq!(
+            gcd(
+                e!((2 * a - b) * (a + c)).expand(),
+                e!((b - 2 * a) * (c - a)).expand(),
+                [e!(a), e!(b), e!(c)]
+            ),
+            Some(e!(2 * a - b))
+        );
+    }
+
+    #[test]
+    fn cancel() {
+        eq!(e!((x ^ 2 - 1) / (x + 1)).cancel(), e!(x - 1));
+        eq!(e!((x ^ 2 - y ^ 2) / (x + y)).cancel(), e!(x - y));
+        eq!(
+            e!((x ^ 2 + 2 * x + 1) / (2 * x ^ 2 - 2)).cancel(),
+            e!((x + 1) / (2 * x - 2))
+        );
+    }
+}
+
+#[cfg(test)]
+mod poly_div {
+    use calcurs_macros::expr as e;
+
+    use super::*;
+    use assert_eq as eq;
+
+    #[test]
+    fn division() {
+        let x = &e!(x);
+        eq!(
+            Expr::poly_div(&e!(5 * x ^ 2 + 4 * x + 1), &e!(2 * x + 3), x),
+            Some((e!(5 / 2 * x - 7 / 4), e!(25 / 4)))
+        );
+        eq!(
+            Expr::poly_div(&e!(x ^ 3 - 1), &e!(x - 1), x),
+            Some((e!(x ^ 2 + x + 1), e!(0)))
+        );
+        eq!(
+            Expr::poly_div(&e!(x + 1), &e!(x ^ 2), x),
+            Some((e!(0), e!(x + 1)))
+        );
+        eq!(
+            Expr::poly_div(&e!(x ^ 2 + a * x + b), &e!(x + a), x),
+            Some((e!(x), e!(b)))
+        );
+        eq!(
+            Expr::poly_div(&e!(x ^ 2), &e!(a * x + 1), x),
+            Some((e!(x / a - 1 / a ^ 2), e!(1 / a ^ 2)))
+        );
+        eq!(Expr::poly_div(&e!(x), &e!(0), x), None);
+        eq!(Expr::poly_div(&e!(x ^ (1 / 2)), &e!(x), x), None);
+    }
+
+    #[test]
+    fn pseudo_division() {
+        let x = &e!(x);
+        eq!(
+            Expr::poly_pseudo_div(&e!(x ^ 2), &e!(a * x + 1), x),
+            Some((e!(a * x - 1), e!(1)))
+        );
+        eq!(
+            Expr::poly_pseudo_div(&e!(x ^ 3 + y * x + 1), &e!(2 * y * x - 1), x),
+            Some((
+                e!(4 * y ^ 2 * x ^ 2 + 2 * y * x + 4 * y ^ 3 + 1),
+                e!(12 * y ^ 3 + 1)
+            ))
+        );
+    }
+
+    #[test]
+    fn generalized_var() {
+        // a*y^2 + 2*y = (a*y + 2) * y, with y = x^2 + 1
+        let y = &e!(x ^ 2 + 1);
+        eq!(
+            Expr::poly_div(&e!(a * (x ^ 2 + 1) ^ 2 + 2 * (x ^ 2 + 1)), y, y),
+            Some((e!(a * (x ^ 2 + 1) + 2), e!(0)))
+        );
+        let s = &e!(sin(x));
+        eq!(
+            Expr::poly_div(&e!(sin(x) ^ 3 + 2 * sin(x) + 1), &e!(sin(x) + 1), s),
+            Some((e!(sin(x) ^ 2 - sin(x) + 3), e!(-2)))
+        );
+    }
+}
@@ -1,18 +1,23 @@
 use std::{cmp::Ordering, ops, str::FromStr};
 
 use calcurs_macros::arith_ops;
-use derive_more::{Add, AddAssign, Debug, Display, Div, DivAssign, Mul, MulAssign, Sub, SubAssign};
-use ref_cast::RefCast;
+use derive_more::{
+    Add, AddAssign, Debug, Display, Div, DivAssign, From, Into, Mul, MulAssign, Sub, SubAssign,
+};
+use malachite::{
+    self as mal,
+    num::{arithmetic::traits as marith, conversion::traits as mconv},
+    rounding_modes::RoundingMode,
+};
 use serde::{Deserialize, Serialize};
 
-/*
 #[derive(Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display, From, Into)]
 #[arith_ops(ref, self.0)]
-#[from(i32, u32, i64, u64)]
-#[into(mal::Rational)]
+#[from(i32, u32, i64, u64, i128, u128, mal::Integer)]
+#[into(mal::Integer)]
 #[debug("{}", self.0)]
 #[repr(transparent)]
-pub struct Int(mal::Integer);
+pub struct Int(pub(crate) mal::Integer);
 
 impl num::Zero for Int {
     fn zero() -> Self {
@@ -20,7 +25,21 @@ impl num::Zero for Int {
     }
 
     fn is_zero(&self) -> bool {
-        self.is_zero()
+        self == &Int::ZERO
+    }
+}
+
+impl num::One for Int {
+    fn one() -> Self {
+        Int::ONE
+    }
+}
+
+impl ops::Neg for Int {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Int(-self.0)
     }
 }
 
@@ -28,7 +47,7 @@ impl ops::Rem<&Int> for Int {
     type Output = Self;
 
     fn rem(self, rhs: &Self) -> Self::Output {
-        Int(self.0.rem(&rhs.0))
+        Int(self.0 % &rhs.0)
     }
 }
 
@@ -36,7 +55,7 @@ impl ops::Rem for Int {
     type Output = Self;
 
     fn rem(self, rhs: Self) -> Self::Output {
-        Int(self.0.rem(rhs.0))
+        Int(self.0 % rhs.0)
     }
 }
 
@@ -49,6 +68,14 @@ impl num::Num for Int {
     }
 }
 
+impl FromStr for Int {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        mal::Integer::from_str(s).map(Int)
+    }
+}
+
 impl num::Integer for Int {
     fn div_floor(&self, other: &Self) -> Self {
         Int(marith::DivMod::div_mod(&self.0, &other.0).0)
@@ -59,7 +86,7 @@ impl num::Integer for Int {
     }
 
     fn gcd(&self, other: &Self) -> Self {
-        Int(marith::Gcd::gcd(self.0.unsigned_abs_ref(), other.0.unsigned_abs_ref()).into())
+        Int::gcd(self, other)
     }
 
     fn lcm(&self, other: &Self) -> Self {
@@ -67,15 +94,15 @@ impl num::Integer for Int {
     }
 
     fn is_multiple_of(&self, other: &Self) -> bool {
-        self.mod_floor(other).is_zero()
+        self.mod_floor(other) == Int::ZERO
     }
 
     fn is_even(&self) -> bool {
-        marith::Parity::even(&self.0)
+        Int::is_even(self)
     }
 
     fn is_odd(&self) -> bool {
-        marith::Parity::odd(&self.0)
+        Int::is_odd(self)
     }
 
     fn div_rem(&self, other: &Self) -> (Self, Self) {
@@ -100,6 +127,29 @@ impl num::FromPrimitive for Int {
     fn from_u128(n: u128) -> Option<Self> {
         Some(Int(n.into()))
     }
+
+    /// only succeeds if n is an integer
+    fn from_f64(n: f64) -> Option<Self> {
+        mal::Integer::try_from(n).ok().map(Int)
+    }
+}
+
+impl num::ToPrimitive for Int {
+    fn to_i64(&self) -> Option<i64> {
+        i64::try_from(&self.0).ok()
+    }
+
+    fn to_u64(&self) -> Option<u64> {
+        u64::try_from(&self.0).ok()
+    }
+
+    fn to_u128(&self) -> Option<u128> {
+        u128::try_from(&self.0).ok()
+    }
+
+    fn to_i128(&self) -> Option<i128> {
+        i128::try_from(&self.0).ok()
+    }
 }
 
 impl num_integer::Roots for Int {
@@ -156,45 +206,313 @@ impl Int {
         Self(n.into())
     }
 
-    pub fn prime_factorize(&self) {
-        //num_prime::nt_funcs::factorize(self.0);
-        todo!()
+    /// witnesses of the miller-rabin test, deterministic for n < 3.3 * 10^24
+    const WITNESSES: [u32; 13] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41];
+    /// factors below this bound are found by trial division
+    const TRIAL_BOUND: u32 = 1 << 10;
+
+    pub fn bits(&self) -> u64 {
+        mal::num::logic::traits::SignificantBits::significant_bits(self.0.unsigned_abs_ref())
+    }
+
+    /// miller-rabin primality test
+    pub fn is_prime(&self) -> bool {
+        if self < &Int::TWO {
+            return false;
+        }
+        for p in Self::WITNESSES.map(Int::from) {
+            if self == &p {
+                return true;
+            } else if num::Integer::is_multiple_of(self, &p) {
+                return false;
+            }
+        }
+
+        // n - 1 = d * 2^s
+        let n_1 = self.clone() - Int::ONE;
+        let s = n_1.0.trailing_zeros().unwrap_or(0);
+        let d = Int(n_1.0.clone() >> s);
+
+        'witness: for a in Self::WITNESSES.map(Int::from) {
+            let mut x = a.pow_mod(&d, self).unwrap();
+            if x.is_one() || x == n_1 {
+                continue;
+            }
+            for _ in 1..s {
+                x = (x.clone() * &x) % self;
+                if x == n_1 {
+                    continue 'witness;
+                }
+            }
+            return false;
+        }
+        true
+    }
+
+    /// smallest prime greater than self
+    pub fn next_prime(&self) -> Int {
+        if self < &Int::TWO {
+            return Int::TWO;
+        }
+        let mut n = self.clone() + Int::ONE;
+        if n.is_even() {
+            n += Int::ONE;
+        }
+        while !n.is_prime() {
+            n += Int::TWO;
+        }
+        n
+    }
+
+    /// floored modulo, the result has the sign of m
+    pub fn modulo(&self, m: &Int) -> Option<Int> {
+        match m.is_zero() {
+            true => None,
+            false => Some(num::Integer::mod_floor(self, m)),
+        }
+    }
+
+    /// x with self * x = 1 mod m, for m > 0
+    pub fn mod_inv(&self, m: &Int) -> Option<Int> {
+        if !m.is_pos() {
+            return None;
+        } else if m.is_one() {
+            return Some(Int::ZERO);
+        }
+        let x = num::Integer::mod_floor(self, m);
+        if x.is_zero() {
+            return None;
+        }
+        marith::ModInverse::mod_inverse(x.0.unsigned_abs_ref(), m.0.unsigned_abs_ref())
+            .map(|inv| Int(inv.into()))
+    }
+
+    /// self^exp mod m, for m > 0
+    ///
+    /// negative exponents use the inverse of self, if it exists
+    pub fn pow_mod(&self, exp: &Int, m: &Int) -> Option<Int> {
+        if !m.is_pos() {
+            return None;
+        }
+        let base = match exp.is_neg() {
+            true => self.mod_inv(m)?,
+            false => num::Integer::mod_floor(self, m),
+        };
+        let res = marith::ModPow::mod_pow(
+            base.0.unsigned_abs_ref().clone(),
+            exp.0.unsigned_abs_ref(),
+            m.0.unsigned_abs_ref(),
+        );
+        Some(Int(res.into()))
+    }
+
+    /// removes all factors below [Int::TRIAL_BOUND], returns the factors found and the cofactor
+    fn trial_division(&self) -> (Vec<Int>, Int) {
+        let mut n = self.abs();
+        let mut factors = vec![];
+        let mut d = 2;
+        while d < Self::TRIAL_BOUND && Int::from(d * d) <= n {
+            let p = Int::from(d);
+            while num::Integer::is_multiple_of(&n, &p) {
+                n = num::Integer::div_floor(&n, &p);
+                factors.push(p.clone());
+            }
+            d += if d == 2 { 1 } else { 2 };
+        }
+        if n > Int::ONE && Int::from(d * d) > n {
+            // no factor <= sqrt(n) left
+            factors.push(n);
+            n = Int::ONE;
+        }
+        (factors, n)
+    }
+
+    fn factorize_rec(n: Int, factors: &mut Vec<Int>) {
+        if n.is_one() {
+            return;
+        } else if n.is_prime() {
+            factors.push(n);
+            return;
+        }
+        let d = n.pollard_rho();
+        let rest = num::Integer::div_floor(&n, &d);
+        Self::factorize_rec(d, factors);
+        Self::factorize_rec(rest, factors);
+    }
+
+    /// non-trivial factor of an odd composite number, brent's variant of pollard's rho
+    fn pollard_rho(&self) -> Int {
+        const STEPS: u64 = 128;
+        let n = self;
+        let diff = |a: &Int, b: &Int| (a.clone() - b).abs();
+
+        for c in (1u32..).map(Int::from) {
+            let f = |x: &Int| (x.clone() * x + &c) % n;
+            let (mut x, mut y, mut ys) = (Int::TWO, Int::TWO, Int::TWO);
+            let (mut q, mut g) = (Int::ONE, Int::ONE);
+            let mut r = 1;
+
+            while g.is_one() {
+                x = y.clone();
+                for _ in 0..r {
+                    y = f(&y);
+                }
+                let mut k = 0;
+                while k < r && g.is_one() {
+                    ys = y.clone();
+                    for _ in 0..STEPS.min(r - k) {
+                        y = f(&y);
+                        q = (q * diff(&x, &y)) % n;
+                    }
+                    g = q.gcd(n);
+                    k += STEPS;
+                }
+                r *= 2;
+            }
+
+            if &g == n {
+                // the batch overshot, redo it one step at a time
+                loop {
+                    ys = f(&ys);
+                    g = diff(&x, &ys).gcd(n);
+                    if !g.is_one() {
+                        break;
+                    }
+                }
+            }
+            if &g != n {
+                return g;
+            }
+        }
+        unreachable!()
+    }
+
+    /// prime factors of |self| with their multiplicity, in ascending order
+    ///
+    /// small factors are found by trial division, the rest with pollard's rho
+    pub fn prime_factorize(&self) -> Vec<(Int, u32)> {
+        if self.is_zero() {
+            return vec![];
+        }
+        let (mut primes, rest) = self.trial_division();
+        Self::factorize_rec(rest, &mut primes);
+        primes.sort();
+
+        let mut factors: Vec<(Int, u32)> = vec![];
+        for p in primes {
+            match factors.last_mut() {
+                Some((q, mult)) if q == &p => *mult += 1,
+                _ => factors.push((p, 1)),
+            }
+        }
+        factors
+    }
+
+    /// euler's totient function, the number of k <= n coprime to n
+    pub fn totient(&self) -> Option<Int> {
+        if !self.is_pos() {
+            return None;
+        }
+        Some(
+            self.prime_factorize()
+                .into_iter()
+                .fold(Int::ONE, |phi, (p, k)| {
+                    phi * p.pow(&Int::from(k - 1)).unwrap() * (p - Int::ONE)
+                }),
+        )
+    }
+
+    /// positive divisors of |self| in ascending order
+    pub fn divisors(&self) -> Vec<Int> {
+        let mut divs = vec![];
+        if self.is_zero() {
+            return divs;
+        }
+        divs.push(Int::ONE);
+        for (p, k) in self.prime_factorize() {
+            let prev = divs.clone();
+            let mut pk = Int::ONE;
+            for _ in 0..k {
+                pk *= &p;
+                divs.extend(prev.iter().map(|d| d.clone() * &pk));
+            }
+        }
+        divs.sort();
+        divs
+    }
+
+    /// split |self| = outer^q * inner, such that inner has no q-th power factor
+    ///
+    /// large cofactors are only checked for being a perfect power, as factoring them is slow
+    pub fn split_power(&self, q: u32) -> (Int, Int) {
+        if self.is_zero() {
+            return (Int::ZERO, Int::ONE);
+        }
+        let (mut primes, rest) = self.trial_division();
+        let mut inner = Int::ONE;
+        if rest.bits() <= 64 {
+            Self::factorize_rec(rest, &mut primes);
+        } else {
+            let root = num_integer::Roots::nth_root(&rest, q);
+            match root.pow(&Int::from(q)) == Some(rest.clone()) {
+                true => primes.extend(std::iter::repeat_n(root, q as usize)),
+                false => inner = rest,
+            }
+        }
+        primes.sort();
+
+        let mut outer = Int::ONE;
+        for chunk in primes.chunk_by(|a, b| a == b) {
+            let (p, k) = (&chunk[0], chunk.len() as u32);
+            outer *= p.pow(&Int::from(k / q)).unwrap();
+            inner *= p.pow(&Int::from(k % q)).unwrap();
+        }
+        (outer, inner)
     }
 
     pub fn pow(&self, expon: &Self) -> Option<Self> {
         if let Ok(n) = u64::try_from(&expon.0) {
-            Some(Self(marith::Pow::pow(self.0.clone(), n)))
+            Some(Self(marith::Pow::pow(&self.0, n)))
         } else {
             None
         }
     }
-}
 
-impl num::One for Int {
-    fn one() -> Self {
-        Int::ONE
+    pub fn f64_approx(&self) -> f64 {
+        mconv::RoundingFrom::rounding_from(&self.0, RoundingMode::Nearest).0
     }
 }
 
-impl num::ToPrimitive for Int {
-    fn to_i64(&self) -> Option<i64> {
-        i64::try_from(&self.0).ok()
+impl Serialize for Int {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.0.to_string())
     }
-
-    fn to_u64(&self) -> Option<u64> {
-        u64::try_from(&self.0).ok()
+}
+impl<'de> Deserialize<'de> for Int {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        mal::Integer::from_str(&s)
+            .map(Int)
+            .map_err(|_| serde::de::Error::custom(format!("invalid integer: {s}")))
     }
+}
 
-    fn to_u128(&self) -> Option<u128> {
-        u128::try_from(&self.0).ok()
-    }
+pub fn binomial_coeff(n: &Int, k: &Int) -> Int {
+    Int::binomial_coeff(n, k)
+}
 
-    fn to_i128(&self) -> Option<i128> {
-        i128::try_from(&self.0).ok()
+impl From<Int> for Rational {
+    fn from(value: Int) -> Self {
+        Self::new_int(value)
     }
 }
 
-/*
 impl TryFrom<Rational> for Int {
     type Error = ();
     fn try_from(value: Rational) -> Result<Self, Self::Error> {
@@ -204,135 +522,45 @@ impl TryFrom<Rational> for Int {
         }
     }
 }
-*/
-*/
-
-//#[derive(Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display, RefCast)]
-//#[arith_ops(ref, self.0)]
-//#[debug("{}", self.0)]
-//#[repr(transparent)]
-//pub struct Int(i128);
-//
-//impl From<i128> for Int {
-//    fn from(value: i128) -> Self {
-//        Self(value.into())
-//    }
-//}
-//
-//impl From<Int> for Rational {
-//    fn from(value: Int) -> Self {
-//        Self::new_int(value.0)
-//    }
-//}
-//
-//impl ops::Deref for Int {
-//    type Target = i128;
-//
-//    fn deref(&self) -> &Self::Target {
-//        todo!()
-//    }
-//}
-//
-//impl Int {
-//    pub const MINUS_TWO: Self = Int::new(-2);
-//    pub const MINUS_ONE: Self = Int::new(-1);
-//    pub const ZERO: Self = Int::new(0);
-//    pub const ONE: Self = Int::new(1);
-//    pub const TWO: Self = Int::new(2);
-//
-//    pub const fn new(v: i128) -> Self {
-//        Self(v)
-//    }
-//
-//    pub fn binomial_coeff(n: i128, k: i128) -> i128 {
-//        num::integer::binomial(n, k)
-//    }
-//}
-
-pub type Int = i128;
-
-pub fn binomial_coeff(n: Int, k: Int) -> Int {
-    num::integer::binomial(n, k)
-}
 
-/*
-impl From<Int> for Rational {
-    fn from(value: Int) -> Self {
-        Self::new_int(value.0)
-    }
-}
-*/
-
-#[derive(
-    Default,
-    Copy,
-    Clone,
-    PartialEq,
-    Eq,
-    PartialOrd,
-    Ord,
-    Hash,
-    Debug,
-    Display,
-    Serialize,
-    Deserialize,
-)]
+#[derive(Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display)]
 #[arith_ops(ref, self.0)]
 #[debug("{}", self.0)]
-pub struct Rational(pub(crate) num_rational::Ratio<Int>);
-//pub struct Rational(pub(crate) mal::Rational);
+pub struct Rational(pub(crate) mal::Rational);
 
 impl Rational {
-    //pub const MINUS_TWO: Self = Rational(mal::Rational::const_from_signed(-2));
-    //pub const MINUS_ONE: Self = Rational(mal::Rational::const_from_signed(-1));
-    //pub const ZERO: Self = Rational(mal::Rational::const_from_signed(0));
-    //pub const ONE: Self = Rational(mal::Rational::const_from_signed(1));
-    //pub const TWO: Self = Rational(mal::Rational::const_from_signed(2));
-
-    pub const MINUS_TWO: Self = Rational::new_raw(-2, 1);
-    pub const MINUS_ONE: Self = Rational::new_raw(-1, 1);
-    pub const ZERO: Self = Rational::new_raw(0, 1);
-    pub const ONE: Self = Rational::new_raw(1, 1);
-    pub const TWO: Self = Rational::new_raw(2, 1);
+    pub const MINUS_TWO: Self = Rational(mal::Rational::const_from_signed(-2));
+    pub const MINUS_ONE: Self = Rational(mal::Rational::const_from_signed(-1));
+    pub const ZERO: Self = Rational(mal::Rational::const_from_signed(0));
+    pub const ONE: Self = Rational(mal::Rational::const_from_signed(1));
+    pub const TWO: Self = Rational(mal::Rational::const_from_signed(2));
 
-    const fn new_raw(n: Int, d: Int) -> Self {
-        Self(num_rational::Ratio::new_raw(n, d))
+    pub fn new(n: impl Into<Int>, d: impl Into<Int>) -> Self {
+        Self(mal::Rational::from_integers(n.into().0, d.into().0))
     }
 
-    pub fn new(n: impl Into<Int>, d: impl Into<Int>) -> Self {
-        Self(num_rational::Ratio::new(n.into(), d.into()))
+    pub fn new_int(n: impl Into<Int>) -> Self {
+        Self(mal::Rational::from(n.into().0))
     }
 
-    pub const fn new_int(n: Int) -> Self {
-        //Rational(mal::Rational::const_from_signed(n))
-        Self::new_raw(n, 1)
+    pub fn numer(&self) -> Int {
+        let n = Int(mal::Integer::from(self.0.numerator_ref().clone()));
+        match self.is_neg() {
+            true => -n,
+            false => n,
+        }
     }
 
-    pub const fn numer(&self) -> Int {
-        *self.0.numer()
-        //let sign = match self.is_neg() {
-        //    true => Int::MINUS_ONE,
-        //    false => Int::ONE,
-        //};
-        //sign * Int(mal::Integer::from(self.0.numerator_ref().clone()))
+    pub fn denom(&self) -> Int {
+        Int(mal::Integer::from(self.0.denominator_ref().clone()))
     }
 
     pub fn to_int(&self) -> Option<Int> {
-        if self.0.is_integer() {
-            Some(self.0.to_integer())
-        } else {
-            None
-        }
-        //Some(Int(mal::Integer::try_from(self.0.clone()).ok()?))
+        Some(Int(mal::Integer::try_from(&self.0).ok()?))
     }
 
     pub fn f64_approx(&self) -> f64 {
-        self.numer() as f64 / self.denom() as f64
-    }
-
-    pub const fn denom(&self) -> Int {
-        *self.0.denom()
-        //Int(mal::Integer::from(self.0.denominator_ref().clone()))
+        mconv::RoundingFrom::rounding_from(&self.0, RoundingMode::Nearest).0
     }
 
     pub fn is_min_two(&self) -> bool {
@@ -351,27 +579,19 @@ impl Rational {
         self == &Self::TWO
     }
     pub fn is_pos(&self) -> bool {
-        self.numer() > 0
-        //matches!(marith::Sign::sign(&self.0), Ordering::Greater)
+        matches!(marith::Sign::sign(&self.0), Ordering::Greater)
     }
     pub fn is_neg(&self) -> bool {
-        self.numer() < 0
-        //matches!(marith::Sign::sign(&self.0), Ordering::Less)
+        matches!(marith::Sign::sign(&self.0), Ordering::Less)
     }
     pub fn is_int(&self) -> bool {
-        self.0.is_integer()
-        //mconv::IsInteger::is_integer(&self.0)
+        mconv::IsInteger::is_integer(&self.0)
     }
     pub fn is_fraction(&self) -> bool {
         !self.is_int()
     }
     pub fn is_even(&self) -> bool {
-        if self.is_int() {
-            self.numer() % 2 == 0
-            //marith::Parity::even(self.0.numerator_ref())
-        } else {
-            false
-        }
+        self.is_int() && marith::Parity::even(self.0.numerator_ref())
     }
     pub fn is_odd(&self) -> bool {
         self.is_int() && !self.is_even()
@@ -381,35 +601,28 @@ impl Rational {
     #[inline(always)]
     pub fn inverse(self) -> Option<Self> {
         if self.is_zero() {
-            //None
             None
         } else {
-            let num = self.numer();
-            let denom = self.denom();
-            Some(Self::from((denom, num)))
+            Some(Self(marith::Reciprocal::reciprocal(self.0)))
         }
     }
 
     pub fn abs(self) -> Self {
-        Self(num::traits::abs(self.0))
+        Self(marith::Abs::abs(self.0))
     }
 
     pub fn floor(self) -> Int {
-        *self.0.floor().numer()
+        Int(marith::Floor::floor(self.0))
     }
 
+    /// split [self] into its integer quotient and the remaining fraction
+    ///
+    /// n/d -> (quot, rem/d)
     pub fn div_rem(&self) -> (Self, Self) {
         let denom = self.denom();
-        let num = self.numer();
-        //let (num, den) = self.0.to_numerator_and_denominator();
-        let (quot, rem) = num::integer::div_rem(denom, num);
-        //let (quot, rem) = marith::DivRem::div_rem(num, den);
+        let numer = self.numer();
+        let (quot, rem) = num::Integer::div_rem(&numer, &denom);
         (Self::new_int(quot), Self::new(rem, denom))
-
-        //(
-        //    Self(mal::Rational::from(quot)),
-        //    (Self(mal::Rational::from(rem)) / Self::from(denom)),
-        //)
     }
 
     /// will calculate [self] to the power of an integer number.
@@ -440,43 +653,39 @@ impl Rational {
         debug_assert!(rhs.is_pos());
 
         if rhs.is_int() {
-            let exp = rhs.numer();
-            if let Ok(exp) = usize::try_from(exp) {
-                //marith::PowAssign::pow_assign(&mut self.0, exp);
-                if let Some(pow) = num::checked_pow(self.0, exp) {
-                    return (Self(pow), Rational::ZERO);
+            return match u64::try_from(&rhs.numer().0) {
+                Ok(exp) if self.pow_bits(exp) <= Self::MAX_POW_BITS => {
+                    (Self(marith::Pow::pow(self.0, exp)), Rational::ZERO)
                 }
-            } else {
-                return (self, rhs);
-            }
+                _ => (self, rhs),
+            };
         }
 
         // ensure that the exponent is < 1
         // a^(b/c) -> ( b/c -> quot + rem ) -> a^quot * a^rem  // apply the quotient
-        if rhs.numer() > rhs.denom() {
-            //let (num, den) = rhs.0.to_numerator_and_denominator();
-            let numer = rhs.numer();
-            let denom = rhs.denom();
-            let (quot, rem) = num::integer::div_rem(numer, denom); //marith::DivRem::div_rem(num, den);
-                                                                   //let rem_exp = Self(mal::Rational::from(rem));
-            let rem_exp = Self::new_int(rem);
-            if let Ok(apply_exp) = usize::try_from(quot) {
-                if let Some(pow) = num::checked_pow(self.0, apply_exp) {
-                    return (Self(pow), rem_exp);
+        if rhs > Rational::ONE {
+            let (quot, rem_exp) = rhs.div_rem();
+            if let Ok(apply_exp) = u64::try_from(&quot.numer().0) {
+                if self.pow_bits(apply_exp) <= Self::MAX_POW_BITS {
+                    return (Self(marith::Pow::pow(self.0, apply_exp)), rem_exp);
                 }
             }
-            //self.0.pow(quot)
-
-            //if let Ok(apply_exp) = u64::try_from(&quot) {
-            //    marith::PowAssign::pow_assign(&mut self.0, apply_exp);
-            //    return (self, rem_exp);
-            //}
         }
 
         // no change
         (self, rhs)
     }
 
+    /// powers with more bits are left symbolic, e.g 2^(10^15)
+    const MAX_POW_BITS: u64 = 1 << 20;
+
+    /// an estimate of the bits of numerator and denominator of [self]^exp
+    fn pow_bits(&self, exp: u64) -> u64 {
+        // n^exp has about exp * log2(n) bits, powers of 0 and ±1 stay small
+        let log2 = |i: Int| i.bits().saturating_sub(1);
+        (log2(self.numer()) + log2(self.denom())).saturating_mul(exp)
+    }
+
     pub fn pow_basic(self, rhs: Self) -> Option<Self> {
         let (pow, rest) = self.pow(rhs);
         if rest != Rational::ZERO {
@@ -487,8 +696,6 @@ impl Rational {
     }
 
     pub fn int_gcd(&self, rhs: &Self) -> Option<Rational> {
-        use num::Integer;
-
         if self.denom() == rhs.denom() {
             let n_gcd = Rational::from(self.numer().gcd(&rhs.numer()));
             Some(n_gcd / Rational::from(self.denom()))
@@ -498,26 +705,6 @@ impl Rational {
     }
 }
 
-/*
-impl Serialize for Int {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        serializer.serialize_str(&self.0.to_string())
-    }
-}
-impl<'de> Deserialize<'de> for Int {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        let s = String::deserialize(deserializer)?;
-        Ok(Self(mal::Integer::from_str(&s).unwrap()))
-    }
-}
-*/
-/*
 impl Serialize for Rational {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -532,40 +719,35 @@ impl<'de> Deserialize<'de> for Rational {
         D: serde::Deserializer<'de>,
     {
         let s = String::deserialize(deserializer)?;
-        Ok(Self(mal::Rational::from_str(&s).unwrap()))
+        mal::Rational::from_str(&s)
+            .map(Rational)
+            .map_err(|_| serde::de::Error::custom(format!("invalid rational: {s}")))
     }
 }
-*/
-
-//impl<I: Into<i128>> From<I> for Rational {
-//    fn from(value: I) -> Self {
-//        Self::new_int(value.into())
-//    }
-//}
 
 impl From<i128> for Rational {
     fn from(value: i128) -> Self {
-        Self::new_int(value.into())
+        Self::new_int(value)
     }
 }
 impl From<i64> for Rational {
     fn from(value: i64) -> Self {
-        Self::new_int(value.into())
+        Self::new_int(value)
     }
 }
 impl From<u64> for Rational {
     fn from(value: u64) -> Self {
-        Self::new_int(value.into())
+        Self::new_int(value)
     }
 }
 impl From<i32> for Rational {
     fn from(value: i32) -> Self {
-        Self::new_int(value.into())
+        Self::new_int(value)
     }
 }
 impl From<u32> for Rational {
     fn from(value: u32) -> Self {
-        Self::new_int(value.into())
+        Self::new_int(value)
     }
 }
 impl From<(i128, i128)> for Rational {
@@ -578,3 +760,110 @@ impl From<(i32, i32)> for Rational {
         Self::new(value.0, value.1)
     }
 }
+impl From<(Int, Int)> for Rational {
+    fn from(value: (Int, Int)) -> Self {
+        Self::new(value.0, value.1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use assert_eq as eq;
+
+    #[test]
+    fn big_pow() {
+        let (pow, rem) = Rational::TWO.pow(Rational::from(200));
+        eq!(rem, Rational::ZERO);
+        eq!(
+            pow.to_string(),
+            "1606938044258990275541962092341162602522202993782792835301376"
+        );
+        eq!(pow.clone() / pow, Rational::ONE);
+
+        let (pow, rem) = Rational::from((2, 3)).pow(Rational::from((-7, 2)));
+        eq!(pow, Rational::from((27, 8)));
+        eq!(rem, Rational::from((1, 2)));
+
+        // too large to compute, stays symbolic
+        let huge = Rational::from(10i64.pow(15));
+        eq!(
+            Rational::TWO.pow(huge.clone()),
+            (Rational::TWO, huge.clone())
+        );
+        eq!(
+            Rational::ONE.pow(huge.clone()),
+            (Rational::ONE, Rational::ZERO)
+        );
+        eq!(
+            Rational::MINUS_ONE.pow(huge.clone() + Rational::ONE),
+            (Rational::MINUS_ONE, Rational::ZERO)
+        );
+        let (_, rem) = Rational::from(3).pow(huge / Rational::TWO + Rational::from((1, 2)));
+        eq!(
+            rem,
+            Rational::from((1, 2)) + Rational::from(10i64.pow(15) / 2)
+        );
+    }
+
+    #[test]
+    fn big_binomial() {
+        let c = binomial_coeff(&Int::from(200), &Int::from(100));
+        eq!(
+            c.to_string(),
+            "90548514656103281165404177077484163874504589675413336841320"
+        );
+    }
+
+    #[test]
+    fn prime_factorize() {
+        let factors = |n: i64| -> Vec<(i64, u32)> {
+            Int::from(n)
+                .prime_factorize()
+                .into_iter()
+                .map(|(p, k)| (i64::try_from(&p.0).unwrap(), k))
+                .collect()
+        };
+        eq!(factors(1), vec![]);
+        eq!(factors(-12), vec![(2, 2), (3, 1)]);
+        eq!(factors(1009 * 1009 * 7), vec![(7, 1), (1009, 2)]);
+        // semiprime out of reach of trial division
+        eq!(
+            factors(1_000_003 * 998_244_353),
+            vec![(1_000_003, 1), (998_244_353, 1)]
+        );
+
+        // 2^127 - 1
+        let n: Int = "170141183460469231731687303715884105727".parse().unwrap();
+        assert!(n.is_prime());
+        assert!(!(n.clone() * Int::from(3)).is_prime());
+        // carmichael number
+        assert!(!Int::from(561).is_prime());
+        eq!(Int::from(89).next_prime(), Int::from(97));
+        eq!(Int::from(-5).next_prime(), Int::TWO);
+    }
+
+    #[test]
+    fn number_theory() {
+        let i = Int::from;
+        eq!(i(36).totient(), Some(i(12)));
+        eq!(i(0).totient(), None);
+        eq!(i(12).divisors(), [1, 2, 3, 4, 6, 12].map(i));
+        eq!(i(-7).modulo(&i(3)), Some(i(2)));
+        eq!(i(7).modulo(&i(-3)), Some(i(-2)));
+        eq!(i(7).modulo(&i(0)), None);
+        eq!(i(3).mod_inv(&i(7)), Some(i(5)));
+        eq!(i(4).mod_inv(&i(8)), None);
+        eq!(i(2).pow_mod(&i(10), &i(1000)), Some(i(24)));
+        eq!(i(3).pow_mod(&i(-1), &i(7)), Some(i(5)));
+        eq!(i(72).split_power(2), (i(6), i(2)));
+        eq!(i(72).split_power(3), (i(2), i(9)));
+    }
+
+    #[test]
+    fn serde() {
+        let r = Rational::new(Int::TWO.pow(&Int::from(130)).unwrap(), -3);
+        let json = serde_json::to_string(&r).unwrap();
+        eq!(serde_json::from_str::<Rational>(&json).unwrap(), r);
+    }
+}
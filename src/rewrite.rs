@@ -0,0 +1,431 @@
+//! Rewrite rules in Wolfram syntax, loaded at runtime
+//!
+//! A rule file contains definitions like `Sin[x_]^2 + Cos[x_]^2 := 1` or
+//! `Sin[x_]^2 := 1 - Cos[x]^2 /; FreeQ[x, y]`. The left hand sides are matched like the
+//! compiled integration rules of [crate::rubi], the right hand sides and conditions are
+//! evaluated with the same utility functions, e.g `FreeQ`, `IntegerQ` or `Simp`. A sum or
+//! product on the left also matches part of a larger one, so the first rule rewrites
+//! `sin(a)^2 + cos(a)^2 + y` to `1 + y`.
+
+use std::{fmt, io, path::Path};
+
+use crate::atom::{Expr, SymbolicExpr};
+use crate::rubi::{Bindings, Name, Pattern, WlfrmAtom, WlfrmBuiltins, WlfrmFuncArgs};
+use crate::utils::HashMap;
+use crate::wolfram::{self, OpKind, Pat, SymExpr, WolframError};
+
+/// rewrites of one expression before giving up, rules can rewrite an expression back to itself
+const MAX_STEPS: usize = 64;
+
+#[derive(Debug)]
+pub enum RuleError {
+    Parse(WolframError),
+    /// e.g an unknown function or an operator that has no builtin
+    Unsupported(String),
+    Io(io::Error),
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleError::Parse(err) => write!(f, "{err}"),
+            RuleError::Unsupported(what) => write!(f, "unsupported rule: {what}"),
+            RuleError::Io(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for RuleError {}
+
+impl From<WolframError> for RuleError {
+    fn from(value: WolframError) -> Self {
+        RuleError::Parse(value)
+    }
+}
+
+impl From<io::Error> for RuleError {
+    fn from(value: io::Error) -> Self {
+        RuleError::Io(value)
+    }
+}
+
+type Result<T> = std::result::Result<T, RuleError>;
+
+type Builtin = fn(WlfrmFuncArgs) -> WlfrmAtom;
+
+/// the right hand side or condition of a rule, with the builtins resolved when loading
+#[derive(Debug, Clone)]
+enum Term {
+    /// a pattern name or a variable of `With`
+    Var(Name),
+    Atom(WlfrmAtom),
+    Call(Builtin, Vec<Term>),
+    And(Box<Term>, Box<Term>),
+    Or(Box<Term>, Box<Term>),
+    List(Vec<Term>),
+    With(Vec<(Name, Term)>, Box<Term>),
+    /// [WlfrmAtom::None] if the condition is not `True`
+    Cond(Box<Term>, Box<Term>),
+}
+
+type Env = HashMap<Name, WlfrmAtom>;
+
+impl Term {
+    fn eval(&self, env: &Env) -> WlfrmAtom {
+        match self {
+            Term::Var(n) => env[n].clone(),
+            Term::Atom(a) => a.clone(),
+            Term::Call(f, args) => {
+                let mut args: Vec<_> = args.iter().map(|a| a.eval(env)).collect();
+                f(match args.len() {
+                    1 => WlfrmFuncArgs::One(args.pop().unwrap()),
+                    2 => WlfrmFuncArgs::Two(args.try_into().unwrap()),
+                    3 => WlfrmFuncArgs::Three(args.try_into().unwrap()),
+                    _ => WlfrmFuncArgs::Var(args),
+                })
+            }
+            Term::And(l, r) => {
+                WlfrmAtom::from(l.eval(env) == WlfrmAtom::TRUE && r.eval(env) == WlfrmAtom::TRUE)
+            }
+            Term::Or(l, r) => {
+                WlfrmAtom::from(l.eval(env) == WlfrmAtom::TRUE || r.eval(env) == WlfrmAtom::TRUE)
+            }
+            Term::List(l) => WlfrmAtom::List(l.iter().map(|a| a.eval(env)).collect()),
+            Term::With(vars, body) => {
+                let mut env = env.clone();
+                for (n, val) in vars {
+                    let val = val.eval(&env);
+                    env.insert(n.clone(), val);
+                }
+                body.eval(&env)
+            }
+            Term::Cond(body, cond) => match cond.eval(env) == WlfrmAtom::TRUE {
+                true => body.eval(env),
+                false => WlfrmAtom::None,
+            },
+        }
+    }
+}
+
+/// the constants of Wolfram, any other symbol is a variable
+fn symbol(id: &str) -> WlfrmAtom {
+    match id {
+        "Pi" => Expr::pi().into(),
+        "E" => Expr::e().into(),
+        "I" => Expr::i().into(),
+        "True" => WlfrmAtom::TRUE,
+        "False" => WlfrmAtom::FALSE,
+        _ => Expr::var(id).into(),
+    }
+}
+
+fn pattern(p: &Pat) -> Result<Pattern> {
+    let patterns = |ps: &[Pat]| ps.iter().map(pattern).collect::<Result<Vec<_>>>();
+    Ok(match p {
+        Pat::Sym(x) => Pattern::Sym(x.clone().into()),
+        Pat::Blank(a) => Pattern::Blank(a.clone().into()),
+        Pat::Opt(a) => Pattern::Opt(a.clone().into()),
+        Pat::Num(n) => Pattern::Lit(Expr::rational((n.0 as i128, n.1 as i128))),
+        Pat::Ident(id) => match symbol(id) {
+            WlfrmAtom::Expr(e) => Pattern::Lit(e),
+            _ => return Err(RuleError::Unsupported(format!("{id} in pattern"))),
+        },
+        Pat::Func(f, args) => Pattern::Func(f.clone().into(), patterns(args)?),
+        Pat::Sum(args) => Pattern::Sum(patterns(args)?),
+        Pat::Prod(args) => Pattern::Prod(patterns(args)?),
+        Pat::Pow(b, e) => Pattern::Pow(Box::new([pattern(b)?, pattern(e)?])),
+    })
+}
+
+/// resolves the builtins of the right hand side and condition of a rule
+struct Compiler {
+    /// the pattern names and variables of `With` in scope
+    vars: Vec<String>,
+}
+
+impl Compiler {
+    fn terms(&mut self, es: &[SymExpr]) -> Result<Vec<Term>> {
+        es.iter().map(|e| self.term(e)).collect()
+    }
+
+    fn builtin(name: &str, args: Vec<Term>) -> Result<Term> {
+        match WlfrmBuiltins::get(name) {
+            Some(f) => Ok(Term::Call(f, args)),
+            None => Err(RuleError::Unsupported(format!("unknown function: {name}"))),
+        }
+    }
+
+    fn term(&mut self, e: &SymExpr) -> Result<Term> {
+        use OpKind as O;
+        use SymExpr as S;
+        Ok(match e {
+            S::Ident(id) if self.vars.contains(id) => Term::Var(id.clone().into()),
+            S::Ident(id) => Term::Atom(symbol(id)),
+            S::Integer(n) => Term::Atom(Expr::rational(*n as i128).into()),
+            S::Float(f) => Term::Atom(Expr::float(f.0).into()),
+            S::UnryOp(O::Sub, e) => Self::builtin("Minus", vec![self.term(e)?])?,
+            S::BinOp(O::And, l, r) => Term::And(self.term(l)?.into(), self.term(r)?.into()),
+            S::BinOp(O::Or, l, r) => Term::Or(self.term(l)?.into(), self.term(r)?.into()),
+            S::BinOp(op, l, r) => {
+                let name = match op {
+                    O::Add => "Plus",
+                    O::Sub => "Subtract",
+                    O::Mul => "Times",
+                    O::Div => "Divide",
+                    O::Pow => "Power",
+                    O::Eq => "EqQ",
+                    O::NEq => "NeQ",
+                    O::Lt => "LtQ",
+                    O::Ge => "GtQ",
+                    O::LtEq => "LeQ",
+                    O::GeEq => "GeQ",
+                    op => return Err(RuleError::Unsupported(format!("operator {op:?}"))),
+                };
+                Self::builtin(name, vec![self.term(l)?, self.term(r)?])?
+            }
+            S::List(l) => Term::List(self.terms(&l.args)?),
+            S::FuncCall(call) => Self::builtin(&call.name, self.terms(&call.args)?)?,
+            S::Scope(wolfram::Scope {
+                kind: wolfram::ScopeKind::With,
+                vars,
+                pat,
+            }) => {
+                let n_vars = self.vars.len();
+                let res = self.with(&vars.args, pat);
+                self.vars.truncate(n_vars);
+                res?
+            }
+            e => return Err(RuleError::Unsupported(format!("{e:?}"))),
+        })
+    }
+
+    fn with(&mut self, vars: &[SymExpr], body: &wolfram::Pattern) -> Result<Term> {
+        let mut lets = vec![];
+        for v in vars {
+            let SymExpr::BinOp(OpKind::Assign, name, val) = v else {
+                return Err(RuleError::Unsupported(format!("With variable {v:?}")));
+            };
+            let SymExpr::Ident(name) = &**name else {
+                return Err(RuleError::Unsupported(format!("With variable {v:?}")));
+            };
+            lets.push((name.clone().into(), self.term(val)?));
+            self.vars.push(name.clone());
+        }
+        Ok(Term::With(lets, self.pattern(body)?.into()))
+    }
+
+    fn pattern(&mut self, pat: &wolfram::Pattern) -> Result<Term> {
+        let body = self.term(&pat.pat)?;
+        Ok(match &pat.cond {
+            None => body,
+            Some(cond) => Term::Cond(body.into(), self.term(cond)?.into()),
+        })
+    }
+}
+
+/// a rule `lhs := rhs /; cond`
+#[derive(Debug, Clone)]
+pub struct Rule {
+    lhs: Pattern,
+    rhs: Term,
+}
+
+impl Rule {
+    fn new(lhs: &SymExpr, rhs: &wolfram::Pattern) -> Result<Self> {
+        let lhs = Pat::new(lhs).map_err(RuleError::Unsupported)?;
+        let mut vars = vec![];
+        lhs.names(&mut vars);
+        let rhs = Compiler { vars }.pattern(rhs)?;
+        Ok(Rule {
+            lhs: pattern(&lhs)?,
+            rhs,
+        })
+    }
+
+    /// the right hand side for the first match of `e` that meets the condition
+    ///
+    /// A sum or product on the left can match some of the terms of `e`, the others are kept
+    fn apply(&self, e: &Expr) -> Option<Expr> {
+        let mut res = None;
+        self.lhs
+            .matches_part(e, &Bindings::default(), &mut |b, rest| {
+                let env = b
+                    .iter()
+                    .map(|(n, e)| (n.clone(), WlfrmAtom::from(e.clone())))
+                    .collect();
+                match self.rhs.eval(&env) {
+                    WlfrmAtom::Expr(r) if !r.is_undef() => {
+                        let r = rest.iter().fold(r, |r, t| match self.lhs {
+                            Pattern::Prod(_) => r * t,
+                            _ => r + t,
+                        });
+                        res = Some(r.reduce());
+                        true
+                    }
+                    _ => false,
+                }
+            });
+        res
+    }
+}
+
+/// rules loaded from Wolfram source, see [crate::rewrite]
+#[derive(Debug, Clone, Default)]
+pub struct RuleSet {
+    rules: Vec<Rule>,
+}
+
+impl RuleSet {
+    /// the rules defined in `src`, every top level expression has to be a definition
+    pub fn parse(src: &str) -> Result<Self> {
+        let files = wolfram::parse(src)?;
+        let mut rules = vec![];
+        for e in files.exprs() {
+            let rule = match e {
+                SymExpr::FuncDef(fd) => Rule::new(&SymExpr::FuncCall(fd.call.clone()), &fd.pat)?,
+                SymExpr::SetDelayed(lhs, rhs) => Rule::new(lhs, rhs)?,
+                e => return Err(RuleError::Unsupported(format!("{e:?} is not a rule"))),
+            };
+            rules.push(rule);
+        }
+        Ok(Self { rules })
+    }
+
+    /// the rules of a `.m` file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// rewrites the subexpressions of `e` bottom up with the first rule that changes them
+    fn rewrite(&self, e: &Expr) -> Expr {
+        let mut e = e.clone();
+        e.args_mut().iter_mut().for_each(|a| *a = self.rewrite(a));
+        let e = e.reduce();
+        self.rules
+            .iter()
+            .find_map(|r| r.apply(&e).filter(|r| r != &e))
+            .unwrap_or(e)
+    }
+
+    /// rewrites `e` until no rule applies
+    pub fn apply(&self, e: &Expr) -> Expr {
+        let mut e = e.reduce();
+        for _ in 0..MAX_STEPS {
+            let next = self.rewrite(&e);
+            if next == e {
+                break;
+            }
+            e = next;
+        }
+        e
+    }
+}
+
+impl Expr {
+    /// [self] rewritten with the `rules` until none of them applies, see [RuleSet]
+    pub fn rewrite(&self, rules: &RuleSet) -> Expr {
+        rules.apply(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use assert_eq as eq;
+    use calcurs_macros::expr as e;
+
+    fn rules(src: &str) -> RuleSet {
+        RuleSet::parse(src).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    #[test]
+    fn pythagorean_identity() {
+        let r = rules("Sin[x_]^2 + Cos[x_]^2 := 1");
+        eq!(r.len(), 1);
+        eq!(e!(sin(y) ^ 2 + cos(y) ^ 2).rewrite(&r), e!(1));
+        eq!(e!(sin(y) ^ 2 + z + cos(y) ^ 2).rewrite(&r), e!(1 + z));
+        eq!(
+            e!(sin(a) ^ 2 + cos(a) ^ 2 + sin(b) ^ 2 + cos(b) ^ 2).rewrite(&r),
+            e!(2)
+        );
+        eq!(
+            e!(sin(y) ^ 2 + cos(z) ^ 2).rewrite(&r),
+            e!(sin(y) ^ 2 + cos(z) ^ 2)
+        );
+        // the rule is applied to subexpressions
+        eq!(
+            e!(arc_tan(sin(y) ^ 2 + cos(y) ^ 2) * y).rewrite(&r),
+            e!(arc_tan(1) * y)
+        );
+    }
+
+    #[test]
+    fn partial_products() {
+        let r = rules("Sin[x_]*Csc[x_] := 1");
+        eq!(e!(2 * y * sin(z) * csc(z)).rewrite(&r), e!(2 * y));
+        eq!(e!(sin(z) * csc(y)).rewrite(&r), e!(sin(z) * csc(y)));
+    }
+
+    #[test]
+    fn conditions() {
+        let r = rules(
+            "(* Sin[y]^2 stays *)
+            Sin[x_]^2 := 1 - Cos[x]^2 /; FreeQ[x, y]
+            Cos[n_*x_] := 2*Cos[x]^2 - 1 /; EqQ[n, 2] && AtomQ[x]",
+        );
+        eq!(r.len(), 2);
+        eq!(e!(sin(z) ^ 2).rewrite(&r), e!(1 - cos(z) ^ 2));
+        eq!(e!(sin(y) ^ 2).rewrite(&r), e!(sin(y) ^ 2));
+        eq!(e!(cos(2 * z)).rewrite(&r), e!(2 * cos(z) ^ 2 - 1));
+        eq!(e!(cos(3 * z)).rewrite(&r), e!(cos(3 * z)));
+    }
+
+    #[test]
+    fn function_heads() {
+        let r = rules(
+            "Tan[ArcTan[x_]] := x
+            ArcSin[u_] := With[{v = 2*u}, v/2 + ArcCos[u]]",
+        );
+        eq!(e!(tan(arc_tan(a + b))).rewrite(&r), e!(a + b));
+        eq!(e!(arc_sin(y)).rewrite(&r), e!(y + arc_cos(y)));
+        eq!(e!(tan(arc_cos(y))).rewrite(&r), e!(tan(arc_cos(y))));
+    }
+
+    #[test]
+    fn errors() {
+        let err = RuleSet::parse("Sin[x_ := x").unwrap_err();
+        assert!(matches!(err, RuleError::Parse(_)), "{err}");
+        let err = RuleSet::parse("Sin[x_] := Foo[x]").unwrap_err();
+        assert!(
+            matches!(&err, RuleError::Unsupported(f) if f.contains("Foo")),
+            "{err}"
+        );
+        let err = RuleSet::parse("Sin[x_] + 1").unwrap_err();
+        assert!(matches!(err, RuleError::Unsupported(_)), "{err}");
+        let err = RuleSet::load("does/not/exist.m").unwrap_err();
+        assert!(matches!(err, RuleError::Io(_)), "{err}");
+    }
+
+    #[test]
+    fn load() {
+        // tests run in parallel, possibly in several processes
+        let name = format!("calcurs_rewrite_load_{}.m", std::process::id());
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(
+            &path,
+            "(* ::Subsection:: *)\nCos[x_]^2 := (1 + Cos[2*x])/2\n",
+        )
+        .unwrap();
+        let r = RuleSet::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        eq!(e!(cos(y) ^ 2).rewrite(&r), e!((1 + cos(2 * y)) / 2));
+    }
+}
@@ -1,18 +1,368 @@
-use crate::atom::{Atom, Expr, Real};
-use crate::rational::Rational;
+use std::{borrow::Cow, cell::Cell, cmp};
 
-//calcurs_macros::integration_rules!();
+use crate::atom::{Atom, Expr, Func, Irrational, Prod, Real, Sum, SymbolicExpr};
+use crate::polynomial::VarSet;
+use crate::rational::{Int, Rational};
+use crate::solve::fresh_var;
+use crate::utils::HashMap;
+
+calcurs_macros::integration_rules!("1.1");
+
+/// recursive [Expr::integrate] calls before giving up, rules can rewrite an integrand into one
+/// that leads back to it
+const MAX_DEPTH: usize = 12;
+
+thread_local! {
+    static RULES: Vec<IntRule> = rubi_rules::integration_rules();
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// the name of a pattern, static for the compiled rules and owned for rules loaded at runtime
+pub(crate) type Name = Cow<'static, str>;
+
+/// the expressions bound to the pattern names of a rule
+pub(crate) type Bindings = HashMap<Name, Expr>;
+
+/// the left hand side of a rule, e.g `(a_. + b_.*x_)^m_` is
+/// `Pow([Sum([Opt("a"), Prod([Opt("b"), Blank("x")])]), Blank("m")])`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Pattern {
+    /// a variable, `x_Symbol`
+    Sym(Name),
+    /// any expression, `a_`
+    Blank(Name),
+    /// any expression or the identity of the surrounding sum, product or power, `a_.`
+    Opt(Name),
+    Lit(Expr),
+    /// a function and its arguments, e.g `Sin[x_]`
+    Func(Name, Vec<Pattern>),
+    Sum(Vec<Pattern>),
+    Prod(Vec<Pattern>),
+    /// [base, exponent]
+    Pow(Box<[Pattern; 2]>),
+}
+
+fn bind(name: &Name, e: &Expr, b: &Bindings, k: &mut dyn FnMut(&Bindings) -> bool) -> bool {
+    match b.get(name) {
+        Some(v) => v == e && k(b),
+        None => {
+            let mut b = b.clone();
+            b.insert(name.clone(), e.clone());
+            k(&b)
+        }
+    }
+}
+
+fn without<T: Clone>(items: &[T], i: usize) -> Vec<T> {
+    let mut items = items.to_vec();
+    items.remove(i);
+    items
+}
+
+impl Pattern {
+    fn is_unbound(&self, b: &Bindings) -> bool {
+        matches!(self, Pattern::Blank(n) | Pattern::Opt(n) if !b.contains_key(n))
+    }
+
+    /// calls `k` for every way [self] matches `e` until it returns true
+    pub(crate) fn matches(
+        &self,
+        e: &Expr,
+        b: &Bindings,
+        k: &mut dyn FnMut(&Bindings) -> bool,
+    ) -> bool {
+        use Pattern as P;
+        match self {
+            P::Sym(n) => e.is_var() && bind(n, e, b, k),
+            P::Blank(n) | P::Opt(n) => bind(n, e, b, k),
+            P::Lit(l) => l == e && k(b),
+            P::Func(name, ps) => match e.atom() {
+                Atom::Func(f) if wlfrm_head(f).is_some_and(|h| h == name) => {
+                    Self::matches_seq(ps, f.args(), b, k)
+                }
+                _ => false,
+            },
+            P::Pow(p) => {
+                let [base, exp] = &**p;
+                if let Atom::Pow(pow) = e.atom() {
+                    let found = base.matches(pow.base(), b, &mut |b| {
+                        exp.matches(pow.exponent(), b, &mut *k)
+                    });
+                    if found {
+                        return true;
+                    }
+                }
+                match exp {
+                    P::Opt(n) => base.matches(e, b, &mut |b| bind(n, &Expr::one(), b, &mut *k)),
+                    _ => false,
+                }
+            }
+            P::Sum(ps) | P::Prod(ps) => {
+                let sum = matches!(self, P::Sum(_));
+                let terms = match (e.atom(), sum) {
+                    (Atom::Sum(Sum { args }), true) | (Atom::Prod(Prod { args }), false) => args,
+                    _ => std::slice::from_ref(e),
+                };
+                Self::matches_flat(ps, terms, sum, b, &mut |b, rest| rest.is_empty() && k(b))
+            }
+        }
+    }
+
+    /// like [Pattern::matches], but a sum or product pattern can also match some of the terms
+    /// of a sum or product, e.g `Sin[x_]^2 + Cos[x_]^2` matches `sin(a)^2 + cos(a)^2 + y`
+    ///
+    /// `k` gets the terms that were not matched
+    pub(crate) fn matches_part(
+        &self,
+        e: &Expr,
+        b: &Bindings,
+        k: &mut dyn FnMut(&Bindings, &[Expr]) -> bool,
+    ) -> bool {
+        use Pattern as P;
+        match (self, e.atom()) {
+            (P::Sum(ps), Atom::Sum(Sum { args })) | (P::Prod(ps), Atom::Prod(Prod { args })) => {
+                let sum = matches!(self, P::Sum(_));
+                Self::matches_flat(ps, args, sum, b, &mut |b, rest| {
+                    rest.len() < args.len() && k(b, rest)
+                })
+            }
+            _ => self.matches(e, b, &mut |b| k(b, &[])),
+        }
+    }
+
+    /// matches the patterns to the arguments of a function in order
+    fn matches_seq(
+        ps: &[Pattern],
+        args: &[Expr],
+        b: &Bindings,
+        k: &mut dyn FnMut(&Bindings) -> bool,
+    ) -> bool {
+        match (ps, args) {
+            ([], []) => k(b),
+            ([p, ps @ ..], [a, args @ ..]) => {
+                p.matches(a, b, &mut |b| Self::matches_seq(ps, args, b, &mut *k))
+            }
+            _ => false,
+        }
+    }
+
+    /// matches the patterns to the terms of a sum or product in any order, `k` gets the terms
+    /// that are left over
+    ///
+    /// The last unbound blank takes all remaining terms, e.g `a_ + b_.*x_` matches
+    /// `1 + y + 2*x` with a = 1 + y. Optional patterns can be absent
+    fn matches_flat(
+        ps: &[Pattern],
+        terms: &[Expr],
+        sum: bool,
+        b: &Bindings,
+        k: &mut dyn FnMut(&Bindings, &[Expr]) -> bool,
+    ) -> bool {
+        use Pattern as P;
+        let id = || if sum { Expr::zero() } else { Expr::one() };
+        // patterns that can't take an arbitrary term are tried first
+        let Some(i) = ps.iter().position(|p| !p.is_unbound(b)).or(match ps {
+            [] => None,
+            _ => Some(0),
+        }) else {
+            return k(b, terms);
+        };
+        let (p, rest) = (&ps[i], without(ps, i));
+
+        if let (P::Blank(n) | P::Opt(n), true) = (p, rest.is_empty() && p.is_unbound(b)) {
+            let k = &mut |b: &Bindings| k(b, &[]);
+            return match terms {
+                [] => matches!(p, P::Opt(_)) && bind(n, &id(), b, k),
+                [t] => bind(n, t, b, k),
+                _ if sum => bind(
+                    n,
+                    &Sum {
+                        args: terms.to_vec(),
+                    }
+                    .into(),
+                    b,
+                    k,
+                ),
+                _ => bind(
+                    n,
+                    &Prod {
+                        args: terms.to_vec(),
+                    }
+                    .into(),
+                    b,
+                    k,
+                ),
+            };
+        }
+
+        for j in 0..terms.len() {
+            let rest_terms = without(terms, j);
+            let found = p.matches(&terms[j], b, &mut |b| {
+                Self::matches_flat(&rest, &rest_terms, sum, b, &mut *k)
+            });
+            if found {
+                return true;
+            }
+        }
+        match p {
+            P::Opt(n) => bind(n, &id(), b, &mut |b| {
+                Self::matches_flat(&rest, terms, sum, b, &mut *k)
+            }),
+            _ => false,
+        }
+    }
+}
+
+/// an `Int[pat, x_Symbol] := rhs /; cond` rule generated by [calcurs_macros::integration_rules]
+pub(crate) struct IntRule {
+    /// the rule file and the position in it, e.g `1.1.1.1 (a+b x)^m: 2`
+    pub name: &'static str,
+    /// the name of the integration variable in the pattern
+    pub var: &'static str,
+    pub pat: Pattern,
+    /// returns [WlfrmAtom::None] if the condition is not met or the result contains an
+    /// integral that could not be solved
+    pub apply: fn(&Bindings) -> WlfrmAtom,
+}
+
+impl IntRule {
+    fn apply(&self, e: &Expr, x: &Expr) -> Option<Expr> {
+        let mut b = Bindings::default();
+        b.insert(self.var.into(), x.clone());
+        let mut res = None;
+        self.pat.matches(e, &b, &mut |b| match (self.apply)(b) {
+            WlfrmAtom::Expr(r) if !r.is_undef() => {
+                res = Some(r);
+                true
+            }
+            _ => false,
+        });
+        if res.is_some() {
+            log::trace!("rubi: {}", self.name);
+        }
+        res
+    }
+}
+
+/// the name of `f` in Wolfram, e.g `Sin` or `Log` for the natural logarithm
+fn wlfrm_head(f: &Func) -> Option<&'static str> {
+    use Func as F;
+    Some(match f {
+        F::Sin(_) => "Sin",
+        F::ArcSin(_) => "ArcSin",
+        F::Cos(_) => "Cos",
+        F::ArcCos(_) => "ArcCos",
+        F::Tan(_) => "Tan",
+        F::ArcTan(_) => "ArcTan",
+        F::Sec(_) => "Sec",
+        F::ArcSec(_) => "ArcSec",
+        F::Cot(_) => "Cot",
+        F::ArcCot(_) => "ArcCot",
+        F::Csc(_) => "Csc",
+        F::ArcCsc(_) => "ArcCsc",
+        F::Re(_) => "Re",
+        F::Im(_) => "Im",
+        F::Conj(_) => "Conjugate",
+        F::Arg(_) => "Arg",
+        F::IsPrime(_) => "PrimeQ",
+        F::NextPrime(_) => "NextPrime",
+        F::Totient(_) => "EulerPhi",
+        F::Divisors(_) => "Divisors",
+        F::Mod(_) => "Mod",
+        F::PowMod(_) => "PowerMod",
+        F::ModInv(_) => "ModularInverse",
+        F::Log(Real::Irrational(Irrational::E), _) => "Log",
+        F::Log(..) | F::RootOf(_) | F::Order(_) => return None,
+    })
+}
+
+/// distributes integer powers of products, e.g 1/(x (x + 1)) = x^-1 (x + 1)^-1, which is
+/// how the rules expect them
+fn rubi_form(e: &Expr) -> Expr {
+    let mut e = e.clone();
+    e.args_mut().iter_mut().for_each(|a| *a = rubi_form(a));
+    match e.atom() {
+        Atom::Pow(pow) if pow.exponent().is_int() && pow.base().is_prod() => {
+            let exp = pow.exponent();
+            let args = pow
+                .base()
+                .args()
+                .iter()
+                .map(|a| rubi_form(&Expr::pow(a, exp)));
+            Prod {
+                args: args.collect(),
+            }
+            .into()
+        }
+        _ => e,
+    }
+}
+
+fn integrate(e: &Expr, x: &Expr) -> Option<Expr> {
+    if e.free_of(x) {
+        return Some(e * x);
+    }
+    match e.atom() {
+        Atom::Sum(Sum { args }) => {
+            return args
+                .iter()
+                .map(|a| a.integrate(x))
+                .try_fold(Expr::zero(), |sum, a| Some(sum + a?));
+        }
+        Atom::Prod(Prod { args }) if args.iter().any(|a| a.free_of(x)) => {
+            let (c, f): (Vec<_>, Vec<_>) = args.iter().cloned().partition(|a| a.free_of(x));
+            let (c, f) = (Prod { args: c }, Prod { args: f });
+            return Some(Expr::from(c) * Expr::from(f).integrate(x)?);
+        }
+        _ => (),
+    }
+
+    let res = RULES.with(|rules| rules.iter().find_map(|r| r.apply(e, x)));
+    if res.is_some() {
+        return res;
+    }
+    // e.g (x + 1)^2 (x + 2) is integrated term by term
+    let expanded = e.expand().reduce();
+    match expanded.is_sum() && &expanded != e {
+        true => expanded.integrate(x),
+        false => None,
+    }
+}
+
+impl Expr {
+    /// the antiderivative of [self] in `x`, without the integration constant
+    ///
+    /// Uses the rules of Rubi, the rule-based integrator, compiled from its Wolfram source
+    /// by [calcurs_macros::integration_rules]. So far only the rules for binomial products,
+    /// e.g (a + b x)^m (c + d x)^n or (a + b x^2)^p, are included and only those whose
+    /// utility functions are implemented by [WlfrmBuiltins]. Sums and constant factors are
+    /// integrated term by term. Returns [None] if `x` is not a variable or no rule applies
+    pub fn integrate(&self, x: &Expr) -> Option<Expr> {
+        if !x.is_var() {
+            return None;
+        }
+        let depth = DEPTH.with(|d| d.get());
+        if depth >= MAX_DEPTH {
+            return None;
+        }
+        DEPTH.with(|d| d.set(depth + 1));
+        let res = integrate(&rubi_form(&self.reduce()), x);
+        DEPTH.with(|d| d.set(depth));
+        res.map(|r| r.reduce())
+    }
+}
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Hash)]
 pub(crate) enum WlfrmAtom {
     None,
     Bool(bool),
     Expr(Expr),
+    List(Vec<WlfrmAtom>),
 }
 
 impl WlfrmAtom {
-    const FALSE: Self = WlfrmAtom::Bool(false);
-    const TRUE: Self = WlfrmAtom::Bool(true);
+    pub(crate) const FALSE: Self = WlfrmAtom::Bool(false);
+    pub(crate) const TRUE: Self = WlfrmAtom::Bool(true);
 }
 
 impl From<bool> for WlfrmAtom {
@@ -25,6 +375,28 @@ impl From<Expr> for WlfrmAtom {
         WlfrmAtom::Expr(value)
     }
 }
+impl From<Option<Expr>> for WlfrmAtom {
+    fn from(value: Option<Expr>) -> Self {
+        value.map_or(WlfrmAtom::None, WlfrmAtom::Expr)
+    }
+}
+
+impl WlfrmAtom {
+    fn to_expr(&self) -> Option<&Expr> {
+        match self {
+            WlfrmAtom::Expr(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    /// the elements of a list or the atom itself
+    fn elems(&self) -> &[WlfrmAtom] {
+        match self {
+            WlfrmAtom::List(l) => l,
+            a => std::slice::from_ref(a),
+        }
+    }
+}
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Hash)]
 pub(crate) enum WlfrmFuncArgs {
@@ -44,6 +416,26 @@ impl WlfrmFuncArgs {
         }
     }
 
+    fn into_vec(self) -> Vec<WlfrmAtom> {
+        match self {
+            WlfrmFuncArgs::One(arg) => vec![arg],
+            WlfrmFuncArgs::Two(args) => args.into(),
+            WlfrmFuncArgs::Three(args) => args.into(),
+            WlfrmFuncArgs::Var(args) => args,
+        }
+    }
+
+    /// [None] if any argument is not an expression
+    fn exprs(self) -> Option<Vec<Expr>> {
+        self.into_vec()
+            .into_iter()
+            .map(|a| match a {
+                WlfrmAtom::Expr(e) => Some(e),
+                _ => None,
+            })
+            .collect()
+    }
+
     fn get_arg(self) -> Option<WlfrmAtom> {
         match self {
             WlfrmFuncArgs::One(arg) => Some(arg),
@@ -73,6 +465,15 @@ macro_rules! wlfrm_fn {
     ($name:ident $arg:tt { $($body:tt)* } $($names:ident $args:tt { $($bodies:tt)* })*) => {
         pub(crate) fn $name $arg -> WlfrmAtom { {$($body)*}.into() }
         $(pub(crate) fn $names $args -> WlfrmAtom { {$($bodies)*}.into() })*
+
+        /// the builtin called `name` in Wolfram, e.g `FreeQ`
+        pub(crate) fn get(name: &str) -> Option<fn(WlfrmFuncArgs) -> WlfrmAtom> {
+            match name {
+                stringify!($name) => Some(Self::$name),
+                $(stringify!($names) => Some(Self::$names),)*
+                _ => None,
+            }
+        }
     }
 }
 
@@ -115,6 +516,402 @@ macro_rules! arg {
     };
 }
 
+fn expr1(args: WArgs) -> Option<Expr> {
+    let [e] = args.exprs()?.try_into().ok()?;
+    Some(e)
+}
+
+fn expr2(args: WArgs) -> Option<[Expr; 2]> {
+    args.exprs()?.try_into().ok()
+}
+
+fn expr3(args: WArgs) -> Option<[Expr; 3]> {
+    args.exprs()?.try_into().ok()
+}
+
+/// u == v after simplification
+fn eq_q(u: &Expr, v: &Expr) -> bool {
+    if u == v {
+        return true;
+    }
+    (u - v).expand().reduce().is_zero()
+}
+
+/// true if every argument compares to the next with `ord`, e.g LtQ[-1, m, 0]
+fn cmp_chain(args: WArgs, ord: impl Fn(cmp::Ordering) -> bool) -> bool {
+    args.exprs().is_some_and(|es| {
+        es.len() >= 2
+            && es
+                .windows(2)
+                .all(|w| w[0].cmp_real(&w[1]).is_some_and(&ord))
+    })
+}
+
+/// the integer `u` compares to `n` with `ord`, e.g IGtQ[m, 0]
+fn int_cmp(args: WArgs, ord: impl Fn(cmp::Ordering) -> bool) -> bool {
+    expr2(args).is_some_and(|[u, n]| u.is_int() && u.cmp_real(&n).is_some_and(ord))
+}
+
+/// true if `u` is positive or looks positive, e.g a symbol or -a*(-b)
+fn pos_q(u: &Expr) -> bool {
+    if let Some(ord) = u.cmp_real(&Expr::zero()) {
+        return ord.is_gt();
+    }
+    match u.atom() {
+        Atom::Pow(pow) if pow.exponent().is_odd() => pos_q(pow.base()),
+        Atom::Prod(Prod { args }) => args.iter().filter(|a| !pos_q(a)).count() % 2 == 0,
+        Atom::Sum(Sum { args }) => args.first().is_some_and(pos_q),
+        _ => true,
+    }
+}
+
+fn linear_q(u: &Expr, x: &Expr) -> bool {
+    let vars = VarSet::new([x]);
+    let u = u.expand().reduce();
+    let poly = u.as_polynomial_view(&vars);
+    poly.check() && poly.degree_of(x) == Some(Int::ONE)
+}
+
+/// expands `u` in powers of a linear factor (a + b x)^m, e.g x/(x + 1) = 1 - (x + 1)^-1, or
+/// into partial fractions, so the terms are integrable by the rules for linear binomials.
+/// Otherwise the products and integer powers are expanded
+fn expand_integrand(u: &Expr, x: &Expr) -> Expr {
+    let vars = VarSet::new([x]);
+    let factors = match u.atom() {
+        Atom::Prod(Prod { args }) => args.as_slice(),
+        _ => std::slice::from_ref(u),
+    };
+    for (i, f) in factors.iter().enumerate() {
+        let (base, m) = (f.base(), f.exponent());
+        let pos_int = m.is_rational_and(|m| m.is_int() && m.is_pos());
+        if pos_int || !m.free_of(x) || !linear_q(&base, x) {
+            continue;
+        }
+        let rest = Expr::from(Prod {
+            args: without(factors, i),
+        })
+        .expand()
+        .reduce();
+        if !rest.as_polynomial_view(&vars).check() {
+            continue;
+        }
+        let coeffs = base
+            .expand()
+            .reduce()
+            .as_polynomial_view(&vars)
+            .coeffs_of(x);
+        let a = coeffs.get(&Int::ZERO).cloned().unwrap_or(Expr::zero());
+        let b = coeffs[&Int::ONE].clone();
+        // the polynomial rest in powers of t = a + b x
+        let t = fresh_var(std::slice::from_ref(u), "t");
+        let rest = rest.substitude(x, &((&t - a) / b)).expand().reduce();
+        let t_vars = VarSet::new([&t]);
+        let poly = rest.as_polynomial_view(&t_vars);
+        if poly.check() {
+            return poly
+                .coeffs_of(&t)
+                .into_iter()
+                .fold(Expr::zero(), |sum, (k, c)| {
+                    sum + c * Expr::pow(&base, &m + Expr::from(k))
+                });
+        }
+    }
+    let apart = u.apart(x);
+    match &apart != u {
+        true => apart,
+        false => u.expand(),
+    }
+}
+
+fn terms(u: &Expr) -> &[Expr] {
+    match u.atom() {
+        Atom::Sum(Sum { args }) => args,
+        _ => std::slice::from_ref(u),
+    }
+}
+
+fn factors(u: &Expr) -> &[Expr] {
+    match u.atom() {
+        Atom::Prod(Prod { args }) => args,
+        _ => std::slice::from_ref(u),
+    }
+}
+
+/// splits the term `t` into coefficient and exponent of `x`, e.g 3 a x^2 -> (3 a, 2).
+/// Terms depending on `x` in other ways, e.g sin(x), are their own coefficient of x^0
+fn monomial(t: &Expr, x: &Expr) -> (Expr, Expr) {
+    let mut coeff = Expr::one();
+    let mut exp = Expr::zero();
+    for f in factors(t) {
+        if f.free_of(x) {
+            coeff *= f;
+        } else if f == x {
+            exp += Expr::one();
+        } else if f.is_pow() && f.base() == *x && f.exponent().free_of(x) {
+            exp += f.exponent();
+        } else {
+            return (t.clone(), Expr::zero());
+        }
+    }
+    (coeff.reduce(), exp.reduce())
+}
+
+/// the expanded `u` as pairs of coefficient and exponent of `x`, equal exponents are combined
+fn monomials(u: &Expr, x: &Expr) -> Vec<(Expr, Expr)> {
+    let u = u.expand().reduce();
+    let mut res: Vec<(Expr, Expr)> = vec![];
+    for t in terms(&u) {
+        let (c, e) = monomial(t, x);
+        match res.iter_mut().find(|(_, e2)| eq_q(&e, e2)) {
+            Some((c2, _)) => *c2 = (&*c2 + c).reduce(),
+            None => res.push((c, e)),
+        }
+    }
+    res.retain(|(c, _)| !c.is_zero());
+    res
+}
+
+/// Coefficient[u, x, n]
+fn coefficient(u: &Expr, x: &Expr, n: &Expr) -> Expr {
+    monomials(u, x)
+        .into_iter()
+        .find(|(_, e)| eq_q(e, n))
+        .map_or(Expr::zero(), |(c, _)| c)
+}
+
+/// Exponent[u, x], the largest exponent of `x`. [None] if the exponents are not comparable
+fn exponent(u: &Expr, x: &Expr) -> Option<Expr> {
+    monomials(u, x)
+        .into_iter()
+        .map(|(_, e)| e)
+        .try_fold(Expr::zero(), |max, e| match e.cmp_real(&max)? {
+            cmp::Ordering::Greater => Some(e),
+            _ => Some(max),
+        })
+}
+
+/// true if `u` is a polynomial in `x`, or in `x^k` for the form x^k
+fn poly_q(u: &Expr, x: &Expr) -> bool {
+    let (x, k) = match x.is_pow() {
+        true => (x.base(), x.exponent().try_unwrap_int()),
+        false => (x.clone(), Some(Int::ONE)),
+    };
+    let Some(k) = k.filter(Int::is_pos) else {
+        return false;
+    };
+    if !x.is_var() {
+        return false;
+    }
+    let vars = VarSet::new([&x]);
+    let u = u.expand().reduce();
+    let poly = u.as_polynomial_view(&vars);
+    poly.check()
+        && poly
+            .coeffs_of(&x)
+            .keys()
+            .all(|d| num::Integer::is_multiple_of(d, &k))
+}
+
+/// ExpandToSum[u, x], the sum of the monomials of `x` in `u` with simplified coefficients
+fn expand_to_sum(u: &Expr, x: &Expr) -> Expr {
+    monomials(u, x)
+        .into_iter()
+        .fold(Expr::zero(), |sum, (c, e)| sum + c * Expr::pow(x, e))
+        .reduce()
+}
+
+/// the number of atoms and heads of `u`, like Mathematica's LeafCount
+fn leaf_count(u: &Expr) -> usize {
+    match u.atom() {
+        Atom::Rational(r) if !r.is_int() => 3,
+        _ if u.is_atom() => 1,
+        _ => 1 + u.args().iter().map(leaf_count).sum::<usize>(),
+    }
+}
+
+fn same_head(u: &Expr, v: &Expr) -> bool {
+    std::mem::discriminant(u.atom()) == std::mem::discriminant(v.atom())
+}
+
+/// SimplerQ[u, v], true if `u` is simpler than `v`
+fn simpler_q(u: &Expr, v: &Expr) -> bool {
+    match (u.atom(), v.atom()) {
+        (Atom::Rational(a), Atom::Rational(b)) if a.is_int() && b.is_int() => {
+            if a == b {
+                false
+            } else if a.clone().abs() == b.clone().abs() {
+                b.is_neg()
+            } else {
+                a.clone().abs() < b.clone().abs()
+            }
+        }
+        (Atom::Rational(a), _) if a.is_int() => true,
+        (_, Atom::Rational(b)) if b.is_int() => false,
+        (Atom::Rational(a), Atom::Rational(b)) => match a.denom() == b.denom() {
+            true => simpler_q(&Expr::from(a.numer()), &Expr::from(b.numer())),
+            false => a.denom() < b.denom(),
+        },
+        (Atom::Rational(_), _) => true,
+        (_, Atom::Rational(_)) => false,
+        _ if u.is_number() => !v.is_number() || u <= v,
+        _ if v.is_number() => false,
+        _ if u.is_atom() => !v.is_atom() || u <= v,
+        _ if v.is_atom() => false,
+        _ if same_head(u, v) && u.n_args() == v.n_args() => {
+            match leaf_count(u).cmp(&leaf_count(v)) {
+                cmp::Ordering::Equal => u
+                    .args()
+                    .iter()
+                    .zip(v.args())
+                    .find(|(a, b)| a != b)
+                    .is_some_and(|(a, b)| simpler_q(a, b)),
+                ord => ord.is_lt(),
+            }
+        }
+        _ if same_head(u, v) => u.n_args() < v.n_args(),
+        _ => leaf_count(u) < leaf_count(v),
+    }
+}
+
+/// splits off the rational factor, e.g -2 a b -> (-2, a b)
+fn numeric_factor(u: &Expr) -> (Rational, Expr) {
+    match factors(u).split_first() {
+        Some((first, rest)) if first.is_rational() && !rest.is_empty() => {
+            let Atom::Rational(r) = first.atom() else {
+                unreachable!()
+            };
+            let rest = Expr::from(Prod {
+                args: rest.to_vec(),
+            });
+            (r.clone(), rest.reduce())
+        }
+        _ => match u.atom() {
+            Atom::Rational(r) => (r.clone(), Expr::one()),
+            _ => (Rational::ONE, u.clone()),
+        },
+    }
+}
+
+/// true if adding `v` cancels terms of `u` without flipping their sign, e.g u = 2a, v = -a
+fn sum_simpler_aux_q(u: &Expr, v: &Expr) -> bool {
+    if v.is_sum() {
+        return terms(v)
+            .iter()
+            .all(|t| t.is_rational() || sum_simpler_aux_q(u, t));
+    }
+    if u.is_sum() {
+        return terms(u).iter().any(|t| sum_simpler_aux_q(t, v));
+    }
+    let (nu, u) = numeric_factor(u);
+    let (nv, v) = numeric_factor(v);
+    if nv.is_zero() || u != v {
+        return false;
+    }
+    let half = Rational::new(-1, 2);
+    let r = nu.clone() / nv;
+    r < half || (r == half && nu.is_neg())
+}
+
+/// SumSimplerQ[u, v], true if u + v is simpler than `u`
+fn sum_simpler_q(u: &Expr, v: &Expr) -> bool {
+    match (u.atom(), v.atom()) {
+        (Atom::Rational(a), Atom::Rational(b)) => {
+            if b.is_zero() {
+                false
+            } else if b.is_pos() {
+                a < &Rational::MINUS_ONE
+            } else {
+                a >= &(Rational::ZERO - b.clone())
+            }
+        }
+        _ => sum_simpler_aux_q(&u.expand().reduce(), &v.expand().reduce()),
+    }
+}
+
+/// SimplerSqrtQ[u, v], true if Rt[u, 2] is simpler than Rt[v, 2]
+fn simpler_sqrt_q(u: &Expr, v: &Expr) -> bool {
+    let neg = |e: &Expr| e.cmp_real(&Expr::zero()).is_some_and(cmp::Ordering::is_lt);
+    if neg(v) && !neg(u) {
+        return true;
+    }
+    if neg(u) && !neg(v) {
+        return false;
+    }
+    let rt = |e: &Expr| Expr::sqrt(e).reduce();
+    let (su, sv) = (rt(u), rt(v));
+    let lt = |a: &Expr, b: &Expr| a.cmp_real(b).is_some_and(cmp::Ordering::is_lt);
+    if su.is_int() {
+        return !sv.is_int() || lt(&su, &sv);
+    }
+    if sv.is_int() {
+        return false;
+    }
+    if su.is_rational() {
+        return !sv.is_rational() || lt(&su, &sv);
+    }
+    if sv.is_rational() {
+        return false;
+    }
+    if pos_q(u) != pos_q(v) {
+        return pos_q(u);
+    }
+    match leaf_count(&su).cmp(&leaf_count(&sv)) {
+        cmp::Ordering::Equal => v > u,
+        ord => ord.is_lt(),
+    }
+}
+
+/// the greatest rational dividing all `rs`, e.g GCD[1/2, 1/3] = 1/6
+fn rational_gcd(rs: &[Rational]) -> Rational {
+    rs.iter().fold(Rational::ZERO, |g, r| {
+        let n = g.numer().gcd(&r.numer());
+        let d = num::Integer::lcm(&g.denom(), &r.denom());
+        Rational::new(n, d)
+    })
+}
+
+fn numeric_q(u: &Expr) -> bool {
+    !u.is_var() && u.args().iter().all(numeric_q)
+}
+
+/// e.g a (2 + 4 x) -> 1 + 2 x
+fn remove_content(u: &Expr, x: &Expr) -> Expr {
+    let (_, u) = u.separate_factors(x);
+    if !u.is_sum() {
+        return u;
+    }
+    let content: Vec<_> = terms(&u).iter().map(|t| numeric_factor(t).0).collect();
+    let g = rational_gcd(&content);
+    match g.is_zero() || g.is_one() {
+        true => u,
+        false => terms(&u)
+            .iter()
+            .fold(Expr::zero(), |sum, t| sum + t / Expr::from(g.clone()))
+            .reduce(),
+    }
+}
+
+/// true if sqrt(u) is free of radicals for a positive `u`, e.g 4 a^2
+fn nice_sqrt_q(u: &Expr) -> bool {
+    match u.atom() {
+        Atom::Rational(r) => r.is_pos(),
+        Atom::Pow(pow) => pow.exponent().is_even(),
+        Atom::Prod(Prod { args }) => args.iter().all(nice_sqrt_q),
+        _ => false,
+    }
+}
+
+/// the argument that compares to all others with `pick`, e.g Max[1, 3, 2] = 3.
+/// [None] if two arguments are not comparable
+fn extremum(args: WArgs, pick: cmp::Ordering) -> Option<Expr> {
+    let mut es = args.exprs()?.into_iter();
+    let first = es.next()?;
+    es.try_fold(first, |m, e| match e.cmp_real(&m)? == pick {
+        true => Some(e),
+        false => Some(m),
+    })
+}
+
 pub(crate) struct WlfrmBuiltins;
 
 use WlfrmAtom as WA;
@@ -129,7 +926,7 @@ impl WlfrmBuiltins {
     }
 
     TrueQ(arg: WArgs) {
-        arg!(arg) == WA::FALSE
+        arg!(arg) == WA::TRUE
     }
 
     IntegerQ(arg: WArgs) {
@@ -198,25 +995,165 @@ impl WlfrmBuiltins {
 
     ComplexNumberQ(_: WArgs) { false }
 
-    //PossibleZeroQ,
-    //NonsumQ,
-    //OrderedQ,
-    //CoprimeQ,
-    //PerfectSquareQ,
-    //MemberQ,
-    //FreeQ,
-
-    //QuadraticProductQ,
-    //PowerQ,
-    //PowerOfLinearQ,
-    //PowerOfLinearMatchQ,
-    //IntegerPowerQ,
-    //FractionalPowerQ,
-    //IntegersQ,
-    //PolynomialQ,
-    //ComplexNumberQ,
-    //NumericQ,
-    //ListQ,
+    IntegersQ(args: WArgs) {
+        args.exprs().is_some_and(|es| es.iter().all(|e| e.is_int()))
+    }
+
+    RationalQ(args: WArgs) {
+        args.exprs().is_some_and(|es| es.iter().all(|e| e.is_rational()))
+    }
+
+    // FreeQ[{a, b}, x]
+    FreeQ(args: WArgs) {
+        let [u, x] = args!(args, 2);
+        u.elems().iter().all(|u| {
+            x.elems().iter().all(|x| match (u.to_expr(), x.to_expr()) {
+                (Some(u), Some(x)) => u.free_of(x),
+                _ => true,
+            })
+        })
+    }
+
+    // polynomial of degree one in x
+    LinearQ(args: WArgs) {
+        let [u, x] = args!(args, 2);
+        match x.to_expr() {
+            Some(x) => u.elems().iter().all(|u| u.to_expr().is_some_and(|u| linear_q(u, x))),
+            None => false,
+        }
+    }
+
+    EqQ(args: WArgs) {
+        expr2(args).is_some_and(|[u, v]| eq_q(&u, &v))
+    }
+    NeQ(args: WArgs) {
+        expr2(args).is_some_and(|[u, v]| !eq_q(&u, &v))
+    }
+
+    GtQ(args: WArgs) { cmp_chain(args, cmp::Ordering::is_gt) }
+    LtQ(args: WArgs) { cmp_chain(args, cmp::Ordering::is_lt) }
+    GeQ(args: WArgs) { cmp_chain(args, cmp::Ordering::is_ge) }
+    LeQ(args: WArgs) { cmp_chain(args, cmp::Ordering::is_le) }
+
+    IGtQ(args: WArgs) { int_cmp(args, cmp::Ordering::is_gt) }
+    ILtQ(args: WArgs) { int_cmp(args, cmp::Ordering::is_lt) }
+    IGeQ(args: WArgs) { int_cmp(args, cmp::Ordering::is_ge) }
+    ILeQ(args: WArgs) { int_cmp(args, cmp::Ordering::is_le) }
+
+    PosQ(arg: WArgs) {
+        expr1(arg).is_some_and(|u| pos_q(&u))
+    }
+    NegQ(arg: WArgs) {
+        expr1(arg).is_some_and(|u| !eq_q(&u, &Expr::zero()) && !pos_q(&u))
+    }
+
+    PossibleZeroQ(arg: WArgs) {
+        expr1(arg).is_some_and(|u| eq_q(&u, &Expr::zero()))
+    }
+    NonsumQ(arg: WArgs) {
+        expr1(arg).is_some_and(|u| !u.is_sum())
+    }
+    // CoprimeQ[6, 35]
+    CoprimeQ(args: WArgs) {
+        expr2(args).is_some_and(|[u, v]| match (u.try_unwrap_int(), v.try_unwrap_int()) {
+            (Some(u), Some(v)) => u.gcd(&v) == Int::ONE,
+            _ => false,
+        })
+    }
+    // rationals with a rational square root and even powers, e.g 4/9 or a^2
+    PerfectSquareQ(arg: WArgs) {
+        expr1(arg).is_some_and(|u| match u.atom() {
+            Atom::Rational(r) => !r.is_neg() && Expr::sqrt(&u).reduce().is_rational(),
+            _ => nice_sqrt_q(&u),
+        })
+    }
+    // MemberQ[{a, b}, a]
+    MemberQ(args: WArgs) {
+        let [list, e] = args!(args, 2);
+        list.elems().contains(&e)
+    }
+    PowerQ(arg: WArgs) {
+        expr1(arg).is_some_and(|u| u.is_pow())
+    }
+    IntegerPowerQ(arg: WArgs) {
+        expr1(arg).is_some_and(|u| u.is_pow() && u.exponent().is_int())
+    }
+    FractionalPowerQ(arg: WArgs) {
+        expr1(arg).is_some_and(|u| {
+            u.is_pow() && u.exponent().is_rational_and(Rational::is_fraction)
+        })
+    }
+    // PolynomialQ[u, x], u is a polynomial in x with arbitrary coefficients
+    PolynomialQ(args: WArgs) {
+        expr2(args).is_some_and(|[u, x]| poly_q(&u, &x))
+    }
+    // PolyQ[u, x] or PolyQ[u, x, n], a polynomial of degree n. x can be a power x^k
+    PolyQ(args: WArgs) {
+        args.exprs().is_some_and(|es| match es.as_slice() {
+            [u, x] => poly_q(u, x),
+            [u, x, n] => {
+                poly_q(u, x)
+                    && exponent(u, x).is_some_and(|m| eq_q(&m, n))
+                    && !coefficient(u, x, n).is_zero()
+            }
+            _ => false,
+        })
+    }
+    // free of variables, e.g Sqrt[2] + Pi
+    NumericQ(arg: WArgs) {
+        expr1(arg).is_some_and(|u| numeric_q(&u))
+    }
+    ListQ(arg: WArgs) {
+        matches!(arg!(arg), WA::List(_))
+    }
+    // LinearPairQ[u, v, x], u and v are linear in x and u/v is free of x
+    LinearPairQ(args: WArgs) {
+        expr3(args).is_some_and(|[u, v, x]| {
+            let c = |e: &Expr, n| coefficient(e, &x, &Expr::from(n));
+            linear_q(&u, &x)
+                && linear_q(&v, &x)
+                && !eq_q(&c(&u, 0), &Expr::zero())
+                && eq_q(&(c(&u, 0) * c(&v, 1)), &(c(&u, 1) * c(&v, 0)))
+        })
+    }
+    // IntQuadraticQ[a, b, c, d, e, m, p, x], (d + e x)^m (a + b x + c x^2)^p has an elementary
+    // antiderivative
+    IntQuadraticQ(args: WArgs) {
+        args.exprs().is_some_and(|es| {
+            let [a, b, c, d, e, m, p, _] = &es[..] else {
+                return false;
+            };
+            let ints = |es: &[Expr]| es.iter().all(|e| e.reduce().is_int());
+            let zero = |u: Expr| eq_q(&u, &Expr::zero());
+            let two = Expr::two();
+            p.is_int()
+                || (m.is_int() && m.is_rational_and(Rational::is_pos))
+                || ints(&[&two * m, &two * p])
+                || ints(&[m.clone(), Expr::from(4) * p])
+                || ints(&[m.clone(), p + Expr::rational((1, 3))])
+                    && (zero(
+                        Expr::pow(c, &two) * Expr::pow(d, &two) - b * c * d * e
+                            + Expr::pow(b, &two) * Expr::pow(e, &two)
+                            - Expr::from(3) * a * c * Expr::pow(e, &two),
+                    ) || zero(
+                        Expr::pow(c, &two) * Expr::pow(d, &two) - b * c * d * e
+                            - Expr::from(2) * Expr::pow(b, &two) * Expr::pow(e, &two)
+                            + Expr::from(9) * a * c * Expr::pow(e, &two),
+                    ))
+        })
+    }
+    NiceSqrtQ(arg: WArgs) {
+        expr1(arg).is_some_and(|u| !u.is_neg() && nice_sqrt_q(&u))
+    }
+    SimplerQ(args: WArgs) {
+        expr2(args).is_some_and(|[u, v]| simpler_q(&u, &v))
+    }
+    SumSimplerQ(args: WArgs) {
+        expr2(args).is_some_and(|[u, v]| sum_simpler_q(&u, &v))
+    }
+    SimplerSqrtQ(args: WArgs) {
+        expr2(args).is_some_and(|[u, v]| simpler_sqrt_q(&u, &v))
+    }
 
     Sin(arg: WArgs) {
         let a = expr!(arg!(arg));
@@ -242,6 +1179,22 @@ impl WlfrmBuiltins {
         let a = expr!(arg!(arg));
         Expr::arc_tan(a)
     }
+    // the inverse hyperbolic functions in terms of logarithms
+    ArcSinh(arg: WArgs) {
+        expr1(arg).map(|z| Expr::ln(&z + Expr::sqrt(Expr::pow(&z, Expr::two()) + Expr::one())))
+    }
+    ArcCosh(arg: WArgs) {
+        expr1(arg).map(|z| {
+            let root = Expr::sqrt(&z + Expr::one()) * Expr::sqrt(&z - Expr::one());
+            Expr::ln(z + root)
+        })
+    }
+    ArcTanh(arg: WArgs) {
+        expr1(arg).map(|z| {
+            let half = Expr::rational((1, 2));
+            half * (Expr::ln(Expr::one() + &z) - Expr::ln(Expr::one() - z))
+        })
+    }
 
     //Sinh,
     //ArcSinh,
@@ -302,43 +1255,188 @@ impl WlfrmBuiltins {
         let e = expr!(arg!(arg));
         Expr::exp(e)
     }
+
+    // the real n-th root for odd n
+    Rt(args: WArgs) {
+        expr2(args).map(|[u, n]| {
+            let inv = Expr::pow(&n, Expr::min_one());
+            match u.is_neg() && n.is_odd() {
+                true => Expr::min_one() * Expr::pow(Expr::min_one() * u, inv),
+                false => Expr::pow(u, inv),
+            }
+            .reduce()
+        })
+    }
+
+    Plus(args: WArgs) {
+        args.exprs().map(|es| es.into_iter().fold(Expr::zero(), |sum, e| sum + e))
+    }
+    Times(args: WArgs) {
+        args.exprs().map(|es| es.into_iter().fold(Expr::one(), |prod, e| prod * e))
+    }
+    Subtract(args: WArgs) {
+        expr2(args).map(|[u, v]| u - v)
+    }
+    Divide(args: WArgs) {
+        expr2(args).map(|[u, v]| u / v)
+    }
+    Power(args: WArgs) {
+        expr2(args).map(|[u, v]| Expr::pow(u, v))
+    }
+    Minus(arg: WArgs) {
+        expr1(arg).map(|u| Expr::min_one() * u)
+    }
     //LogGamma,
     //LogIntegral,
     //ProductLog,
     //PolyLog,
 
 
-    //Binomial,
-
-    //Sign,
-    //Denominator,
-    //Numerator ,
-    //Exponent,
-    //Quotient,
-    //FractionalPart,
-    //PolynomialRemainder,
-    //PolynomialQuotient,
-    //PolynomialQuotientRemainder,
-    //IntegerPart,
-    //Coefficient,
-    //CoefficientList,
-    //Last,
-    //Head,
-
-    //Sum,
-    //Product,
-    //Mod,
-    //Max,
-    //GCD,
-
-    //Floor,
-    //Abs,
+    Binomial(args: WArgs) {
+        expr2(args).and_then(|[n, k]| {
+            Some(Expr::from(Int::binomial_coeff(&n.try_unwrap_int()?, &k.try_unwrap_int()?)))
+        })
+    }
+    // -1, 0 or 1 for real numbers
+    Sign(arg: WArgs) {
+        expr1(arg).and_then(|u| {
+            Some(match u.cmp_real(&Expr::zero())? {
+                cmp::Ordering::Less => Expr::min_one(),
+                cmp::Ordering::Equal => Expr::zero(),
+                cmp::Ordering::Greater => Expr::one(),
+            })
+        })
+    }
+    Abs(arg: WArgs) {
+        expr1(arg).and_then(|u| match u.cmp_real(&Expr::zero())? {
+            cmp::Ordering::Less => Some((Expr::min_one() * u).reduce()),
+            _ => Some(u),
+        })
+    }
+    Denominator(arg: WArgs) {
+        expr1(arg).map(|u| u.denominator())
+    }
+    Numerator(arg: WArgs) {
+        expr1(arg).map(|u| u.numerator())
+    }
+    Denom(arg: WArgs) {
+        expr1(arg).map(|u| u.denominator())
+    }
+    Numer(arg: WArgs) {
+        expr1(arg).map(|u| u.numerator())
+    }
+    // combines the terms over a common denominator and cancels common factors
+    Together(arg: WArgs) {
+        expr1(arg).map(|u| u.rationalize().cancel())
+    }
+    // truncated like IntegerPart, non-numbers are their own fractional part
+    FracPart(arg: WArgs) {
+        expr1(arg).map(|u| match u.atom() {
+            Atom::Rational(r) => Expr::from(r.clone() - Rational::new_int(r.numer() / r.denom())),
+            _ => u,
+        })
+    }
+    IntPart(arg: WArgs) {
+        expr1(arg).map(|u| match u.atom() {
+            Atom::Rational(r) => Expr::from(r.numer() / r.denom()),
+            _ => Expr::zero(),
+        })
+    }
+    FractionalPart(arg: WArgs) {
+        Self::FracPart(arg)
+    }
+    IntegerPart(arg: WArgs) {
+        Self::IntPart(arg)
+    }
+    Floor(arg: WArgs) {
+        expr1(arg).and_then(|u| match u.atom() {
+            Atom::Rational(r) => Some(Expr::from(r.clone().floor())),
+            _ => None,
+        })
+    }
+    // Mod[m, n], the remainder with the sign of n
+    Mod(args: WArgs) {
+        expr2(args).and_then(|[m, n]| {
+            let m = m.try_unwrap_int()?;
+            Some(Expr::from(m.modulo(&n.try_unwrap_int()?)?))
+        })
+    }
+    // GCD[a, b, ...] of rationals
+    GCD(args: WArgs) {
+        args.exprs().and_then(|es| {
+            let rs: Option<Vec<_>> = es
+                .iter()
+                .map(|e| match e.atom() {
+                    Atom::Rational(r) => Some(r.clone()),
+                    _ => None,
+                })
+                .collect();
+            Some(Expr::from(rational_gcd(&rs?)))
+        })
+    }
+    Max(args: WArgs) { extremum(args, cmp::Ordering::Greater) }
+    Min(args: WArgs) { extremum(args, cmp::Ordering::Less) }
+    LeafCount(arg: WArgs) {
+        expr1(arg).map(|u| Expr::from(leaf_count(&u) as u64))
+    }
+    // Coefficient[u, x, n] of x^n in the expanded u, n defaults to 1
+    Coefficient(args: WArgs) {
+        args.exprs().and_then(|es| match es.as_slice() {
+            [u, x] => Some(coefficient(u, x, &Expr::one())),
+            [u, x, n] => Some(coefficient(u, x, n)),
+            _ => None,
+        })
+    }
+    Coeff(args: WArgs) {
+        Self::Coefficient(args)
+    }
+    // Expon[u, x], the largest exponent of x in the expanded u
+    Expon(args: WArgs) {
+        expr2(args).and_then(|[u, x]| exponent(&u, &x))
+    }
+    Exponent(args: WArgs) {
+        Self::Expon(args)
+    }
+    // ExpandToSum[u, x] or ExpandToSum[u, v, x], v expanded in x and distributed over u
+    ExpandToSum(args: WArgs) {
+        args.exprs().and_then(|es| match es.as_slice() {
+            [u, x] => Some(expand_to_sum(u, x)),
+            [u, v, x] => Some(
+                terms(&expand_to_sum(v, x))
+                    .iter()
+                    .fold(Expr::zero(), |sum, t| sum + u * t)
+                    .reduce(),
+            ),
+            _ => None,
+        })
+    }
+    PolynomialQuotient(args: WArgs) {
+        expr3(args).and_then(|[u, v, x]| Some(Expr::poly_div(&u, &v, &x)?.0.reduce()))
+    }
+    PolynomialRemainder(args: WArgs) {
+        expr3(args).and_then(|[u, v, x]| Some(Expr::poly_div(&u, &v, &x)?.1.reduce()))
+    }
+    // PolynomialDivide[u, v, x], the quotient plus the remainder over v
+    PolynomialDivide(args: WArgs) {
+        expr3(args).and_then(|[u, v, x]| {
+            let (q, r) = Expr::poly_div(&u, &v, &x)?;
+            Some((q + r / v).reduce())
+        })
+    }
+    // PolynomialGCD[u, v], monic over the rationals in one variable
+    PolynomialGCD(args: WArgs) {
+        expr2(args).and_then(|[u, v]| {
+            let mut vars: Vec<_> = u.variables().into_iter().chain(v.variables()).collect();
+            vars.sort();
+            vars.dedup();
+            Expr::poly_gcd(&u, &v, &VarSet::new(&vars))
+        })
+    }
     Not(arg: WArgs) {
-        let e = arg!(arg);
-        match e {
-            WlfrmAtom::Bool(b) => !b,
-            _ => todo!(),
-            }
+        match arg!(arg) {
+            WlfrmAtom::Bool(b) => WlfrmAtom::Bool(!b),
+            _ => WlfrmAtom::None,
+        }
     }
     //Zeta,
 
@@ -351,6 +1449,9 @@ impl WlfrmBuiltins {
         let [f, x] = expr!(f, x);
         f.derivative(x)
     }
+    D(args: WArgs) {
+        expr2(args).map(|[u, x]| u.derivative(x).reduce())
+    }
     //SinhIntegral,
     //SinIntegral,
     //CoshIntegral,
@@ -367,6 +1468,32 @@ impl WlfrmBuiltins {
         let e = expr!(arg!(arg));
         e.expand()
     }
+    ExpandIntegrand(args: WArgs) {
+        expr2(args).map(|[u, x]| expand_integrand(&u, &x))
+    }
+    // RemoveContent[u, x], u without the factors free of x and the rational content of a sum
+    RemoveContent(args: WArgs) {
+        expr2(args).map(|[u, x]| remove_content(&u, &x))
+    }
+    Simp(args: WArgs) {
+        expr2(args).map(|[u, _]| u.reduce())
+    }
+    SimplifyIntegrand(args: WArgs) {
+        expr2(args).map(|[u, _]| u.cancel())
+    }
+    Simplify(arg: WArgs) {
+        expr1(arg).map(|u| u.reduce())
+    }
+    // Subst[u, x, v] replaces x with v
+    Subst(args: WArgs) {
+        expr3(args).map(|[u, x, v]| u.substitude(&x, &v))
+    }
+    Int(args: WArgs) {
+        expr2(args).and_then(|[u, x]| u.integrate(&x))
+    }
+    // no elementary antiderivative, the rule does not apply
+    Unintegrable(_: WArgs) { WA::None }
+    CannotIntegrate(_: WArgs) { WA::None }
     //FunctionExpand,
     //TrigToExp,
     //FullSimplify,
@@ -389,7 +1516,6 @@ impl WlfrmBuiltins {
     //f,
     //Reverse,
     //Sow,
-    //Together,
     //Rule,
     //Scan,
     //Rest,
@@ -447,7 +1573,6 @@ impl WlfrmBuiltins {
     //J_,
     //Im,
     //InverseFunction,
-    //LeafCount,
     //Order,
     //ClearAll,
     //Unevaluated,
@@ -456,12 +1581,9 @@ impl WlfrmBuiltins {
     //Reap,
     //F_,
     //Do,
-    //Min,
-    //PolynomialGCD,
     //LCM,
     //Erf,
     //h,
-    //D,
     //g,
     //Prepend,
     //Sort,
@@ -471,3 +1593,446 @@ impl WlfrmBuiltins {
     //TimeConstrained,
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::atom::Var;
+    use assert_eq as eq;
+    use calcurs_macros::expr as e;
+
+    /// integrates `f` and compares the derivative of the result to `f` at x = 0.37, a = 0.61,
+    /// b = 1.3 and m = 0.7
+    fn check(f: Expr) {
+        let x = e!(x);
+        let res = f
+            .integrate(&x)
+            .unwrap_or_else(|| panic!("could not integrate {f:?}"));
+        let vars: HashMap<Var, f64> = [("x", 0.37), ("a", 0.61), ("b", 1.3), ("m", 0.7)]
+            .into_iter()
+            .map(|(v, val)| (v.into(), val))
+            .collect();
+        let df = res.derivative(&x).evalf(&vars).unwrap();
+        let f = f.evalf(&vars).unwrap();
+        assert!((df - f).abs() < 1e-9, "d/dx {res:?} = {df} != {f}");
+    }
+
+    type B = WlfrmBuiltins;
+
+    /// calls the builtin `f` with the reduced `args`
+    fn call(f: fn(WArgs) -> WA, args: &[Expr]) -> WA {
+        let mut args: Vec<_> = args.iter().map(|a| WA::from(a.reduce())).collect();
+        f(match args.len() {
+            1 => WArgs::One(args.remove(0)),
+            2 => WArgs::Two(args.try_into().unwrap()),
+            3 => WArgs::Three(args.try_into().unwrap()),
+            _ => WArgs::Var(args),
+        })
+    }
+
+    fn is(f: fn(WArgs) -> WA, args: &[Expr]) -> bool {
+        match call(f, args) {
+            WA::Bool(b) => b,
+            res => panic!("expected a boolean, found: {res:?}"),
+        }
+    }
+
+    /// true if `f(args)` is equal to `res` after expansion
+    fn gives(f: fn(WArgs) -> WA, args: &[Expr], res: Expr) -> bool {
+        match call(f, args) {
+            WA::Expr(e) => eq_q(&e, &res.reduce()),
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn predicates() {
+        let x = e!(x);
+        eq!(B::TrueQ(WArgs::One(WA::TRUE)), WA::TRUE);
+        eq!(B::TrueQ(WArgs::One(WA::FALSE)), WA::FALSE);
+        eq!(B::FalseQ(WArgs::One(WA::TRUE)), WA::FALSE);
+        let list = WA::List(vec![e!(a).into(), x.clone().into()]);
+        eq!(
+            B::FreeQ(WArgs::Two([list.clone(), x.clone().into()])),
+            WA::FALSE
+        );
+        eq!(B::ListQ(WArgs::One(list.clone())), WA::TRUE);
+        eq!(B::MemberQ(WArgs::Two([list, e!(a).into()])), WA::TRUE);
+        assert!(is(B::FreeQ, &[e!(a + b), x.clone()]));
+        assert!(!is(B::FreeQ, &[e!(a * x), x.clone()]));
+
+        assert!(is(B::IntegerQ, &[e!(2)]));
+        assert!(!is(B::IntegerQ, &[e!(1 / 2)]));
+        assert!(is(B::IntegersQ, &[e!(2), e!(-3)]));
+        assert!(!is(B::IntegersQ, &[e!(2), e!(m)]));
+        assert!(is(B::FractionQ, &[e!(1 / 2)]));
+        assert!(is(B::RationalQ, &[e!(1 / 2), e!(3)]));
+        assert!(is(B::NumericQ, &[Expr::sqrt(Expr::two()) + Expr::pi()]));
+        assert!(!is(B::NumericQ, &[e!(a + 1)]));
+
+        assert!(is(
+            B::EqQ,
+            &[e!((a + b) ^ 2), e!(a ^ 2 + 2 * a * b + b ^ 2)]
+        ));
+        assert!(is(B::NeQ, &[e!(a), e!(b)]));
+        assert!(is(B::PossibleZeroQ, &[e!((x + 1) ^ 2 - x ^ 2 - 2 * x - 1)]));
+        assert!(is(B::GtQ, &[e!(2), e!(1 / 2)]));
+        assert!(is(B::LtQ, &[e!(-1), e!(-1 / 2), e!(0)]));
+        assert!(!is(B::LtQ, &[e!(-1), e!(m), e!(0)]));
+        assert!(is(B::IGtQ, &[e!(3), e!(0)]));
+        assert!(!is(B::IGtQ, &[e!(1 / 2), e!(0)]));
+        assert!(is(B::ILtQ, &[e!(-3), e!(0)]));
+        assert!(!is(B::PosQ, &[e!(-3)]));
+        assert!(is(B::PosQ, &[e!(a)]));
+        assert!(is(B::NegQ, &[e!(-a)]));
+        assert!(!is(B::NegQ, &[e!(0)]));
+        assert!(is(B::CoprimeQ, &[e!(6), e!(35)]));
+        assert!(!is(B::CoprimeQ, &[e!(6), e!(9)]));
+        assert!(is(B::PerfectSquareQ, &[e!(4 / 9)]));
+        assert!(is(B::PerfectSquareQ, &[e!(a ^ 2)]));
+        assert!(!is(B::PerfectSquareQ, &[e!(2)]));
+        assert!(is(B::NiceSqrtQ, &[e!(4 * a ^ 2)]));
+        assert!(!is(B::NiceSqrtQ, &[e!(-4)]));
+        assert!(!is(B::NiceSqrtQ, &[e!(a)]));
+
+        assert!(is(B::SumQ, &[e!(a + b)]));
+        assert!(is(B::NonsumQ, &[e!(a * b)]));
+        assert!(is(B::PowerQ, &[e!(x ^ a)]));
+        assert!(is(B::IntegerPowerQ, &[e!(x ^ 2)]));
+        assert!(!is(B::IntegerPowerQ, &[e!(sqrt(x))]));
+        assert!(is(B::FractionalPowerQ, &[e!(sqrt(x))]));
+    }
+
+    #[test]
+    fn polynomial_predicates() {
+        let x = e!(x);
+        assert!(is(B::LinearQ, &[e!(a + b * x), x.clone()]));
+        assert!(!is(B::LinearQ, &[e!(x ^ 2), x.clone()]));
+        assert!(!is(B::LinearQ, &[e!(a), x.clone()]));
+        assert!(is(B::PolynomialQ, &[e!(x ^ 2 + a * x), x.clone()]));
+        assert!(is(B::PolynomialQ, &[e!((1 + x) ^ 2), x.clone()]));
+        assert!(!is(B::PolynomialQ, &[e!(sqrt(x)), x.clone()]));
+        assert!(!is(B::PolynomialQ, &[e!(1 / (1 + x)), x.clone()]));
+        assert!(is(B::PolyQ, &[e!(1 + x ^ 2), e!(x ^ 2)]));
+        assert!(!is(B::PolyQ, &[e!(1 + x), e!(x ^ 2)]));
+        assert!(is(B::PolyQ, &[e!(a + b * x ^ 2), x.clone(), e!(2)]));
+        assert!(!is(B::PolyQ, &[e!(a + b * x ^ 2), x.clone(), e!(1)]));
+        assert!(is(
+            B::LinearPairQ,
+            &[e!(2 + 4 * x), e!(1 + 2 * x), x.clone()]
+        ));
+        assert!(!is(B::LinearPairQ, &[e!(2 + 4 * x), e!(1 + x), x.clone()]));
+        let [a, b, c, d, e] = [e!(a), e!(b), e!(c), e!(d), e!(e)];
+        let int_quad = |m, p| {
+            is(
+                B::IntQuadraticQ,
+                &[&a, &b, &c, &d, &e, &m, &p, &x].map(Clone::clone),
+            )
+        };
+        assert!(int_quad(e!(m), e!(2)));
+        assert!(int_quad(e!(1 / 2), e!(3 / 2)));
+        assert!(!int_quad(e!(m), e!(1 / 3)));
+    }
+
+    #[test]
+    fn simpler() {
+        assert!(is(B::SimplerQ, &[e!(2), e!(3)]));
+        assert!(is(B::SimplerQ, &[e!(2), e!(-2)]));
+        assert!(!is(B::SimplerQ, &[e!(-2), e!(2)]));
+        assert!(is(B::SimplerQ, &[e!(3), e!(1 / 2)]));
+        assert!(is(B::SimplerQ, &[e!(1 / 2), e!(x)]));
+        assert!(is(B::SimplerQ, &[e!(x), e!(1 + x)]));
+        assert!(!is(B::SimplerQ, &[e!(1 + x), e!(x)]));
+        assert!(is(B::SumSimplerQ, &[e!(-2), e!(1)]));
+        assert!(!is(B::SumSimplerQ, &[e!(1), e!(1)]));
+        assert!(is(B::SumSimplerQ, &[e!(a + b), e!(-b)]));
+        assert!(is(B::SumSimplerQ, &[e!(2 * a), e!(-a)]));
+        assert!(!is(B::SumSimplerQ, &[e!(a), e!(b)]));
+        assert!(is(B::SimplerSqrtQ, &[e!(4), e!(2)]));
+        assert!(!is(B::SimplerSqrtQ, &[e!(2), e!(4)]));
+        assert!(!is(B::SimplerSqrtQ, &[e!(-1), e!(1)]));
+        assert!(is(B::SimplerSqrtQ, &[e!(a), e!(-a)]));
+    }
+
+    #[test]
+    fn polynomials() {
+        let x = e!(x);
+        let poly = e!(a + b * x + c * x ^ 2);
+        assert!(gives(
+            B::Coefficient,
+            &[poly.clone(), x.clone(), e!(2)],
+            e!(c)
+        ));
+        assert!(gives(
+            B::Coefficient,
+            &[poly.clone(), x.clone(), e!(0)],
+            e!(a)
+        ));
+        assert!(gives(
+            B::Coefficient,
+            &[poly.clone(), x.clone(), e!(3)],
+            e!(0)
+        ));
+        assert!(gives(B::Coefficient, &[e!((1 + x) ^ 2), x.clone()], e!(2)));
+        assert!(gives(
+            B::Coeff,
+            &[e!(a + 3 * sqrt(x)), x.clone(), e!(1 / 2)],
+            e!(3)
+        ));
+        assert!(gives(B::Expon, &[e!((1 + x) ^ 3), x.clone()], e!(3)));
+        assert!(gives(B::Expon, &[e!(a), x.clone()], e!(0)));
+        assert!(gives(
+            B::Exponent,
+            &[e!(sqrt(x) + x ^ (3 / 2)), x.clone()],
+            e!(3 / 2)
+        ));
+        eq!(call(B::Expon, &[e!(x ^ m + x), x.clone()]), WA::None);
+
+        // the coefficients of x are collected
+        let WA::Expr(sum) = call(B::ExpandToSum, &[e!((a + b * x) * (c + d * x)), x.clone()])
+        else {
+            panic!("ExpandToSum failed")
+        };
+        eq!(terms(&sum).len(), 3);
+        assert!(eq_q(
+            &sum,
+            &e!(a * c + (a * d + b * c) * x + b * d * x ^ 2).reduce()
+        ));
+        let args = [e!(x), e!((1 + x) ^ 2), x.clone()];
+        assert!(gives(B::ExpandToSum, &args, e!(x + 2 * x ^ 2 + x ^ 3)));
+
+        let u = e!(x ^ 2 + 3 * x + 2);
+        assert!(gives(
+            B::PolynomialQuotient,
+            &[u, e!(x + 1), x.clone()],
+            e!(x + 2)
+        ));
+        let u = e!(x ^ 2 + 1);
+        assert!(gives(
+            B::PolynomialRemainder,
+            &[u.clone(), e!(x + 1), x.clone()],
+            e!(2)
+        ));
+        let res = e!(x - 1 + 2 / (x + 1));
+        assert!(gives(B::PolynomialDivide, &[u, e!(x + 1), x.clone()], res));
+        let args = [e!(x ^ 2 - 1), e!(x ^ 2 + 2 * x + 1)];
+        assert!(gives(B::PolynomialGCD, &args, e!(x + 1)));
+        eq!(
+            call(B::PolynomialQuotient, &[e!(sqrt(x)), e!(x + 1), x.clone()]),
+            WA::None
+        );
+
+        assert!(gives(
+            B::RemoveContent,
+            &[e!(a * (2 + 4 * x)), x.clone()],
+            e!(1 + 2 * x)
+        ));
+        assert!(gives(
+            B::RemoveContent,
+            &[e!(3 * x ^ 2), x.clone()],
+            e!(x ^ 2)
+        ));
+        assert!(gives(B::D, &[e!(a * x ^ 2), x.clone()], e!(2 * a * x)));
+        assert!(gives(
+            B::Subst,
+            &[e!(x ^ 2), x.clone(), e!(a + 1)],
+            e!((a + 1) ^ 2)
+        ));
+        let WA::Expr(together) = call(B::Together, &[e!(1 / x + 1 / y)]) else {
+            panic!("Together failed")
+        };
+        assert!(eq_q(&together.denominator(), &e!(x * y).reduce()));
+    }
+
+    #[test]
+    fn numbers() {
+        assert!(gives(B::GCD, &[e!(6), e!(9)], e!(3)));
+        assert!(gives(B::GCD, &[e!(1 / 2), e!(1 / 3)], e!(1 / 6)));
+        assert!(gives(B::Sign, &[e!(-3)], e!(-1)));
+        assert!(gives(B::Abs, &[e!(-1 / 2)], e!(1 / 2)));
+        eq!(call(B::Sign, &[e!(a)]), WA::None);
+        assert!(gives(B::Max, &[e!(1), e!(3), e!(2)], e!(3)));
+        assert!(gives(B::Min, &[e!(1), e!(-3)], e!(-3)));
+        eq!(call(B::Max, &[e!(a), e!(1)]), WA::None);
+        assert!(gives(B::Mod, &[e!(-1), e!(3)], e!(2)));
+        assert!(gives(B::Floor, &[e!(-1 / 2)], e!(-1)));
+        assert!(gives(B::FractionalPart, &[e!(-7 / 3)], e!(-1 / 3)));
+        assert!(gives(B::IntegerPart, &[e!(-7 / 3)], e!(-2)));
+        assert!(gives(B::Binomial, &[e!(5), e!(2)], e!(10)));
+        assert!(gives(B::LeafCount, &[e!(1 + a + b ^ 2)], e!(6)));
+        assert!(gives(B::LeafCount, &[e!(1 / 2)], e!(3)));
+        assert!(gives(B::Rt, &[e!(-8), e!(3)], e!(-2)));
+        assert!(gives(B::Numer, &[e!(a / b)], e!(a)));
+        assert!(gives(B::Denom, &[e!(a / b)], e!(b)));
+        eq!(call(B::Unintegrable, &[e!(x), e!(x)]), WA::None);
+        assert!(gives(B::Numerator, &[e!(2 * a / (3 * b))], e!(2 * a)));
+        assert!(gives(B::Denominator, &[e!(2 * a / (3 * b))], e!(3 * b)));
+        assert!(gives(B::FracPart, &[e!(7 / 3)], e!(1 / 3)));
+        assert!(gives(B::FracPart, &[e!(-7 / 3)], e!(-1 / 3)));
+        assert!(gives(B::FracPart, &[e!(a)], e!(a)));
+        assert!(gives(B::IntPart, &[e!(-7 / 3)], e!(-2)));
+        assert!(gives(B::IntPart, &[e!(a)], e!(0)));
+    }
+
+    #[test]
+    fn types() {
+        assert!(is(B::NumberQ, &[e!(1 / 2)]));
+        assert!(!is(B::NumberQ, &[e!(a)]));
+        assert!(is(B::EvenQ, &[e!(4)]));
+        assert!(!is(B::EvenQ, &[e!(3)]));
+        assert!(is(B::OddQ, &[e!(3)]));
+        assert!(!is(B::OddQ, &[e!(1 / 3)]));
+        assert!(is(B::ProductQ, &[e!(a * b)]));
+        assert!(!is(B::ProductQ, &[e!(a + b)]));
+        assert!(is(B::PowQ, &[e!(a ^ b)]));
+        assert!(!is(B::PowQ, &[e!(a)]));
+        assert!(is(B::AtomQ, &[e!(a)]));
+        assert!(is(B::AtomQ, &[e!(2)]));
+        assert!(!is(B::AtomQ, &[e!(a + 1)]));
+        assert!(!is(B::ComplexNumberQ, &[e!(2)]));
+
+        assert!(is(B::GeQ, &[e!(2), e!(2), e!(1)]));
+        assert!(!is(B::GeQ, &[e!(1), e!(2)]));
+        assert!(is(B::LeQ, &[e!(-1), e!(1 / 2), e!(1 / 2)]));
+        assert!(!is(B::LeQ, &[e!(a), e!(1)]));
+        assert!(is(B::IGeQ, &[e!(2), e!(2)]));
+        assert!(!is(B::IGeQ, &[e!(5 / 2), e!(2)]));
+        assert!(is(B::ILeQ, &[e!(-2), e!(0)]));
+        assert!(!is(B::ILeQ, &[e!(1), e!(0)]));
+
+        eq!(B::Not(WArgs::One(WA::TRUE)), WA::FALSE);
+        eq!(B::Not(WArgs::One(WA::FALSE)), WA::TRUE);
+        eq!(B::Not(WArgs::One(e!(a).into())), WA::None);
+    }
+
+    #[test]
+    fn arithmetic() {
+        let x = e!(x);
+        assert!(gives(B::Plus, &[e!(a), e!(b), e!(a)], e!(2 * a + b)));
+        assert!(gives(B::Times, &[e!(a), e!(b), e!(a)], e!(a ^ 2 * b)));
+        assert!(gives(B::Subtract, &[e!(a), e!(b)], e!(a - b)));
+        assert!(gives(B::Divide, &[e!(a), e!(b)], e!(a / b)));
+        assert!(gives(B::Power, &[e!(a), e!(3)], e!(a ^ 3)));
+        assert!(gives(B::Minus, &[e!(a)], e!(-1 * a)));
+        assert!(gives(B::Sqrt, &[e!(4)], e!(2)));
+        assert!(gives(B::Exp, &[e!(0)], e!(1)));
+        assert!(gives(B::Log, &[e!(1)], e!(0)));
+        let log2 = Expr::log(Rational::from(2), e!(a));
+        assert!(gives(B::Log, &[e!(2), e!(a)], log2));
+
+        assert!(gives(B::Sin, &[e!(0)], e!(0)));
+        assert!(gives(B::Cos, &[e!(0)], e!(1)));
+        assert!(gives(B::Sin, &[e!(x)], e!(sin(x))));
+        assert!(gives(B::Cos, &[e!(x)], e!(cos(x))));
+        assert!(gives(B::Tan, &[e!(x)], e!(tan(x))));
+        assert!(gives(B::ArcSin, &[e!(x)], e!(arc_sin(x))));
+        assert!(gives(B::ArcCos, &[e!(x)], e!(arc_cos(x))));
+        assert!(gives(B::ArcTan, &[e!(x)], e!(arc_tan(x))));
+        assert!(gives(B::ArcSinh, &[e!(0)], e!(0)));
+        assert!(gives(B::ArcSinh, &[e!(2)], e!(ln(2 + sqrt(5)))));
+        assert!(gives(B::ArcCosh, &[e!(2)], e!(ln(2 + sqrt(3)))));
+        assert!(gives(B::ArcTanh, &[e!(0)], e!(0)));
+        let res = e!((ln(3 / 2) - ln(1 / 2)) / 2);
+        assert!(gives(B::ArcTanh, &[e!(1 / 2)], res));
+
+        assert!(gives(B::Derivative, &[e!(x ^ 3), x.clone()], e!(3 * x ^ 2)));
+        assert!(gives(
+            B::Expand,
+            &[e!((a + b) ^ 2)],
+            e!(a ^ 2 + 2 * a * b + b ^ 2)
+        ));
+        assert!(gives(B::Simp, &[e!(x + x), x.clone()], e!(2 * x)));
+        assert!(gives(B::Simplify, &[e!(a * b / a)], e!(b)));
+        let args = [e!((x ^ 2 - 1) / (x - 1)), x.clone()];
+        assert!(gives(B::SimplifyIntegrand, &args, e!(x + 1)));
+    }
+
+    #[test]
+    fn integrand() {
+        let x = e!(x);
+        let args = [e!(x * (1 + x) ^ m), x.clone()];
+        assert!(gives(
+            B::ExpandIntegrand,
+            &args,
+            e!((1 + x) ^ (m + 1) - (1 + x) ^ m)
+        ));
+        let args = [e!(a + b * x), x.clone()];
+        assert!(gives(B::ExpandIntegrand, &args, e!(a + b * x)));
+        assert!(gives(B::Int, &[e!(2 * x), x.clone()], e!(x ^ 2)));
+        eq!(call(B::Int, &[e!(sin(x)), x.clone()]), WA::None);
+        eq!(call(B::CannotIntegrate, &[e!(sin(x)), x.clone()]), WA::None);
+    }
+
+    #[test]
+    fn matcher() {
+        // a_. + b_.*x_
+        let pat = Pattern::Sum(vec![
+            Pattern::Opt("a".into()),
+            Pattern::Prod(vec![Pattern::Opt("b".into()), Pattern::Sym("x".into())]),
+        ]);
+        let matches = |e: Expr| {
+            let mut res = vec![];
+            let b = [("x".into(), e!(x))].into_iter().collect();
+            pat.matches(&e.reduce(), &b, &mut |b| {
+                res.push((b["a"].clone(), b["b"].clone()));
+                false
+            });
+            res
+        };
+        eq!(matches(e!(x)), vec![(e!(0), e!(1))]);
+        eq!(matches(e!(3 * x + 2)), vec![(e!(2), e!(3))]);
+        eq!(matches(e!(1 + y + 2 * y * x)).len(), 1);
+        eq!(matches(e!(y)), vec![]);
+    }
+
+    #[test]
+    fn linear() {
+        let x = e!(x);
+        eq!(e!(1 / x).integrate(&x), Some(e!(ln(x))));
+        eq!(e!(x ^ 3).integrate(&x), Some(e!(x ^ 4 / 4).reduce()));
+        eq!(e!(5).integrate(&x), Some(e!(5 * x).reduce()));
+        check(e!(1 / (2 * x + 3)));
+        check(e!((2 * x + 3) ^ 5));
+        check(e!((a + b * x) ^ m));
+        check(e!(1 / ((x + 1) * (x + 2))));
+        check(e!(x / (x + 1)));
+        check(e!(x ^ 2 * (x + 1) ^ (1 / 3)));
+        check(e!(sqrt(x + 1) * (x + 2)));
+        check(e!(sqrt(x + 1) / x));
+        check(e!(1 / ((2 * x + 1) ^ 2 * (x + 3))));
+        check(e!(sqrt(2 * x + 1) * sqrt(3 * x + 1)));
+        check(e!(3 * x ^ 2 + 2 * x + 1));
+    }
+
+    #[test]
+    fn quadratic() {
+        let x = e!(x);
+        eq!(e!(1 / (1 + x ^ 2)).integrate(&x), Some(e!(arc_tan(x))));
+        eq!(e!(1 / sqrt(1 - x ^ 2)).integrate(&x), Some(e!(arc_sin(x))));
+        check(e!(1 / (4 + 9 * x ^ 2)));
+        check(e!(1 / (1 - x ^ 2)));
+        check(e!(1 / sqrt(1 + x ^ 2)));
+        check(e!(sqrt(1 - x ^ 2)));
+        check(e!(x / (1 + x ^ 2)));
+        check(e!(x ^ 2 / (1 + x ^ 2)));
+        check(e!(1 / (x * (1 + x ^ 2))));
+        check(e!(1 / (a + b * x ^ 2)));
+        check(e!(x ^ 3 / (1 + x ^ 4)));
+    }
+
+    #[test]
+    fn polynomial_factor() {
+        check(e!((x ^ 2 + 1) / (x + 1)));
+        check(e!((x ^ 3 + x) / (x ^ 2 + 2)));
+        check(e!(x ^ 5 / (1 + x ^ 3)));
+        check(e!((1 + 2 * x + x ^ 2) * sqrt(1 + x ^ 2)));
+    }
+
+    #[test]
+    fn no_rule() {
+        let x = e!(x);
+        eq!(e!(sin(x)).integrate(&x), None);
+        eq!(e!(x ^ x).integrate(&x), None);
+        eq!(e!(x).integrate(&e!(2 * x)), None);
+    }
+}
@@ -0,0 +1,744 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    atom::{Atom, Expr, Func, Infinity, SymbolicExpr},
+    rational::Rational,
+    utils::HashMap,
+};
+
+/// how many extra orders are tried when looking for the leading term of an expression
+const LOOKAHEAD: i32 = 8;
+
+fn simp(e: &Expr) -> Expr {
+    e.expand().reduce()
+}
+
+/// a truncated series c_1 t^k_1 + c_2 t^k_2 + ... + O(t^order) in the expansion variable t
+#[derive(Clone, Debug)]
+struct Series {
+    /// (k, c) sorted by k, all coefficients are nonzero
+    terms: Vec<(Rational, Expr)>,
+    /// [None] if the series is exact
+    order: Option<Rational>,
+}
+
+impl Series {
+    fn zero() -> Self {
+        Self {
+            terms: vec![],
+            order: None,
+        }
+    }
+
+    fn constant(c: Expr) -> Self {
+        Self::monomial(Rational::ZERO, c)
+    }
+
+    fn monomial(k: Rational, c: Expr) -> Self {
+        let c = simp(&c);
+        let terms = if c.is_zero() { vec![] } else { vec![(k, c)] };
+        Self { terms, order: None }
+    }
+
+    /// O(t^order)
+    fn big_o(order: Rational) -> Self {
+        Self {
+            terms: vec![],
+            order: Some(order),
+        }
+    }
+
+    fn from_terms(
+        terms: impl IntoIterator<Item = (Rational, Expr)>,
+        order: Option<Rational>,
+    ) -> Self {
+        let mut sum: BTreeMap<Rational, Expr> = BTreeMap::new();
+        for (k, c) in terms {
+            if order.as_ref().is_some_and(|o| &k >= o) {
+                continue;
+            }
+            let e = sum.entry(k).or_insert_with(Expr::zero);
+            *e = &*e + c;
+        }
+        let terms = sum
+            .into_iter()
+            .map(|(k, c)| (k, simp(&c)))
+            .filter(|(_, c)| !c.is_zero())
+            .collect();
+        Self { terms, order }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.terms.is_empty() && self.order.is_none()
+    }
+
+    /// exponent of the leading term
+    fn val(&self) -> Option<&Rational> {
+        self.terms.first().map(|(k, _)| k)
+    }
+
+    /// exponent of the leading term or of the order term, [None] for zero
+    fn low(&self) -> Option<Rational> {
+        self.val().or(self.order.as_ref()).cloned()
+    }
+
+    /// coefficient of t^k
+    fn coeff(&self, k: &Rational) -> Expr {
+        self.terms
+            .iter()
+            .find(|(e, _)| e == k)
+            .map_or_else(Expr::zero, |(_, c)| c.clone())
+    }
+
+    /// drop all terms from t^n, an exact series stays exact if no term was dropped
+    fn truncate(mut self, n: &Rational) -> Self {
+        let len = self.terms.len();
+        self.terms.retain(|(k, _)| k < n);
+        self.order = match self.order {
+            Some(o) => Some(o.min(n.clone())),
+            None if self.terms.len() < len => Some(n.clone()),
+            None => None,
+        };
+        self
+    }
+
+    fn add(&self, rhs: &Series) -> Series {
+        let order = match (&self.order, &rhs.order) {
+            (Some(a), Some(b)) => Some(a.clone().min(b.clone())),
+            (a, b) => a.clone().or(b.clone()),
+        };
+        Series::from_terms(self.terms.iter().chain(&rhs.terms).cloned(), order)
+    }
+
+    fn mul(&self, rhs: &Series) -> Series {
+        if self.is_zero() || rhs.is_zero() {
+            return Series::zero();
+        }
+        // (a + O(t^n)) * (b + O(t^m)) = ab + O(t^(n + low(b))) + O(t^(m + low(a)))
+        let order = [
+            self.order.clone().zip(rhs.low()),
+            rhs.order.clone().zip(self.low()),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|(o, l)| o + l)
+        .min();
+
+        let prods = self.terms.iter().flat_map(|(k1, c1)| {
+            rhs.terms
+                .iter()
+                .map(move |(k2, c2)| (k1.clone() + k2, c1 * c2))
+        });
+        Series::from_terms(prods, order)
+    }
+
+    fn scale(&self, c: &Expr) -> Series {
+        if c.is_zero() {
+            return Series::zero();
+        }
+        let terms = self.terms.iter().map(|(k, a)| (k.clone(), a * c));
+        Series::from_terms(terms, self.order.clone())
+    }
+
+    /// multiply by t^s
+    fn shift(&self, s: &Rational) -> Series {
+        Series {
+            terms: self
+                .terms
+                .iter()
+                .map(|(k, c)| (k.clone() + s, c.clone()))
+                .collect(),
+            order: self.order.clone().map(|o| o + s),
+        }
+    }
+
+    /// split into the constant term and the rest
+    ///
+    /// returns [None] if the series has terms with negative exponent
+    fn split_const(&self) -> Option<(Expr, Series)> {
+        if self.val().is_some_and(|k| k.is_neg()) {
+            return None;
+        }
+        let c0 = self.coeff(&Rational::ZERO);
+        let rest = self.terms.iter().filter(|(k, _)| !k.is_zero()).cloned();
+        Some((c0, Series::from_terms(rest, self.order.clone())))
+    }
+
+    /// sum of c_k * h^k for k = 0, 1, ... up to O(t^n), where h has a positive valuation
+    ///
+    /// the coefficients are generated by `coeff`, which returns [None] when the sum terminates
+    fn power_sum(h: &Series, n: &Rational, mut coeff: impl FnMut(u32) -> Option<Expr>) -> Series {
+        let mut sum = Series::zero();
+        let mut pow = Series::constant(Expr::one());
+        for k in 0.. {
+            let Some(c) = coeff(k) else { break };
+            sum = sum.add(&pow.scale(&c));
+            pow = pow.mul(h);
+            match pow.low() {
+                None => break,
+                Some(low) if &low >= n => return sum.add(&Series::big_o(n.clone())),
+                _ if pow.terms.last().is_some_and(|(k, _)| k >= n) => pow = pow.truncate(n),
+                _ => (),
+            }
+        }
+        sum
+    }
+
+    /// exp(s)
+    fn exp(&self, n: &Rational) -> Option<Series> {
+        let (c0, h) = self.split_const()?;
+        let mut fact = Expr::one();
+        let sum = Series::power_sum(&h, n, |k| {
+            if k > 0 {
+                fact = &fact / Expr::from(k);
+            }
+            Some(fact.clone())
+        });
+        Some(sum.scale(&Expr::exp(c0)))
+    }
+
+    /// (sin(s), cos(s))
+    fn sin_cos(&self, n: &Rational) -> Option<(Series, Series)> {
+        let (c0, h) = self.split_const()?;
+        let taylor = |parity: u32| {
+            let mut fact = Expr::one();
+            Series::power_sum(&h, n, |k| {
+                if k > 0 {
+                    fact = &fact / Expr::from(k);
+                }
+                Some(match k % 4 {
+                    _ if k % 2 != parity => Expr::zero(),
+                    0 | 1 => fact.clone(),
+                    _ => Expr::min_one() * &fact,
+                })
+            })
+        };
+        // sin(c0 + h) = sin(c0) cos(h) + cos(c0) sin(h)
+        let (sin_h, cos_h) = (taylor(1), taylor(0));
+        let (sin_c, cos_c) = (Expr::sin(&c0).reduce(), Expr::cos(&c0).reduce());
+        let sin = sin_h.scale(&cos_c).add(&cos_h.scale(&sin_c));
+        let cos = cos_h
+            .scale(&cos_c)
+            .add(&sin_h.scale(&(Expr::min_one() * sin_c)));
+        Some((sin, cos))
+    }
+
+    /// s^p with the relative precision `rel`
+    fn pow(&self, p: &Expr, rel: &Rational) -> Option<Series> {
+        let (v, c) = self.terms.first()?.clone();
+        let vp = match p.atom() {
+            Atom::Rational(p) => v.clone() * p,
+            _ if v.is_zero() => Rational::ZERO,
+            _ => return None,
+        };
+        // s = c t^v (1 + u)
+        let u = self
+            .shift(&(Rational::ZERO - &v))
+            .scale(&Expr::pow(&c, Expr::min_one()))
+            .add(&Series::constant(Expr::min_one()));
+        // binomial series: (1 + u)^p = sum (p choose k) u^k
+        let mut binom = Expr::one();
+        let sum = Series::power_sum(&u, rel, |k| {
+            if k > 0 {
+                binom = simp(&(&binom * (p - Expr::from(k - 1)) / Expr::from(k)));
+            }
+            (!binom.is_zero()).then(|| binom.clone())
+        });
+        Some(sum.scale(&Expr::pow(c, p)).shift(&vp))
+    }
+
+    /// ln(1 + u) for u with a positive valuation
+    fn log1p(u: &Series, n: &Rational) -> Series {
+        Series::power_sum(u, n, |k| {
+            Some(match k {
+                0 => Expr::zero(),
+                _ if k % 2 == 0 => Expr::from(Rational::from((-1, k as i32))),
+                _ => Expr::from(Rational::from((1, k as i32))),
+            })
+        })
+    }
+
+    /// d/dt, the coefficients must be free of t
+    fn deriv(&self) -> Series {
+        let terms = self
+            .terms
+            .iter()
+            .map(|(k, c)| (k.clone() - Rational::ONE, Expr::from(k.clone()) * c));
+        Series::from_terms(terms, self.order.clone().map(|o| o - Rational::ONE))
+    }
+
+    /// the antiderivative without constant term
+    ///
+    /// returns [None] if there is a t^-1 term
+    fn integrate(&self) -> Option<Series> {
+        let mut terms = vec![];
+        for (k, c) in &self.terms {
+            let k = k.clone() + Rational::ONE;
+            if k.is_zero() {
+                return None;
+            }
+            terms.push((k.clone(), c / Expr::from(k)));
+        }
+        Some(Series::from_terms(
+            terms,
+            self.order.clone().map(|o| o + Rational::ONE),
+        ))
+    }
+}
+
+/// the point of expansion: x = x0 + t, x = 1/t or x = -1/t
+#[derive(Clone, Debug)]
+enum Point {
+    Finite(Expr),
+    PosInf,
+    NegInf,
+}
+
+/// expands expressions in `x` around a [Point], computed series are cached by their precision
+struct Expansion<'a> {
+    x: &'a Expr,
+    point: Point,
+    cache: HashMap<(Expr, Rational), Option<Series>>,
+}
+
+impl Expansion<'_> {
+    /// the expansion variable t in terms of x
+    fn t(&self) -> Expr {
+        match &self.point {
+            Point::Finite(x0) => self.x - x0,
+            Point::PosInf => Expr::pow(self.x, Expr::min_one()),
+            Point::NegInf => Expr::min_one() / self.x,
+        }
+    }
+
+    /// ln(t) in terms of x
+    fn ln_t(&self) -> Expr {
+        match &self.point {
+            Point::Finite(x0) => Expr::ln(self.x - x0),
+            Point::PosInf => Expr::min_one() * Expr::ln(self.x),
+            Point::NegInf => Expr::min_one() * Expr::ln(Expr::min_one() * self.x),
+        }
+    }
+
+    /// series of `e` up to O(t^n)
+    fn ser(&mut self, e: &Expr, n: &Rational) -> Option<Series> {
+        let key = (e.clone(), n.clone());
+        if let Some(s) = self.cache.get(&key) {
+            return s.clone();
+        }
+        let s = self.expand(e, n).map(|s| s.truncate(n));
+        self.cache.insert(key, s.clone());
+        s
+    }
+
+    /// t^k in terms of x
+    fn t_pow(&self, k: &Rational) -> Expr {
+        let min_k = Expr::from(Rational::ZERO - k);
+        match &self.point {
+            // (-1/x)^k = (-1)^k x^-k
+            Point::NegInf if k.is_int() => {
+                Expr::pow(Expr::min_one(), Expr::from(k.clone())) * Expr::pow(self.x, min_k)
+            }
+            Point::Finite(_) | Point::NegInf => Expr::pow(self.t(), Expr::from(k.clone())),
+            Point::PosInf => Expr::pow(self.x, min_k),
+        }
+    }
+
+    /// series of `e` with a leading term, starting with the precision `n`
+    ///
+    /// returns [None] if no term is found within a few orders, e.g for sin(x)^2 + cos(x)^2 - 1
+    fn lead(&mut self, e: &Expr, n: &Rational) -> Option<Series> {
+        for k in 0..=LOOKAHEAD {
+            let s = self.ser(e, &(n.clone() + Rational::from(k)))?;
+            if !s.terms.is_empty() || s.order.is_none() {
+                return Some(s);
+            }
+        }
+        None
+    }
+
+    /// series of `e` with the relative precision `rel`, i.e up to O(t^(v + rel))
+    /// where t^v is the leading term
+    fn rel(&mut self, e: &Expr, rel: &Rational) -> Option<Series> {
+        let s = self.lead(e, rel)?;
+        let Some(v) = s.val() else { return Some(s) };
+        let n = v.clone() + rel;
+        if s.order.as_ref().is_some_and(|o| o < &n) {
+            self.ser(e, &n)
+        } else {
+            Some(s.truncate(&n))
+        }
+    }
+
+    fn expand(&mut self, e: &Expr, n: &Rational) -> Option<Series> {
+        use Atom as A;
+        if e.is_undef() {
+            return None;
+        } else if e.free_of(self.x) {
+            return Some(Series::constant(e.clone()));
+        }
+
+        match e.atom() {
+            A::Var(_) => Some(match &self.point {
+                Point::Finite(x0) => Series::from_terms(
+                    [(Rational::ZERO, x0.clone()), (Rational::ONE, Expr::one())],
+                    None,
+                ),
+                Point::PosInf => Series::monomial(Rational::MINUS_ONE, Expr::one()),
+                Point::NegInf => Series::monomial(Rational::MINUS_ONE, Expr::min_one()),
+            }),
+            A::Sum(sum) => {
+                let mut res = Series::zero();
+                for a in &sum.args {
+                    res = res.add(&self.ser(a, n)?);
+                }
+                Some(res)
+            }
+            A::Prod(prod) => self.prod(&prod.args, n),
+            A::Pow(pow) => self.pow(pow.base(), pow.exponent(), n),
+            A::Func(f) => self.func(f, n),
+            _ => None,
+        }
+    }
+
+    fn prod(&mut self, args: &[Expr], n: &Rational) -> Option<Series> {
+        let mut factors = vec![];
+        let mut total = Rational::ZERO;
+        for a in args {
+            let s = self.lead(a, n)?;
+            if s.is_zero() {
+                return Some(s);
+            }
+            total += s.val().unwrap();
+            factors.push(s);
+        }
+        // the i-th factor is needed up to O(t^(n - sum of the other valuations))
+        let mut res = Series::constant(Expr::one());
+        for (a, s) in args.iter().zip(factors) {
+            let m = n.clone() - &total + s.val().unwrap();
+            let s = if s.order.as_ref().is_some_and(|o| o < &m) {
+                self.ser(a, &m)?
+            } else {
+                s.truncate(&m)
+            };
+            res = res.mul(&s);
+        }
+        Some(res)
+    }
+
+    fn pow(&mut self, base: &Expr, exp: &Expr, n: &Rational) -> Option<Series> {
+        if !exp.free_of(self.x) {
+            if base.is_e() {
+                return self.ser(exp, n)?.exp(n);
+            }
+            // b^p = exp(p * ln(b))
+            let e = Expr::pow_raw(Expr::e(), Expr::mul_raw(exp, Expr::ln(base)));
+            return self.ser(&e, n);
+        }
+
+        let s = self.lead(base, n)?;
+        let Some(v) = s.val() else {
+            // 0^p
+            return exp.is_rational_and(|p| p.is_pos()).then(Series::zero);
+        };
+        let vp = match exp.atom() {
+            Atom::Rational(p) => v.clone() * p,
+            _ if v.is_zero() => Rational::ZERO,
+            _ => return None,
+        };
+        // c^p t^(vp) (1 + u)^p, where (1 + u)^p is needed up to O(t^(n - vp))
+        let rel = n.clone() - &vp;
+        if !rel.is_pos() {
+            return Some(Series::big_o(n.clone()));
+        }
+        self.rel(base, &rel)?.pow(exp, &rel)
+    }
+
+    fn func(&mut self, f: &Func, n: &Rational) -> Option<Series> {
+        use Expr as E;
+        use Func as F;
+        let inv = |e: &E| E::pow_raw(e, E::min_one());
+
+        match f {
+            F::Sin(g) | F::Cos(g) => {
+                let (sin, cos) = self.ser(g, n)?.sin_cos(n)?;
+                Some(if f.is_sin() { sin } else { cos })
+            }
+            F::Tan(g) => self.ser(&E::mul_raw(E::sin(g), inv(&E::cos(g))), n),
+            F::Cot(g) => self.ser(&E::mul_raw(E::cos(g), inv(&E::sin(g))), n),
+            F::Sec(g) => self.ser(&inv(&E::cos(g)), n),
+            F::Csc(g) => self.ser(&inv(&E::sin(g)), n),
+            F::ArcSec(g) => self.ser(&E::arc_cos(inv(g)), n),
+            F::ArcCsc(g) => self.ser(&E::arc_sin(inv(g)), n),
+            F::ArcSin(g) | F::ArcCos(g) | F::ArcTan(g) | F::ArcCot(g) => self.arc(f, g, n),
+            F::Log(base, g) => {
+                let s = self.rel(g, n)?;
+                let (v, c) = s.terms.first()?.clone();
+                // ln(c t^v (1 + u)) = ln(c) + v ln(t) + ln(1 + u)
+                let u = s
+                    .shift(&(Rational::ZERO - &v))
+                    .scale(&E::pow(&c, E::min_one()))
+                    .add(&Series::constant(E::min_one()));
+                let c0 = E::ln(c) + E::from(v) * self.ln_t();
+                let ln = Series::log1p(&u, n).add(&Series::constant(c0));
+                Some(match f.is_nat_log() {
+                    true => ln,
+                    false => ln.scale(&inv(&E::ln(E::from(base.clone())))),
+                })
+            }
+            F::Order(g) => {
+                let s = self.lead(g, n)?;
+                Some(s.low().map_or_else(Series::zero, Series::big_o))
+            }
+            F::Re(_)
+            | F::Im(_)
+            | F::Conj(_)
+            | F::Arg(_)
+            | F::IsPrime(_)
+            | F::NextPrime(_)
+            | F::Totient(_)
+            | F::Divisors(_)
+            | F::Mod(_)
+            | F::PowMod(_)
+            | F::ModInv(_)
+            | F::RootOf(_) => self.taylor(&E::from(f.clone()), n),
+        }
+    }
+
+    /// F(g) = F(g0) + integral of F'(g) dg/dt dt for the inverse trigonometric functions
+    fn arc(&mut self, f: &Func, g: &Expr, n: &Rational) -> Option<Series> {
+        use Expr as E;
+        use Func as F;
+        let s = self.ser(g, n)?;
+        let half_pi = E::pi() / E::two();
+
+        if s.val().is_some_and(|v| v.is_neg()) {
+            let inv_g = E::arc_tan(E::pow_raw(g, E::min_one()));
+            let c = &s.terms[0].1;
+            return match f {
+                // arctan(g) = +-pi/2 - arctan(1/g)
+                F::ArcTan(_) if c.is_number() => {
+                    let at = self.ser(&inv_g, n)?.scale(&E::min_one());
+                    let sign = if c.is_neg() { E::min_one() } else { E::one() };
+                    Some(at.add(&Series::constant(sign * half_pi)))
+                }
+                F::ArcCot(_) => self.ser(&inv_g, n),
+                _ => None,
+            };
+        }
+        if s.terms.iter().any(|(_, c)| !c.free_of(self.x)) {
+            return None;
+        }
+
+        let g0 = s.coeff(&Rational::ZERO);
+        let asin0 = match () {
+            _ if g0.is_zero() => Some(E::zero()),
+            _ if g0.is_one() => Some(half_pi.clone()),
+            _ if g0.is_min_one() => Some(E::min_one() * &half_pi),
+            _ => None,
+        };
+        let one = Series::constant(E::one());
+        let sq = s.mul(&s);
+        // arcsin'(g) = (1 - g^2)^(-1/2), arctan'(g) = (1 + g^2)^-1
+        let asin = || {
+            one.add(&sq.scale(&E::min_one()))
+                .pow(&E::from(Rational::from((-1, 2))), n)
+        };
+        let atan = || one.add(&sq).pow(&E::min_one(), n);
+        let (df, c0) = match f {
+            F::ArcSin(_) => (asin()?, asin0.unwrap_or_else(|| E::arc_sin(&g0))),
+            F::ArcCos(_) => (
+                asin()?.scale(&E::min_one()),
+                asin0.map_or_else(|| E::arc_cos(&g0), |a| &half_pi - a),
+            ),
+            F::ArcTan(_) if g0.is_zero() => (atan()?, E::zero()),
+            F::ArcTan(_) => (atan()?, E::arc_tan(&g0)),
+            F::ArcCot(_) if g0.is_zero() => (atan()?.scale(&E::min_one()), half_pi),
+            F::ArcCot(_) => (atan()?.scale(&E::min_one()), E::arc_cot(&g0)),
+            _ => unreachable!(),
+        };
+        let int = df.mul(&s.deriv()).integrate()?;
+        Some(int.add(&Series::constant(c0)))
+    }
+
+    /// sum of f^(k)(x0)/k! t^k, only at a finite point
+    fn taylor(&mut self, e: &Expr, n: &Rational) -> Option<Series> {
+        let Point::Finite(x0) = &self.point else {
+            return None;
+        };
+        let mut terms = vec![];
+        let mut d = e.clone();
+        let mut fact = Expr::one();
+        let mut k = 0;
+        while &Rational::from(k) < n {
+            let c = d.substitude(self.x, x0).reduce();
+            if c.is_undef() || c.is_infinity() || !c.free_of(self.x) {
+                return None;
+            }
+            terms.push((Rational::from(k), c / &fact));
+            k += 1;
+            d = d.derivative(self.x).reduce();
+            fact *= Expr::from(k);
+        }
+        Some(Series::from_terms(terms, Some(n.clone())))
+    }
+}
+
+/// the terms of the series of `e` in `t` at 0 up to O(t), see [Expr::limit]
+///
+/// a logarithmic singularity is kept in the coefficients as ln(t). If there is no term below
+/// t^1 the leading term is searched for a few more orders. Returns [None] if the terms below
+/// t^0 are unknown
+pub(crate) fn terms_at_zero(e: &Expr, t: &Expr) -> Option<Vec<(Rational, Expr)>> {
+    let mut exp = Expansion {
+        x: t,
+        point: Point::Finite(Expr::zero()),
+        cache: HashMap::default(),
+    };
+    let s = exp.ser(e, &Rational::ONE)?;
+    match s.order {
+        Some(o) if s.terms.is_empty() => {
+            let lead = exp.lead(e, &Rational::ONE);
+            o.is_pos().then(|| lead.map_or(vec![], |s| s.terms))
+        }
+        _ => Some(s.terms),
+    }
+}
+
+impl Expr {
+    /// the series of [self] in `x` around `x0` up to the order term O((x - x0)^order)
+    ///
+    /// `x0` can be oo or -oo, the series is then in 1/x. Negative and fractional exponents
+    /// are allowed, e.g 1/sin(x) = 1/x + x/6 + O(x^3), and a logarithmic singularity is kept
+    /// in the constant term. The order term is left out if the series is exact. Returns [None]
+    /// if `x` is not a variable or [self] has no series expansion at `x0`, e.g sin(1/x) at 0
+    pub fn series(&self, x: &Expr, x0: &Expr, order: u32) -> Option<Expr> {
+        if !x.is_var() {
+            return None;
+        }
+        let point = match x0.atom() {
+            Atom::Infinity(Infinity::Pos) => Point::PosInf,
+            Atom::Infinity(Infinity::Neg) => Point::NegInf,
+            Atom::Infinity(Infinity::Complex) | Atom::Undef => return None,
+            _ if x0.free_of(x) => Point::Finite(x0.clone()),
+            _ => return None,
+        };
+        let mut exp = Expansion {
+            x,
+            point,
+            cache: HashMap::default(),
+        };
+        let n = Rational::from(order);
+        let s = exp.ser(self, &n)?;
+
+        let mut res = Expr::zero();
+        for (k, c) in &s.terms {
+            res += c * exp.t_pow(k);
+        }
+        if let Some(o) = s.order {
+            // the sign of t doesn't matter in the order term
+            if let Point::NegInf = exp.point {
+                exp.point = Point::PosInf;
+            }
+            res += Expr::order(exp.t_pow(&o));
+        }
+        Some(res.reduce())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::atom::Var;
+    use calcurs_macros::expr as e;
+
+    /// the order terms and the value of the remaining terms at x = 0.37, a = 0.61
+    fn split_order(e: &Expr) -> (Vec<Expr>, f64) {
+        let terms = match e.atom() {
+            Atom::Sum(sum) => sum.args.clone(),
+            _ => vec![e.clone()],
+        };
+        let (order, rest): (Vec<_>, Vec<_>) = terms
+            .into_iter()
+            .partition(|t| matches!(t.atom(), Atom::Func(Func::Order(_))));
+        let vars: HashMap<Var, f64> = [("x".into(), 0.37), ("a".into(), 0.61)]
+            .into_iter()
+            .collect();
+        let val = rest.iter().map(|t| t.evalf(&vars).unwrap()).sum();
+        (order, val)
+    }
+
+    macro_rules! series {
+        ($f:expr, $x0:expr, $n:literal => $res:expr) => {{
+            let (f, res) = ($f, $res);
+            let s = f.series(&e!(x), &$x0, $n).unwrap();
+            let ((o1, v1), (o2, v2)) = (split_order(&s), split_order(&res));
+            assert!(
+                o1 == o2 && (v1 - v2).abs() < 1e-9,
+                "series of {f}: {s} != {res}"
+            );
+        }};
+    }
+
+    fn o(e: Expr) -> Expr {
+        Expr::order(e)
+    }
+
+    #[test]
+    fn taylor() {
+        series!(e!(sin(x)), e!(0), 6 => e!(x - x^3/6 + x^5/120) + o(e!(x^6)));
+        series!(e!(exp(x)), e!(0), 4 => e!(1 + x + x^2/2 + x^3/6) + o(e!(x^4)));
+        series!(e!(ln(1 + x)), e!(0), 4 => e!(x - x^2/2 + x^3/3) + o(e!(x^4)));
+        series!(e!(arc_tan(x)), e!(0), 6 => e!(x - x^3/3 + x^5/5) + o(e!(x^6)));
+        series!(e!(tan(x)), e!(0), 6 => e!(x + x^3/3 + 2*x^5/15) + o(e!(x^6)));
+        series!(e!(ln(cos(x))), e!(0), 5 => e!(-(x^2)/2 - x^4/12) + o(e!(x^5)));
+        series!(e!(exp(sin(x))), e!(0), 4 => e!(1 + x + x^2/2) + o(e!(x^4)));
+        series!(e!(2^x), e!(0), 2 => e!(1 + ln(2) * x) + o(e!(x^2)));
+        series!(e!(1/(1 - x)), e!(0), 3 => e!(1 + x + x^2) + o(e!(x^3)));
+        series!(
+            e!(sin(x)), e!(a), 3 =>
+            e!(sin(a) + cos(a) * (x - a) - sin(a) * (x - a)^2 / 2) + o(e!((x - a)^3))
+        );
+        // exact
+        series!(e!((1 + x)^2), e!(0), 5 => e!(1 + 2*x + x^2));
+        series!(e!(x^3 - x), e!(1), 5 => e!(2*(x - 1) + 3*(x - 1)^2 + (x - 1)^3));
+    }
+
+    #[test]
+    fn laurent_puiseux() {
+        series!(e!(1/sin(x)), e!(0), 4 => e!(1/x + x/6 + 7*x^3/360) + o(e!(x^4)));
+        series!(e!(cot(x)), e!(0), 2 => e!(1/x - x/3) + o(e!(x^2)));
+        series!(e!(exp(x)/x), e!(0), 2 => e!(1/x + 1 + x/2) + o(e!(x^2)));
+        series!(e!(1/(1 - cos(x))), e!(0), 1 => e!(2/x^2 + 1/6) + o(e!(x)));
+        series!(e!(sqrt(1 + x)), e!(0), 3 => e!(1 + x/2 - x^2/8) + o(e!(x^3)));
+        series!(e!(sqrt(x)), e!(0), 3 => e!(sqrt(x)));
+        series!(
+            e!(sqrt(x + x^2)), e!(0), 3 =>
+            e!(x^(1/2) + x^(3/2)/2 - x^(5/2)/8) + o(e!(x^3))
+        );
+        series!(
+            e!(arc_sin(1 - x)), e!(0), 2 =>
+            e!(pi/2 - sqrt(2) * x^(1/2) - sqrt(2) * x^(3/2) / 12) + o(e!(x^2))
+        );
+        series!(e!(ln(sin(x))), e!(0), 4 => e!(ln(x) - x^2/6) + o(e!(x^4)));
+        series!(e!(order(x^2) + x), e!(0), 5 => e!(x) + o(e!(x^2)));
+    }
+
+    #[test]
+    fn at_infinity() {
+        series!(e!(1/(x + 1)), e!(oo), 4 => e!(1/x - 1/x^2 + 1/x^3) + o(e!(x^(-4))));
+        series!(e!(sqrt(x^2 + 1) - x), e!(oo), 4 => e!(1/(2*x) - 1/(8*x^3)) + o(e!(x^(-4))));
+        series!(e!(arc_tan(x)), e!(oo), 4 => e!(pi/2 - 1/x + 1/(3*x^3)) + o(e!(x^(-4))));
+        series!(e!(ln(1 + 1/x)), e!(oo), 3 => e!(1/x - 1/(2*x^2)) + o(e!(x^(-3))));
+        series!(e!((x^3 + 1)/(x - 1)), e!(oo), 1 => e!(x^2 + x + 1) + o(e!(x^(-1))));
+        series!(e!(x/(x^2 + 1)), e!(-oo), 4 => e!(1/x - 1/x^3) + o(e!(x^(-4))));
+    }
+
+    #[test]
+    fn no_series() {
+        let x = e!(x);
+        assert!(e!(sin(1 / x)).series(&x, &e!(0), 3).is_none());
+        assert!(e!(exp(x)).series(&x, &e!(oo), 3).is_none());
+        assert!(e!(sin(x)).series(&e!(2), &e!(0), 3).is_none());
+    }
+}
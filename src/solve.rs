@@ -0,0 +1,824 @@
+use std::{collections::HashMap, slice};
+
+use num::ToPrimitive;
+
+use crate::{
+    atom::{Atom, Expr, Func, RelOp, SymbolicExpr},
+    factor,
+    matrix::{self, Matrix},
+    polynomial::{RatPoly, VarSet},
+    rational::Rational,
+};
+
+/// cube roots of unity: 1, (-1 + i*sqrt(3))/2, (-1 - i*sqrt(3))/2
+fn unit_roots() -> [Expr; 3] {
+    let half = Expr::from(Rational::from((1, 2)));
+    let im = Expr::i() * Expr::sqrt(Expr::from(3)) * &half;
+    let re = Expr::min_one() * &half;
+    [Expr::one(), &re + &im, re - im]
+}
+
+/// -b/a
+fn linear(c: &[Expr]) -> Vec<Expr> {
+    vec![Expr::min_one() * &c[0] / &c[1]]
+}
+
+/// (-b +- sqrt(b^2 - 4ac)) / 2a
+fn quadratic(c: &[Expr]) -> Vec<Expr> {
+    let (a, b) = (&c[2], &c[1]);
+    let disc = Expr::sqrt(Expr::pow(b, Expr::two()) - Expr::from(4) * a * &c[0]);
+    let denom = Expr::two() * a;
+    vec![
+        (Expr::min_one() * b + &disc) / &denom,
+        (Expr::min_one() * b - disc) / denom,
+    ]
+}
+
+/// cardano's formula on the depressed cubic t^3 + pt + q, with x = t - b/3a
+fn cubic(c: &[Expr]) -> Vec<Expr> {
+    let e = |n: i32| Expr::from(n);
+    let (a, b, c, d) = (&c[3], &c[2], &c[1], &c[0]);
+    let shift = b / (e(3) * a);
+    let p = (e(3) * a * c - Expr::pow(b, e(2))) / (e(3) * Expr::pow(a, e(2)));
+    let q = (e(2) * Expr::pow(b, e(3)) - e(9) * a * b * c + e(27) * Expr::pow(a, e(2)) * d)
+        / (e(27) * Expr::pow(a, e(3)));
+    let cbrt = |x: Expr| Expr::pow(x, Expr::from(Rational::from((1, 3))));
+
+    let roots = unit_roots();
+    if p.is_zero() {
+        let u = cbrt(Expr::min_one() * q);
+        return roots.iter().map(|w| w * &u - &shift).collect();
+    }
+
+    let half_q = q / e(2);
+    let disc = Expr::sqrt(Expr::pow(&half_q, e(2)) + Expr::pow(&p, e(3)) / e(27));
+    let mut u = cbrt(Expr::min_one() * &half_q + &disc);
+    if u.is_zero() {
+        u = cbrt(Expr::min_one() * half_q - disc);
+    }
+    // t = u + v, with uv = -p/3
+    roots
+        .iter()
+        .map(|w| {
+            let wu = w * &u;
+            &wu - &p / (e(3) * &wu) - &shift
+        })
+        .collect()
+}
+
+/// ferrari's method on the depressed quartic y^4 + py^2 + qy + r, with x = y - b/4a
+fn quartic(c: &[Expr], x: &Expr) -> Option<Vec<Expr>> {
+    let e = |n: i32| Expr::from(n);
+    let r = |n: i32, d: i32| Expr::from(Rational::from((n, d)));
+    let a = &c[4];
+    let (b, c, d, f) = (&c[3] / a, &c[2] / a, &c[1] / a, &c[0] / a);
+    let shift = &b / e(4);
+    let p = &c - r(3, 8) * Expr::pow(&b, e(2));
+    let q = &d - &b * &c / e(2) + Expr::pow(&b, e(3)) / e(8);
+    let s = f - &b * d / e(4) + Expr::pow(&b, e(2)) * c / e(16) - r(3, 256) * Expr::pow(&b, e(4));
+
+    let ys = if q.is_zero() {
+        // biquadratic: y^2 = z with z^2 + pz + s = 0
+        quadratic(&[s, p, Expr::one()])
+            .into_iter()
+            .flat_map(|z| {
+                let y = Expr::sqrt(z);
+                [y.clone(), Expr::min_one() * y]
+            })
+            .collect()
+    } else {
+        // a root m != 0 of the resolvent cubic 8m^3 + 8pm^2 + (2p^2 - 8s)m - q^2
+        let resolvent = [
+            Expr::min_one() * Expr::pow(&q, e(2)),
+            e(2) * Expr::pow(&p, e(2)) - e(8) * &s,
+            e(8) * &p,
+            e(8),
+        ];
+        let m = poly_roots(resolvent.to_vec(), x)?
+            .into_iter()
+            .map(|(m, _)| m)
+            .find(|m| !m.is_zero())?;
+        let sqrt_2m = Expr::sqrt(e(2) * &m);
+
+        let mut ys = vec![];
+        for s1 in [Expr::one(), Expr::min_one()] {
+            let inner = e(2) * &p + e(2) * &m + &s1 * e(2) * &q / &sqrt_2m;
+            let root = Expr::sqrt(Expr::min_one() * inner);
+            for s2 in [Expr::one(), Expr::min_one()] {
+                ys.push((&s1 * &sqrt_2m + s2 * &root) / e(2));
+            }
+        }
+        ys
+    };
+    Some(ys.into_iter().map(|y| y - &shift).collect())
+}
+
+/// roots of c[0] + c[1] x + ... with radicals, for a degree up to 4
+fn radicals(c: &[Expr], x: &Expr) -> Option<Vec<Expr>> {
+    let roots = match c.len() - 1 {
+        1 => linear(c),
+        2 => quadratic(c),
+        3 => cubic(c),
+        4 => quartic(c, x)?,
+        _ => return None,
+    };
+    Some(roots.iter().map(Expr::reduce).collect())
+}
+
+/// roots of c[0] + c[1] x + ... + c[n] x^n with their multiplicity
+///
+/// polynomials with rational coefficients are factored first, irreducible factors
+/// of degree > 4 are solved with rootof
+fn poly_roots(mut c: Vec<Expr>, x: &Expr) -> Option<Vec<(Expr, u32)>> {
+    let rational: Option<Vec<Rational>> = c
+        .iter()
+        .map(|c| match c.atom() {
+            Atom::Rational(r) => Some(r.clone()),
+            _ => None,
+        })
+        .collect();
+
+    let mut roots = vec![];
+    if let Some(q) = rational {
+        for (f, mult) in factor::factor_list(&RatPoly::from_dense(&q)) {
+            let n = f.len() - 1;
+            let c: Vec<_> = f.iter().cloned().map(Expr::from).collect();
+            match radicals(&c, x) {
+                Some(rs) => roots.extend(rs.into_iter().map(|r| (r, mult))),
+                None if n > 4 => {
+                    let f = factor::z_to_expr(&f, x);
+                    roots.extend((0..n).map(|k| (Expr::root_of(&f, Expr::from(k as u64)), mult)))
+                }
+                None => (),
+            }
+        }
+    } else {
+        let zeros = c.iter().take_while(|c| c.is_zero()).count();
+        if zeros > 0 {
+            roots.push((Expr::zero(), zeros as u32));
+            c.drain(..zeros);
+        }
+        if c.len() > 1 {
+            roots.extend(radicals(&c, x)?.into_iter().map(|r| (r, 1)));
+        }
+    }
+
+    // merge equal roots
+    let mut res: Vec<(Expr, u32)> = vec![];
+    for (r, mult) in roots {
+        match res.iter_mut().find(|(s, _)| s == &r) {
+            Some((_, m)) => *m += mult,
+            None => res.push((r, mult)),
+        }
+    }
+    Some(res)
+}
+
+/// the solutions of an equation, see [Expr::solve]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolutionSet {
+    /// no solution
+    Empty,
+    /// finitely many solutions
+    Finite(Vec<Expr>),
+    /// the solutions for every integer `n`, e.g pi/6 + 2*pi*n
+    Family { sols: Vec<Expr>, n: Expr },
+    /// every value is a solution, e.g x = x
+    All,
+}
+
+impl SolutionSet {
+    fn from_vec(sols: Vec<Expr>, n: &Expr) -> Self {
+        let mut uniq: Vec<Expr> = vec![];
+        for s in sols {
+            let s = s.expand().reduce();
+            if !uniq.contains(&s) {
+                uniq.push(s);
+            }
+        }
+
+        if uniq.is_empty() {
+            SolutionSet::Empty
+        } else if uniq.iter().all(|s| s.free_of(n)) {
+            SolutionSet::Finite(uniq)
+        } else {
+            SolutionSet::Family {
+                sols: uniq,
+                n: n.clone(),
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        matches!(self, SolutionSet::Empty)
+    }
+
+    /// the listed solutions, empty for [SolutionSet::Empty] and [SolutionSet::All]
+    pub fn sols(&self) -> &[Expr] {
+        match self {
+            SolutionSet::Finite(sols) | SolutionSet::Family { sols, .. } => sols,
+            SolutionSet::Empty | SolutionSet::All => &[],
+        }
+    }
+}
+
+/// a variable named `name` or `name'`, `name''`, ... not occurring in any of `exprs`
+pub(crate) fn fresh_var(exprs: &[Expr], name: &str) -> Expr {
+    let mut name = name.to_string();
+    while !exprs.iter().all(|e| e.free_of(&Expr::var(&name))) {
+        name.push('\'');
+    }
+    Expr::var(&name)
+}
+
+/// isolates `x` in lhs = rhs by inverting the outermost operation of lhs
+struct Solver<'a> {
+    x: &'a Expr,
+    /// the integer parameter of periodic solutions
+    n: Expr,
+}
+
+impl Solver<'_> {
+    fn solve(&self, lhs: &Expr, rhs: &Expr) -> Option<SolutionSet> {
+        let f = (lhs - rhs).expand().reduce();
+        if f.free_of(self.x) {
+            return Some(match f.is_zero() {
+                true => SolutionSet::All,
+                false => SolutionSet::Empty,
+            });
+        }
+
+        let sols = match (lhs.free_of(self.x), rhs.free_of(self.x)) {
+            (_, true) => self.isolate(lhs, rhs),
+            (true, false) => self.isolate(rhs, lhs),
+            (false, false) => self.isolate(&f, &Expr::zero()),
+        };
+        let sols = sols.or_else(|| self.clear_denominator(&f))?;
+        Some(SolutionSet::from_vec(sols, &self.n))
+    }
+
+    /// solves p / q = 0 as p = 0, e.g x / (x - 1) = 1 becomes 1 = 0
+    ///
+    /// roots of q are not removed here, [Expr::solve] drops them when checking the solutions
+    fn clear_denominator(&self, f: &Expr) -> Option<Vec<Expr>> {
+        let f = f.rationalize();
+        if f.denominator().free_of(self.x) {
+            return None;
+        }
+        let numer = f.numerator().expand().reduce();
+        self.isolate(&numer, &Expr::zero())
+    }
+
+    /// solutions of lhs = rhs, where lhs depends on `x` and rhs is free of `x`
+    fn isolate(&self, lhs: &Expr, rhs: &Expr) -> Option<Vec<Expr>> {
+        use Atom as A;
+        let x = self.x;
+        let lhs = lhs.flatten();
+        if lhs == x {
+            return Some(vec![rhs.clone()]);
+        }
+        if let Some(roots) = (lhs - rhs).solve_poly(x) {
+            return Some(roots.into_iter().map(|(r, _)| r).collect());
+        }
+
+        match lhs.atom() {
+            A::Sum(sum) => {
+                let (dep, indep): (Vec<_>, Vec<_>) = sum.iter_args().partition(|a| !a.free_of(x));
+                let indep = indep.into_iter().fold(Expr::zero(), Expr::add);
+                match dep.as_slice() {
+                    [term] => self.isolate(term, &(rhs - indep)),
+                    _ => self.substitute_kernel(&(lhs - rhs)),
+                }
+            }
+            A::Prod(prod) => {
+                let (dep, indep): (Vec<_>, Vec<_>) = prod.iter_args().partition(|a| !a.free_of(x));
+                let indep = indep.into_iter().fold(Expr::one(), Expr::mul);
+                match dep.as_slice() {
+                    [factor] => self.isolate(factor, &(rhs / indep)),
+                    // a * b = 0 => a = 0 or b = 0
+                    _ if rhs.is_zero() => self.union(dep.iter().map(|f| (*f, Expr::zero()))),
+                    _ => self.substitute_kernel(&(lhs - rhs)),
+                }
+            }
+            A::Pow(pow) => self.isolate_pow(pow.base(), pow.exponent(), rhs),
+            A::Func(func) => self.isolate_func(func, rhs),
+            _ => None,
+        }
+    }
+
+    /// solutions of all lhs_i = rhs_i
+    fn union<'b>(&self, eqs: impl IntoIterator<Item = (&'b Expr, Expr)>) -> Option<Vec<Expr>> {
+        let mut sols = vec![];
+        for (lhs, rhs) in eqs {
+            sols.extend(self.isolate(lhs, &rhs)?);
+        }
+        Some(sols)
+    }
+
+    /// solves f = 0 as a polynomial in the only kernel k of `x`, e.g ln(x)^2 - 3*ln(x) + 2
+    ///
+    /// powers b^(m*u) of the same base are written as k^m with k = b^u, e.g exp(2x) = exp(x)^2
+    fn substitute_kernel(&self, f: &Expr) -> Option<Vec<Expr>> {
+        let kernels: Vec<_> = f
+            .variables()
+            .into_iter()
+            .filter(|v| !v.free_of(self.x))
+            .collect();
+        if kernels.contains(self.x) {
+            return None;
+        }
+
+        let t = fresh_var(slice::from_ref(f), "t");
+        let (k, g) = match kernels.as_slice() {
+            [k] => (k.clone(), f.substitude(k, &t)),
+            _ => {
+                let base = kernels[0].base();
+                let same_base = |k: &Expr| k.is_pow() && k.base() == base;
+                if !base.free_of(self.x) || !kernels.iter().all(same_base) {
+                    return None;
+                }
+                // the exponent u, such that all exponents are positive integer multiples of u
+                let u = kernels.iter().map(Expr::exponent).find(|u| {
+                    kernels.iter().all(|k| {
+                        (k.exponent() / u)
+                            .reduce()
+                            .try_unwrap_int()
+                            .is_some_and(|m| m.is_pos())
+                    })
+                })?;
+                let g = kernels.iter().fold(f.clone(), |g, k| {
+                    let m = (k.exponent() / &u).reduce();
+                    g.substitude(k, &Expr::pow(&t, m))
+                });
+                (Expr::pow(base, u), g)
+            }
+        };
+        if !g.free_of(self.x) {
+            return None;
+        }
+        let roots = g.solve_poly(&t)?;
+        self.union(roots.into_iter().map(|(r, _)| (&k, r)))
+    }
+
+    fn isolate_pow(&self, base: &Expr, exp: &Expr, rhs: &Expr) -> Option<Vec<Expr>> {
+        let x = self.x;
+        match (base.free_of(x), exp.free_of(x)) {
+            // u^e = c
+            (false, true) => {
+                if rhs.is_zero() && exp.cmp_real(&Expr::zero()).is_some_and(|o| o.is_lt()) {
+                    return Some(vec![]);
+                }
+                match exp.try_unwrap_int() {
+                    // all n-th roots: t^n - c = 0
+                    Some(n) if !n.is_zero() => {
+                        let t = fresh_var(slice::from_ref(rhs), "t");
+                        let c = match n.is_neg() {
+                            true => Expr::one() / rhs,
+                            false => rhs.clone(),
+                        };
+                        let n = Expr::from(n.abs());
+                        match (Expr::pow(&t, n) - c).solve_poly(&t) {
+                            Some(roots) => self.union(roots.into_iter().map(|(r, _)| (base, r))),
+                            None => self.isolate(base, &Expr::pow(rhs, Expr::one() / exp)),
+                        }
+                    }
+                    _ => self.isolate(base, &Expr::pow(rhs, Expr::one() / exp)),
+                }
+            }
+            // b^u = c => u = ln(c) / ln(b)
+            (true, false) => {
+                if rhs.is_zero() {
+                    return Some(vec![]);
+                } else if rhs.cmp_real(&Expr::zero()).is_some_and(|o| o.is_lt()) {
+                    // only complex solutions
+                    return None;
+                }
+                let u = match base.is_e() {
+                    true => Expr::ln(rhs),
+                    false => Expr::ln(rhs) / Expr::ln(base),
+                };
+                self.isolate(exp, &u)
+            }
+            _ => None,
+        }
+    }
+
+    fn isolate_func(&self, func: &Func, rhs: &Expr) -> Option<Vec<Expr>> {
+        use Expr as E;
+        use Func as F;
+
+        let two_pi_n = E::two() * E::pi() * &self.n;
+        let pi_n = E::pi() * &self.n;
+        // |c| > 1 for a real number c
+        let outside_unit = |c: &E| {
+            c.cmp_real(&E::one()).is_some_and(|o| o.is_gt())
+                || c.cmp_real(&E::min_one()).is_some_and(|o| o.is_lt())
+        };
+
+        let (u, sols) = match func {
+            // only complex solutions
+            F::Sin(u) | F::Cos(u) if outside_unit(rhs) => return None,
+            // sin(u) = c => u = arcsin(c) + 2*pi*n or pi - arcsin(c) + 2*pi*n
+            F::Sin(u) => {
+                let asin = E::arc_sin(rhs);
+                (u, vec![&asin + &two_pi_n, E::pi() - asin + two_pi_n])
+            }
+            // cos(u) = c => u = +-arccos(c) + 2*pi*n
+            F::Cos(u) => {
+                let acos = E::arc_cos(rhs);
+                (u, vec![&acos + &two_pi_n, E::min_one() * acos + two_pi_n])
+            }
+            F::Tan(u) => (u, vec![E::arc_tan(rhs) + pi_n]),
+            F::Cot(u) => (u, vec![E::arc_cot(rhs) + pi_n]),
+            F::Sec(u) => return self.isolate(&E::cos(u), &(E::one() / rhs)),
+            F::Csc(u) => return self.isolate(&E::sin(u), &(E::one() / rhs)),
+            F::ArcSin(u) => (u, vec![E::sin(rhs)]),
+            F::ArcCos(u) => (u, vec![E::cos(rhs)]),
+            F::ArcTan(u) => (u, vec![E::tan(rhs)]),
+            F::ArcCot(u) => (u, vec![E::cot(rhs)]),
+            F::ArcSec(u) => (u, vec![E::sec(rhs)]),
+            F::ArcCsc(u) => (u, vec![E::csc(rhs)]),
+            // log_b(u) = c => u = b^c
+            F::Log(base, u) => (u, vec![E::pow(E::from(base.clone()), rhs)]),
+            _ => return None,
+        };
+        self.union(sols.into_iter().map(|c| (u, c)))
+    }
+}
+
+impl Expr {
+    /// solve an equation lhs = rhs for `x`
+    ///
+    /// expressions that are not an equation are solved for self = 0. `x` is isolated by
+    /// inverting the operations applied to it, polynomial subproblems are solved with
+    /// [Expr::solve_poly]. Periodic solutions are given in terms of an integer parameter.
+    ///
+    /// solutions are complex numbers and roots are taken on the principal branch, e.g
+    /// x^(1/3) = -2 has no solution. The inverses of the transcendental functions are only
+    /// known on the reals, so e.g exp(x) = -3 and sin(x) = 2 return [None].
+    /// Returns [None] if the equation can't be solved
+    pub fn solve(&self, x: &Expr) -> Option<SolutionSet> {
+        let (lhs, rhs) = match self.atom() {
+            Atom::Rel(rel) if rel.op == RelOp::Eq => (rel.lhs().clone(), rel.rhs().clone()),
+            Atom::Rel(_) | Atom::Logic(_) | Atom::Bool(_) => return None,
+            _ => (self.clone(), Expr::zero()),
+        };
+        let solver = Solver {
+            x,
+            n: fresh_var(slice::from_ref(self), "n"),
+        };
+
+        let sols = solver.solve(&lhs.reduce(), &rhs.reduce())?;
+        let SolutionSet::Finite(sols) = sols else {
+            return Some(sols);
+        };
+        // drop candidates that don't satisfy the equation, e.g x = 1 for x^(1/2) = -1
+        let holds = |s: &Expr| {
+            let eval = |e: &Expr| e.substitude(x, s).evalf_complex(&HashMap::new());
+            if let (Ok(l), Ok(r)) = (eval(&lhs), eval(&rhs)) {
+                return (l - r).norm() <= 1e-9 * l.norm().max(r.norm()).max(1.0);
+            }
+            // the equation is undefined at a root of a denominator
+            let eq = Expr::rel(RelOp::Eq, &lhs, &rhs).substitude(x, s).reduce();
+            !eq.is_undef() && !matches!(eq.atom(), Atom::Bool(false))
+        };
+        let sols = sols.into_iter().filter(holds).collect();
+        Some(SolutionSet::from_vec(sols, &solver.n))
+    }
+
+    /// roots of a polynomial in `x` with their multiplicity
+    ///
+    /// up to degree 4 the roots are given with radicals, polynomials with rational
+    /// coefficients are factored first and the remaining roots are returned as
+    /// rootof(p, k). Returns [None] if the expression is not a polynomial in `x`,
+    /// is zero or the roots can't be expressed
+    pub fn solve_poly(&self, x: &Expr) -> Option<Vec<(Expr, u32)>> {
+        let p = self.expand();
+        let vars = VarSet::from(x.clone());
+        let view = p.as_polynomial_view(&vars);
+        if !view.check() {
+            return None;
+        }
+
+        let n = view.degree_of(x).map_or(Some(0), |n| n.to_usize())?;
+        let mut c = vec![Expr::zero(); n + 1];
+        for (deg, coeff) in view.coeffs_of(x) {
+            let deg = deg.to_usize()?;
+            if !coeff.free_of(x) {
+                return None;
+            }
+            c[deg] = coeff.reduce();
+        }
+
+        if n == 0 {
+            return (!c[0].is_zero()).then(Vec::new);
+        }
+        poly_roots(c, x)
+    }
+}
+
+/// the solutions of a linear system, see [solve_linear]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinearSolution {
+    /// the value of every unknown
+    Unique(Vec<Expr>),
+    /// the value of every unknown in terms of the unknowns in `free`,
+    /// which can be chosen arbitrarily
+    Infinite { sols: Vec<Expr>, free: Vec<Expr> },
+    /// the equations contradict each other
+    Inconsistent,
+}
+
+/// the row \[a_1, .., a_n, b\] of a_1 x_1 + ... + a_n x_n = b
+///
+/// returns [None] if the equation is not linear in the unknowns
+fn linear_row(eq: &Expr, unknowns: &[Expr], vars: &VarSet) -> Option<Vec<Expr>> {
+    let f = match eq.atom() {
+        Atom::Rel(rel) if rel.op == RelOp::Eq => rel.lhs() - rel.rhs(),
+        Atom::Rel(_) | Atom::Logic(_) | Atom::Bool(_) => return None,
+        _ => eq.clone(),
+    };
+    let f = f.expand().reduce();
+    let terms = match f.atom() {
+        Atom::Sum(sum) => sum.iter_args().cloned().collect(),
+        _ => vec![f],
+    };
+
+    let n = unknowns.len();
+    let mut row = vec![Expr::zero(); n + 1];
+    for t in terms {
+        let (c, vp) = t.as_monomial_view(vars).coeff()?;
+        if vp.is_const() {
+            row[n] -= c;
+            continue;
+        }
+        let i = unknowns.iter().position(|x| vp.degree_of(x).is_some())?;
+        if !vp.total_deg().is_one() {
+            return None;
+        }
+        row[i] += c;
+    }
+    Some(row.iter().map(Expr::reduce).collect())
+}
+
+/// solve a system of equations that are linear in `unknowns`
+///
+/// expressions that are not an equation are treated as expr = 0. The augmented matrix
+/// is brought into reduced row echelon form with [Matrix::rref], which clears the
+/// denominators of every row before the fraction-free elimination, so the coefficients can
+/// be arbitrary expressions free of the unknowns. Unknowns without a pivot are the
+/// parameters of an infinite solution set.
+///
+/// Returns [None] if an equation is not linear in the unknowns
+pub fn solve_linear(eqs: &[Expr], unknowns: &[Expr]) -> Option<LinearSolution> {
+    let vars = VarSet::new(unknowns);
+    let n = unknowns.len();
+    let rows = eqs
+        .iter()
+        .map(|eq| linear_row(eq, unknowns, &vars))
+        .collect::<Option<Vec<_>>>()?;
+    let (a, pivots) = Matrix::new(rows.len(), n + 1, rows.concat()).rref();
+
+    // a pivot in the last column means 0 = 1
+    if pivots.last() == Some(&n) {
+        return Some(LinearSolution::Inconsistent);
+    }
+
+    let free: Vec<_> = (0..n).filter(|c| !pivots.contains(c)).collect();
+    let mut sols = unknowns.to_vec();
+    for (r, &c) in pivots.iter().enumerate() {
+        let x = free
+            .iter()
+            .map(|&j| &a[(r, j)] * &unknowns[j])
+            .fold(a[(r, n)].clone(), |sum, t| sum - t);
+        sols[c] = matrix::simplify(&x);
+    }
+
+    if free.is_empty() {
+        return Some(LinearSolution::Unique(sols));
+    }
+    let free = free.into_iter().map(|c| unknowns[c].clone()).collect();
+    Some(LinearSolution::Infinite { sols, free })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use assert_eq as eq;
+    use calcurs_macros::expr as e;
+
+    /// numeric value with the principal branch of all roots
+    fn complex(e: &Expr) -> num::Complex<f64> {
+        e.evalf_complex(&std::collections::HashMap::new()).unwrap()
+    }
+
+    fn check_roots(p: Expr, degree: u32) {
+        let x = &e!(x);
+        let roots = p.solve_poly(x).unwrap();
+        eq!(roots.iter().map(|(_, m)| m).sum::<u32>(), degree, "{p}");
+        for (r, _) in roots {
+            let val = complex(&p.substitude(x, &r));
+            assert!(val.norm() < 1e-9, "{p} at {r} = {val}");
+        }
+    }
+
+    #[test]
+    fn solve_poly() {
+        let x = &e!(x);
+        let solve = |e: Expr| e.solve_poly(x).unwrap();
+
+        eq!(solve(e!(2 * x - 3)), vec![(e!(3 / 2), 1)]);
+        eq!(solve(e!(x ^ 2 - 1)), vec![(e!(1), 1), (e!(-1), 1)]);
+        eq!(
+            solve(e!(x ^ 2 - 2)),
+            vec![(e!(2 ^ (1 / 2)), 1), (e!(-1 * 2 ^ (1 / 2)), 1)]
+        );
+        eq!(solve(e!(x ^ 2 + 1)), vec![(e!(I), 1), (e!(-I), 1)]);
+        eq!(
+            solve(e!((x - 1) ^ 3 * (x + 2))),
+            vec![(e!(-2), 1), (e!(1), 3)]
+        );
+        eq!(solve(e!(a * x + b)), vec![(e!(-b / a), 1)]);
+        eq!(solve(e!(a * x ^ 2 + a * x)), vec![(e!(0), 1), (e!(-1), 1)]);
+        eq!(solve(e!(3)), vec![]);
+        eq!(e!(0).solve_poly(x), None);
+        eq!(e!(sin(x) + x).solve_poly(x), None);
+        eq!(
+            solve(e!(x ^ 5 - x + 1)),
+            (0..5)
+                .map(|k| (Expr::root_of(e!(x ^ 5 - x + 1), Expr::from(k)), 1))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn solve() {
+        let x = &e!(x);
+        let norm =
+            |s: &[Expr]| -> Vec<_> { s.iter().map(|s| s.expand().reduce().sort_args()).collect() };
+        let check = |eq: Expr, res: Vec<Expr>| {
+            let sols = eq.solve(x).unwrap();
+            eq!(norm(sols.sols()), norm(&res), "{eq}");
+        };
+        let pi_n = || e!(pi * n);
+
+        check(e!(2 * x + 3 == 7), vec![e!(2)]);
+        check(e!(x ^ 2 == 2), vec![e!(2 ^ (1 / 2)), e!(-1 * 2 ^ (1 / 2))]);
+        check(e!(ln(x) == c), vec![e!(exp(c))]);
+        check(e!(ln(x + 1) == 2), vec![e!(exp(2) - 1)]);
+        check(e!(exp(x) == 3), vec![e!(ln(3))]);
+        check(e!(x ^ (1 / 2) == 3), vec![e!(9)]);
+        check(e!(1 / x == 2), vec![e!(1 / 2)]);
+        check(e!(x / (x - 1) == 2), vec![e!(2)]);
+        check(e!((x ^ 2 - 1) / (x - 1) == 0), vec![e!(-1)]);
+        check(e!(1 / x + 1 / (x + 1) == 0), vec![e!(-1 / 2)]);
+        check(e!(exp(2 * x) - 3 * exp(x) + 2), vec![e!(ln(2)), e!(0)]);
+        check(e!(ln(x) ^ 2 - 3 * ln(x) + 2), vec![e!(exp(2)), e!(exp(1))]);
+        check(
+            e!(sin(x) == 1 / 2),
+            vec![
+                e!(arc_sin(1 / 2) + 2 * pi * n),
+                e!(pi - arc_sin(1 / 2) + 2 * pi * n),
+            ],
+        );
+        check(
+            e!(sin(2 * x) == c),
+            vec![
+                e!(arc_sin(c) / 2) + pi_n(),
+                e!(pi / 2 - arc_sin(c) / 2) + pi_n(),
+            ],
+        );
+        check(e!(tan(x) == 1), vec![e!(arc_tan(1)) + pi_n()]);
+        check(e!(arc_tan(x) == 1), vec![e!(tan(1))]);
+        check(
+            e!(x * cos(x) == 0),
+            vec![
+                e!(arc_cos(0) + 2 * pi * n),
+                e!(-1 * arc_cos(0) + 2 * pi * n),
+                e!(0),
+            ],
+        );
+
+        // n is taken, the parameter is called n'
+        let m = &Expr::var("n'");
+        let sols = e!(sin(x + n) == 0).solve(x).unwrap();
+        assert!(matches!(&sols, SolutionSet::Family { n, .. } if n == m));
+        eq!(
+            norm(sols.sols()),
+            norm(&[
+                e!(arc_sin(0) - n) + e!(2 * pi) * m,
+                e!(pi - arc_sin(0) - n) + e!(2 * pi) * m,
+            ])
+        );
+        eq!(e!(x == x).solve(x), Some(SolutionSet::All));
+        eq!(e!(x + 1 == x).solve(x), Some(SolutionSet::Empty));
+        eq!(e!(1 / x == 0).solve(x), Some(SolutionSet::Empty));
+        eq!(e!(x / (x - 1) == 1).solve(x), Some(SolutionSet::Empty));
+        eq!(e!(exp(x) == 0).solve(x), Some(SolutionSet::Empty));
+        eq!(e!(x ^ (1 / 2) == -1).solve(x), Some(SolutionSet::Empty));
+        // principal branch: (-8)^(1/3) = 1 + sqrt(3)i
+        eq!(e!(x ^ (1 / 3) == -2).solve(x), Some(SolutionSet::Empty));
+        check(e!(x ^ (1 / 3) == 2), vec![e!(8)]);
+        // the solutions are complex
+        eq!(e!(cos(x) == 2).solve(x), None);
+        eq!(e!(exp(2 * x) == -3).solve(x), None);
+        eq!(e!(sin(x) + x == 0).solve(x), None);
+        eq!(e!(x < 1).solve(x), None);
+    }
+
+    #[test]
+    fn solve_linear() {
+        use super::solve_linear as solve;
+        use LinearSolution as LS;
+        let (x, y, z) = (e!(x), e!(y), e!(z));
+        let unique = |s: Option<LinearSolution>, res: Vec<Expr>| {
+            let Some(LS::Unique(s)) = s else {
+                panic!("expected a unique solution, got {s:?}");
+            };
+            for (s, r) in s.iter().zip(&res) {
+                let diff = (s - r).rationalize().numerator().expand().reduce();
+                assert!(diff.is_zero(), "{s} != {r}");
+            }
+        };
+
+        eq!(
+            solve(&[e!(x + y == 3), e!(x - y == 1)], &[x.clone(), y.clone()]),
+            Some(LS::Unique(vec![e!(2), e!(1)]))
+        );
+        unique(
+            solve(
+                &[e!(a * x + b * y == c), e!(d * x + f * y == g)],
+                &[x.clone(), y.clone()],
+            ),
+            vec![
+                e!((c * f - b * g) / (a * f - b * d)),
+                e!((a * g - c * d) / (a * f - b * d)),
+            ],
+        );
+        unique(
+            solve(
+                &[e!(x / 2 + y / 3 == 1), e!(x == y)],
+                &[x.clone(), y.clone()],
+            ),
+            vec![e!(6 / 5), e!(6 / 5)],
+        );
+        unique(
+            solve(&[e!(x / a == 1), e!(y - x / b)], &[x.clone(), y.clone()]),
+            vec![e!(a), e!(a / b)],
+        );
+        // voltage divider
+        unique(
+            solve(
+                &[e!(u == i * r1 + i * r2), e!(u2 == i * r2)],
+                &[e!(i), e!(u2)],
+            ),
+            vec![e!(u / (r1 + r2)), e!(u * r2 / (r1 + r2))],
+        );
+        // nodal analysis with conductances given as 1/r
+        unique(
+            solve(
+                &[
+                    e!((v1 - u) / r1 + v1 / r2 + (v1 - v2) / r3),
+                    e!((v2 - v1) / r3 + v2 / r4),
+                ],
+                &[e!(v1), e!(v2)],
+            ),
+            vec![
+                e!(u * r2 * (r3 + r4) / (r1 * r2 + r1 * r3 + r1 * r4 + r2 * r3 + r2 * r4)),
+                e!(u * r2 * r4 / (r1 * r2 + r1 * r3 + r1 * r4 + r2 * r3 + r2 * r4)),
+            ],
+        );
+        eq!(
+            solve(
+                &[e!(x + y + z - 1), e!(2 * x + 2 * y + 2 * z - 2), e!(y - z)],
+                &[x.clone(), y.clone(), z.clone()]
+            ),
+            Some(LS::Infinite {
+                sols: vec![e!(1 - 2 * z), z.clone(), z.clone()],
+                free: vec![z.clone()],
+            })
+        );
+        eq!(
+            solve(&[e!(x + y == 1), e!(x + y == 2)], &[x.clone(), y.clone()]),
+            Some(LS::Inconsistent)
+        );
+        eq!(solve(&[e!(x * y == 1)], &[x.clone(), y.clone()]), None);
+        eq!(solve(&[e!(x ^ 2 == 1)], &[e!(x)]), None);
+        eq!(solve(&[e!(x < 1)], &[e!(x)]), None);
+    }
+
+    #[test]
+    fn radicals() {
+        check_roots(e!(x ^ 2 + x + 1), 2);
+        check_roots(e!(x ^ 3 - 2), 3);
+        check_roots(e!(x ^ 3 + x + 1), 3);
+        check_roots(e!(x ^ 3 - 3 * x + 1), 3);
+        check_roots(e!(2 * x ^ 3 - 4 * x ^ 2 + x + 3), 3);
+        check_roots(e!(x ^ 4 + 1), 4);
+        check_roots(e!(x ^ 4 + x + 1), 4);
+        check_roots(e!(x ^ 4 - 4 * x ^ 3 + 2 * x - 3), 4);
+        check_roots(e!(x ^ 6 - 1), 6);
+        check_roots(e!(x ^ 5 - x + 1), 5);
+        check_roots(e!((x ^ 5 - x + 1) * (x ^ 2 - 2)), 7);
+    }
+}
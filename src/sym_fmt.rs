@@ -1,9 +1,10 @@
-use std::{collections::VecDeque, fmt, ops};
+use std::{collections::VecDeque, fmt, marker::PhantomData, ops};
 
 use derive_more::IsVariant;
 
 use crate::{
-    atom::{self, unicode, Atom, Irrational, SymbolicExpr},
+    atom::{self, unicode, Atom, Infinity, Irrational, Real, RelOp, SymbolicExpr},
+    float::Float,
     rational::Rational,
 };
 
@@ -12,6 +13,9 @@ pub enum FmtAtom {
     Undef,
     Rational(Rational),
     Irrational(Irrational),
+    Float(Float),
+    ImagUnit,
+    Infinity(Infinity),
     Var(String),
     Sum(VecDeque<FmtAtom>),
     Prod(VecDeque<FmtAtom>),
@@ -19,6 +23,14 @@ pub enum FmtAtom {
     Func(atom::Func, Vec<FmtAtom>),
     Fraction(Box<FmtAtom>, Box<FmtAtom>),
     UnrySub(Box<FmtAtom>),
+    Bool(bool),
+    Rel(RelOp, Box<FmtAtom>, Box<FmtAtom>),
+    And(Vec<FmtAtom>),
+    Or(Vec<FmtAtom>),
+    Not(Box<FmtAtom>),
+    List(Vec<FmtAtom>),
+    /// the rows of a [Matrix](crate::matrix::Matrix)
+    Matrix(Vec<Vec<FmtAtom>>),
 }
 
 impl FmtAtom {
@@ -34,7 +46,7 @@ impl From<&Atom> for FmtAtom {
             Atom::Rational(r) => {
                 if r.is_neg() {
                     FmtAtom::UnrySub(FmtAtom::Rational(r.clone().abs()).into())
-                } else if r.is_fraction() && r.numer() == 1 {
+                } else if r.is_fraction() && r.numer().is_one() {
                     let n = Atom::from(r.numer());
                     let d = Atom::from(r.denom());
                     FmtAtom::Fraction(Self::from(&n).into(), Self::from(&d).into())
@@ -43,6 +55,13 @@ impl From<&Atom> for FmtAtom {
                 }
             }
             Atom::Irrational(i) => FmtAtom::Irrational(*i),
+            Atom::Float(x) if x.is_neg() => FmtAtom::UnrySub(FmtAtom::Float(x.abs()).into()),
+            Atom::Float(x) => FmtAtom::Float(*x),
+            Atom::ImagUnit => FmtAtom::ImagUnit,
+            Atom::Infinity(Infinity::Neg) => {
+                FmtAtom::UnrySub(FmtAtom::Infinity(Infinity::Pos).into())
+            }
+            Atom::Infinity(i) => FmtAtom::Infinity(*i),
             Atom::Var(v) => FmtAtom::Var(v.to_string()),
             Atom::Prod(atom::Prod { args }) => args
                 .iter()
@@ -69,6 +88,23 @@ impl From<&Atom> for FmtAtom {
                 func.clone(),
                 func.iter_args().map(|a| FmtAtom::from(a.atom())).collect(),
             ),
+            Atom::Bool(b) => FmtAtom::Bool(*b),
+            Atom::Rel(rel) => FmtAtom::Rel(
+                rel.op,
+                FmtAtom::from(rel.lhs().atom()).into(),
+                FmtAtom::from(rel.rhs().atom()).into(),
+            ),
+            Atom::Logic(logic) => {
+                let args = logic.iter_args().map(|a| FmtAtom::from(a.atom()));
+                match logic {
+                    atom::Logic::And(_) => FmtAtom::And(args.collect()),
+                    atom::Logic::Or(_) => FmtAtom::Or(args.collect()),
+                    atom::Logic::Not(x) => FmtAtom::Not(FmtAtom::from(x.atom()).into()),
+                }
+            }
+            Atom::List(list) => {
+                FmtAtom::List(list.iter().map(|a| FmtAtom::from(a.atom())).collect())
+            }
         }
     }
 }
@@ -142,6 +178,12 @@ pub trait SymbolicFormatter {
     fn symbl_add(f: &mut fmt::Formatter<'_>) -> fmt::Result;
     /// x [*] y
     fn symbl_mul(f: &mut fmt::Formatter<'_>) -> fmt::Result;
+    /// x^a[ * ]y
+    fn spaced_mul(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Self::space(f)?;
+        Self::symbl_mul(f)?;
+        Self::space(f)
+    }
     /// x [/] y
     fn symbl_div(f: &mut fmt::Formatter<'_>) -> fmt::Result;
     /// x[^]y
@@ -150,6 +192,14 @@ pub trait SymbolicFormatter {
     fn space(f: &mut fmt::Formatter<'_>) -> fmt::Result;
     /// (a[,] b[,] ...)
     fn comma(f: &mut fmt::Formatter<'_>) -> fmt::Result;
+    /// x [<] y
+    fn symbl_rel(op: RelOp, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+    /// a [and] b
+    fn symbl_and(f: &mut fmt::Formatter<'_>) -> fmt::Result;
+    /// a [or] b
+    fn symbl_or(f: &mut fmt::Formatter<'_>) -> fmt::Result;
+    /// [not] a
+    fn symbl_not(f: &mut fmt::Formatter<'_>) -> fmt::Result;
 
     /// [(] ... )
     fn lparen(f: &mut fmt::Formatter<'_>) -> fmt::Result;
@@ -162,8 +212,69 @@ pub trait SymbolicFormatter {
 
     fn irrational(i: &Irrational, f: &mut fmt::Formatter<'_>) -> fmt::Result;
 
+    /// prints the number of digits given by the formatter precision, if any
+    fn float(x: &Float, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(x, f)
+    }
+
+    fn imag_unit(f: &mut fmt::Formatter<'_>) -> fmt::Result;
+
+    fn infinity(i: &Infinity, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+
     fn var(v: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result;
 
+    fn boolean(b: bool, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+
+    fn rel(op: RelOp, lhs: &FmtAtom, rhs: &FmtAtom, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Self::fmt_w_prec(sum_prec(), lhs, f)?;
+        Self::space(f)?;
+        Self::symbl_rel(op, f)?;
+        Self::space(f)?;
+        Self::fmt_w_prec(sum_prec(), rhs, f)
+    }
+
+    /// a [op] b [op] ..., where op is and / or with precedence `prec`
+    fn connective(
+        args: &[FmtAtom],
+        prec: u32,
+        symbl: fn(&mut fmt::Formatter<'_>) -> fmt::Result,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        let mut args = args.iter();
+        if let Some(a) = args.next() {
+            Self::fmt_w_prec(prec + 1, a, f)?;
+        }
+        for a in args {
+            Self::space(f)?;
+            symbl(f)?;
+            Self::space(f)?;
+            Self::fmt_w_prec(prec + 1, a, f)?;
+        }
+        Ok(())
+    }
+
+    /// [{a, b, ...}]
+    fn list(args: &[FmtAtom], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{")?;
+        for (i, a) in args.iter().enumerate() {
+            if i > 0 {
+                Self::comma(f)?;
+                Self::space(f)?;
+            }
+            Self::atom(a, f)?;
+        }
+        write!(f, "}}")
+    }
+
+    /// the entries of a matrix, row by row
+    fn matrix(rows: &[Vec<FmtAtom>], f: &mut fmt::Formatter<'_>) -> fmt::Result;
+
+    /// [not x]
+    fn not(x: &FmtAtom, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Self::symbl_not(f)?;
+        Self::fmt_w_prec(pow_prec(), x, f)
+    }
+
     fn sum(args: &VecDeque<FmtAtom>, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut args = args.iter();
 
@@ -214,9 +325,7 @@ pub trait SymbolicFormatter {
                     Self::fmt_w_prec(prod_prec(), curr, f)?;
                 }
                 (Some(F::Pow(..)), _, _) => {
-                    Self::space(f)?;
-                    Self::symbl_mul(f)?;
-                    Self::space(f)?;
+                    Self::spaced_mul(f)?;
                     Self::fmt_w_prec(prod_prec(), curr, f)?;
                 }
                 _ => {
@@ -299,6 +408,9 @@ pub trait SymbolicFormatter {
             FA::Undef => Self::undef(f),
             FA::Rational(r) => Self::rational(r, f),
             FA::Irrational(i) => Self::irrational(i, f),
+            FA::Float(x) => Self::float(x, f),
+            FA::ImagUnit => Self::imag_unit(f),
+            FA::Infinity(i) => Self::infinity(i, f),
             FA::Var(v) => Self::var(v, f),
             FA::Sum(sum) => Self::sum(sum, f),
             FA::Prod(prod) => Self::prod(prod, f),
@@ -306,6 +418,13 @@ pub trait SymbolicFormatter {
             FA::Func(func, args) => Self::func(func, args, f),
             FA::Fraction(n, d) => Self::frac(n, d, f),
             FA::UnrySub(x) => Self::unry_sub(x, f),
+            FA::Bool(b) => Self::boolean(*b, f),
+            FA::Rel(op, lhs, rhs) => Self::rel(*op, lhs, rhs, f),
+            FA::And(args) => Self::connective(args, and_prec(), Self::symbl_and, f),
+            FA::Or(args) => Self::connective(args, or_prec(), Self::symbl_or, f),
+            FA::Not(x) => Self::not(x, f),
+            FA::List(args) => Self::list(args, f),
+            FA::Matrix(rows) => Self::matrix(rows, f),
         }
     }
 }
@@ -349,6 +468,26 @@ impl SymbolicFormatter for UnicodeFmt {
         write!(f, ",")
     }
 
+    #[inline]
+    fn symbl_rel(op: RelOp, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{op}")
+    }
+
+    #[inline]
+    fn symbl_and(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", unicode::and())
+    }
+
+    #[inline]
+    fn symbl_or(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", unicode::or())
+    }
+
+    #[inline]
+    fn symbl_not(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", unicode::not())
+    }
+
     #[inline]
     fn lparen(f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "(")
@@ -374,44 +513,410 @@ impl SymbolicFormatter for UnicodeFmt {
         write!(f, "{i}")
     }
 
+    #[inline]
+    fn imag_unit(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "i")
+    }
+
+    #[inline]
+    fn infinity(i: &Infinity, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{i}")
+    }
+
     #[inline]
     fn var(v: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{v}")
     }
 
+    #[inline]
+    fn boolean(b: bool, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{b}")
+    }
+
     #[inline]
     fn unry_sub(x: &FmtAtom, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", unicode::unry_sub())?;
         Self::fmt_w_prec(prod_prec(), x, f)
     }
+
+    /// a grid with the columns aligned to the left:
+    ///
+    /// ⎡1  x⎤
+    /// ⎣0  y⎦
+    fn matrix(rows: &[Vec<FmtAtom>], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cells: Vec<Vec<String>> = rows
+            .iter()
+            .map(|r| r.iter().map(|e| e.fmt_with::<Self>().to_string()).collect())
+            .collect();
+        let n_cols = cells.first().map_or(0, Vec::len);
+        let widths: Vec<usize> = (0..n_cols)
+            .map(|j| {
+                cells
+                    .iter()
+                    .map(|r| r[j].chars().count())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        if cells.is_empty() {
+            return write!(f, "[]");
+        }
+        for (i, row) in cells.iter().enumerate() {
+            let (l, r) = match i {
+                _ if cells.len() == 1 => ("[", "]"),
+                0 => (unicode::lbrack_top(), unicode::rbrack_top()),
+                _ if i == cells.len() - 1 => (unicode::lbrack_bot(), unicode::rbrack_bot()),
+                _ => (unicode::lbrack_mid(), unicode::rbrack_mid()),
+            };
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{l}")?;
+            for (j, (cell, w)) in row.iter().zip(&widths).enumerate() {
+                if j > 0 {
+                    write!(f, "  ")?;
+                }
+                write!(f, "{cell:<w$}")?;
+            }
+            write!(f, "{r}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LatexFmt;
+
+impl LatexFmt {
+    fn func_name(func: &atom::Func, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use atom::Func as F;
+        match func {
+            F::Sin(_) => write!(f, "\\sin"),
+            F::ArcSin(_) => write!(f, "\\arcsin"),
+            F::Cos(_) => write!(f, "\\cos"),
+            F::ArcCos(_) => write!(f, "\\arccos"),
+            F::Tan(_) => write!(f, "\\tan"),
+            F::ArcTan(_) => write!(f, "\\arctan"),
+            F::Sec(_) => write!(f, "\\sec"),
+            F::ArcSec(_) => write!(f, "\\operatorname{{arcsec}}"),
+            F::Cot(_) => write!(f, "\\cot"),
+            F::ArcCot(_) => write!(f, "\\operatorname{{arccot}}"),
+            F::Csc(_) => write!(f, "\\csc"),
+            F::ArcCsc(_) => write!(f, "\\operatorname{{arccsc}}"),
+            F::Re(_) => write!(f, "\\operatorname{{Re}}"),
+            F::Im(_) => write!(f, "\\operatorname{{Im}}"),
+            F::Conj(_) => write!(f, "\\overline"),
+            F::Arg(_) => write!(f, "\\arg"),
+            F::Mod(_) => write!(f, "\\operatorname{{mod}}"),
+            F::IsPrime(_)
+            | F::NextPrime(_)
+            | F::Totient(_)
+            | F::Divisors(_)
+            | F::PowMod(_)
+            | F::ModInv(_)
+            | F::RootOf(_) => {
+                write!(f, "\\operatorname{{{}}}", func.name())
+            }
+            F::Order(_) => write!(f, "\\operatorname{{O}}"),
+            F::Log(Real::Irrational(Irrational::E), _) => write!(f, "\\ln"),
+            F::Log(base, _) => {
+                write!(f, "\\log_{{")?;
+                match base {
+                    Real::Rational(r) => Self::rational(r, f)?,
+                    Real::Irrational(i) => Self::irrational(i, f)?,
+                    Real::Float(x) => Self::float(x, f)?,
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+
+    /// x^(1/n) is written as [\sqrt[n]{x}]
+    fn root_index(e: &FmtAtom) -> Option<&Rational> {
+        match e {
+            FmtAtom::Fraction(n, d) if **n == FmtAtom::ONE => match d.as_ref() {
+                FmtAtom::Rational(r) if r.is_int() => Some(r),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+impl SymbolicFormatter for LatexFmt {
+    #[inline]
+    fn symbl_sub(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "-")
+    }
+
+    #[inline]
+    fn symbl_add(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "+")
+    }
+
+    #[inline]
+    fn symbl_mul(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\\cdot ")
+    }
+
+    #[inline]
+    fn spaced_mul(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // \cdot already ends with the space separating it from the next command
+        write!(f, " \\cdot ")
+    }
+
+    #[inline]
+    fn symbl_div(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "/")
+    }
+
+    #[inline]
+    fn symbl_pow(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "^")
+    }
+
+    #[inline]
+    fn space(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, " ")
+    }
+
+    #[inline]
+    fn comma(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, ",")
+    }
+
+    fn symbl_rel(op: RelOp, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match op {
+            RelOp::Eq => write!(f, "="),
+            RelOp::Ne => write!(f, "\\neq"),
+            RelOp::Lt => write!(f, "<"),
+            RelOp::Le => write!(f, "\\leq"),
+            RelOp::Gt => write!(f, ">"),
+            RelOp::Ge => write!(f, "\\geq"),
+        }
+    }
+
+    #[inline]
+    fn symbl_and(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\\land")
+    }
+
+    #[inline]
+    fn symbl_or(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\\lor")
+    }
+
+    #[inline]
+    fn symbl_not(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\\neg ")
+    }
+
+    #[inline]
+    fn lparen(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\\left(")
+    }
+
+    #[inline]
+    fn rparen(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\\right)")
+    }
+
+    #[inline]
+    fn undef(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\\text{{undefined}}")
+    }
+
+    fn rational(r: &Rational, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if r.is_int() {
+            write!(f, "{r}")
+        } else {
+            write!(f, "\\frac{{{}}}{{{}}}", r.numer(), r.denom())
+        }
+    }
+
+    #[inline]
+    fn irrational(i: &Irrational, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match i {
+            Irrational::E => write!(f, "e"),
+            Irrational::PI => write!(f, "\\pi"),
+        }
+    }
+
+    #[inline]
+    fn imag_unit(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "i")
+    }
+
+    fn infinity(i: &Infinity, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match i {
+            Infinity::Pos => write!(f, "\\infty"),
+            Infinity::Neg => write!(f, "-\\infty"),
+            Infinity::Complex => write!(f, "\\tilde{{\\infty}}"),
+        }
+    }
+
+    /// a single letter, a letter with a subscript [x_{12}] or [\text{name}], so that the name
+    /// is not read as a product or as the constants e and i
+    fn var(v: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut chars = v.chars();
+        let (c, rest) = match chars.next() {
+            Some(c) if c.is_alphabetic() && c != 'e' && c != 'i' => (c, chars.as_str()),
+            _ => return write!(f, "\\text{{{}}}", v.replace('_', "\\_")),
+        };
+        match rest.strip_prefix('_') {
+            _ if rest.is_empty() => write!(f, "{c}"),
+            Some(sub) if !sub.is_empty() && !sub.contains('_') => write!(f, "{c}_{{{sub}}}"),
+            _ => write!(f, "\\text{{{}}}", v.replace('_', "\\_")),
+        }
+    }
+
+    #[inline]
+    fn boolean(b: bool, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\\text{{{b}}}")
+    }
+
+    fn pow(b: &FmtAtom, e: &FmtAtom, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use FmtAtom as F;
+        if let Some(n) = Self::root_index(e) {
+            write!(f, "\\sqrt")?;
+            if n != &Rational::TWO {
+                write!(f, "[{n}]")?;
+            }
+            write!(f, "{{")?;
+            Self::atom(b, f)?;
+            return write!(f, "}}");
+        }
+
+        match b {
+            F::Func(func, args) if e.is_number() && !func.is_conj() => {
+                Self::func_name(func, f)?;
+                write!(f, "^{{")?;
+                Self::atom(e, f)?;
+                write!(f, "}}")?;
+                Self::func_args(args, f)
+            }
+            _ => {
+                Self::fmt_w_prec(pow_prec() + 1, b, f)?;
+                write!(f, "^{{")?;
+                Self::atom(e, f)?;
+                write!(f, "}}")
+            }
+        }
+    }
+
+    /// [\frac{n}{d}]
+    fn frac(n: &FmtAtom, d: &FmtAtom, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\\frac{{")?;
+        Self::atom(n, f)?;
+        write!(f, "}}{{")?;
+        Self::atom(d, f)?;
+        write!(f, "}}")
+    }
+
+    fn func(func: &atom::Func, args: &[FmtAtom], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Self::func_name(func, f)?;
+        if func.is_conj() {
+            // [\overline{z}]
+            write!(f, "{{")?;
+            Self::atom(&args[0], f)?;
+            return write!(f, "}}");
+        }
+        Self::func_args(args, f)
+    }
+
+    /// [\{a, b, ...\}]
+    fn list(args: &[FmtAtom], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\\{{")?;
+        for (i, a) in args.iter().enumerate() {
+            if i > 0 {
+                Self::comma(f)?;
+                Self::space(f)?;
+            }
+            Self::atom(a, f)?;
+        }
+        write!(f, "\\}}")
+    }
+
+    /// [\begin{bmatrix}a & b \\ c & d\end{bmatrix}]
+    fn matrix(rows: &[Vec<FmtAtom>], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\\begin{{bmatrix}}")?;
+        for (i, row) in rows.iter().enumerate() {
+            if i > 0 {
+                write!(f, " \\\\ ")?;
+            }
+            for (j, e) in row.iter().enumerate() {
+                if j > 0 {
+                    write!(f, " & ")?;
+                }
+                Self::atom(e, f)?;
+            }
+        }
+        write!(f, "\\end{{bmatrix}}")
+    }
+}
+
+/// displays a [FmtAtom] using the formatter [F]
+pub struct FmtWith<'a, F> {
+    atom: &'a FmtAtom,
+    _fmt: PhantomData<F>,
+}
+
+impl<F: SymbolicFormatter> fmt::Display for FmtWith<'_, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        F::atom(self.atom, f)
+    }
 }
 
 impl FmtAtom {
     pub fn pow(self, exp: Self) -> Self {
         FmtAtom::Pow(self.into(), exp.into())
     }
+
+    pub fn fmt_with<F: SymbolicFormatter>(&self) -> FmtWith<'_, F> {
+        FmtWith {
+            atom: self,
+            _fmt: PhantomData,
+        }
+    }
 }
 
-pub const fn sum_prec() -> u32 {
+pub const fn or_prec() -> u32 {
     1
 }
-pub const fn prod_prec() -> u32 {
+pub const fn and_prec() -> u32 {
     2
 }
-pub const fn pow_prec() -> u32 {
+pub const fn rel_prec() -> u32 {
     3
 }
-pub const fn atom_prec() -> u32 {
+pub const fn sum_prec() -> u32 {
     4
 }
+pub const fn prod_prec() -> u32 {
+    5
+}
+pub const fn pow_prec() -> u32 {
+    6
+}
+pub const fn atom_prec() -> u32 {
+    7
+}
 
 impl FmtAtom {
     pub fn prec(&self) -> u32 {
         match self {
-            FmtAtom::Func(_, _) | FmtAtom::Undef | FmtAtom::Irrational(_) | FmtAtom::Var(_) => {
-                atom_prec()
-            }
+            FmtAtom::Func(_, _)
+            | FmtAtom::Undef
+            | FmtAtom::Irrational(_)
+            | FmtAtom::ImagUnit
+            | FmtAtom::Infinity(_)
+            | FmtAtom::Var(_)
+            | FmtAtom::Bool(_)
+            | FmtAtom::List(_)
+            | FmtAtom::Matrix(_) => atom_prec(),
             FmtAtom::Rational(r) if r.is_int() => atom_prec(),
+            FmtAtom::Float(_) => atom_prec(),
 
             FmtAtom::Pow(_, _) => pow_prec(),
 
@@ -419,11 +924,16 @@ impl FmtAtom {
             FmtAtom::Fraction(_, _) | FmtAtom::Rational(_) => prod_prec(),
 
             FmtAtom::Sum(_) => sum_prec(),
+
+            FmtAtom::Not(_) => pow_prec(),
+            FmtAtom::Rel(..) => rel_prec(),
+            FmtAtom::And(_) => and_prec(),
+            FmtAtom::Or(_) => or_prec(),
         }
     }
 
     pub fn is_number(&self) -> bool {
-        self.is_rational() || self.is_irrational()
+        self.is_rational() || self.is_irrational() || self.is_float()
     }
     pub fn is_func_pow_number(&self) -> bool {
         match self {
@@ -446,6 +956,8 @@ pub fn implicit_prefix_mul(e: &FmtAtom) -> bool {
         F::Var(_)
         | F::Rational(_)
         | F::Irrational(_)
+        | F::Float(_)
+        | F::ImagUnit
         | F::Sum(_)
         | F::Func(_, _)
         | F::Pow(_, _) => true,
@@ -456,7 +968,7 @@ pub fn implicit_prefix_mul(e: &FmtAtom) -> bool {
 pub fn implicit_postfix_mul(e: &FmtAtom) -> bool {
     use FmtAtom as F;
     match e {
-        F::Rational(_) | F::Irrational(_) => true,
+        F::Rational(_) | F::Irrational(_) | F::Float(_) => true,
         _ => false,
     }
 }
@@ -498,6 +1010,9 @@ mod test_unicode_fmt {
             (e!(sin(x) * sin(x)), "sin^2(x)"),
             (e!(x ^ y ^ z), "(x^y)^z"),
             (e!(x ^ (y ^ z)), "x^(y^z)"),
+            (e!(x + 1 <= 2 * y), "x + 1 ≤ 2y"),
+            (e!(a < b && (c == d || e != f)), "a < b ∧ (c = d ∨ e ≠ f)"),
+            (e!(!(x > 1) || false), "¬(x > 1) ∨ false"),
         ];
 
         for (e, res) in fmt_res {
@@ -505,3 +1020,97 @@ mod test_unicode_fmt {
         }
     }
 }
+
+#[cfg(test)]
+mod test_latex_fmt {
+    use super::atom::Expr;
+    use crate::rational::Rational;
+
+    use calcurs_macros::expr as e;
+
+    #[test]
+    fn golden() {
+        let fmt_res = vec![
+            (e!(undef), r"\text{undefined}"),
+            (e!(3), "3"),
+            (e!(-2), "-2"),
+            (e!(2 / 3), r"\frac{2}{3}"),
+            (e!(1 / 2), r"\frac{1}{2}"),
+            (e!(pi), r"\pi"),
+            (Expr::e(), "e"),
+            (e!(x), "x"),
+            (Expr::var("x_1"), "x_{1}"),
+            (Expr::var("speed"), r"\text{speed}"),
+            (e!(e), r"\text{e}"),
+            (e!(a + b), "a + b"),
+            (e!(y + -x), "y - x"),
+            (e!(a + b * c), r"a + b\cdot c"),
+            (e!(2 * x * y), r"2x\cdot y"),
+            (e!(a / b), r"\frac{a}{b}"),
+            (e!(3 * 1 / x), r"\frac{3}{x}"),
+            (e!((x + y) / (x * y)), r"\frac{x + y}{x\cdot y}"),
+            (
+                e!((x + y) * (a + b)),
+                r"\left(x + y\right)\left(a + b\right)",
+            ),
+            (e!(x ^ 2), "x^{2}"),
+            (e!(2 * x ^ (a + b)), "2x^{a + b}"),
+            (e!((1 + x) ^ 2), r"\left(1 + x\right)^{2}"),
+            (e!(x ^ y ^ z), r"\left(x^{y}\right)^{z}"),
+            (e!(x ^ (1 / 2)), r"\sqrt{x}"),
+            (e!((x + 1) ^ (1 / 3)), r"\sqrt[3]{x + 1}"),
+            (e!(x ^ (3 / 2)), r"x^{\frac{3}{2}}"),
+            (e!(exp(x)), "e^{x}"),
+            (e!(sin(x)), r"\sin\left(x\right)"),
+            (e!(arc_sin(x)), r"\arcsin\left(x\right)"),
+            (e!(cos(x)), r"\cos\left(x\right)"),
+            (e!(arc_cos(x)), r"\arccos\left(x\right)"),
+            (e!(tan(x)), r"\tan\left(x\right)"),
+            (e!(arc_tan(x)), r"\arctan\left(x\right)"),
+            (e!(sec(x)), r"\sec\left(x\right)"),
+            (e!(arc_sec(x)), r"\operatorname{arcsec}\left(x\right)"),
+            (e!(cot(x)), r"\cot\left(x\right)"),
+            (e!(arc_cot(x)), r"\operatorname{arccot}\left(x\right)"),
+            (e!(csc(x)), r"\csc\left(x\right)"),
+            (e!(arc_csc(x)), r"\operatorname{arccsc}\left(x\right)"),
+            (e!(ln(x)), r"\ln\left(x\right)"),
+            (e!(2 + 3 * I), "2 + 3i"),
+            (e!(re(x)), r"\operatorname{Re}\left(x\right)"),
+            (e!(im(x)), r"\operatorname{Im}\left(x\right)"),
+            (e!(conj(x + 1)), r"\overline{x + 1}"),
+            (e!(arg(x)), r"\arg\left(x\right)"),
+            (e!(0.5 * x), "0.5x"),
+            (e!(oo), r"\infty"),
+            (e!(-oo), r"-\infty"),
+            (e!(zoo), r"\tilde{\infty}"),
+            (e!(log10(x)), r"\log_{10}\left(x\right)"),
+            (
+                e!(powmod(x, 2, 5)),
+                r"\operatorname{powmod}\left(x, 2, 5\right)",
+            ),
+            (e!(order(x)), r"\operatorname{O}\left(x\right)"),
+            (e!(x >= 1 / 2), r"x \geq \frac{1}{2}"),
+            (
+                e!(x != y || !(a == b)),
+                r"x \neq y \lor \neg \left(a = b\right)",
+            ),
+            (e!(true && false), r"\text{true} \land \text{false}"),
+            (
+                Expr::log(Rational::TWO, e!(x + 1)),
+                r"\log_{2}\left(x + 1\right)",
+            ),
+            (
+                e!(sin(x) ^ 2 * cos(x)),
+                r"\sin^{2}\left(x\right)\cos\left(x\right)",
+            ),
+            (
+                e!(sin(x) ^ (x + y) * tan(x)),
+                r"\sin\left(x\right)^{x + y} \cdot \tan\left(x\right)",
+            ),
+        ];
+
+        for (e, res) in fmt_res {
+            assert_eq!(e.to_latex(), res)
+        }
+    }
+}
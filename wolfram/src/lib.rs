@@ -0,0 +1,1117 @@
+//! Lexer and parser for the subset of the Wolfram language used by rule files like Rubi
+//!
+//! Shared by `calcurs_macros`, which compiles the Rubi rules at build time, and `calcu-rs`,
+//! which loads rewrite rules at runtime.
+
+use std::{fmt, ops};
+
+use derive_more::Debug;
+use logos::Logos;
+use ordered_float::OrderedFloat;
+
+type F64 = OrderedFloat<f64>;
+
+/// byte range into the parsed source
+pub type Span = ops::Range<usize>;
+
+/// an error while lexing or parsing Wolfram source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WolframError {
+    pub msg: String,
+    pub span: Span,
+}
+
+impl WolframError {
+    fn new(msg: impl Into<String>, span: Span) -> Self {
+        Self {
+            msg: msg.into(),
+            span,
+        }
+    }
+
+    /// the line of `src` containing the error
+    pub fn line<'a>(&self, src: &'a str) -> &'a str {
+        let start = self.span.start.min(src.len());
+        let line_start = src[..start].rfind('\n').map_or(0, |pos| pos + 1);
+        let line_end = src[start..].find('\n').map_or(src.len(), |pos| start + pos);
+        &src[line_start..line_end]
+    }
+}
+
+impl fmt::Display for WolframError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {}..{}", self.msg, self.span.start, self.span.end)
+    }
+}
+
+impl std::error::Error for WolframError {}
+
+type Result<T> = std::result::Result<T, WolframError>;
+
+fn parse_string(lex: &mut logos::Lexer<Wolfram>) -> String {
+    let chars = lex.remainder().chars();
+
+    let mut str = String::new();
+    let mut escape = false;
+    let mut len = 0;
+
+    for c in chars {
+        len += c.len_utf8();
+
+        if escape {
+            match c {
+                'n' => str.push('\n'),
+                't' => str.push('\t'),
+                _ => str.push_str(&format!("\\{}", c)),
+            }
+            escape = false;
+        } else {
+            match c {
+                '\\' => escape = true,
+                '"' => break,
+                _ => str.push(c),
+            }
+        }
+    }
+
+    lex.bump(len);
+    str
+}
+
+/// [None] for an unclosed comment
+fn parse_comment(lex: &mut logos::Lexer<Wolfram>) -> Option<String> {
+    // the comment is sliced from the source, the lexer would skip its whitespace
+    let start = lex.span().end;
+    if !lex.remainder().contains("*)") {
+        return None;
+    }
+    loop {
+        if lex.next()? == Ok(Wolfram::CommentEnd) {
+            break;
+        }
+    }
+    Some(lex.source()[start..lex.span().start].to_owned())
+}
+
+#[derive(Logos, Clone, Debug, PartialEq, PartialOrd)]
+#[logos(skip r"[ \t\r\f]+")]
+pub enum Wolfram {
+    #[token("(*", parse_comment)]
+    Comment(String),
+    #[token("*)")]
+    CommentEnd,
+    #[token("\n")]
+    NL,
+
+    #[regex("[A-Za-z$_][A-Za-z0-9$_]*", |lex| lex.slice().to_owned())]
+    Ident(String),
+
+    #[regex(r"[0-9]+\.", |lex| lex.slice().trim_end_matches('.').parse::<u64>().ok(), priority=100)]
+    #[regex(r"[0-9]+", |lex| lex.slice().parse::<u64>().ok(), priority=100)]
+    Integer(u64),
+
+    #[regex(r"(?:0|[1-9]\d*)(?:\.\d+)?(?:[eE][+-]?\d+)?", |lex| lex.slice().parse::<f64>().ok(), priority=5)]
+    Float(f64),
+
+    #[token("\"", parse_string)]
+    Str(String),
+
+    #[token("+")]
+    Add,
+    #[token("-")]
+    Sub,
+    #[token("*")]
+    #[token("\\[Star]")]
+    Mul,
+    #[token("/")]
+    Div,
+    #[token("^")]
+    Pow,
+
+    #[token("&&")]
+    And,
+    #[token("||")]
+    Or,
+    #[token("!")]
+    Not,
+
+    #[token("==")]
+    Eq,
+    #[token("!=")]
+    NEq,
+    #[token("<")]
+    Lt,
+    #[token(">")]
+    Ge,
+    #[token("<=")]
+    LtEq,
+    #[token(">=")]
+    GeEq,
+    #[token("===")]
+    Same,
+    #[token("=!=")]
+    UnSame,
+
+    #[token("=")]
+    Assign,
+    #[token(":=")]
+    Def,
+    #[token("/;")]
+    Cond,
+    #[token("/.")]
+    Replace,
+    #[token("->")]
+    Rule,
+    #[token("\\")]
+    BackSlash,
+    #[token("#")]
+    Slot,
+    #[token("'")]
+    Deriv,
+
+    #[token("(")]
+    LParen,
+    #[token(")")]
+    RParen,
+    #[token("[")]
+    LBracket,
+    #[token("]")]
+    RBracket,
+    #[token("{")]
+    LBrace,
+    #[token("}")]
+    RBrace,
+
+    #[token(".")]
+    Dot,
+    #[token(",")]
+    Comma,
+    #[token(";")]
+    Semicolon,
+    #[token(":")]
+    Colon,
+    #[token("::")]
+    DoublColon,
+
+    /// never lexed, returned by the parser after the last token
+    Eof,
+}
+
+impl Wolfram {
+    const fn op_prec(&self) -> u32 {
+        use Wolfram as W;
+        match self {
+            W::Replace => 1,
+            W::Rule => 2,
+            W::Assign => 3,
+            W::Or => 4,
+            W::And => 5,
+            W::NEq | W::Eq | W::Ge | W::GeEq | W::Lt | W::LtEq | W::Same | W::UnSame => 6,
+            W::Add | W::Sub => 7,
+            W::Mul | W::Div => 8,
+            W::Pow => 9,
+            _ => 0,
+        }
+    }
+
+    const fn skippable(&self) -> bool {
+        use Wolfram as W;
+        matches!(self, W::Comment(_) | W::CommentEnd | W::NL)
+    }
+}
+
+/// an expression with an optional condition, `pat /; cond`
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Pattern {
+    pub pat: Box<SymExpr>,
+    pub cond: Option<Box<SymExpr>>,
+}
+
+impl fmt::Debug for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.cond.is_some() {
+            write!(f, "(")?;
+        }
+        write!(f, "{:?}", self.pat)?;
+        if let Some(cond) = &self.cond {
+            write!(f, "/;  {cond:?})")?;
+        }
+        Ok(())
+    }
+}
+
+/// `f[args] := pat /; cond`
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct FuncDef {
+    pub call: FuncCall,
+    pub pat: Pattern,
+}
+
+impl fmt::Debug for FuncDef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} := {:?}", self.call, self.pat)?;
+        Ok(())
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct FuncCall {
+    pub name: String,
+    pub args: Vec<SymExpr>,
+}
+
+impl fmt::Debug for FuncCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{:?}", self.name, self.args)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum OpKind {
+    Assign,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+    And,
+    Or,
+    NEq,
+    Eq,
+    Ge,
+    GeEq,
+    Lt,
+    LtEq,
+    Same,
+    UnSame,
+    Rule,
+    Replace,
+    Fac,
+    Deriv,
+}
+
+impl OpKind {
+    fn binary(value: &Wolfram) -> Option<Self> {
+        use OpKind as O;
+        use Wolfram as W;
+        Some(match value {
+            W::Assign => O::Assign,
+            W::Add => O::Add,
+            W::Sub => O::Sub,
+            W::Mul => O::Mul,
+            W::Div => O::Div,
+            W::Pow => O::Pow,
+            W::And => O::And,
+            W::Or => O::Or,
+            W::Eq => O::Eq,
+            W::NEq => O::NEq,
+            W::Ge => O::Ge,
+            W::GeEq => O::GeEq,
+            W::Lt => O::Lt,
+            W::LtEq => O::LtEq,
+            W::Same => O::Same,
+            W::UnSame => O::UnSame,
+            W::Rule => O::Rule,
+            W::Replace => O::Replace,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct List {
+    pub args: Vec<SymExpr>,
+}
+
+impl fmt::Debug for List {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.args)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ScopeKind {
+    With,
+    Block,
+    Module,
+}
+
+impl TryFrom<&str> for ScopeKind {
+    type Error = ();
+
+    fn try_from(value: &str) -> std::result::Result<Self, Self::Error> {
+        match value {
+            "With" => Ok(ScopeKind::With),
+            "Block" => Ok(ScopeKind::Block),
+            "Module" => Ok(ScopeKind::Module),
+            _ => Err(()),
+        }
+    }
+}
+
+/// `With[{vars}, pat /; cond]`
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Scope {
+    pub kind: ScopeKind,
+    pub vars: List,
+    pub pat: Pattern,
+}
+
+impl fmt::Debug for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}[{:?}, {:?}]", self.kind, self.vars, self.pat)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum SymExpr {
+    #[debug("{_0}")]
+    Ident(String),
+    #[debug("({_0} /; {_1:?})")]
+    IdentRestr(String, Vec<SymExpr>),
+    #[debug("{}", _0.as_slice().join("::"))]
+    IdentField(Vec<String>),
+    #[debug("{_0}")]
+    Pat(String),
+    #[debug("({_0}.)")]
+    PatOpt(String),
+    #[debug("({_0}:{_1:?})")]
+    PatRestr(String, Vec<SymExpr>),
+    #[debug("{_0}")]
+    Integer(u64),
+    #[debug("{_0}")]
+    Float(F64),
+    #[debug("\"{_0}\"")]
+    Str(String),
+    #[debug("#{}", _0.map(|i| i.to_string()).unwrap_or_default())]
+    Slot(Option<u64>),
+
+    #[debug("{_0:?}[{_1:?}, {_2:?}]")]
+    BinOp(OpKind, Box<Self>, Box<Self>),
+    #[debug("{_0:?}[{_1:?}]")]
+    UnryOp(OpKind, Box<Self>),
+
+    #[debug("{{{_0:?}}}")]
+    List(List),
+    #[debug("{_0:?}[[{_1:?}]]")]
+    Part(Box<SymExpr>, Vec<SymExpr>),
+    #[debug("{_0:?}")]
+    FuncDef(FuncDef),
+    /// `lhs := pat /; cond` where `lhs` is not a function call, e.g `Sin[x_]^2 := ...`
+    #[debug("{_0:?} := {_1:?}")]
+    SetDelayed(Box<SymExpr>, Pattern),
+    #[debug("{_0:?}")]
+    FuncCall(FuncCall),
+    #[debug("{_0:?}[{_1:?}]")]
+    Call(Box<SymExpr>, Vec<SymExpr>),
+
+    #[debug("{_0:?}")]
+    Scope(Scope),
+    #[debug("MatchQ[{_0:?}, {_1:?}]")]
+    MatchQ(Box<SymExpr>, Pattern),
+    #[debug("Compnd[{_0:?}]")]
+    Compnd(Vec<SymExpr>),
+}
+
+impl SymExpr {
+    fn binary(op: &Wolfram, lhs: Self, rhs: Self, span: Span) -> Result<Self> {
+        match OpKind::binary(op) {
+            Some(op) => Ok(Self::BinOp(op, lhs.into(), rhs.into())),
+            _ => Err(WolframError::new(
+                format!("unexpected binary op: {op:?}"),
+                span,
+            )),
+        }
+    }
+}
+
+/// the top level expressions of a file
+#[derive(Debug, Default, Clone)]
+pub struct WolframContext {
+    pub exprs: Vec<SymExpr>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct File {
+    /// the `FILE: ...` comment starting the file
+    pub name: String,
+    pub cntxt: WolframContext,
+}
+
+impl File {
+    /// the title of the rule file, e.g `1.1.1.2 (a+b x)^m (c+d x)^n`
+    pub fn title(&self) -> &str {
+        let path = self.name.trim().trim_start_matches("FILE:").trim();
+        let file = path.rsplit('/').next().unwrap_or(path);
+        file.strip_suffix(".m").unwrap_or(file)
+    }
+
+    /// true if any directory or the file itself is in the section, e.g `1.1.1` for
+    /// `1.1 Binomial products/1.1.1 Linear/...`
+    pub fn in_section(&self, section: &str) -> bool {
+        self.name.split('/').any(|dir| {
+            dir.trim()
+                .strip_prefix(section)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '.']))
+        })
+    }
+}
+
+/// the parsed source, split into files by `(* FILE: ... *)` comments
+#[derive(Debug, Default, Clone)]
+pub struct WolframFiles {
+    pub files: Vec<File>,
+}
+
+impl WolframFiles {
+    fn next_file(&mut self, name: impl Into<String>) {
+        self.files.push(File {
+            name: name.into(),
+            cntxt: Default::default(),
+        })
+    }
+
+    fn push_expr(&mut self, e: SymExpr) {
+        if self.files.is_empty() {
+            self.next_file("NO FILENAME");
+        }
+        self.files.last_mut().unwrap().cntxt.exprs.push(e)
+    }
+
+    /// the top level expressions of all files
+    pub fn exprs(&self) -> impl Iterator<Item = &SymExpr> {
+        self.files.iter().flat_map(|f| f.cntxt.exprs.iter())
+    }
+}
+
+/// parses the top level expressions of `src`, e.g the rules of a `.m` file
+pub fn parse(src: &str) -> Result<WolframFiles> {
+    let mut tokens = vec![];
+    for (tok, span) in Wolfram::lexer(src).spanned() {
+        match tok {
+            Ok(tok) => tokens.push((tok, span)),
+            Err(()) => {
+                let msg = format!("could not lex: {:?}", &src[span.clone()]);
+                return Err(WolframError::new(msg, span));
+            }
+        }
+    }
+    let mut t = Parser {
+        tokens,
+        pos: 0,
+        files: Default::default(),
+    };
+    loop {
+        t.skip_comment_nl();
+        if t.check(&Wolfram::Eof) {
+            break;
+        }
+        let expr = parse_statement(&mut t)?;
+        t.files.push_expr(expr);
+    }
+    Ok(t.files)
+}
+
+struct Parser {
+    tokens: Vec<(Wolfram, Span)>,
+    pos: usize,
+    files: WolframFiles,
+}
+
+impl Parser {
+    fn current(&self) -> &Wolfram {
+        self.tokens.get(self.pos).map_or(&Wolfram::Eof, |(t, _)| t)
+    }
+
+    fn current_span(&self) -> Span {
+        match self.tokens.get(self.pos) {
+            Some((_, span)) => span.clone(),
+            None => {
+                let end = self.tokens.last().map_or(0, |(_, span)| span.end);
+                end..end
+            }
+        }
+    }
+
+    fn next(&mut self) {
+        if self.pos < self.tokens.len() {
+            self.pos += 1;
+        }
+    }
+
+    fn skip_while(&mut self, skip: impl Fn(&Wolfram) -> bool) {
+        while skip(self.current()) {
+            if let Wolfram::Comment(c) = self.current() {
+                if c.contains("FILE:") {
+                    let name = c.clone();
+                    self.files.next_file(name)
+                }
+            }
+            self.next();
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        self.skip_while(|t| matches!(t, Wolfram::Comment(_)))
+    }
+
+    fn skip_comment_nl(&mut self) {
+        self.skip_while(Wolfram::skippable)
+    }
+
+    fn advance(&mut self) {
+        self.next();
+        self.skip_comment();
+    }
+
+    fn check_peek(&self, t: &Wolfram) -> bool {
+        self.tokens
+            .get(self.pos + 1)
+            .is_some_and(|(tok, _)| tok == t)
+    }
+
+    fn check(&self, expected: &Wolfram) -> bool {
+        self.current() == expected
+    }
+
+    fn error(&self, msg: impl Into<String>) -> WolframError {
+        WolframError::new(msg, self.current_span())
+    }
+
+    fn expect(&mut self, expected: &Wolfram) -> Result<()> {
+        if !self.check(expected) {
+            let msg = format!("expected: {expected:?}, found: {:?}", self.current());
+            return Err(self.error(msg));
+        }
+        self.advance();
+        Ok(())
+    }
+}
+
+/// an expression or a definition `lhs := pat /; cond`
+fn parse_statement(t: &mut Parser) -> Result<SymExpr> {
+    let expr = parse_expr(t)?;
+    if !t.check(&Wolfram::Def) {
+        return Ok(expr);
+    }
+    t.expect(&Wolfram::Def)?;
+    let pat = parse_pattern(t)?;
+    Ok(SymExpr::SetDelayed(expr.into(), pat))
+}
+
+fn parse_func_def(call: FuncCall, t: &mut Parser) -> Result<FuncDef> {
+    t.expect(&Wolfram::Def)?;
+    let pat = parse_pattern(t)?;
+    Ok(FuncDef { call, pat })
+}
+
+fn parse_scope(kind: ScopeKind, t: &mut Parser) -> Result<Scope> {
+    use Wolfram as W;
+    t.expect(&W::LBracket)?;
+    let vars = parse_list(t)?;
+    t.expect(&W::Comma)?;
+    let pat = parse_pattern(t)?;
+    t.expect(&W::RBracket)?;
+    Ok(Scope { kind, vars, pat })
+}
+
+fn parse_matchq(t: &mut Parser) -> Result<SymExpr> {
+    use Wolfram as W;
+    t.expect(&W::LBracket)?;
+    let expr = parse_expr(t)?;
+    t.expect(&W::Comma)?;
+    let pat = parse_pattern(t)?;
+    t.expect(&W::RBracket)?;
+    Ok(SymExpr::MatchQ(expr.into(), pat))
+}
+
+fn parse_pattern(t: &mut Parser) -> Result<Pattern> {
+    use Wolfram as W;
+    let pat = parse_expr(t)?.into();
+    let mut cond = None;
+
+    if t.check(&W::Cond) {
+        t.expect(&W::Cond)?;
+        cond = Some(Box::new(parse_expr(t)?));
+    }
+    Ok(Pattern { pat, cond })
+}
+
+fn parse_args(t: &mut Parser) -> Result<Vec<SymExpr>> {
+    use Wolfram as W;
+    let mut args = vec![];
+    t.expect(&W::LBracket)?;
+    loop {
+        if t.check(&W::RBracket) {
+            break;
+        }
+        let arg = parse_expr(t)?;
+        args.push(arg);
+        if t.check(&W::RBracket) {
+            break;
+        }
+        t.expect(&W::Comma)?;
+    }
+    t.expect(&W::RBracket)?;
+    Ok(args)
+}
+
+fn parse_call(name: String, t: &mut Parser) -> Result<SymExpr> {
+    if let Ok(sk) = ScopeKind::try_from(name.as_str()) {
+        return Ok(SymExpr::Scope(parse_scope(sk, t)?));
+    } else if name == "MatchQ" {
+        return parse_matchq(t);
+    }
+
+    let args = parse_args(t)?;
+    let call = FuncCall { name, args };
+    if t.check(&Wolfram::Def) {
+        Ok(SymExpr::FuncDef(parse_func_def(call, t)?))
+    } else {
+        Ok(SymExpr::FuncCall(call))
+    }
+}
+
+fn parse_ident(str: String, t: &mut Parser) -> Result<SymExpr> {
+    use Wolfram as W;
+    Ok(if str.ends_with('_') {
+        // is a pattern
+        if t.check(&W::Dot) {
+            t.advance();
+            SymExpr::PatOpt(str)
+        } else if t.check(&W::Colon) {
+            t.advance();
+            let r = parse_expr(t)?;
+            SymExpr::PatRestr(str, vec![r])
+        } else {
+            SymExpr::Pat(str)
+        }
+    } else if t.check(&W::Colon) {
+        t.advance();
+        let r = parse_expr(t)?;
+        SymExpr::IdentRestr(str, vec![r])
+    } else if t.check(&W::DoublColon) {
+        let mut ids = vec![str];
+        while t.check(&W::DoublColon) {
+            t.expect(&W::DoublColon)?;
+            match t.current().clone() {
+                W::Ident(id) => ids.push(id),
+                tok => return Err(t.error(format!("expected identifier, found: {tok:?}"))),
+            }
+            t.advance();
+        }
+        SymExpr::IdentField(ids)
+    } else {
+        SymExpr::Ident(str)
+    })
+}
+
+fn parse_list(t: &mut Parser) -> Result<List> {
+    use Wolfram as W;
+    let mut args = vec![];
+    t.expect(&W::LBrace)?;
+    loop {
+        if t.check(&W::RBrace) {
+            break;
+        }
+        let arg = parse_expr(t)?;
+        args.push(arg);
+        t.skip_comment_nl();
+        if t.check(&W::RBrace) {
+            break;
+        }
+        t.expect(&W::Comma)?;
+    }
+    t.expect(&W::RBrace)?;
+    Ok(List { args })
+}
+
+fn parse_operand(t: &mut Parser) -> Result<SymExpr> {
+    use SymExpr as SE;
+    use Wolfram as W;
+
+    Ok(match t.current().clone() {
+        W::Ident(v) => {
+            t.advance();
+            if t.check(&W::LBracket) && !t.check_peek(&W::LBracket) {
+                parse_call(v, t)?
+            } else {
+                parse_ident(v, t)?
+            }
+        }
+        W::Str(v) => {
+            t.advance();
+            SE::Str(v)
+        }
+        W::Integer(v) => {
+            t.advance();
+            SE::Integer(v)
+        }
+        W::Float(v) => {
+            t.advance();
+            SE::Float(v.into())
+        }
+        W::LParen => {
+            t.advance();
+            let expr = parse_expr(t)?;
+            t.expect(&W::RParen)?;
+            expr
+        }
+        W::LBrace => SE::List(parse_list(t)?),
+        W::Add => {
+            t.advance();
+            parse_expr(t)?
+        }
+        W::Sub => {
+            t.advance();
+            let expr = parse_bin_expr(t, W::Mul.op_prec() + 1)?;
+            SE::UnryOp(OpKind::Sub, expr.into())
+        }
+        W::Slot => {
+            t.advance();
+            if let W::Integer(i) = t.current().clone() {
+                t.advance();
+                SE::Slot(i.into())
+            } else {
+                SE::Slot(None)
+            }
+        }
+        tok if tok.skippable() => {
+            t.skip_comment_nl();
+            parse_operand(t)?
+        }
+        W::Eof => return Err(t.error("unexpected end of input")),
+        tok => return Err(t.error(format!("unexpected token: {tok:?}"))),
+    })
+}
+
+fn parse_unary(expr: SymExpr, t: &mut Parser) -> Result<SymExpr> {
+    use Wolfram as W;
+    let unary = if t.check(&W::LBracket) && t.check_peek(&W::LBracket) {
+        t.expect(&W::LBracket)?;
+        let indx = parse_args(t)?;
+        t.expect(&W::RBracket)?;
+        SymExpr::Part(expr.into(), indx)
+    } else if t.check(&W::LBracket) {
+        let args = parse_args(t)?;
+        SymExpr::Call(expr.into(), args)
+    } else if t.check(&W::Not) {
+        t.expect(&W::Not)?;
+        SymExpr::UnryOp(OpKind::Fac, expr.into())
+    } else if t.check(&W::Deriv) {
+        t.expect(&W::Deriv)?;
+        SymExpr::UnryOp(OpKind::Deriv, expr.into())
+    } else {
+        return Ok(expr);
+    };
+    parse_unary(unary, t)
+}
+
+fn parse_compl_expr(t: &mut Parser) -> Result<SymExpr> {
+    let expr = parse_operand(t)?;
+    parse_unary(expr, t)
+}
+
+fn parse_compound(t: &mut Parser) -> Result<SymExpr> {
+    use Wolfram as W;
+    let expr = parse_compl_expr(t)?;
+    if !t.check(&W::Semicolon) {
+        return Ok(expr);
+    }
+
+    let mut exprs = vec![expr];
+    while t.check(&W::Semicolon) {
+        t.expect(&W::Semicolon)?;
+        exprs.push(parse_compl_expr(t)?)
+    }
+    Ok(SymExpr::Compnd(exprs))
+}
+
+fn parse_bin_expr(t: &mut Parser, prec_in: u32) -> Result<SymExpr> {
+    use Wolfram as W;
+    let mut lhs = parse_compound(t)?;
+    loop {
+        let span = t.current_span();
+        let op = match t.current() {
+            // implicit multiplication, e.g a b or 2 (a + b)
+            W::Ident(_) | W::LParen => W::Mul,
+            op => op.clone(),
+        };
+        if op.op_prec() < prec_in || op.op_prec() == 0 {
+            break;
+        }
+        if !matches!(t.current(), W::Ident(_) | W::LParen) {
+            t.advance();
+        }
+        let rhs = parse_bin_expr(t, op.op_prec() + 1)?;
+        // := binds loosest, but a call takes it as its definition, e.g in a*f[x_] := x
+        lhs = match rhs {
+            SymExpr::FuncDef(FuncDef { call, pat }) => {
+                let rhs = SymExpr::FuncCall(call);
+                SymExpr::SetDelayed(SymExpr::binary(&op, lhs, rhs, span)?.into(), pat)
+            }
+            SymExpr::SetDelayed(rhs, pat) => {
+                SymExpr::SetDelayed(SymExpr::binary(&op, lhs, *rhs, span)?.into(), pat)
+            }
+            rhs => SymExpr::binary(&op, lhs, rhs, span)?,
+        };
+    }
+    Ok(lhs)
+}
+
+fn parse_expr(t: &mut Parser) -> Result<SymExpr> {
+    parse_bin_expr(t, 1)
+}
+
+/// a rational literal, numbers in patterns are folded when the pattern is built
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Num(pub i64, pub i64);
+
+impl Num {
+    pub const ZERO: Num = Num(0, 1);
+    pub const ONE: Num = Num(1, 1);
+    pub const MIN_ONE: Num = Num(-1, 1);
+
+    pub fn new(n: i64, d: i64) -> Self {
+        let (mut a, mut b) = (n.abs(), d.abs());
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+        let g = a.max(1) * d.signum();
+        Num(n / g, d / g)
+    }
+
+    pub fn is_int(&self) -> bool {
+        self.1 == 1
+    }
+
+    fn add(self, rhs: Num) -> Num {
+        Num::new(self.0 * rhs.1 + rhs.0 * self.1, self.1 * rhs.1)
+    }
+
+    fn mul(self, rhs: Num) -> Num {
+        Num::new(self.0 * rhs.0, self.1 * rhs.1)
+    }
+
+    fn pow(self, e: i64) -> Option<Num> {
+        let (n, d) = match e < 0 {
+            true if self.0 == 0 => return None,
+            true => (self.1, self.0),
+            false => (self.0, self.1),
+        };
+        let e = u32::try_from(e.unsigned_abs()).ok()?;
+        Some(Num::new(n.checked_pow(e)?, d.checked_pow(e)?))
+    }
+}
+
+/// the left hand side of a rule, normalized like the expressions it is matched against
+///
+/// e.g `1/(a_ + b_.*x_)^(3/2)` becomes `Pow[Sum[a, Prod[b, x]], -3/2]`
+#[derive(Clone, Debug, PartialEq)]
+pub enum Pat {
+    /// `x_Symbol`, any variable
+    Sym(String),
+    /// `a_`
+    Blank(String),
+    /// `a_.`, defaults to the identity of the surrounding sum, product or power
+    Opt(String),
+    Num(Num),
+    /// a symbol matching itself, e.g `Pi` or `a`
+    Ident(String),
+    /// a function with a fixed head, e.g `Sin[x_]`
+    Func(String, Vec<Pat>),
+    Sum(Vec<Pat>),
+    Prod(Vec<Pat>),
+    Pow(Box<Pat>, Box<Pat>),
+}
+
+impl Pat {
+    fn assoc(args: impl IntoIterator<Item = Pat>, sum: bool) -> Pat {
+        let id = if sum { Num::ZERO } else { Num::ONE };
+        let mut n = id;
+        let mut rest = vec![];
+        for a in args {
+            match a {
+                Pat::Sum(args) if sum => rest.extend(args),
+                Pat::Prod(args) if !sum => rest.extend(args),
+                Pat::Num(m) if sum => n = n.add(m),
+                Pat::Num(m) => n = n.mul(m),
+                a => rest.push(a),
+            }
+        }
+        if n != id {
+            rest.insert(0, Pat::Num(n));
+        }
+        match rest.len() {
+            0 => Pat::Num(id),
+            1 => rest.pop().unwrap(),
+            _ if sum => Pat::Sum(rest),
+            _ => Pat::Prod(rest),
+        }
+    }
+
+    fn pow(base: Pat, exp: Pat) -> Pat {
+        match (base, exp) {
+            (b, Pat::Num(Num::ONE)) => b,
+            (Pat::Num(b), Pat::Num(e)) if e.is_int() && b.pow(e.0).is_some() => {
+                Pat::Num(b.pow(e.0).unwrap())
+            }
+            (Pat::Prod(args), Pat::Num(e)) if e.is_int() => {
+                Pat::assoc(args.into_iter().map(|a| Pat::pow(a, Pat::Num(e))), false)
+            }
+            (Pat::Pow(b, e1), Pat::Num(e)) if e.is_int() && matches!(*e1, Pat::Num(_)) => match *e1
+            {
+                Pat::Num(e1) => Pat::pow(*b, Pat::Num(e1.mul(e))),
+                _ => unreachable!(),
+            },
+            (b, e) => Pat::Pow(b.into(), e.into()),
+        }
+    }
+
+    pub fn new(e: &SymExpr) -> std::result::Result<Pat, String> {
+        use OpKind as O;
+        use SymExpr as S;
+        let neg = |p: Pat| Pat::assoc([Pat::Num(Num::MIN_ONE), p], false);
+        Ok(match e {
+            S::Pat(p) => Pat::Blank(var_name(p.trim_end_matches('_'))?),
+            S::PatOpt(p) => Pat::Opt(var_name(p.trim_end_matches('_'))?),
+            S::Ident(id) => match id.strip_suffix("_Symbol") {
+                Some(x) => Pat::Sym(var_name(x)?),
+                None => Pat::Ident(var_name(id)?),
+            },
+            S::Integer(n) => Pat::Num(Num::new(*n as i64, 1)),
+            S::UnryOp(O::Sub, e) => neg(Pat::new(e)?),
+            S::BinOp(op, l, r) => {
+                let (l, r) = (Pat::new(l)?, Pat::new(r)?);
+                match op {
+                    O::Add => Pat::assoc([l, r], true),
+                    O::Sub => Pat::assoc([l, neg(r)], true),
+                    O::Mul => Pat::assoc([l, r], false),
+                    O::Div => Pat::assoc([l, Pat::pow(r, Pat::Num(Num::MIN_ONE))], false),
+                    O::Pow => Pat::pow(l, r),
+                    op => return Err(format!("{op:?} in pattern")),
+                }
+            }
+            S::FuncCall(FuncCall { name, args }) if name == "Sqrt" && args.len() == 1 => {
+                Pat::pow(Pat::new(&args[0])?, Pat::Num(Num::new(1, 2)))
+            }
+            S::FuncCall(FuncCall { name, args }) if name == "Exp" && args.len() == 1 => {
+                Pat::pow(Pat::Ident("E".into()), Pat::new(&args[0])?)
+            }
+            S::FuncCall(FuncCall { name, args }) => Pat::Func(
+                var_name(name)?,
+                args.iter()
+                    .map(Pat::new)
+                    .collect::<std::result::Result<_, _>>()?,
+            ),
+            e => return Err(format!("{e:?} in pattern")),
+        })
+    }
+
+    /// the names bound by the pattern, in order of appearance
+    pub fn names(&self, names: &mut Vec<String>) {
+        match self {
+            Pat::Sym(n) | Pat::Blank(n) | Pat::Opt(n) => {
+                if !names.contains(n) {
+                    names.push(n.clone())
+                }
+            }
+            Pat::Num(_) | Pat::Ident(_) => (),
+            Pat::Func(_, args) | Pat::Sum(args) | Pat::Prod(args) => {
+                args.iter().for_each(|a| a.names(names))
+            }
+            Pat::Pow(b, e) => {
+                b.names(names);
+                e.names(names);
+            }
+        }
+    }
+}
+
+pub fn var_name(name: &str) -> std::result::Result<String, String> {
+    match !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric()) {
+        true => Ok(name.to_owned()),
+        false => Err(format!("invalid name: {name}")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use assert_eq as eq;
+
+    fn exprs(src: &str) -> Vec<SymExpr> {
+        let files = parse(src).unwrap_or_else(|e| panic!("{e}\nline: {}", e.line(src)));
+        files.exprs().cloned().collect()
+    }
+
+    #[test]
+    fn definitions() {
+        let es = exprs(
+            "(* FILE: trig.m *)
+            Sin[x_]^2 := 1 - Cos[x]^2 /; FreeQ[x, y]
+            Log[E^x_] := x",
+        );
+        eq!(es.len(), 2);
+        let SymExpr::SetDelayed(lhs, rhs) = &es[0] else {
+            panic!("{:?}", es[0])
+        };
+        eq!(format!("{lhs:?}"), "Pow[Sin[x_], 2]");
+        assert!(rhs.cond.is_some());
+        let SymExpr::FuncDef(fd) = &es[1] else {
+            panic!("{:?}", es[1])
+        };
+        eq!(fd.call.name, "Log");
+        eq!(fd.pat.cond, None);
+        eq!(parse("a := b").unwrap().files[0].name, "NO FILENAME");
+        let es = exprs("a_ + b_*f[x_] := x");
+        let SymExpr::SetDelayed(lhs, _) = &es[0] else {
+            panic!("{:?}", es[0])
+        };
+        eq!(format!("{lhs:?}"), "Add[a_, Mul[b_, f[x_]]]");
+    }
+
+    #[test]
+    fn errors() {
+        let src = "f[x_] := x\ng[x_ := (x";
+        let err = parse(src).unwrap_err();
+        eq!(err.line(src), "g[x_ := (x");
+        eq!(err.to_string(), "expected: Comma, found: Def at 16..18");
+        let err = parse("f[x] := 1 (* unclosed").unwrap_err();
+        eq!(err.span, 10..12);
+        let err = parse("f[x] := (1 + ").unwrap_err();
+        eq!(err.msg, "unexpected end of input");
+    }
+
+    #[test]
+    fn patterns() {
+        let pat = |src: &str| Pat::new(&exprs(src)[0]);
+        let (a, b, x) = (
+            Pat::Blank("a".into()),
+            Pat::Opt("b".into()),
+            Pat::Blank("x".into()),
+        );
+        eq!(
+            pat("1/(a_ + b_.*x_)^(3/2)"),
+            Ok(Pat::Pow(
+                Pat::Sum(vec![a.clone(), Pat::Prod(vec![b, x.clone()])]).into(),
+                Pat::Num(Num(-3, 2)).into(),
+            ))
+        );
+        eq!(
+            pat("Sqrt[Sin[x_]]"),
+            Ok(Pat::Pow(
+                Pat::Func("Sin".into(), vec![x.clone()]).into(),
+                Pat::Num(Num(1, 2)).into()
+            ))
+        );
+        eq!(
+            pat("Exp[a_] Pi"),
+            Ok(Pat::Prod(vec![
+                Pat::Pow(Pat::Ident("E".into()).into(), a.into()),
+                Pat::Ident("Pi".into())
+            ]))
+        );
+        assert!(pat("x_ == 1").is_err());
+    }
+}